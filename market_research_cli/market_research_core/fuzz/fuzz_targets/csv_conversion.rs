@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use market_research_core::parsers;
+
+fuzz_target!(|input: &str| {
+    if let Some(markdown) = parsers::csv_table(input) {
+        assert!(markdown.starts_with('|'));
+    }
+});
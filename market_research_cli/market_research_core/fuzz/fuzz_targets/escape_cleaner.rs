@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use market_research_core::parsers;
+
+fuzz_target!(|input: &str| {
+    let cleaned = parsers::clean_escapes(input);
+    // Cleaning only removes text, and leaves content without escape sequences alone
+    assert!(cleaned.len() <= input.len());
+    if !input.contains('\x1b') && !input.contains("ESC") {
+        assert_eq!(cleaned, input);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use market_research_core::parsers;
+
+fuzz_target!(|input: &str| {
+    // Whatever the front matter holds, the body is the input after it
+    if let Some((_, body)) = parsers::report_metadata(input) {
+        assert!(input.ends_with(body.as_str()));
+    }
+    if let Some((_, body)) = parsers::front_matter_mapping(input) {
+        assert!(input.ends_with(body));
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use market_research_core::parsers;
+
+fuzz_target!(|input: &str| {
+    let (_, title) = parsers::extract_html(input);
+    if let Some(title) = title {
+        assert!(!title.is_empty());
+    }
+});
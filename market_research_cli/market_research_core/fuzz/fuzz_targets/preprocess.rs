@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use market_research_core::parsers;
+
+fuzz_target!(|input: &str| {
+    let _ = parsers::preprocess_markdown(input);
+});
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::panics::lock;
+use crate::{index, lock_report, write_atomic};
+
+pub const ACTIVITY_FILE: &str = ".activity.json";
+
+/// Recently opened entries kept per directory
+const MAX_RECENT: usize = 100;
+
+/// Reads of one report closer together than this are recorded as a single access
+const ACCESS_DEBOUNCE: Duration = Duration::from_secs(60);
+
+/// A pinned report
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Pin {
+    pub filename: String,
+    pub pinned_at: String,
+}
+
+/// The last time a report was opened
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Access {
+    pub filename: String,
+    pub opened_at: String,
+}
+
+/// Pins and recently opened reports for one reports directory, shared by every front-end
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Activity {
+    #[serde(default)]
+    pub pinned: Vec<Pin>,
+    /// Most recent first, one entry per report
+    #[serde(default)]
+    pub recent: Vec<Access>,
+}
+
+fn activity_path(reports_dir: &str) -> PathBuf {
+    Path::new(reports_dir).join(ACTIVITY_FILE)
+}
+
+impl Activity {
+    /// Load the activity file, returning empty state if it does not exist yet
+    pub fn load(reports_dir: &str) -> Result<Self> {
+        let path = activity_path(reports_dir);
+        if !path.exists() {
+            return Ok(Activity::default());
+        }
+        let bytes = fs::read(&path).context("Failed to read activity file")?;
+        serde_json::from_slice(&bytes).context("Activity file is corrupted")
+    }
+}
+
+/// Apply a change to the activity file while holding its lock
+pub fn update_activity<T, F>(reports_dir: &str, change: F) -> Result<T>
+where
+    F: FnOnce(&mut Activity) -> T,
+{
+    let path = activity_path(reports_dir);
+    let _lock = lock_report(&path, true)?;
+    let mut activity = Activity::load(reports_dir)?;
+    let result = change(&mut activity);
+    write_atomic(&path, &serde_json::to_vec_pretty(&activity)?)?;
+    Ok(result)
+}
+
+/// Move a report to the front of the recently opened list
+pub fn record_access(reports_dir: &str, filename: &str) -> Result<()> {
+    update_activity(reports_dir, |activity| {
+        activity.recent.retain(|access| access.filename != filename);
+        activity.recent.insert(0, Access { filename: filename.to_string(), opened_at: Local::now().to_rfc3339() });
+        activity.recent.truncate(MAX_RECENT);
+    })
+}
+
+/// Accesses recorded recently by one process, so reading a report over and over doesn't lock and rewrite the
+/// activity file every time. A report read again within `ACCESS_DEBOUNCE` keeps its place in the recent list
+#[derive(Default)]
+pub struct AccessDebounce {
+    recorded: Mutex<HashMap<String, Instant>>,
+}
+
+impl AccessDebounce {
+    /// Record an access unless this report's last one was recorded less than `ACCESS_DEBOUNCE` ago
+    pub fn record(&self, reports_dir: &str, filename: &str) -> Result<()> {
+        {
+            let mut recorded = lock(&self.recorded);
+            let now = Instant::now();
+            recorded.retain(|_, at| now.duration_since(*at) < ACCESS_DEBOUNCE);
+            if recorded.contains_key(filename) {
+                return Ok(());
+            }
+            recorded.insert(filename.to_string(), now);
+        }
+        record_access(reports_dir, filename)
+    }
+}
+
+/// Record that a file path was opened, if it lives directly in a managed reports directory
+pub fn record_path_access(path: &Path) -> Result<()> {
+    let (dir, filename) = match (path.parent(), path.file_name().and_then(|n| n.to_str())) {
+        (Some(dir), Some(filename)) => (dir.to_string_lossy().to_string(), filename.to_string()),
+        _ => return Ok(()),
+    };
+    let dir = if dir.is_empty() { ".".to_string() } else { dir };
+    // Only directories with a report index are report libraries; other opened files are not tracked
+    if !Path::new(&dir).join(index::INDEX_FILE).exists() {
+        return Ok(());
+    }
+    record_access(&dir, &filename)
+}
+
+/// Pin a report; returns false if it was already pinned
+pub fn pin(reports_dir: &str, filename: &str) -> Result<bool> {
+    update_activity(reports_dir, |activity| {
+        if activity.pinned.iter().any(|pin| pin.filename == filename) {
+            return false;
+        }
+        activity.pinned.push(Pin { filename: filename.to_string(), pinned_at: Local::now().to_rfc3339() });
+        true
+    })
+}
+
+/// Unpin a report; returns false if it was not pinned
+pub fn unpin(reports_dir: &str, filename: &str) -> Result<bool> {
+    update_activity(reports_dir, |activity| {
+        let before = activity.pinned.len();
+        activity.pinned.retain(|pin| pin.filename != filename);
+        activity.pinned.len() != before
+    })
+}
@@ -0,0 +1,225 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::{Deserialize, Serialize};
+
+use crate::ids::new_report_id;
+use crate::panics::guard;
+use crate::sections::split_sections;
+use crate::{lock_report, write_atomic};
+
+/// A reviewer comment anchored to a heading and/or a line range of a report
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Annotation {
+    pub id: String,
+    pub author: String,
+    pub comment: String,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// 1-based, inclusive line range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_end: Option<usize>,
+    #[serde(default)]
+    pub resolved: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<String>,
+}
+
+impl Annotation {
+    /// Whether the anchor no longer matches the report (heading renamed or lines removed)
+    fn is_stale(&self, content: &str) -> bool {
+        let heading_missing = self
+            .heading
+            .as_ref()
+            .map(|heading| !split_sections(content).iter().any(|section| &section.heading == heading))
+            .unwrap_or(false);
+        let lines_missing = self.line_end.or(self.line_start).map(|end| end > content.lines().count()).unwrap_or(false);
+        heading_missing || lines_missing
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("id", &self.id)?;
+        dict.set_item("author", &self.author)?;
+        dict.set_item("comment", &self.comment)?;
+        dict.set_item("created_at", &self.created_at)?;
+        dict.set_item("heading", &self.heading)?;
+        dict.set_item("line_start", self.line_start)?;
+        dict.set_item("line_end", self.line_end)?;
+        dict.set_item("resolved", self.resolved)?;
+        dict.set_item("resolved_by", &self.resolved_by)?;
+        dict.set_item("resolved_at", &self.resolved_at)?;
+        Ok(dict)
+    }
+}
+
+/// Hidden `.{name}.annotations.json` sidecar next to the report
+fn sidecar_path(report: &Path) -> PathBuf {
+    let name = report.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    report.with_file_name(format!(".{}.annotations.json", name))
+}
+
+fn load(sidecar: &Path) -> Result<Vec<Annotation>> {
+    if !sidecar.exists() {
+        return Ok(Vec::new());
+    }
+    let bytes = fs::read(sidecar).context("Failed to read annotations")?;
+    serde_json::from_slice(&bytes).context("Annotations file is corrupted")
+}
+
+/// Load, change and save a report's annotations under the sidecar's lock
+fn update<T, F>(sidecar: &Path, change: F) -> Result<T>
+where
+    F: FnOnce(&mut Vec<Annotation>) -> Result<T>,
+{
+    let _lock = lock_report(sidecar, true)?;
+    let mut annotations = load(sidecar)?;
+    let result = change(&mut annotations)?;
+    write_atomic(sidecar, &serde_json::to_vec_pretty(&annotations)?)?;
+    Ok(result)
+}
+
+fn to_py_err(e: anyhow::Error) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update annotations: {}", e))
+}
+
+/// Reviewer comments on reports, stored in a JSON sidecar per report
+#[pyclass]
+pub struct Annotations {
+    reports_dir: String,
+}
+
+impl Annotations {
+    fn report_path(&self, filename: &str) -> PyResult<PathBuf> {
+        let path = Path::new(&self.reports_dir).join(filename);
+        if !path.is_file() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("Report file not found: {}", filename)
+            ));
+        }
+        Ok(path)
+    }
+}
+
+#[pymethods]
+impl Annotations {
+    #[new]
+    fn new(reports_dir: &str) -> PyResult<Self> {
+        guard("Annotations.new", || {
+            Ok(Annotations { reports_dir: reports_dir.to_string() })
+        })
+    }
+
+    /// Attach a comment to a report, optionally anchored to a heading and/or 1-based line range; returns its id
+    #[pyo3(signature = (filename, author, comment, heading=None, line_start=None, line_end=None))]
+    fn add(
+        &self,
+        filename: &str,
+        author: &str,
+        comment: &str,
+        heading: Option<String>,
+        line_start: Option<usize>,
+        line_end: Option<usize>,
+    ) -> PyResult<String> {
+        guard("Annotations.add", || {
+            let path = self.report_path(filename)?;
+            let content = fs::read_to_string(&path)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read report: {}", e)))?;
+
+            // Validate the anchor against the current report so comments never point nowhere
+            if let Some(heading) = &heading {
+                if !split_sections(&content).iter().any(|section| &section.heading == heading) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Heading not found in {}: {}", filename, heading)
+                    ));
+                }
+            }
+            let line_end = line_end.or(line_start);
+            if let (Some(start), Some(end)) = (line_start, line_end) {
+                let line_count = content.lines().count();
+                if start == 0 || start > end || end > line_count {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Invalid line range {}-{}; the report has {} lines", start, end, line_count)
+                    ));
+                }
+            }
+            if line_start.is_none() && line_end.is_some() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("line_end requires line_start"));
+            }
+
+            let annotation = Annotation {
+                id: new_report_id(),
+                author: author.to_string(),
+                comment: comment.to_string(),
+                created_at: Local::now().to_rfc3339(),
+                heading,
+                line_start,
+                line_end,
+                resolved: false,
+                resolved_by: None,
+                resolved_at: None,
+            };
+            let id = annotation.id.clone();
+            update(&sidecar_path(&path), |annotations| {
+                annotations.push(annotation);
+                Ok(())
+            })
+            .map_err(to_py_err)?;
+            Ok(id)
+        })
+    }
+
+    /// Mark a comment resolved; returns false if it was already resolved
+    #[pyo3(signature = (filename, annotation_id, resolved_by=None))]
+    fn resolve(&self, filename: &str, annotation_id: &str, resolved_by: Option<String>) -> PyResult<bool> {
+        guard("Annotations.resolve", || {
+            let sidecar = sidecar_path(&Path::new(&self.reports_dir).join(filename));
+            update(&sidecar, |annotations| {
+                let annotation = annotations
+                    .iter_mut()
+                    .find(|annotation| annotation.id == annotation_id)
+                    .ok_or_else(|| anyhow!("Annotation not found: {}", annotation_id))?;
+                if annotation.resolved {
+                    return Ok(false);
+                }
+                annotation.resolved = true;
+                annotation.resolved_by = resolved_by;
+                annotation.resolved_at = Some(Local::now().to_rfc3339());
+                Ok(true)
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to resolve annotation: {}", e)))
+        })
+    }
+
+    /// List a report's comments in creation order; each dict has a `stale` flag when its anchor no longer matches
+    #[pyo3(signature = (filename, include_resolved=true))]
+    fn list(&self, filename: &str, include_resolved: bool, py: Python) -> PyResult<PyObject> {
+        guard("Annotations.list", || {
+            let path = Path::new(&self.reports_dir).join(filename);
+            let sidecar = sidecar_path(&path);
+            let annotations = {
+                let _lock = lock_report(&sidecar, false)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to lock annotations: {}", e)))?;
+                load(&sidecar).map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load annotations: {}", e)))?
+            };
+            let content = fs::read_to_string(&path).unwrap_or_default();
+
+            let result = PyList::empty(py);
+            for annotation in annotations.iter().filter(|a| include_resolved || !a.resolved) {
+                let dict = annotation.to_dict(py)?;
+                dict.set_item("stale", annotation.is_stale(&content))?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+use crate::charts::ChartMode;
+use crate::history;
+use crate::facts::{facts_from_py, Facts};
+use crate::highlight;
+use crate::redact::RedactionProfile;
+use crate::render::RenderOptions;
+use crate::sandbox::Sandbox;
+use crate::sanitize::SanitizeMode;
+use crate::themes::Theme;
+use crate::toc;
+use crate::wikilinks::Library;
+use crate::{lock_report, sha256_hex, write_atomic};
+
+pub const ARTIFACTS_FILE: &str = ".artifacts.json";
+
+/// Content-addressed store of exported files, `<hash[..2]>/<hash>.<format>`; `ARTIFACTS_FILE` is its manifest
+pub const STORE_DIR: &str = ".artifacts";
+
+/// The export options of an artifact, kept so it can be regenerated the same way
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ExportSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charts: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagrams: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deterministic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_file: Option<String>,
+    /// Redaction profile name or profile file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stylesheet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub css: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Kept so regenerating untrusted content is sandboxed like the original export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<Sandbox>,
+    /// Sanitize mode, `safe`, `strict` or `off`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sanitize: Option<String>,
+    /// Code highlighting theme, or `none`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+    /// Table of contents depth, 0 for none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toc: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footnotes: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_punctuation: Option<bool>,
+    /// Reports directory wikilinks resolve against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wikilinks: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citations: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub math: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mermaid: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_images: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facts: Option<Facts>,
+}
+
+impl ExportSettings {
+    /// Capture the export options dict as given (validated separately by `RenderOptions::from_dict`)
+    pub fn from_dict(options: Option<&PyDict>) -> PyResult<Self> {
+        let mut settings = ExportSettings::default();
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(settings),
+        };
+        if let Some(value) = options.get_item("charts") {
+            settings.charts = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("diagrams") {
+            settings.diagrams = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("metrics") {
+            settings.metrics = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("deterministic") {
+            settings.deterministic = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("policy_file") {
+            settings.policy_file = value.extract()?;
+        }
+        if let Some(value) = options.get_item("redaction") {
+            settings.redaction = value.extract()?;
+        }
+        if let Some(value) = options.get_item("stylesheet") {
+            settings.stylesheet = value.extract()?;
+        }
+        if let Some(value) = options.get_item("theme") {
+            settings.theme = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("css") {
+            settings.css = value.extract()?;
+        }
+        if let Some(value) = options.get_item("template") {
+            settings.template = value.extract()?;
+        }
+        if let Some(value) = options.get_item("sandbox") {
+            settings.sandbox = Sandbox::from_py(value)?;
+        }
+        if let Some(value) = options.get_item("sanitize") {
+            settings.sanitize = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("highlight") {
+            settings.highlight = Some(highlight::setting_from_py(value)?);
+        }
+        if let Some(value) = options.get_item("toc") {
+            settings.toc = Some(toc::depth_from_py(value)?);
+        }
+        if let Some(value) = options.get_item("footnotes") {
+            settings.footnotes = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("emoji") {
+            settings.emoji = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("smart_punctuation") {
+            settings.smart_punctuation = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("wikilinks") {
+            settings.wikilinks = value.extract()?;
+        }
+        if let Some(value) = options.get_item("citations") {
+            settings.citations = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("math") {
+            settings.math = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("mermaid") {
+            settings.mermaid = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("inline_images") {
+            settings.inline_images = Some(value.extract()?);
+        }
+        if let Some(value) = options.get_item("facts") {
+            settings.facts = Some(facts_from_py(value)?);
+        }
+        Ok(settings)
+    }
+
+    /// Rendering options equivalent to the original export
+    pub fn render_options(&self) -> Result<RenderOptions> {
+        let mut options = RenderOptions::default();
+        if let Some(charts) = &self.charts {
+            options.charts = ChartMode::parse(charts).ok_or_else(|| anyhow!("Unknown charts mode '{}'", charts))?;
+        }
+        if let Some(diagrams) = self.diagrams {
+            options.diagrams = diagrams;
+        }
+        if let Some(metrics) = self.metrics {
+            options.metrics = metrics;
+        }
+        if let Some(deterministic) = self.deterministic {
+            options.deterministic = deterministic;
+        }
+        options.policy_file = self.policy_file.clone();
+        options.stylesheet = self.stylesheet.clone();
+        options.css = self.css.clone();
+        options.template = self.template.clone();
+        if let Some(theme) = &self.theme {
+            options.theme = Theme::parse(theme).ok_or_else(|| anyhow!("Unknown theme '{}'", theme))?;
+        }
+        options.sandbox = self.sandbox.clone();
+        if let Some(mode) = &self.sanitize {
+            options.sanitize = SanitizeMode::parse(mode).ok_or_else(|| anyhow!("Unknown sanitize mode '{}'", mode))?;
+        }
+        options.facts = self.facts.clone();
+        if let Some(setting) = &self.highlight {
+            options.highlight = highlight::theme_for(setting)?;
+        }
+        if let Some(depth) = self.toc {
+            options.toc = depth;
+        }
+        if let Some(footnotes) = self.footnotes {
+            options.footnotes = footnotes;
+        }
+        if let Some(emoji) = self.emoji {
+            options.emoji = emoji;
+        }
+        if let Some(smart_punctuation) = self.smart_punctuation {
+            options.smart_punctuation = smart_punctuation;
+        }
+        if let Some(reports_dir) = &self.wikilinks {
+            options.wikilinks = Some(Library::load(reports_dir)?);
+        }
+        if let Some(citations) = self.citations {
+            options.citations = citations;
+        }
+        if let Some(math) = self.math {
+            options.math = math;
+        }
+        if let Some(mermaid) = self.mermaid {
+            options.mermaid = mermaid;
+        }
+        if let Some(inline_images) = self.inline_images {
+            options.inline_images = inline_images;
+        }
+        if let Some(profile) = &self.redaction {
+            options.redaction = Some(RedactionProfile::load(profile)?);
+        }
+        Ok(options)
+    }
+
+    /// These settings with every option set in `overrides` replacing the recorded one
+    pub fn merged(&self, overrides: &ExportSettings) -> ExportSettings {
+        ExportSettings {
+            charts: overrides.charts.clone().or_else(|| self.charts.clone()),
+            diagrams: overrides.diagrams.or(self.diagrams),
+            metrics: overrides.metrics.or(self.metrics),
+            deterministic: overrides.deterministic.or(self.deterministic),
+            policy_file: overrides.policy_file.clone().or_else(|| self.policy_file.clone()),
+            redaction: overrides.redaction.clone().or_else(|| self.redaction.clone()),
+            stylesheet: overrides.stylesheet.clone().or_else(|| self.stylesheet.clone()),
+            theme: overrides.theme.clone().or_else(|| self.theme.clone()),
+            css: overrides.css.clone().or_else(|| self.css.clone()),
+            template: overrides.template.clone().or_else(|| self.template.clone()),
+            sandbox: overrides.sandbox.clone().or_else(|| self.sandbox.clone()),
+            sanitize: overrides.sanitize.clone().or_else(|| self.sanitize.clone()),
+            highlight: overrides.highlight.clone().or_else(|| self.highlight.clone()),
+            toc: overrides.toc.or(self.toc),
+            footnotes: overrides.footnotes.or(self.footnotes),
+            emoji: overrides.emoji.or(self.emoji),
+            smart_punctuation: overrides.smart_punctuation.or(self.smart_punctuation),
+            wikilinks: overrides.wikilinks.clone().or_else(|| self.wikilinks.clone()),
+            citations: overrides.citations.or(self.citations),
+            math: overrides.math.or(self.math),
+            mermaid: overrides.mermaid.or(self.mermaid),
+            inline_images: overrides.inline_images.or(self.inline_images),
+            facts: overrides.facts.clone().or_else(|| self.facts.clone()),
+        }
+    }
+
+    /// Whether the options depend only on themselves, not on files (policy, stylesheet, template, redaction profile,
+    /// linked reports) that may have changed
+    fn is_self_contained(&self) -> bool {
+        self.policy_file.is_none()
+            && self.stylesheet.is_none()
+            && self.template.is_none()
+            && self.wikilinks.is_none()
+            && self.redaction.as_deref().is_none_or(|profile| RedactionProfile::builtin(profile).is_some())
+    }
+}
+
+/// One file exported from a report
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Artifact {
+    pub format: String,
+    /// Absolute path of the exported file
+    pub path: String,
+    pub sha256: String,
+    /// Hash of the report content the artifact was generated from
+    pub source_sha256: String,
+    pub exported_at: String,
+    #[serde(default)]
+    pub settings: ExportSettings,
+    /// Export job that produced it, whose converter output `get_converter_log` returns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+}
+
+/// Artifacts per report filename, one entry per output path
+pub type ArtifactRegistry = BTreeMap<String, Vec<Artifact>>;
+
+fn registry_path(reports_dir: &str) -> PathBuf {
+    Path::new(reports_dir).join(ARTIFACTS_FILE)
+}
+
+/// Load the registry, returning an empty one if nothing has been exported yet
+pub fn load_artifacts(reports_dir: &str) -> Result<ArtifactRegistry> {
+    let path = registry_path(reports_dir);
+    if !path.exists() {
+        return Ok(ArtifactRegistry::new());
+    }
+    let bytes = fs::read(&path).context("Failed to read artifact registry")?;
+    serde_json::from_slice(&bytes).context("Artifact registry is corrupted")
+}
+
+/// Record an export of `filename` and move its file into the store, replacing any earlier artifact at the same path
+pub fn record_artifact(
+    reports_dir: &str,
+    filename: &str,
+    format: &str,
+    target: &Path,
+    source: &[u8],
+    settings: &ExportSettings,
+    job_id: &str,
+) -> Result<()> {
+    let path = fs::canonicalize(target).unwrap_or_else(|_| target.to_path_buf()).to_string_lossy().to_string();
+    let sha256 = sha256_hex(&fs::read(target)?);
+    store_object(reports_dir, target, &sha256, format).context("Failed to store artifact")?;
+    let artifact = Artifact {
+        format: format.to_string(),
+        sha256,
+        path,
+        source_sha256: sha256_hex(source),
+        exported_at: Local::now().to_rfc3339(),
+        settings: settings.clone(),
+        job_id: Some(job_id.to_string()),
+    };
+
+    let registry_file = registry_path(reports_dir);
+    let _lock = lock_report(&registry_file, true)?;
+    let mut registry = load_artifacts(reports_dir)?;
+    let artifacts = registry.entry(filename.to_string()).or_default();
+    artifacts.retain(|existing| existing.path != artifact.path);
+    artifacts.push(artifact);
+    write_atomic(&registry_file, &serde_json::to_vec_pretty(&registry)?)?;
+    Ok(())
+}
+
+/// The report content an artifact was generated from: the current report if unchanged since, else the
+/// matching revision from report history
+pub fn exported_source(reports_dir: &str, filename: &str, artifact: &Artifact, current: &str) -> Result<String> {
+    if sha256_hex(current.as_bytes()) == artifact.source_sha256 {
+        return Ok(current.to_string());
+    }
+    if !history::is_enabled(reports_dir) {
+        return Err(anyhow!("report changed since export and history is not enabled; pass use_current=True"));
+    }
+    for entry in history::history(reports_dir, filename)? {
+        let content = history::show_at(reports_dir, filename, &entry.commit)?;
+        if sha256_hex(content.as_bytes()) == artifact.source_sha256 {
+            return Ok(content);
+        }
+    }
+    Err(anyhow!("the exported version of the report is not in its history; pass use_current=True"))
+}
+
+fn object_path(reports_dir: &str, sha256: &str, format: &str) -> PathBuf {
+    Path::new(reports_dir).join(STORE_DIR).join(&sha256[..2]).join(format!("{}.{}", sha256, format))
+}
+
+/// Keep one stored copy per distinct content and leave `target` as a hard link to it
+fn store_object(reports_dir: &str, target: &Path, sha256: &str, format: &str) -> Result<()> {
+    let object = object_path(reports_dir, sha256, format);
+    if let Some(parent) = object.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    {
+        // Identical reports exported in parallel produce the same object
+        let _lock = lock_report(&object, true)?;
+        if !object.exists() {
+            write_atomic(&object, &fs::read(target)?)?;
+        }
+    }
+    link_to(&object, target)
+}
+
+/// Replace `target` with a hard link to `object`, keeping a plain copy where links are unsupported (e.g. across filesystems)
+fn link_to(object: &Path, target: &Path) -> Result<()> {
+    let name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = target.with_file_name(format!(".{}.{}.link.tmp", name, std::process::id()));
+    let _ = fs::remove_file(&temp);
+    match fs::hard_link(object, &temp) {
+        Ok(()) => {
+            let result = fs::rename(&temp, target);
+            // Renaming onto a link to the same file succeeds without removing the temp link
+            let _ = fs::remove_file(&temp);
+            result?;
+        }
+        Err(_) if target.exists() => {}
+        Err(_) => {
+            fs::copy(object, target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Link `target` to a stored export of the same report content in the same format with the same options, if one
+/// exists and is intact; returns false when the report has to be rendered
+pub fn reuse_stored(reports_dir: &str, filename: &str, format: &str, source: &[u8], settings: &ExportSettings, target: &Path) -> Result<bool> {
+    if !settings.is_self_contained() {
+        return Ok(false);
+    }
+    let source_sha256 = sha256_hex(source);
+    let wanted = serde_json::to_value(settings)?;
+    let registry = load_artifacts(reports_dir)?;
+    let stored = registry.get(filename).into_iter().flatten().find(|artifact| {
+        artifact.format == format
+            && artifact.source_sha256 == source_sha256
+            && serde_json::to_value(&artifact.settings).ok().as_ref() == Some(&wanted)
+    });
+    let stored = match stored {
+        Some(stored) => stored,
+        None => return Ok(false),
+    };
+    let object = object_path(reports_dir, &stored.sha256, format);
+    match fs::read(&object) {
+        Ok(bytes) if sha256_hex(&bytes) == stored.sha256 => {}
+        _ => return Ok(false),
+    }
+    link_to(&object, target)?;
+    Ok(true)
+}
+
+/// Outcome of checking the store against its manifest
+#[derive(Default)]
+pub struct StoreCheck {
+    pub objects: usize,
+    pub bytes: u64,
+    /// Objects whose content no longer matches their hash
+    pub corrupted: Vec<String>,
+    /// Recorded artifacts (report, path) with no stored object
+    pub missing: Vec<(String, String)>,
+    /// Objects no recorded artifact refers to
+    pub unreferenced: Vec<String>,
+    pub removed: usize,
+}
+
+/// Re-hash every stored object, cross-check it with the manifest and optionally delete unreferenced objects
+pub fn check_store(reports_dir: &str, remove_unreferenced: bool) -> Result<StoreCheck> {
+    let registry = load_artifacts(reports_dir)?;
+    let mut referenced = HashSet::new();
+    let mut check = StoreCheck::default();
+    for (filename, artifacts) in &registry {
+        for artifact in artifacts {
+            let object = object_path(reports_dir, &artifact.sha256, &artifact.format);
+            if !object.is_file() {
+                check.missing.push((filename.clone(), artifact.path.clone()));
+            }
+            referenced.insert(object);
+        }
+    }
+
+    let store = Path::new(reports_dir).join(STORE_DIR);
+    if !store.is_dir() {
+        return Ok(check);
+    }
+    for shard in fs::read_dir(&store)? {
+        let shard = shard?.path();
+        if !shard.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&shard)? {
+            let path = entry?.path();
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            // Lock and temp files
+            if name.starts_with('.') || !path.is_file() {
+                continue;
+            }
+            let relative = path.strip_prefix(reports_dir).unwrap_or(&path).to_string_lossy().to_string();
+            if !referenced.contains(&path) {
+                if remove_unreferenced {
+                    fs::remove_file(&path)?;
+                    check.removed += 1;
+                } else {
+                    check.unreferenced.push(relative);
+                }
+                continue;
+            }
+            let bytes = fs::read(&path)?;
+            check.objects += 1;
+            check.bytes += bytes.len() as u64;
+            let hash = name.split('.').next().unwrap_or_default();
+            if sha256_hex(&bytes) != hash {
+                check.corrupted.push(relative);
+            }
+        }
+    }
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::{check_store, record_artifact, reuse_stored, ExportSettings};
+    use crate::export_report;
+    use crate::render::RenderOptions;
+
+    /// A reports directory under the temp dir, removed when dropped
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("artifacts_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Scratch(dir)
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Export `content` as markdown to `target` and record it, as `export_many` does
+    fn export(reports_dir: &str, content: &str, target: &Path) {
+        export_report(content, target, "md", &RenderOptions::default()).unwrap();
+        record_artifact(reports_dir, "report.md", "md", target, content.as_bytes(), &ExportSettings::default(), "job").unwrap();
+    }
+
+    #[test]
+    fn reexport_to_the_same_path_keeps_stored_objects_intact() {
+        let scratch = Scratch::new("reexport");
+        let target = scratch.0.join("out.md");
+        export(scratch.path(), "# Report\n\nFirst draft\n", &target);
+        export(scratch.path(), "# Report\n\nSecond draft\n", &target);
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "# Report\n\nSecond draft\n");
+        let check = check_store(scratch.path(), false).unwrap();
+        assert!(check.corrupted.is_empty(), "corrupted: {:?}", check.corrupted);
+        assert!(check.missing.is_empty(), "missing: {:?}", check.missing);
+        // The first draft's object is no longer recorded but still holds the first draft
+        assert_eq!(check.objects, 1);
+        assert_eq!(check.unreferenced.len(), 1);
+    }
+
+    #[test]
+    fn reexport_over_a_reused_link_keeps_stored_objects_intact() {
+        let scratch = Scratch::new("reuse");
+        let content = "# Report\n\nBody\n";
+        export(scratch.path(), content, &scratch.0.join("first.md"));
+
+        let second = scratch.0.join("second.md");
+        assert!(reuse_stored(scratch.path(), "report.md", "md", content.as_bytes(), &ExportSettings::default(), &second).unwrap());
+        assert_eq!(fs::read_to_string(&second).unwrap(), content);
+        export(scratch.path(), "# Report\n\nChanged\n", &second);
+
+        assert_eq!(fs::read_to_string(scratch.0.join("first.md")).unwrap(), content);
+        let check = check_store(scratch.path(), false).unwrap();
+        assert!(check.corrupted.is_empty(), "corrupted: {:?}", check.corrupted);
+        assert!(check.missing.is_empty(), "missing: {:?}", check.missing);
+        assert_eq!(check.objects, 2);
+    }
+}
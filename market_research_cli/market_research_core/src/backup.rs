@@ -0,0 +1,296 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::paths::filename_problem;
+use crate::render::source_date_epoch;
+use crate::space::ensure_space;
+use crate::stats::report_date;
+use crate::{list_files, sha256_hex};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const REPORTS_PREFIX: &str = "reports/";
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Largest manifest read when importing; it lists every report, but holds no report content
+const MAX_MANIFEST_BYTES: u64 = 64 * 1024 * 1024;
+
+/// One report recorded in a backup manifest
+#[derive(Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub filename: String,
+    pub size: u64,
+    pub sha256: String,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Index of a backup archive, stored as `manifest.json` at its root
+#[derive(Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: String,
+    pub reports: Vec<BackupEntry>,
+}
+
+/// Outcome of restoring a backup
+pub struct ImportSummary {
+    pub restored: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// Read front matter leniently; backups must not fail on a malformed header
+fn lenient_metadata(content: &str) -> BTreeMap<String, String> {
+    crate::parse_report_metadata(content)
+        .map(|(metadata, _)| metadata.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Write every report with one of `extensions` plus a checksummed manifest into a `.tar.gz` archive.
+/// Deterministic archives pin all timestamps so identical libraries produce identical bytes.
+pub fn export_backup(reports_dir: &str, archive_path: &Path, extensions: &[String], deterministic: bool) -> Result<BackupManifest> {
+    let mut contents = Vec::new();
+    for (filename, _) in list_files(reports_dir, extensions)? {
+        let bytes = fs::read(Path::new(reports_dir).join(&filename))
+            .with_context(|| format!("Failed to read {}", filename))?;
+        contents.push((filename, bytes));
+    }
+
+    // Pinned time: SOURCE_DATE_EPOCH, else the newest report date, else the Unix epoch
+    let created_at = if deterministic {
+        let newest = contents
+            .iter()
+            .map(|(filename, bytes)| report_date(filename, &String::from_utf8_lossy(bytes), None))
+            .max();
+        let pinned = source_date_epoch().or(newest).unwrap_or_default();
+        Utc.from_utc_datetime(&pinned).fixed_offset()
+    } else {
+        Local::now().fixed_offset()
+    };
+
+    let mut manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        created_at: created_at.to_rfc3339(),
+        reports: Vec::new(),
+    };
+    for (filename, bytes) in &contents {
+        manifest.reports.push(BackupEntry {
+            filename: filename.clone(),
+            size: bytes.len() as u64,
+            sha256: sha256_hex(bytes),
+            metadata: lenient_metadata(&String::from_utf8_lossy(bytes)),
+        });
+    }
+
+    // Compression rarely grows text, so the reports' own size bounds the archive
+    let total: u64 = contents.iter().map(|(_, bytes)| bytes.len() as u64).sum();
+    ensure_space(archive_path, total, "the backup")?;
+    if let Some(parent) = archive_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    // Write to a temporary name so a failed export never leaves a truncated archive behind
+    let temp_path = archive_path.with_extension("partial");
+    let result = (|| -> Result<()> {
+        let file = fs::File::create(&temp_path)?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+        let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+        let mtime = created_at.timestamp().max(0) as u64;
+        append_bytes(&mut builder, MANIFEST_NAME, &manifest_json, mtime)?;
+        for (filename, bytes) in &contents {
+            append_bytes(&mut builder, &format!("{}{}", REPORTS_PREFIX, filename), bytes, mtime)?;
+        }
+
+        builder.into_inner()?.finish()?.sync_all()?;
+        fs::rename(&temp_path, archive_path)?;
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    Ok(manifest)
+}
+
+fn append_bytes<W: std::io::Write>(builder: &mut tar::Builder<W>, name: &str, bytes: &[u8], mtime: u64) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    builder.append_data(&mut header, name, bytes)?;
+    Ok(())
+}
+
+/// Why a manifest filename must not be restored, if it must not. Only names `list_files` would return for
+/// `extensions` are allowed: a single visible component, so an archive can never plant `.git/config`, the index or
+/// templates, and one that is portable
+fn member_problem(name: &str, extensions: &[String]) -> Option<String> {
+    let mut components = Path::new(name).components();
+    let single = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+    if !single {
+        return Some(format!("{} is not a file at the top of the reports directory", name));
+    }
+    if name.starts_with('.') {
+        return Some(format!("{} is a hidden file", name));
+    }
+    let extension = Path::new(name).extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    if !extension.is_some_and(|extension| extensions.contains(&extension)) {
+        return Some(format!("{} is not a report file", name));
+    }
+    filename_problem(name)
+}
+
+/// Read the reports stored in a backup archive without restoring them, as (filename, bytes) pairs
+pub fn read_backup_reports(archive_path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let file = fs::File::open(archive_path)
+        .with_context(|| format!("Failed to open backup {}", archive_path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+    let mut reports = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        if let Some(filename) = name.strip_prefix(REPORTS_PREFIX) {
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            reports.push((filename.to_string(), bytes));
+        }
+    }
+    Ok(reports)
+}
+
+/// Stream one archive member into `out`, failing if it is longer than `size`; returns its length and SHA-256
+fn stage_member(entry: &mut impl Read, out: &mut impl Write, size: u64) -> Result<(u64, String)> {
+    let mut limited = entry.take(size + 1);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut written = 0u64;
+    loop {
+        let read = limited.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        written += read as u64;
+        if written > size {
+            return Err(anyhow!("is larger than the {} bytes its manifest entry records", size));
+        }
+        hasher.update(&buffer[..read]);
+        out.write_all(&buffer[..read])?;
+    }
+    Ok((written, hex::encode(hasher.finalize())))
+}
+
+/// Verify a backup archive against its manifest and restore its reports. Reports are streamed to a staging
+/// directory inside `reports_dir` while they are hashed, each limited to the size its manifest entry records, and
+/// only moved into place once every one of them checks out. Reports that would be skipped are hashed but not kept
+pub fn import_backup(reports_dir: &str, archive_path: &Path, extensions: &[String], overwrite: bool) -> Result<ImportSummary> {
+    let file = fs::File::open(archive_path)
+        .with_context(|| format!("Failed to open backup {}", archive_path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut entries = archive.entries()?;
+
+    // `export_backup` writes the manifest first, so the sizes are known before any report is read
+    let mut first = entries.next().ok_or_else(|| anyhow!("Backup archive is empty"))??;
+    if first.path()?.to_string_lossy() != MANIFEST_NAME {
+        return Err(anyhow!("Backup archive does not start with its manifest"));
+    }
+    let mut manifest_json = Vec::new();
+    (&mut first).take(MAX_MANIFEST_BYTES + 1).read_to_end(&mut manifest_json)?;
+    if manifest_json.len() as u64 > MAX_MANIFEST_BYTES {
+        return Err(anyhow!("Backup manifest is larger than {} bytes", MAX_MANIFEST_BYTES));
+    }
+    let manifest: BackupManifest = serde_json::from_slice(&manifest_json).context("Backup manifest is not valid JSON")?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Backup format version {} is newer than supported version {}",
+            manifest.format_version,
+            BACKUP_FORMAT_VERSION
+        ));
+    }
+    let mut positions = HashMap::new();
+    for (position, entry) in manifest.reports.iter().enumerate() {
+        if let Some(problem) = member_problem(&entry.filename, extensions) {
+            return Err(anyhow!("Backup contains an unsafe path: {}", problem));
+        }
+        positions.insert(entry.filename.as_str(), position);
+    }
+
+    let skip: Vec<bool> = manifest
+        .reports
+        .iter()
+        .map(|entry| !overwrite && Path::new(reports_dir).join(&entry.filename).exists())
+        .collect();
+    let restoring: u64 = manifest.reports.iter().zip(&skip).filter(|(_, skip)| !**skip).map(|(entry, _)| entry.size).sum();
+    fs::create_dir_all(reports_dir)?;
+    ensure_space(Path::new(reports_dir), restoring, "the restore")?;
+
+    static STAGING_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let staging = Path::new(reports_dir).join(format!(
+        ".backup_import_{}_{}",
+        std::process::id(),
+        STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir(&staging)?;
+    let result = (|| -> Result<ImportSummary> {
+        // Verify everything before restoring so a corrupted archive restores nothing
+        let mut staged = vec![false; manifest.reports.len()];
+        for entry in entries {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().to_string();
+            let position = match name.strip_prefix(REPORTS_PREFIX).and_then(|filename| positions.get(filename)) {
+                Some(&position) => position,
+                None => continue,
+            };
+            let expected = &manifest.reports[position];
+            let staged_member = match skip[position] {
+                true => stage_member(&mut entry, &mut std::io::sink(), expected.size),
+                false => fs::File::create(staging.join(position.to_string()))
+                    .map_err(anyhow::Error::from)
+                    .and_then(|mut file| stage_member(&mut entry, &mut file, expected.size)),
+            };
+            let (size, sha256) =
+                staged_member.with_context(|| format!("Failed to read {} from the backup", expected.filename))?;
+            if size != expected.size || sha256 != expected.sha256 {
+                return Err(anyhow!("Checksum mismatch for {}", expected.filename));
+            }
+            staged[position] = true;
+        }
+        if let Some(position) = staged.iter().position(|staged| !staged) {
+            return Err(anyhow!("Backup is missing {}", manifest.reports[position].filename));
+        }
+
+        let mut summary = ImportSummary {
+            restored: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for (position, entry) in manifest.reports.iter().enumerate() {
+            if skip[position] {
+                summary.skipped.push(entry.filename.clone());
+                continue;
+            }
+            let target = Path::new(reports_dir).join(&entry.filename);
+            fs::rename(staging.join(position.to_string()), &target)
+                .with_context(|| format!("Failed to restore {}", entry.filename))?;
+            summary.restored.push(entry.filename.clone());
+        }
+        Ok(summary)
+    })();
+
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
@@ -0,0 +1,239 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::panics::{guard, lock};
+
+/// Budget used unless `MARKET_RESEARCH_MEMORY_MB` or `set_memory_budget` chooses another
+const DEFAULT_BUDGET_MB: usize = 1024;
+
+/// Rendering a report holds its source, the pre-processed markdown, the HTML and any generated SVG at once
+const RENDER_FACTOR: usize = 8;
+
+/// Share of the budget one spill buffer may hold before it moves its contents to disk
+const SPILL_SHARE: usize = 4;
+
+const MB: usize = 1024 * 1024;
+
+/// Approximate memory held by batch work in flight, against a limit (0 for none)
+#[derive(Default)]
+struct Usage {
+    limit: usize,
+    in_use: usize,
+    peak: usize,
+    /// Times a task waited for memory to free up
+    waits: usize,
+    spilled: usize,
+}
+
+struct Budget {
+    usage: Mutex<Usage>,
+    freed: Condvar,
+}
+
+fn budget() -> &'static Budget {
+    static BUDGET: OnceLock<Budget> = OnceLock::new();
+    BUDGET.get_or_init(|| {
+        let megabytes = std::env::var("MARKET_RESEARCH_MEMORY_MB")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_BUDGET_MB);
+        Budget { usage: Mutex::new(Usage { limit: megabytes * MB, ..Usage::default() }), freed: Condvar::new() }
+    })
+}
+
+/// Memory reserved for one task, returned to the budget when dropped
+pub struct Reservation {
+    bytes: usize,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let budget = budget();
+        lock(&budget.usage).in_use -= self.bytes;
+        budget.freed.notify_all();
+    }
+}
+
+/// Reserve `bytes` of the budget, waiting while other tasks hold too much of it. A task is always let through
+/// when nothing else holds memory, so one item larger than the whole budget still runs, alone
+pub fn reserve(bytes: usize) -> Reservation {
+    let budget = budget();
+    let mut usage = lock(&budget.usage);
+    let mut waited = false;
+    while usage.limit > 0 && usage.in_use > 0 && usage.in_use + bytes > usage.limit {
+        waited = true;
+        usage = budget.freed.wait(usage).unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+    usage.waits += waited as usize;
+    usage.in_use += bytes;
+    usage.peak = usage.peak.max(usage.in_use);
+    Reservation { bytes }
+}
+
+/// Estimated memory for rendering a report of `source_bytes`
+pub fn render_cost(source_bytes: u64) -> usize {
+    (source_bytes as usize).saturating_mul(RENDER_FACTOR)
+}
+
+/// Bytes a spill buffer may hold in memory: a share of the budget, or unbounded without one
+fn spill_threshold() -> usize {
+    match lock(&budget().usage).limit {
+        0 => usize::MAX,
+        limit => limit / SPILL_SHARE,
+    }
+}
+
+/// Where an item of a `Spill` lives
+enum Slot<T> {
+    Memory(T, usize),
+    Disk(u64),
+}
+
+/// Append-only list of intermediate results that moves to a temporary file once it outgrows its share of the
+/// budget, and reads items back one at a time. The file is private to the current user and removed, with its
+/// directory, when the buffer is dropped
+pub struct Spill<T> {
+    slots: Vec<Slot<T>>,
+    in_memory: usize,
+    threshold: usize,
+    file: Option<(PathBuf, BufWriter<File>, u64)>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> Spill<T> {
+    /// An empty buffer allowed its share of the current budget
+    pub fn within_budget() -> Self {
+        Spill { slots: Vec::new(), in_memory: 0, threshold: spill_threshold(), file: None }
+    }
+
+    /// Add an item of roughly `size` bytes, returning its position
+    pub fn push(&mut self, item: T, size: usize) -> Result<usize> {
+        self.slots.push(Slot::Memory(item, size));
+        self.in_memory += size;
+        if self.in_memory > self.threshold {
+            self.spill()?;
+        }
+        Ok(self.slots.len() - 1)
+    }
+
+    /// Write every item still in memory to the temporary file, one JSON line each
+    fn spill(&mut self) -> Result<()> {
+        if self.file.is_none() {
+            let dir = private_spill_dir()?;
+            let path = dir.join("spill.jsonl");
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let file = match options.open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    let _ = fs::remove_dir(&dir);
+                    return Err(e).with_context(|| format!("Failed to create spill file {}", path.display()));
+                }
+            };
+            self.file = Some((path, BufWriter::new(file), 0));
+        }
+        let (_, writer, offset) = self.file.as_mut().expect("spill file was just created");
+        let mut spilled = 0;
+        for slot in self.slots.iter_mut() {
+            if let Slot::Memory(item, size) = slot {
+                let mut line = serde_json::to_vec(item)?;
+                line.push(b'\n');
+                writer.write_all(&line)?;
+                spilled += *size;
+                *slot = Slot::Disk(*offset);
+                *offset += line.len() as u64;
+            }
+        }
+        writer.flush()?;
+        self.in_memory = 0;
+        lock(&budget().usage).spilled += spilled;
+        Ok(())
+    }
+
+    /// The item at `index`, read back from disk if it was spilled
+    pub fn get(&self, index: usize) -> Result<T> {
+        match &self.slots[index] {
+            Slot::Memory(item, _) => Ok(item.clone()),
+            Slot::Disk(offset) => {
+                let (path, _, _) = self.file.as_ref().expect("spilled items have a file");
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(*offset))?;
+                let mut line = String::new();
+                BufReader::new(file).read_line(&mut line)?;
+                Ok(serde_json::from_str(&line)?)
+            }
+        }
+    }
+}
+
+impl<T> Drop for Spill<T> {
+    fn drop(&mut self) {
+        if let Some((path, _, _)) = self.file.take() {
+            let _ = fs::remove_file(&path);
+            if let Some(dir) = path.parent() {
+                let _ = fs::remove_dir(dir);
+            }
+        }
+    }
+}
+
+/// A new directory in the temp dir that only the current user can open. It is created rather than reused, so a
+/// directory or symlink another user planted under the same name is skipped instead of written through
+fn private_spill_dir() -> Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    let mut attempts = 0;
+    loop {
+        let dir = std::env::temp_dir().join(format!(
+            "market_research_spill_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempts < 100 => attempts += 1,
+            Err(e) => return Err(e).with_context(|| format!("Failed to create spill directory {}", dir.display())),
+        }
+    }
+}
+
+/// Set the approximate memory batch operations (`export_many`, `rebuild_artifacts`, `reexport_all`, `export_site`)
+/// may use, in megabytes; 0 removes the limit. Past it, parallel work waits for memory to free up and large
+/// intermediate results go to temporary files, so big archives run slower instead of running out of memory
+#[pyfunction]
+pub fn set_memory_budget(megabytes: usize) -> PyResult<()> {
+    guard("set_memory_budget", || {
+        let budget = budget();
+        lock(&budget.usage).limit = megabytes.saturating_mul(MB);
+        budget.freed.notify_all();
+        Ok(())
+    })
+}
+
+/// The memory budget and how batch operations have used it: `limit_mb` (0 for none), `in_use_mb`, `peak_mb`,
+/// `waits` (times a task waited for memory) and `spilled_mb` (moved to temporary files)
+#[pyfunction]
+pub fn memory_usage(py: Python) -> PyResult<PyObject> {
+    guard("memory_usage", || {
+        let usage = lock(&budget().usage);
+        let dict = PyDict::new(py);
+        dict.set_item("limit_mb", usage.limit / MB)?;
+        dict.set_item("in_use_mb", usage.in_use as f64 / MB as f64)?;
+        dict.set_item("peak_mb", usage.peak as f64 / MB as f64)?;
+        dict.set_item("waits", usage.waits)?;
+        dict.set_item("spilled_mb", usage.spilled as f64 / MB as f64)?;
+        Ok(dict.into())
+    })
+}
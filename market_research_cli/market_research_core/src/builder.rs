@@ -0,0 +1,216 @@
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_yaml::{Mapping, Value};
+
+use crate::frontmatter::{compose, py_to_yaml};
+use crate::panics::{guard, lock};
+use crate::sections::parse_heading;
+
+/// A named part of the report being assembled
+#[derive(Clone, Debug)]
+struct Part {
+    name: String,
+    heading: String,
+    body: String,
+}
+
+#[derive(Default)]
+struct Parts {
+    title: Option<String>,
+    metadata: Mapping,
+    parts: Vec<Part>,
+}
+
+/// Split a leading heading off a body, as agents often start their section with one
+fn leading_heading(body: &str) -> Option<(String, String)> {
+    let trimmed = body.trim_start_matches(['\n', '\r']);
+    let (first, rest) = trimmed.split_once('\n').unwrap_or((trimmed, ""));
+    let (_, heading) = parse_heading(first)?;
+    Some((heading, rest.to_string()))
+}
+
+/// Headings in `body` shifted so the shallowest one is at `level`, capped at 6; fenced code is left alone
+fn shift_headings(body: &str, level: usize) -> String {
+    let mut in_fence = false;
+    let mut shallowest: Option<usize> = None;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some((found, _)) = parse_heading(line) {
+                shallowest = Some(shallowest.map_or(found, |shallowest: usize| shallowest.min(found)));
+            }
+        }
+    }
+    let shallowest = match shallowest {
+        Some(shallowest) => shallowest,
+        None => return body.to_string(),
+    };
+
+    in_fence = false;
+    body.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            match (in_fence, parse_heading(line)) {
+                (false, Some((found, text))) => {
+                    format!("{} {}", "#".repeat((found + level).saturating_sub(shallowest).min(6)), text)
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unknown_section(name: &str) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown section '{}'", name))
+}
+
+/// Assemble a report from named sections, e.g. the output of several agents. Sections keep the order they were
+/// added in unless moved; sections whose headings match (ignoring case) are merged under the first; and each
+/// section's own headings are shifted to sit below its heading, whatever levels the agent used
+#[pyclass]
+pub struct ReportBuilder {
+    state: Mutex<Parts>,
+}
+
+#[pymethods]
+impl ReportBuilder {
+    #[new]
+    #[pyo3(signature = (title=None, metadata=None))]
+    fn new(title: Option<String>, metadata: Option<&PyDict>) -> PyResult<Self> {
+        guard("ReportBuilder.new", || {
+            let mut parts = Parts { title, ..Parts::default() };
+            if let Some(metadata) = metadata {
+                for (key, value) in metadata.iter() {
+                    parts.metadata.insert(Value::String(key.str()?.to_string()), py_to_yaml(value)?);
+                }
+            }
+            Ok(ReportBuilder { state: Mutex::new(parts) })
+        })
+    }
+
+    /// Add a section, or replace the body of the one already called `name` in place. The heading defaults to the
+    /// body's own leading heading, then to `name`; `position` inserts it at that index instead of at the end
+    #[pyo3(signature = (name, body, heading=None, position=None))]
+    fn add_section(&self, name: &str, body: &str, heading: Option<String>, position: Option<usize>) -> PyResult<()> {
+        guard("ReportBuilder.add_section", || {
+            if name.trim().is_empty() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Section name cannot be empty"));
+            }
+            let (heading, body) = match (heading, leading_heading(body)) {
+                (Some(heading), Some((found, rest))) if found.eq_ignore_ascii_case(heading.trim()) => (heading, rest),
+                (Some(heading), _) => (heading, body.to_string()),
+                (None, Some((found, rest))) => (found, rest),
+                (None, None) => (name.to_string(), body.to_string()),
+            };
+            let part = Part { name: name.to_string(), heading: heading.trim().to_string(), body };
+
+            let mut state = lock(&self.state);
+            match state.parts.iter().position(|existing| existing.name == name) {
+                Some(index) => state.parts[index] = part,
+                None => {
+                    let index = position.unwrap_or(state.parts.len()).min(state.parts.len());
+                    state.parts.insert(index, part);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove a section, returning whether it existed
+    fn remove_section(&self, name: &str) -> PyResult<bool> {
+        guard("ReportBuilder.remove_section", || {
+            let mut state = lock(&self.state);
+            let before = state.parts.len();
+            state.parts.retain(|part| part.name != name);
+            Ok(state.parts.len() != before)
+        })
+    }
+
+    /// Move a section to `position`
+    fn move_section(&self, name: &str, position: usize) -> PyResult<()> {
+        guard("ReportBuilder.move_section", || {
+            let mut state = lock(&self.state);
+            let index = state.parts.iter().position(|part| part.name == name).ok_or_else(|| unknown_section(name))?;
+            let part = state.parts.remove(index);
+            let position = position.min(state.parts.len());
+            state.parts.insert(position, part);
+            Ok(())
+        })
+    }
+
+    /// Put the named sections first, in the given order; the others follow in their current order
+    fn reorder(&self, names: Vec<String>) -> PyResult<()> {
+        guard("ReportBuilder.reorder", || {
+            let mut state = lock(&self.state);
+            if let Some(name) = names.iter().find(|name| !state.parts.iter().any(|part| &part.name == *name)) {
+                return Err(unknown_section(name));
+            }
+            let rank = |part: &Part| names.iter().position(|name| *name == part.name).unwrap_or(names.len());
+            // Stable, so unlisted sections keep their relative order
+            state.parts.sort_by_key(rank);
+            Ok(())
+        })
+    }
+
+    /// Section names in report order
+    fn section_names(&self) -> PyResult<Vec<String>> {
+        guard("ReportBuilder.section_names", || {
+            Ok(lock(&self.state).parts.iter().map(|part| part.name.clone()).collect())
+        })
+    }
+
+    /// The assembled markdown: front matter (title and metadata), the title as `#`, then each section under a
+    /// heading one level below it (`#` without a title)
+    fn build(&self) -> PyResult<String> {
+        guard("ReportBuilder.build", || {
+            let state = lock(&self.state);
+            let level = if state.title.is_some() { 2 } else { 1 };
+
+            // Merge sections under repeated headings into the first one
+            let mut merged: Vec<Part> = Vec::new();
+            for part in &state.parts {
+                match merged.iter_mut().find(|existing| existing.heading.to_lowercase() == part.heading.to_lowercase()) {
+                    Some(existing) => {
+                        existing.body = format!("{}\n\n{}", existing.body.trim_end(), part.body.trim_start_matches('\n'));
+                    }
+                    None => merged.push(part.clone()),
+                }
+            }
+
+            let mut body = String::new();
+            if let Some(title) = &state.title {
+                body.push_str(&format!("# {}\n\n", title));
+            }
+            for part in &merged {
+                body.push_str(&format!("{} {}\n\n", "#".repeat(level), part.heading));
+                let section = shift_headings(part.body.trim_matches(['\n', '\r']).trim_end(), level + 1);
+                if !section.is_empty() {
+                    body.push_str(&section);
+                    body.push_str("\n\n");
+                }
+            }
+            let body = format!("{}\n", body.trim_end());
+
+            let mut metadata = Mapping::new();
+            if let Some(title) = &state.title {
+                metadata.insert(Value::String("title".to_string()), Value::String(title.clone()));
+            }
+            for (key, value) in &state.metadata {
+                if !metadata.contains_key(key) {
+                    metadata.insert(key.clone(), value.clone());
+                }
+            }
+            compose(&metadata, &body)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to build report: {}", e)))
+        })
+    }
+}
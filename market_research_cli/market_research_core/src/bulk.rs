@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde_yaml::Value;
+
+use crate::budget;
+use crate::frontmatter::{tags_of, update_front_matter};
+use crate::{lock_report, write_atomic};
+
+/// Aggregated outcome of a batch operation
+#[derive(Default)]
+pub struct BulkResult {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Run an operation over every target in parallel, collecting successes and failures
+pub fn run<F>(targets: &[String], op: F) -> BulkResult
+where
+    F: Fn(&str) -> Result<()> + Sync + Send,
+{
+    let outcomes: Vec<(String, Result<()>)> = targets
+        .par_iter()
+        .map(|filename| (filename.clone(), op(filename)))
+        .collect();
+
+    let mut result = BulkResult::default();
+    for (filename, outcome) in outcomes {
+        match outcome {
+            Ok(()) => result.succeeded.push(filename),
+            Err(e) => result.failed.push((filename, e.to_string())),
+        }
+    }
+    result
+}
+
+/// `run` for memory-heavy operations: each target first reserves `cost(target)` bytes of the memory budget, so
+/// fewer run at once when targets are large
+pub fn run_budgeted<C, F>(targets: &[String], cost: C, op: F) -> BulkResult
+where
+    C: Fn(&str) -> usize + Sync + Send,
+    F: Fn(&str) -> Result<()> + Sync + Send,
+{
+    run(targets, |target| {
+        let _reservation = budget::reserve(cost(target));
+        op(target)
+    })
+}
+
+/// Add and remove tags in a report's front matter, keeping the body intact
+pub fn retag(path: &Path, add: &[String], remove: &[String]) -> Result<()> {
+    let _lock = lock_report(path, true)?;
+    let content = fs::read_to_string(path)?;
+
+    let updated = update_front_matter(&content, |mapping| {
+        let mut tags = tags_of(mapping);
+        tags.retain(|tag| !remove.contains(tag));
+        for tag in add {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        mapping.insert(
+            Value::String("tags".to_string()),
+            Value::Sequence(tags.into_iter().map(Value::String).collect()),
+        );
+        Ok(())
+    })?;
+
+    if updated != content {
+        write_atomic(path, updated.as_bytes())?;
+    }
+    Ok(())
+}
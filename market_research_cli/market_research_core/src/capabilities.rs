@@ -0,0 +1,166 @@
+use std::process::Command;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::panics::guard;
+
+/// Optional subsystems chosen at build time (`cargo build --no-default-features --features ...`), with whether
+/// this build has them
+pub const FEATURES: &[(&str, bool)] = &[
+    ("charts", cfg!(feature = "charts")),
+    ("highlighting", cfg!(feature = "highlighting")),
+    ("history", cfg!(feature = "history")),
+    ("http", cfg!(feature = "http")),
+    ("server", cfg!(feature = "server")),
+];
+
+/// Capabilities provided by external programs rather than features
+const BACKENDS: &[&str] = &["pdf_export", "pdf_import", "mermaid_pdf"];
+
+/// Names of the optional subsystems compiled into this build
+pub fn enabled_features() -> Vec<&'static str> {
+    FEATURES.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect()
+}
+
+pyo3::create_exception!(
+    market_research_core,
+    CapabilityError,
+    pyo3::exceptions::PyRuntimeError,
+    "A capability this call needs is missing; `capability`, `reason` and `hint` say which, why and how to get it"
+);
+
+/// A capability that is not available, and how to get it
+pub struct Missing {
+    pub capability: String,
+    pub reason: String,
+    pub hint: String,
+}
+
+impl Missing {
+    pub fn message(&self) -> String {
+        format!("{} is not available: {}. {}", self.capability, self.reason, self.hint)
+    }
+
+    /// As a `CapabilityError` with `capability`, `reason`, `hint` and `missing` attributes
+    pub fn to_py(&self) -> PyErr {
+        missing_error(std::slice::from_ref(self))
+    }
+}
+
+/// One `CapabilityError` for everything missing, with the first as `capability` and all names in `missing`
+fn missing_error(missing: &[Missing]) -> PyErr {
+    let message = missing.iter().map(Missing::message).collect::<Vec<_>>().join("\n");
+    let error = CapabilityError::new_err(message);
+    Python::with_gil(|py| {
+        let value = error.value(py);
+        let first = &missing[0];
+        let names: Vec<&str> = missing.iter().map(|missing| missing.capability.as_str()).collect();
+        // Setting attributes on a fresh exception instance cannot fail in practice
+        let _ = value.setattr("capability", &first.capability);
+        let _ = value.setattr("reason", &first.reason);
+        let _ = value.setattr("hint", &first.hint);
+        let _ = value.setattr("missing", names);
+    });
+    error
+}
+
+/// A compiled-out feature as `Missing`
+fn feature(name: &str) -> Missing {
+    Missing {
+        capability: name.to_string(),
+        reason: format!("market_research_core was built without the '{}' feature", name),
+        hint: format!("Rebuild it with --features {} (or with the default features)", name),
+    }
+}
+
+/// The program an external capability needs, the flag that makes it print its version, and how to install it
+fn backend(capability: &str) -> Option<(&'static str, &'static str, &'static str)> {
+    match capability {
+        "pdf_export" => Some((
+            "wkhtmltopdf",
+            "--version",
+            "Install wkhtmltopdf (https://wkhtmltopdf.org/downloads.html, apt install wkhtmltopdf or brew install wkhtmltopdf)",
+        )),
+        "pdf_import" => Some(("pdftotext", "-v", "Install poppler-utils (apt install poppler-utils or brew install poppler)")),
+        "mermaid_pdf" => Some(("mmdc", "--version", "Install mermaid-cli (npm install -g @mermaid-js/mermaid-cli)")),
+        _ => None,
+    }
+}
+
+/// `Missing` for an external capability (`pdf_export`, `pdf_import`, `mermaid_pdf`) whose program is not installed
+pub fn not_installed(capability: &str) -> Missing {
+    let (program, _, hint) = backend(capability).unwrap_or((capability, "", ""));
+    Missing {
+        capability: capability.to_string(),
+        reason: format!("{} was not found on PATH", program),
+        hint: hint.to_string(),
+    }
+}
+
+/// Why `capability` is unavailable, or `None` if it is available; `Err` for unknown names
+pub fn check(capability: &str) -> Result<Option<Missing>, String> {
+    if let Some((_, enabled)) = FEATURES.iter().find(|(name, _)| *name == capability) {
+        return Ok((!enabled).then(|| feature(capability)));
+    }
+    match backend(capability) {
+        Some((program, flag, _)) => Ok(backend_version(program, flag).is_none().then(|| not_installed(capability))),
+        None => {
+            let known: Vec<&str> = FEATURES.iter().map(|(name, _)| *name).chain(BACKENDS.iter().copied()).collect();
+            Err(format!("Unknown capability '{}'. Expected one of: {}", capability, known.join(", ")))
+        }
+    }
+}
+
+/// Error for using a subsystem this build was compiled without
+#[cfg(any(not(feature = "highlighting"), not(feature = "history")))]
+pub fn feature_missing(name: &str) -> anyhow::Error {
+    anyhow::anyhow!(feature(name).message())
+}
+
+/// `feature_missing` as a `CapabilityError`
+pub fn feature_missing_py(name: &str) -> PyErr {
+    feature(name).to_py()
+}
+
+/// First line an external program prints about its version, if it runs at all
+pub fn backend_version(program: &str, flag: &str) -> Option<String> {
+    let output = Command::new(program).arg(flag).output().ok()?;
+    // pdftotext prints its version to stderr
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    text.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string)
+}
+
+/// What this installation can do, as `{name: bool}`: the compiled-in features (`charts`, `highlighting`, `history`,
+/// `http`, `server`) and the external converters found on PATH (`pdf_export` needs wkhtmltopdf, `pdf_import` pdftotext,
+/// `mermaid_pdf` mmdc), so callers can hide or skip what is missing instead of failing mid-run
+#[pyfunction]
+pub fn capabilities(py: Python) -> PyResult<PyObject> {
+    guard("capabilities", || {
+        let names: Vec<&str> = FEATURES.iter().map(|(name, _)| *name).chain(BACKENDS.iter().copied()).collect();
+        let available: Vec<bool> = py.allow_threads(|| {
+            names.iter().map(|name| matches!(check(name), Ok(None))).collect()
+        });
+        let dict = PyDict::new(py);
+        for (name, available) in names.iter().zip(available) {
+            dict.set_item(name, available)?;
+        }
+        Ok(dict.into())
+    })
+}
+
+/// Check up front that every named capability (see `capabilities()`) is available, raising one `CapabilityError`
+/// that covers all missing ones; unknown names are a ValueError
+#[pyfunction]
+pub fn require(capabilities: Vec<String>, py: Python) -> PyResult<()> {
+    guard("require", || {
+        let checked = py
+            .allow_threads(|| capabilities.iter().map(|capability| check(capability)).collect::<Result<Vec<_>, _>>())
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        let missing: Vec<Missing> = checked.into_iter().flatten().collect();
+        match missing.is_empty() {
+            true => Ok(()),
+            false => Err(missing_error(&missing)),
+        }
+    })
+}
@@ -0,0 +1,435 @@
+#[cfg(feature = "charts")]
+use pyo3::prelude::*;
+
+#[cfg(feature = "charts")]
+use crate::panics::guard;
+use crate::tables::{find_tables, parse_number, MarkdownTable};
+
+/// Colors used for chart series, in order
+pub const PALETTE: [&str; 8] = [
+    "#2f6fdf", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#edc948", "#b07aa1", "#9c755f",
+];
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 360.0;
+const MARGIN_LEFT: f64 = 64.0;
+const MARGIN_RIGHT: f64 = 24.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 56.0;
+
+/// Supported chart types
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChartType {
+    Bar,
+    Line,
+    Pie,
+}
+
+impl ChartType {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "bar" | "column" => Some(ChartType::Bar),
+            "line" => Some(ChartType::Line),
+            "pie" => Some(ChartType::Pie),
+            _ => None,
+        }
+    }
+}
+
+/// How charts are embedded for tables marked with `<!-- chart -->`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChartMode {
+    None,
+    Beside,
+    Replace,
+}
+
+impl ChartMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "none" | "" => Some(ChartMode::None),
+            "beside" => Some(ChartMode::Beside),
+            "replace" => Some(ChartMode::Replace),
+            _ => None,
+        }
+    }
+}
+
+/// Labels plus one or more numeric series extracted from a table
+struct ChartData {
+    title: String,
+    labels: Vec<String>,
+    series: Vec<(String, Vec<f64>)>,
+}
+
+/// Escape text for use inside SVG/XML content and attributes
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Format a value compactly for axis ticks and labels (e.g. 4.2M)
+pub fn compact_number(value: f64) -> String {
+    let abs = value.abs();
+    let (scaled, suffix) = if abs >= 1e12 {
+        (value / 1e12, "T")
+    } else if abs >= 1e9 {
+        (value / 1e9, "B")
+    } else if abs >= 1e6 {
+        (value / 1e6, "M")
+    } else if abs >= 1e3 {
+        (value / 1e3, "K")
+    } else {
+        (value, "")
+    };
+
+    let formatted = format!("{:.1}", scaled);
+    let formatted = formatted.strip_suffix(".0").unwrap_or(&formatted);
+    format!("{}{}", formatted, suffix)
+}
+
+/// Round the axis maximum up to a readable value
+fn nice_ceiling(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    let normalized = value / magnitude;
+    let nice = if normalized <= 1.0 {
+        1.0
+    } else if normalized <= 2.0 {
+        2.0
+    } else if normalized <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Use the first column as labels and every fully numeric column as a series
+fn chart_data(table: &MarkdownTable) -> Result<ChartData, String> {
+    if table.header.len() < 2 {
+        return Err("Chart tables need a label column and at least one value column".to_string());
+    }
+    if table.rows.is_empty() {
+        return Err("Chart table has no data rows".to_string());
+    }
+
+    let labels: Vec<String> = table.rows.iter().map(|row| row[0].replace("**", "")).collect();
+    let mut series = Vec::new();
+    for col in 1..table.header.len() {
+        let values: Option<Vec<f64>> = table
+            .rows
+            .iter()
+            .map(|row| parse_number(&row[col]).map(|n| n.value))
+            .collect();
+        if let Some(values) = values {
+            series.push((table.header[col].clone(), values));
+        }
+    }
+
+    if series.is_empty() {
+        return Err("Chart table has no numeric columns".to_string());
+    }
+
+    let title = if series.len() == 1 {
+        series[0].0.clone()
+    } else {
+        table.header[0].clone()
+    };
+
+    Ok(ChartData { title, labels, series })
+}
+
+fn svg_open(out: &mut String, title: &str) {
+    out.push_str(&format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="Arial, sans-serif" font-size="11">"#,
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    ));
+    out.push_str(&format!(
+        r##"<rect width="100%" height="100%" fill="#ffffff"/><text x="{}" y="22" font-size="14" font-weight="bold" text-anchor="middle" fill="#333">{}</text>"##,
+        CHART_WIDTH / 2.0,
+        escape_xml(title)
+    ));
+}
+
+fn svg_legend(out: &mut String, data: &ChartData) {
+    if data.series.len() < 2 {
+        return;
+    }
+    let mut x = MARGIN_LEFT;
+    let y = CHART_HEIGHT - 14.0;
+    for (idx, (name, _)) in data.series.iter().enumerate() {
+        out.push_str(&format!(
+            r##"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{}"/><text x="{:.1}" y="{:.1}" fill="#333">{}</text>"##,
+            x,
+            y - 9.0,
+            PALETTE[idx % PALETTE.len()],
+            x + 14.0,
+            y,
+            escape_xml(name)
+        ));
+        x += 24.0 + name.chars().count() as f64 * 6.5;
+    }
+}
+
+/// Draw gridlines and y-axis ticks; returns the function mapping values to y coordinates
+fn svg_axes(out: &mut String, data: &ChartData) -> impl Fn(f64) -> f64 {
+    let all = data.series.iter().flat_map(|(_, values)| values.iter().copied());
+    let (min, max) = all.fold((0.0_f64, 0.0_f64), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let top = nice_ceiling(max);
+    let bottom = if min < 0.0 { -nice_ceiling(-min) } else { 0.0 };
+
+    let plot_top = MARGIN_TOP;
+    let plot_bottom = CHART_HEIGHT - MARGIN_BOTTOM;
+    let scale = move |v: f64| plot_bottom - (v - bottom) / (top - bottom) * (plot_bottom - plot_top);
+
+    const TICKS: usize = 5;
+    for i in 0..=TICKS {
+        let value = bottom + (top - bottom) * i as f64 / TICKS as f64;
+        let y = scale(value);
+        out.push_str(&format!(
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#e5e5e5"/><text x="{:.1}" y="{:.1}" text-anchor="end" fill="#666">{}</text>"##,
+            MARGIN_LEFT,
+            y,
+            CHART_WIDTH - MARGIN_RIGHT,
+            y,
+            MARGIN_LEFT - 6.0,
+            y + 4.0,
+            compact_number(value)
+        ));
+    }
+    out.push_str(&format!(
+        r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#999"/>"##,
+        MARGIN_LEFT,
+        scale(0.0),
+        CHART_WIDTH - MARGIN_RIGHT,
+        scale(0.0)
+    ));
+
+    scale
+}
+
+fn svg_category_labels(out: &mut String, labels: &[String], slot: f64) {
+    for (idx, label) in labels.iter().enumerate() {
+        let x = MARGIN_LEFT + slot * (idx as f64 + 0.5);
+        let text: String = label.chars().take(14).collect();
+        out.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#333">{}</text>"##,
+            x,
+            CHART_HEIGHT - MARGIN_BOTTOM + 16.0,
+            escape_xml(&text)
+        ));
+    }
+}
+
+fn render_bar(data: &ChartData) -> String {
+    let mut out = String::new();
+    svg_open(&mut out, &data.title);
+    let scale = svg_axes(&mut out, data);
+
+    let slot = (CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT) / data.labels.len() as f64;
+    let group_width = slot * 0.7;
+    let bar_width = group_width / data.series.len() as f64;
+    for (s_idx, (_, values)) in data.series.iter().enumerate() {
+        for (idx, value) in values.iter().enumerate() {
+            let x = MARGIN_LEFT + slot * idx as f64 + (slot - group_width) / 2.0 + bar_width * s_idx as f64;
+            let (y0, y1) = (scale(0.0), scale(*value));
+            out.push_str(&format!(
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{}</title></rect>"#,
+                x,
+                y0.min(y1),
+                bar_width,
+                (y0 - y1).abs(),
+                PALETTE[s_idx % PALETTE.len()],
+                compact_number(*value)
+            ));
+        }
+    }
+
+    svg_category_labels(&mut out, &data.labels, slot);
+    svg_legend(&mut out, data);
+    out.push_str("</svg>");
+    out
+}
+
+fn render_line(data: &ChartData) -> String {
+    let mut out = String::new();
+    svg_open(&mut out, &data.title);
+    let scale = svg_axes(&mut out, data);
+
+    let slot = (CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT) / data.labels.len() as f64;
+    for (s_idx, (_, values)) in data.series.iter().enumerate() {
+        let color = PALETTE[s_idx % PALETTE.len()];
+        let points: Vec<String> = values
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| format!("{:.1},{:.1}", MARGIN_LEFT + slot * (idx as f64 + 0.5), scale(*v)))
+            .collect();
+        out.push_str(&format!(
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+            points.join(" "),
+            color
+        ));
+        for (idx, value) in values.iter().enumerate() {
+            out.push_str(&format!(
+                r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{}"><title>{}</title></circle>"#,
+                MARGIN_LEFT + slot * (idx as f64 + 0.5),
+                scale(*value),
+                color,
+                compact_number(*value)
+            ));
+        }
+    }
+
+    svg_category_labels(&mut out, &data.labels, slot);
+    svg_legend(&mut out, data);
+    out.push_str("</svg>");
+    out
+}
+
+fn render_pie(data: &ChartData) -> Result<String, String> {
+    let values = &data.series[0].1;
+    if values.iter().any(|v| *v < 0.0) {
+        return Err("Pie charts cannot show negative values".to_string());
+    }
+    let total: f64 = values.iter().sum();
+    if total <= 0.0 {
+        return Err("Pie chart values sum to zero".to_string());
+    }
+
+    let mut out = String::new();
+    svg_open(&mut out, &data.series[0].0);
+
+    let (cx, cy, r) = (CHART_WIDTH * 0.35, CHART_HEIGHT / 2.0 + 10.0, 130.0);
+    let mut angle = -std::f64::consts::FRAC_PI_2;
+    for (idx, value) in values.iter().enumerate() {
+        let color = PALETTE[idx % PALETTE.len()];
+        let sweep = value / total * std::f64::consts::TAU;
+        if sweep >= std::f64::consts::TAU - 1e-9 {
+            out.push_str(&format!(r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"/>"#, cx, cy, r, color));
+        } else if sweep > 0.0 {
+            let (x1, y1) = (cx + r * angle.cos(), cy + r * angle.sin());
+            let (x2, y2) = (cx + r * (angle + sweep).cos(), cy + r * (angle + sweep).sin());
+            out.push_str(&format!(
+                r#"<path d="M{:.1},{:.1} L{:.1},{:.1} A{:.1},{:.1} 0 {} 1 {:.1},{:.1} Z" fill="{}"><title>{}</title></path>"#,
+                cx,
+                cy,
+                x1,
+                y1,
+                r,
+                r,
+                if sweep > std::f64::consts::PI { 1 } else { 0 },
+                x2,
+                y2,
+                color,
+                compact_number(*value)
+            ));
+        }
+        angle += sweep;
+
+        let ly = MARGIN_TOP + 20.0 + idx as f64 * 18.0;
+        out.push_str(&format!(
+            r##"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{}"/><text x="{:.1}" y="{:.1}" fill="#333">{} ({:.1}%)</text>"##,
+            CHART_WIDTH * 0.65,
+            ly - 9.0,
+            color,
+            CHART_WIDTH * 0.65 + 16.0,
+            ly,
+            escape_xml(&data.labels[idx]),
+            value / total * 100.0
+        ));
+    }
+
+    out.push_str("</svg>");
+    Ok(out)
+}
+
+/// Render a parsed markdown table as an SVG chart
+pub fn render_chart(table: &MarkdownTable, chart_type: ChartType) -> Result<String, String> {
+    let data = chart_data(table)?;
+    match chart_type {
+        ChartType::Bar => Ok(render_bar(&data)),
+        ChartType::Line => Ok(render_line(&data)),
+        ChartType::Pie => render_pie(&data),
+    }
+}
+
+/// Parse a `<!-- chart -->` or `<!-- chart: line -->` marker line
+fn chart_marker(line: &str) -> Option<ChartType> {
+    let inner = line.trim().strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+    let rest = inner.strip_prefix("chart")?.trim();
+    if rest.is_empty() {
+        return Some(ChartType::Bar);
+    }
+    ChartType::parse(rest.strip_prefix(':')?)
+}
+
+/// Insert charts next to, or in place of, tables preceded by a chart marker
+pub fn embed_charts(markdown: &str, mode: ChartMode) -> String {
+    if mode == ChartMode::None {
+        return markdown.to_string();
+    }
+
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut cursor = 0;
+
+    for table in find_tables(markdown) {
+        let marker = lines[..table.start_line]
+            .iter()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| chart_marker(line));
+        let chart_type = match marker {
+            Some(chart_type) => chart_type,
+            None => continue,
+        };
+        let svg = match render_chart(&table, chart_type) {
+            Ok(svg) => svg,
+            Err(_) => continue,
+        };
+
+        let table_lines = &lines[table.start_line..table.end_line];
+        out.extend(lines[cursor..table.start_line].iter().map(|l| l.to_string()));
+        if mode == ChartMode::Beside {
+            out.extend(table_lines.iter().map(|l| l.to_string()));
+            out.push(String::new());
+        }
+        out.push(format!("<div class=\"chart\">{}</div>", svg));
+        out.push(String::new());
+        cursor = table.end_line;
+    }
+
+    out.extend(lines[cursor..].iter().map(|l| l.to_string()));
+    // `lines()` drops the final newline; put it back so the document still ends the way it did
+    let mut embedded = out.join("\n");
+    if markdown.ends_with('\n') && !embedded.ends_with('\n') {
+        embedded.push('\n');
+    }
+    embedded
+}
+
+/// Convert a simple or grouped markdown table into an SVG chart
+#[cfg(feature = "charts")]
+#[pyfunction]
+#[pyo3(signature = (markdown_table, chart_type="bar"))]
+pub fn table_to_chart(markdown_table: &str, chart_type: &str) -> PyResult<String> {
+    guard("table_to_chart", || {
+        let chart_type = ChartType::parse(chart_type).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Unknown chart type '{}'. Expected bar, line or pie", chart_type)
+            )
+        })?;
+
+        let table = find_tables(markdown_table).into_iter().next().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>("No markdown table found in input")
+        })?;
+
+        render_chart(&table, chart_type).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+    })
+}
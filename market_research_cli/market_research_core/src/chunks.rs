@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+
+use crate::lock_report;
+use crate::panics::{guard, lock};
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+struct ChunkState {
+    reader: Option<BufReader<File>>,
+    /// Bytes of a UTF-8 character split across the previous chunk boundary
+    carry: Vec<u8>,
+}
+
+/// Iterator over a report's text in chunks of roughly `chunk_size` bytes, never splitting a character
+#[pyclass]
+pub struct ReportChunks {
+    state: Mutex<ChunkState>,
+    chunk_size: usize,
+}
+
+impl ReportChunks {
+    /// Open a report for chunked reading. Saves replace files by rename, so the open handle keeps
+    /// reading a consistent version even if the report is rewritten mid-iteration.
+    pub fn open(path: &Path, chunk_size: usize) -> Result<Self> {
+        if chunk_size == 0 {
+            return Err(anyhow!("chunk_size must be positive"));
+        }
+        let file = {
+            let _lock = lock_report(path, false)?;
+            File::open(path)?
+        };
+        Ok(ReportChunks {
+            state: Mutex::new(ChunkState { reader: Some(BufReader::new(file)), carry: Vec::new() }),
+            chunk_size,
+        })
+    }
+
+    fn next_chunk(&self) -> Result<Option<String>> {
+        let mut state = lock(&self.state);
+        let mut buffer = std::mem::take(&mut state.carry);
+
+        while let Some(reader) = state.reader.as_mut() {
+            let wanted = self.chunk_size.saturating_sub(buffer.len()).max(1);
+            let read = reader.by_ref().take(wanted as u64).read_to_end(&mut buffer)?;
+            if read < wanted {
+                state.reader = None;
+            }
+
+            match std::str::from_utf8(&buffer) {
+                Ok(_) => break,
+                // Incomplete character at the end: hold it back for the next chunk
+                Err(e) if e.error_len().is_none() && state.reader.is_some() => {
+                    if e.valid_up_to() > 0 {
+                        state.carry = buffer.split_off(e.valid_up_to());
+                        break;
+                    }
+                }
+                Err(e) => return Err(anyhow!("Report is not valid UTF-8: {}", e)),
+            }
+        }
+
+        if buffer.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8(buffer)?))
+    }
+}
+
+#[pymethods]
+impl ReportChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python) -> PyResult<Option<String>> {
+        guard("ReportChunks.__next__", || {
+            py.allow_threads(|| self.next_chunk())
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read report chunk: {}", e)))
+        })
+    }
+
+    /// Release the file handle before the iterator is exhausted
+    fn close(&self) -> PyResult<()> {
+        guard("ReportChunks.close", || {
+            let mut state = lock(&self.state);
+            state.reader = None;
+            state.carry.clear();
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,155 @@
+use regex::{Captures, Regex};
+use serde_yaml::{Mapping, Value};
+
+use crate::charts::escape_xml;
+use crate::frontmatter::{front_matter_mapping, mapping_str};
+use crate::render::map_outside_fences;
+
+/// One entry of the front matter `sources:` list
+#[derive(Clone, Debug, Default)]
+pub struct Source {
+    pub id: String,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub date: Option<String>,
+}
+
+impl Source {
+    /// A source from its mapping, which needs an `id`
+    fn from_mapping(mapping: &Mapping) -> Option<Self> {
+        Some(Source {
+            id: mapping_str(mapping, "id")?,
+            title: mapping_str(mapping, "title"),
+            url: mapping_str(mapping, "url"),
+            author: mapping_str(mapping, "author"),
+            publisher: mapping_str(mapping, "publisher"),
+            date: mapping_str(mapping, "date"),
+        })
+    }
+
+    /// The reference as plain text, e.g. "Gartner. Widget Forecast. Gartner Inc., 2024"
+    fn summary(&self) -> String {
+        let parts: Vec<&str> = [&self.author, &self.title, &self.publisher, &self.date]
+            .into_iter()
+            .filter_map(|part| part.as_deref())
+            .collect();
+        match parts.is_empty() {
+            true => self.url.clone().unwrap_or_else(|| self.id.clone()),
+            false => parts.join(". "),
+        }
+    }
+
+    /// The reference as a list item body, the title emphasised and the URL linked
+    fn to_html(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(author) = &self.author {
+            parts.push(escape_xml(author));
+        }
+        if let Some(title) = &self.title {
+            parts.push(format!("<em>{}</em>", escape_xml(title)));
+        }
+        if let Some(publisher) = &self.publisher {
+            parts.push(escape_xml(publisher));
+        }
+        if let Some(date) = &self.date {
+            parts.push(escape_xml(date));
+        }
+        if parts.is_empty() && self.url.is_none() {
+            parts.push(escape_xml(&self.id));
+        }
+        let mut entry = parts.join(". ");
+        if let Some(url) = &self.url {
+            if !entry.is_empty() {
+                entry.push_str(". ");
+            }
+            entry.push_str(&format!("<a href=\"{0}\">{0}</a>", escape_xml(url)));
+        }
+        entry
+    }
+}
+
+/// The `sources:` list of a report's front matter; entries without an `id` are skipped
+pub fn sources(markdown: &str) -> Vec<Source> {
+    let mapping = match front_matter_mapping(markdown) {
+        Ok((mapping, _)) => mapping,
+        Err(_) => return Vec::new(),
+    };
+    match mapping.get("sources") {
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::Mapping(entry) => Source::from_mapping(entry),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn citation_pattern() -> Regex {
+    Regex::new(r"\[(@[\w.:/#+-]+(?:\s*;\s*@[\w.:/#+-]+)*)\]").unwrap()
+}
+
+/// Replace `[@id]` and `[@a; @b]` citations of front matter sources with numbered links to a references section
+/// appended to the report, each link showing its reference on hover. Unknown ids, and citations in reports
+/// without `sources:`, are left as written
+pub fn resolve_citations(markdown: &str) -> String {
+    let sources = sources(markdown);
+    if sources.is_empty() {
+        return markdown.to_string();
+    }
+    let pattern = citation_pattern();
+    let mut cited: Vec<usize> = Vec::new();
+
+    let resolved = map_outside_fences(markdown, |line| {
+        pattern
+            .replace_all(line, |caps: &Captures| {
+                // `[@handle](url)` is a link, not a citation
+                if line[caps.get(0).unwrap().end()..].starts_with('(') {
+                    return caps[0].to_string();
+                }
+                let found: Option<Vec<usize>> = caps[1]
+                    .split(';')
+                    .map(|id| sources.iter().position(|source| source.id == id.trim().trim_start_matches('@')))
+                    .collect();
+                let found = match found {
+                    Some(found) => found,
+                    None => return caps[0].to_string(),
+                };
+                let links: Vec<String> = found
+                    .into_iter()
+                    .map(|index| {
+                        let number = match cited.iter().position(|cited| *cited == index) {
+                            Some(position) => position + 1,
+                            None => {
+                                cited.push(index);
+                                cited.len()
+                            }
+                        };
+                        format!(
+                            "<a href=\"#ref-{0}\" title=\"{1}\">{0}</a>",
+                            number,
+                            escape_xml(&sources[index].summary())
+                        )
+                    })
+                    .collect();
+                format!("<sup class=\"citation\">[{}]</sup>", links.join(", "))
+            })
+            .into_owned()
+    });
+
+    if cited.is_empty() {
+        return resolved;
+    }
+
+    // References in citation order
+    let mut out = resolved.trim_end().to_string();
+    out.push_str("\n\n<section class=\"references\">\n<h2 class=\"references-title\">References</h2>\n<ol>\n");
+    for (position, index) in cited.iter().enumerate() {
+        out.push_str(&format!("<li id=\"ref-{}\">{}</li>\n", position + 1, sources[*index].to_html()));
+    }
+    out.push_str("</ol>\n</section>\n");
+    out
+}
@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+
+use crate::capabilities::{backend_version, enabled_features};
+use crate::panics::guard;
+use crate::{backup, history, index, lock_report, progress, write_atomic};
+
+/// Upgrade of a format's raw JSON from one schema version to the next
+type Step = fn(&mut Value) -> Result<()>;
+
+/// Report index upgrades, keyed by the version each one starts from. Add a step here whenever
+/// `INDEX_SCHEMA_VERSION` is raised, so directories written by older builds keep loading
+const INDEX_MIGRATIONS: &[(u32, Step)] = &[];
+
+/// Outcome of migrating one on-disk format
+pub struct Migration {
+    pub format: &'static str,
+    pub file: &'static str,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub migrated: bool,
+}
+
+/// Bring `value` up to `current` in place, returning the version it had. A version newer than `current` was
+/// written by a newer build, and is refused rather than read and later overwritten without its new fields
+fn upgrade(format: &str, value: &mut Value, current: u32, steps: &[(u32, Step)]) -> Result<u32> {
+    let found = value.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if found > current {
+        return Err(anyhow!(
+            "The {} has schema version {}, newer than this build supports ({}); upgrade market_research_core before \
+             using this directory",
+            format,
+            found,
+            current
+        ));
+    }
+    let mut version = found;
+    while version < current {
+        let step = match steps.iter().find(|(from, _)| *from == version) {
+            Some((_, step)) => step,
+            None => return Err(anyhow!("No migration for the {} from schema version {}", format, version)),
+        };
+        step(value).with_context(|| format!("Failed to migrate the {} from schema version {}", format, version))?;
+        version += 1;
+        value["schema_version"] = Value::from(version);
+    }
+    Ok(found)
+}
+
+/// Bring raw report index JSON up to the current schema
+pub fn upgrade_index(value: &mut Value) -> Result<u32> {
+    upgrade("report index", value, index::INDEX_SCHEMA_VERSION, INDEX_MIGRATIONS)
+}
+
+/// Rewrite the report index in the current schema if an older build wrote it; `dry_run` only reports
+pub fn migrate_index(reports_dir: &str, dry_run: bool) -> Result<Migration> {
+    let path = Path::new(reports_dir).join(index::INDEX_FILE);
+    let mut migration = Migration {
+        format: "index",
+        file: index::INDEX_FILE,
+        from_version: index::INDEX_SCHEMA_VERSION,
+        to_version: index::INDEX_SCHEMA_VERSION,
+        migrated: false,
+    };
+    if !path.exists() {
+        return Ok(migration);
+    }
+
+    let _lock = lock_report(&path, true)?;
+    let bytes = fs::read(&path).context("Failed to read report index")?;
+    let mut value: Value = serde_json::from_slice(&bytes).context("Report index is corrupted")?;
+    migration.from_version = upgrade_index(&mut value)?;
+    if migration.from_version < migration.to_version && !dry_run {
+        write_atomic(&path, &serde_json::to_vec_pretty(&value)?)?;
+        migration.migrated = true;
+    }
+    Ok(migration)
+}
+
+/// Crate version, compiled-in features, which external backends are available, and the schema versions of
+/// every versioned file format this build reads and writes
+#[pyfunction]
+pub fn core_info(py: Python) -> PyResult<PyObject> {
+    guard("core_info", || {
+        let (wkhtmltopdf, pdftotext) =
+            py.allow_threads(|| (backend_version("wkhtmltopdf", "--version"), backend_version("pdftotext", "-v")));
+
+        let backends = PyDict::new(py);
+        backends.set_item("wkhtmltopdf", wkhtmltopdf)?;
+        backends.set_item("pdftotext", pdftotext)?;
+        backends.set_item("libgit2", history::libgit2_version())?;
+
+        let schemas = PyDict::new(py);
+        schemas.set_item("index", index::INDEX_SCHEMA_VERSION)?;
+        schemas.set_item("backup", backup::BACKUP_FORMAT_VERSION)?;
+        schemas.set_item("progress_state", progress::STATE_VERSION)?;
+
+        let dict = PyDict::new(py);
+        dict.set_item("version", env!("CARGO_PKG_VERSION"))?;
+        dict.set_item("features", enabled_features())?;
+        dict.set_item("backends", backends)?;
+        dict.set_item("schemas", schemas)?;
+        Ok(dict.into())
+    })
+}
@@ -0,0 +1,242 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+
+use crate::capabilities;
+use crate::joblog;
+use crate::sandbox::{self, Sandbox};
+
+/// Extensions `convert_document` understands
+pub const DOCUMENT_EXTENSIONS: [&str; 7] = ["md", "markdown", "txt", "html", "htm", "docx", "pdf"];
+
+/// A document converted to markdown, with a title if the format carries one
+pub struct Converted {
+    pub markdown: String,
+    pub title: Option<String>,
+}
+
+/// Lowercased extension of a path
+pub fn extension_of(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase()
+}
+
+/// Convert a legacy document to markdown based on its extension; `sandbox` restricts external converters
+pub fn convert_document(path: &Path, sandbox: Option<&Sandbox>) -> Result<Converted> {
+    match extension_of(path).as_str() {
+        "md" | "markdown" => Ok(Converted { markdown: fs::read_to_string(path)?, title: None }),
+        "txt" => Ok(text_to_markdown(&fs::read_to_string(path)?)),
+        "html" | "htm" => Ok(html_to_markdown(&fs::read_to_string(path)?)),
+        "docx" => docx_to_markdown(path),
+        "pdf" => pdf_to_markdown(path, sandbox),
+        other => Err(anyhow!("Unsupported document type: .{}", other)),
+    }
+}
+
+/// Use the first short line as the title when it looks like one
+fn first_line_title(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line.trim_start_matches('#').trim();
+    (!line.is_empty() && line.chars().count() <= 120 && !line.ends_with('.')).then(|| line.to_string())
+}
+
+fn text_to_markdown(text: &str) -> Converted {
+    let text = text.replace("\r\n", "\n");
+    Converted { title: first_line_title(&text), markdown: text }
+}
+
+/// Convert an HTML document to markdown, taking the title from `<title>` when present
+pub fn html_to_markdown(html: &str) -> Converted {
+    let title_tag = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    let title = title_tag
+        .captures(html)
+        .map(|caps| html_unescape(caps[1].trim()))
+        .filter(|title| !title.is_empty());
+
+    // Drop head, scripts and styles so only the visible document is converted
+    let noise = Regex::new(r"(?is)<(head|script|style|noscript)\b.*?</(head|script|style|noscript)>").unwrap();
+    let markdown = html2md::parse_html(&noise.replace_all(html, ""));
+    let markdown = Regex::new(r"\n{3,}").unwrap().replace_all(markdown.trim(), "\n\n").to_string();
+
+    Converted { title: title.or_else(|| first_line_title(&markdown)), markdown }
+}
+
+/// Decode the handful of entities common in titles and attributes
+pub fn html_unescape(text: &str) -> String {
+    text.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+}
+
+/// Value of the `w:val` attribute on an element such as `<w:pStyle w:val="Heading1"/>`
+fn val_attribute(element: &BytesStart) -> Option<String> {
+    element
+        .attributes()
+        .filter_map(|attr| attr.ok())
+        .find(|attr| attr.key.local_name().as_ref() == b"val")
+        .and_then(|attr| attr.unescape_value().ok().map(|v| v.to_string()))
+}
+
+/// Paragraph prefix for a Word paragraph style
+fn style_prefix(style: &str) -> String {
+    let lower = style.to_lowercase();
+    if lower == "title" {
+        return "# ".to_string();
+    }
+    match lower.strip_prefix("heading").and_then(|level| level.trim().parse::<usize>().ok()) {
+        Some(level @ 1..=5) => format!("{} ", "#".repeat(level + 1)),
+        Some(_) => "###### ".to_string(),
+        None if lower.contains("list") => "- ".to_string(),
+        None => String::new(),
+    }
+}
+
+fn read_zip_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut text = String::new();
+    entry.read_to_string(&mut text)?;
+    Ok(Some(text))
+}
+
+/// Convert a .docx package: headings, lists, paragraphs and tables from `word/document.xml`
+fn docx_to_markdown(path: &Path) -> Result<Converted> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?).context("Not a valid .docx file")?;
+    let document = read_zip_entry(&mut archive, "word/document.xml")?
+        .ok_or_else(|| anyhow!("Missing word/document.xml"))?;
+    let core_title = read_zip_entry(&mut archive, "docProps/core.xml")?.and_then(|core| {
+        Regex::new(r"(?s)<dc:title>(.*?)</dc:title>")
+            .unwrap()
+            .captures(&core)
+            .map(|caps| html_unescape(caps[1].trim()))
+            .filter(|title| !title.is_empty())
+    });
+
+    let mut reader = Reader::from_str(&document);
+    let mut blocks: Vec<String> = Vec::new();
+    let mut paragraph = String::new();
+    let mut prefix = String::new();
+    let mut table: Vec<Vec<String>> = Vec::new();
+    let mut table_depth = 0usize;
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"p" => {
+                    paragraph.clear();
+                    prefix.clear();
+                }
+                b"t" => in_text = true,
+                b"numPr" if prefix.is_empty() => prefix = "- ".to_string(),
+                b"tbl" => {
+                    table_depth += 1;
+                    if table_depth == 1 {
+                        table.clear();
+                    }
+                }
+                b"tr" if table_depth == 1 => table.push(Vec::new()),
+                b"tc" if table_depth == 1 => {
+                    if let Some(row) = table.last_mut() {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"pStyle" => prefix = val_attribute(&e).map(|style| style_prefix(&style)).unwrap_or_default(),
+                b"numPr" if prefix.is_empty() => prefix = "- ".to_string(),
+                b"tab" => paragraph.push('\t'),
+                b"br" | b"cr" => paragraph.push(if table_depth > 0 { ' ' } else { '\n' }),
+                _ => {}
+            },
+            Event::Text(t) if in_text => paragraph.push_str(&t.unescape()?),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let text = paragraph.trim();
+                    if table_depth > 0 {
+                        if let Some(cell) = table.last_mut().and_then(|row| row.last_mut()) {
+                            if !cell.is_empty() && !text.is_empty() {
+                                cell.push(' ');
+                            }
+                            cell.push_str(&text.replace('|', "\\|"));
+                        }
+                    } else if !text.is_empty() {
+                        blocks.push(format!("{}{}", prefix, text));
+                    }
+                    paragraph.clear();
+                }
+                b"tbl" => {
+                    table_depth = table_depth.saturating_sub(1);
+                    if table_depth == 0 && !table.is_empty() {
+                        blocks.push(markdown_table(&table));
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let markdown = join_blocks(&blocks);
+    let heading_title = blocks.iter().find_map(|block| block.strip_prefix("# ").map(str::to_string));
+    Ok(Converted { title: core_title.or(heading_title).or_else(|| first_line_title(&markdown)), markdown })
+}
+
+/// Keep consecutive list items together and separate everything else with blank lines
+fn join_blocks(blocks: &[String]) -> String {
+    let mut markdown = String::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            let both_list_items = block.starts_with("- ") && blocks[i - 1].starts_with("- ");
+            markdown.push_str(if both_list_items { "\n" } else { "\n\n" });
+        }
+        markdown.push_str(block);
+    }
+    markdown.push('\n');
+    markdown
+}
+
+/// Render rows as a GFM table, using the first row as the header
+fn markdown_table(rows: &[Vec<String>]) -> String {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0).max(1);
+    let line = |row: &Vec<String>| {
+        let cells: Vec<&str> = (0..width).map(|i| row.get(i).map(|c| c.as_str()).unwrap_or("")).collect();
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(width))];
+    lines.extend(rows[1..].iter().map(line));
+    lines.join("\n")
+}
+
+/// Extract PDF text with poppler's `pdftotext`, treating form feeds as page breaks
+fn pdf_to_markdown(path: &Path, sandbox: Option<&Sandbox>) -> Result<Converted> {
+    let mut command = Command::new("pdftotext");
+    command.arg("-enc").arg("UTF-8").arg(path).arg("-");
+    let output = sandbox::run(&mut command, sandbox).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => anyhow!(capabilities::not_installed("pdf_import").message()),
+        _ => anyhow!("Failed to run pdftotext: {}", e),
+    })?;
+    if !output.status.success() {
+        return Err(anyhow!("pdftotext failed: {}", joblog::tail(&String::from_utf8_lossy(&output.stderr), joblog::TAIL_LINES)));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n");
+    let pages: Vec<&str> = text.split('\u{c}').map(str::trim_end).filter(|page| !page.trim().is_empty()).collect();
+    let markdown = format!("{}\n", pages.join("\n\n---\n\n"));
+    Ok(Converted { title: first_line_title(&markdown), markdown })
+}
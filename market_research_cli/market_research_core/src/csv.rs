@@ -0,0 +1,216 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::panics::guard;
+use crate::tables::{parse_number, render_table, Alignment, MarkdownTable};
+
+/// Delimiters tried when none is given, preferred in this order when they fit the data equally well
+const CANDIDATE_DELIMITERS: [char; 4] = [',', '\t', ';', '|'];
+
+/// Lines looked at when detecting the delimiter
+const SAMPLE_LINES: usize = 20;
+
+/// How delimited text becomes a markdown table
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    /// Field separator; detected from the first lines when not given
+    pub delimiter: Option<char>,
+    /// Whether the first row names the columns
+    pub header: bool,
+    /// Data rows to keep; a note under the table says how many were left out
+    pub max_rows: Option<usize>,
+    /// Right-align columns whose cells are all numbers
+    pub align_numbers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: None,
+            header: true,
+            max_rows: None,
+            align_numbers: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Build options from an optional Python dict, rejecting delimiters that are not a single character
+    pub fn from_dict(options: Option<&PyDict>) -> PyResult<Self> {
+        let mut parsed = CsvOptions::default();
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(parsed),
+        };
+
+        if let Some(value) = options.get_item("delimiter") {
+            let delimiter: Option<String> = value.extract()?;
+            parsed.delimiter = match delimiter.as_deref() {
+                None => None,
+                Some("tab") | Some("\\t") => Some('\t'),
+                Some(text) => {
+                    let mut chars = text.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if c != '"' && c != '\n' && c != '\r' => Some(c),
+                        _ => {
+                            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                                "Invalid delimiter '{}'. Expected a single character other than a quote or newline",
+                                text
+                            )));
+                        }
+                    }
+                }
+            };
+        }
+        if let Some(value) = options.get_item("header") {
+            parsed.header = value.extract()?;
+        }
+        if let Some(value) = options.get_item("max_rows") {
+            parsed.max_rows = value.extract()?;
+        }
+        if let Some(value) = options.get_item("align_numbers") {
+            parsed.align_numbers = value.extract()?;
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Split delimited text into records the RFC 4180 way: fields may be quoted, quotes inside them are doubled, and
+/// quoted fields may span lines. Blank lines are skipped
+pub fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c != '"' {
+                field.push(c);
+            } else if chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                quoted = false;
+            }
+        } else if c == '"' && field.trim().is_empty() {
+            field.clear();
+            quoted = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            if record.len() > 1 || !record[0].trim().is_empty() {
+                records.push(std::mem::take(&mut record));
+            }
+            record.clear();
+        } else {
+            field.push(c);
+        }
+    }
+    if !record.is_empty() || !field.trim().is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// The candidate delimiter that splits the first lines into the most rows of one width, then the most columns.
+/// Comma when nothing splits them
+pub fn detect_delimiter(text: &str) -> char {
+    let sample_end = text.match_indices('\n').nth(SAMPLE_LINES).map_or(text.len(), |(idx, _)| idx);
+    let sample = &text[..sample_end];
+    let mut best = (',', 0, 0);
+    for delimiter in CANDIDATE_DELIMITERS {
+        let records = parse_records(sample, delimiter);
+        let columns = records.first().map_or(0, |record| record.len());
+        if columns < 2 {
+            continue;
+        }
+        let consistent = records.iter().filter(|record| record.len() == columns).count();
+        if (consistent, columns) > (best.1, best.2) {
+            best = (delimiter, consistent, columns);
+        }
+    }
+    best.0
+}
+
+/// A field as table cell text: pipes escaped and line breaks kept as `<br>`
+fn markdown_cell(field: &str) -> String {
+    field.trim().replace('|', "\\|").replace("\r\n", "<br>").replace(['\n', '\r'], "<br>")
+}
+
+/// The table for delimited text and how many data rows it had before `max_rows` applied, or `None` when there
+/// are no rows
+pub fn csv_to_table(text: &str, options: &CsvOptions) -> Option<(MarkdownTable, usize)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let delimiter = options.delimiter.unwrap_or_else(|| detect_delimiter(text));
+    let mut records = parse_records(text, delimiter).into_iter();
+    let first = records.next()?;
+
+    let (header, mut rows) = match options.header {
+        true => (first, Vec::new()),
+        false => (Vec::new(), vec![first]),
+    };
+    rows.extend(records);
+    let total = rows.len();
+    rows.truncate(options.max_rows.unwrap_or(total));
+
+    let columns = rows.iter().map(|row| row.len()).chain([header.len()]).max().unwrap_or(0);
+    let mut header: Vec<String> = match options.header {
+        true => header.iter().map(|field| markdown_cell(field)).collect(),
+        false => (1..=columns).map(|idx| format!("Column {}", idx)).collect(),
+    };
+    header.resize(columns, String::new());
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            let mut cells: Vec<String> = row.iter().map(|field| markdown_cell(field)).collect();
+            cells.resize(columns, String::new());
+            cells
+        })
+        .collect();
+
+    let alignments = (0..columns)
+        .map(|col| {
+            let mut cells = rows.iter().map(|row| &row[col]).filter(|cell| !cell.is_empty()).peekable();
+            let numeric = cells.peek().is_some() && cells.all(|cell| parse_number(cell).is_some());
+            match options.align_numbers && numeric {
+                true => Alignment::Right,
+                false => Alignment::None,
+            }
+        })
+        .collect();
+
+    let table = MarkdownTable { start_line: 0, end_line: 0, header, alignments, rows };
+    Some((table, total))
+}
+
+/// Markdown for delimited text: the table, and a note under it when rows were left out
+pub fn csv_markdown(text: &str, options: &CsvOptions) -> Option<String> {
+    let (table, total) = csv_to_table(text, options)?;
+    let mut markdown = render_table(&table);
+    if table.rows.len() < total {
+        markdown.push_str(&format!("\n\n*Showing {} of {} rows.*", table.rows.len(), total));
+    }
+    markdown.push('\n');
+    Some(markdown)
+}
+
+/// Convert CSV or TSV text into a markdown table. `options` may set `delimiter` (detected among comma, tab,
+/// semicolon and pipe when not given), `header` (the first row names the columns, default true), `max_rows`
+/// (keep the first N data rows and note how many were left out) and `align_numbers` (right-align columns of
+/// numbers, default true)
+#[pyfunction]
+#[pyo3(signature = (csv_text, options=None))]
+pub fn csv_to_markdown(csv_text: &str, options: Option<&PyDict>) -> PyResult<String> {
+    guard("csv_to_markdown", || {
+        let options = CsvOptions::from_dict(options)?;
+        csv_markdown(csv_text, &options)
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>("No rows found in CSV input"))
+    })
+}
@@ -0,0 +1,699 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use pyo3::prelude::*;
+
+use crate::charts::escape_xml;
+use crate::panics::guard;
+
+/// Key/value attributes and bullet lists parsed from a diagram block body
+#[derive(Default)]
+pub struct DiagramSpec {
+    pub attributes: HashMap<String, String>,
+    pub sections: HashMap<String, Vec<String>>,
+    /// Section names in the order they appeared
+    pub order: Vec<String>,
+}
+
+impl DiagramSpec {
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(|s| s.as_str())
+    }
+
+    pub fn items(&self, section: &str) -> &[String] {
+        self.sections.get(section).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+}
+
+/// Parse `key: value` attributes, `section:` headers, and `- item` bullets
+pub fn parse_spec(body: &str) -> DiagramSpec {
+    let mut spec = DiagramSpec::default();
+    let mut current: Option<String> = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            let section = current.clone().unwrap_or_default();
+            if !spec.sections.contains_key(&section) {
+                spec.order.push(section.clone());
+            }
+            spec.sections.entry(section).or_default().push(item.trim().to_string());
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once(':') {
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            if value.is_empty() {
+                if !spec.sections.contains_key(&key) {
+                    spec.order.push(key.clone());
+                    spec.sections.insert(key.clone(), Vec::new());
+                }
+                current = Some(key);
+            } else {
+                // Bullets following `top-left: Leaders` belong to that quadrant
+                spec.attributes.insert(key.clone(), value.to_string());
+                current = Some(key);
+            }
+        }
+    }
+
+    spec
+}
+
+/// Greedy word wrap by character count
+pub fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Emit `<text>` lines for a bullet list inside a box, truncating what does not fit
+fn svg_bullets(out: &mut String, items: &[String], x: f64, y: f64, width: f64, height: f64, color: &str) {
+    let max_chars = ((width - 16.0) / 6.2).max(8.0) as usize;
+    let line_height = 15.0;
+    let max_lines = ((height / line_height).floor() as usize).max(1);
+
+    let mut lines = Vec::new();
+    for item in items {
+        for (idx, line) in wrap_text(item, max_chars.saturating_sub(2)).into_iter().enumerate() {
+            lines.push(if idx == 0 { format!("• {}", line) } else { format!("  {}", line) });
+        }
+    }
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            *last = "…".to_string();
+        }
+    }
+
+    for (idx, line) in lines.iter().enumerate() {
+        out.push_str(&format!(
+            r#"<text x="{:.1}" y="{:.1}" fill="{}" xml:space="preserve">{}</text>"#,
+            x,
+            y + idx as f64 * line_height,
+            color,
+            escape_xml(line)
+        ));
+    }
+}
+
+fn svg_open(width: f64, height: f64) -> String {
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="Arial, sans-serif" font-size="12"><rect width="100%" height="100%" fill="#ffffff"/>"##,
+        w = width,
+        h = height
+    )
+}
+
+fn svg_title(out: &mut String, title: Option<&str>, width: f64) -> f64 {
+    match title {
+        Some(title) => {
+            out.push_str(&format!(
+                r##"<text x="{:.1}" y="24" font-size="15" font-weight="bold" text-anchor="middle" fill="#333">{}</text>"##,
+                width / 2.0,
+                escape_xml(title)
+            ));
+            40.0
+        }
+        None => 10.0,
+    }
+}
+
+/// Render a SWOT block as a four-quadrant SVG
+pub fn render_swot(body: &str) -> Result<String, String> {
+    let spec = parse_spec(body);
+    let lookup = |names: &[&str]| -> Vec<String> {
+        names.iter().flat_map(|name| spec.items(name).iter().cloned()).collect()
+    };
+    let quadrants = [
+        ("Strengths", lookup(&["strengths", "strength", "s"]), "#e3f1e0", "#2e7d32"),
+        ("Weaknesses", lookup(&["weaknesses", "weakness", "w"]), "#fdecea", "#c62828"),
+        ("Opportunities", lookup(&["opportunities", "opportunity", "o"]), "#e3eefc", "#1565c0"),
+        ("Threats", lookup(&["threats", "threat", "t"]), "#fff4e0", "#ef6c00"),
+    ];
+    if quadrants.iter().all(|(_, items, _, _)| items.is_empty()) {
+        return Err("SWOT block needs at least one of strengths, weaknesses, opportunities, threats".to_string());
+    }
+
+    let (width, cell_height) = (680.0, 200.0);
+    let mut header = String::new();
+    let top = svg_title(&mut header, spec.attribute("title"), width);
+    let cell_width = (width - 30.0) / 2.0;
+    let mut svg = svg_open(width, top + cell_height * 2.0 + 20.0);
+    svg.push_str(&header);
+
+    for (idx, (label, items, fill, accent)) in quadrants.iter().enumerate() {
+        let x = 10.0 + (idx % 2) as f64 * (cell_width + 10.0);
+        let y = top + (idx / 2) as f64 * (cell_height + 10.0);
+        svg.push_str(&format!(
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="6" fill="{}" stroke="{}"/><text x="{:.1}" y="{:.1}" font-size="14" font-weight="bold" fill="{}">{}</text>"#,
+            x,
+            y,
+            cell_width,
+            cell_height - 10.0,
+            fill,
+            accent,
+            x + 12.0,
+            y + 22.0,
+            accent,
+            label
+        ));
+        svg_bullets(&mut svg, items, x + 12.0, y + 44.0, cell_width, cell_height - 60.0, "#333");
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// Parse a plotted point written as `Name (x, y)` with coordinates in 0..1
+fn parse_point(item: &str) -> Option<(String, f64, f64)> {
+    let open = item.rfind('(')?;
+    let coords = item[open + 1..].trim_end().strip_suffix(')')?;
+    let (x, y) = coords.split_once(',')?;
+    let x: f64 = x.trim().parse().ok()?;
+    let y: f64 = y.trim().parse().ok()?;
+    Some((item[..open].trim().to_string(), x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)))
+}
+
+/// Render a 2x2 matrix block with quadrant labels, item lists, and optional plotted points
+pub fn render_matrix2x2(body: &str) -> Result<String, String> {
+    let spec = parse_spec(body);
+    let quadrant_keys = ["top-left", "top-right", "bottom-left", "bottom-right"];
+    let points: Vec<(String, f64, f64)> = spec.items("points").iter().filter_map(|p| parse_point(p)).collect();
+    let has_content = quadrant_keys
+        .iter()
+        .any(|k| spec.attribute(k).is_some() || !spec.items(k).is_empty());
+    if !has_content && points.is_empty() {
+        return Err("matrix2x2 block needs quadrant labels, quadrant items, or points".to_string());
+    }
+
+    let width = 640.0;
+    let mut header = String::new();
+    let top = svg_title(&mut header, spec.attribute("title"), width);
+    let (left, size) = (60.0, 520.0);
+    let height = top + size + 50.0;
+    let half = size / 2.0;
+
+    let mut svg = svg_open(width, height);
+    svg.push_str(&header);
+
+    let fills = ["#f3f6fb", "#e3eefc", "#fafafa", "#f3f6fb"];
+    for (idx, key) in quadrant_keys.iter().enumerate() {
+        let x = left + (idx % 2) as f64 * half;
+        let y = top + (idx / 2) as f64 * half;
+        svg.push_str(&format!(
+            r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" stroke="#b0bec5"/>"##,
+            x, y, half, half, fills[idx]
+        ));
+        if let Some(label) = spec.attribute(key) {
+            svg.push_str(&format!(
+                r##"<text x="{:.1}" y="{:.1}" font-size="13" font-weight="bold" fill="#1565c0">{}</text>"##,
+                x + 10.0,
+                y + 20.0,
+                escape_xml(label)
+            ));
+        }
+        svg_bullets(&mut svg, spec.items(key), x + 10.0, y + 40.0, half, half - 50.0, "#333");
+    }
+
+    for (name, px, py) in &points {
+        let cx = left + px * size;
+        let cy = top + (1.0 - py) * size;
+        svg.push_str(&format!(
+            r##"<circle cx="{:.1}" cy="{:.1}" r="6" fill="#e15759"/><text x="{:.1}" y="{:.1}" fill="#333">{}</text>"##,
+            cx,
+            cy,
+            cx + 9.0,
+            cy + 4.0,
+            escape_xml(name)
+        ));
+    }
+
+    if let Some(x_axis) = spec.attribute("x-axis").or_else(|| spec.attribute("x")) {
+        svg.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#555">{} →</text>"##,
+            left + half,
+            top + size + 24.0,
+            escape_xml(x_axis)
+        ));
+    }
+    if let Some(y_axis) = spec.attribute("y-axis").or_else(|| spec.attribute("y")) {
+        let (x, y) = (left - 20.0, top + half);
+        svg.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#555" transform="rotate(-90 {:.1} {:.1})">{} →</text>"##,
+            x,
+            y,
+            x,
+            y,
+            escape_xml(y_axis)
+        ));
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// Parse `date: event` lines (optionally bulleted) in source order
+fn parse_timeline(body: &str) -> (Option<String>, Vec<(String, String)>) {
+    let mut title = None;
+    let mut events = Vec::new();
+    for line in body.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+        if let Some((date, event)) = line.split_once(':') {
+            let (date, event) = (date.trim(), event.trim());
+            if date.eq_ignore_ascii_case("title") {
+                title = Some(event.to_string());
+            } else if !date.is_empty() && !event.is_empty() {
+                events.push((date.to_string(), event.to_string()));
+            }
+        }
+    }
+    (title, events)
+}
+
+/// Render a timeline block as a horizontal SVG with labels alternating above and below the axis
+pub fn render_timeline(body: &str) -> Result<String, String> {
+    let (title, events) = parse_timeline(body);
+    if events.is_empty() {
+        return Err("timeline block needs at least one `date: event` line".to_string());
+    }
+
+    let spacing = 130.0;
+    let width = (events.len() as f64 * spacing + 60.0).max(640.0);
+    let mut header = String::new();
+    let top = svg_title(&mut header, title.as_deref(), width);
+    let axis_y = top + 110.0;
+    let height = axis_y + 120.0;
+
+    let mut svg = svg_open(width, height);
+    svg.push_str(&header);
+    let step = (width - 60.0) / events.len() as f64;
+    svg.push_str(&format!(
+        r##"<line x1="20" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#90a4ae" stroke-width="3"/>"##,
+        axis_y,
+        width - 20.0,
+        axis_y
+    ));
+
+    for (idx, (date, event)) in events.iter().enumerate() {
+        let x = 30.0 + step * (idx as f64 + 0.5);
+        let above = idx % 2 == 0;
+        let color = crate::charts::PALETTE[idx % crate::charts::PALETTE.len()];
+        let (stem_end, date_y, text_y) = if above {
+            (axis_y - 30.0, axis_y - 38.0, axis_y - 95.0)
+        } else {
+            (axis_y + 30.0, axis_y + 48.0, axis_y + 64.0)
+        };
+
+        svg.push_str(&format!(
+            r##"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{:.1}" stroke="{c}"/><circle cx="{x:.1}" cy="{:.1}" r="6" fill="{c}"/><text x="{x:.1}" y="{:.1}" font-weight="bold" text-anchor="middle" fill="{c}">{}</text>"##,
+            axis_y,
+            stem_end,
+            axis_y,
+            date_y,
+            escape_xml(date),
+            x = x,
+            c = color
+        ));
+
+        let lines = wrap_text(event, ((step - 10.0) / 6.5).max(10.0) as usize);
+        for (line_idx, line) in lines.iter().take(4).enumerate() {
+            svg.push_str(&format!(
+                r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#333">{}</text>"##,
+                x,
+                text_y + line_idx as f64 * 14.0,
+                escape_xml(line)
+            ));
+        }
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// Parse a roadmap date (`2025-03-15`, `2025-03`, `2025-Q2`, `2025`) as the first or last day of the period
+fn parse_period(text: &str, end: bool) -> Option<NaiveDate> {
+    let text = text.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let (year, first_month, months) = if let Some((year, quarter)) = text.split_once(['-', ' ']).filter(|(_, q)| q.to_uppercase().starts_with('Q')) {
+        let quarter: u32 = quarter[1..].parse().ok().filter(|q| (1..=4).contains(q))?;
+        (year.parse::<i32>().ok()?, (quarter - 1) * 3 + 1, 3)
+    } else if let Some((year, month)) = text.split_once('-') {
+        let month: u32 = month.parse().ok().filter(|m| (1..=12).contains(m))?;
+        (year.parse::<i32>().ok()?, month, 1)
+    } else {
+        (text.parse::<i32>().ok()?, 1, 12)
+    };
+
+    let start = NaiveDate::from_ymd_opt(year, first_month, 1)?;
+    if !end {
+        return Some(start);
+    }
+    let next_month = first_month + months;
+    let next = if next_month > 12 {
+        NaiveDate::from_ymd_opt(year + 1, next_month - 12, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, next_month, 1)?
+    };
+    next.pred_opt()
+}
+
+/// Render a roadmap block of `task | start | end` rows as a Gantt chart
+pub fn render_roadmap(body: &str) -> Result<String, String> {
+    let mut title = None;
+    let mut tasks = Vec::new();
+    for line in body.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("title:") {
+            title = Some(value.trim().to_string());
+            continue;
+        }
+
+        let cells: Vec<&str> = line.trim_matches('|').split('|').map(|c| c.trim()).collect();
+        if cells.len() < 3 || cells[0].eq_ignore_ascii_case("task") {
+            continue;
+        }
+        let start = parse_period(cells[1], false).ok_or_else(|| format!("Invalid start date '{}'", cells[1]))?;
+        let end = parse_period(cells[2], true).ok_or_else(|| format!("Invalid end date '{}'", cells[2]))?;
+        if end < start {
+            return Err(format!("Task '{}' ends before it starts", cells[0]));
+        }
+        tasks.push((cells[0].to_string(), start, end));
+    }
+    if tasks.is_empty() {
+        return Err("roadmap block needs at least one `task | start | end` row".to_string());
+    }
+
+    let min = tasks.iter().map(|t| t.1).min().unwrap_or_default();
+    let max = tasks.iter().map(|t| t.2).max().unwrap_or_default();
+    let span_days = ((max - min).num_days() + 1) as f64;
+
+    let (width, label_width, row_height) = (760.0, 190.0, 28.0);
+    let mut header = String::new();
+    let top = svg_title(&mut header, title.as_deref(), width) + 24.0;
+    let height = top + tasks.len() as f64 * row_height + 20.0;
+    let plot_width = width - label_width - 20.0;
+    let x_for = |date: NaiveDate| label_width + (date - min).num_days() as f64 / span_days * plot_width;
+
+    let mut svg = svg_open(width, height);
+    svg.push_str(&header);
+
+    // Month ticks for short plans, quarters for medium ones, years for long ones (every few years past two decades,
+    // so a typo like `0025` cannot produce thousands of ticks)
+    let step_months = if span_days <= 370.0 {
+        1
+    } else if span_days <= 3.0 * 365.0 {
+        3
+    } else {
+        12 * (span_days / 365.0 / 20.0).ceil().max(1.0) as i32
+    };
+    let mut tick = NaiveDate::from_ymd_opt(min.year(), 1, 1).unwrap_or(min);
+    while tick <= max {
+        if tick >= min {
+            let x = x_for(tick);
+            let label = match step_months {
+                1 => tick.format("%b %y").to_string(),
+                3 => format!("Q{} {}", (tick.month() - 1) / 3 + 1, tick.format("%y")),
+                _ => tick.format("%Y").to_string(),
+            };
+            svg.push_str(&format!(
+                r##"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{:.1}" stroke="#e0e0e0"/><text x="{x:.1}" y="{:.1}" font-size="10" text-anchor="middle" fill="#666">{}</text>"##,
+                top - 6.0,
+                height - 16.0,
+                top - 10.0,
+                label,
+                x = x
+            ));
+        }
+        let months = tick.year() * 12 + tick.month0() as i32 + step_months;
+        tick = match NaiveDate::from_ymd_opt(months / 12, (months % 12) as u32 + 1, 1) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    for (idx, (task, start, end)) in tasks.iter().enumerate() {
+        let y = top + idx as f64 * row_height;
+        let x0 = x_for(*start);
+        let x1 = x_for(*end + chrono::Duration::days(1));
+        svg.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="end" fill="#333">{}</text><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="4" fill="{}"><title>{}: {} to {}</title></rect>"##,
+            label_width - 10.0,
+            y + row_height / 2.0 + 4.0,
+            escape_xml(&task.chars().take(30).collect::<String>()),
+            x0,
+            y + 5.0,
+            (x1 - x0).max(3.0),
+            row_height - 10.0,
+            crate::charts::PALETTE[idx % crate::charts::PALETTE.len()],
+            escape_xml(task),
+            start,
+            end
+        ));
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// A node in an indented bullet hierarchy
+#[derive(Debug, Default)]
+pub struct TreeNode {
+    pub label: String,
+    pub children: Vec<TreeNode>,
+}
+
+/// Parse indented `- item` bullets into a forest, using indentation width to infer depth
+pub fn parse_tree(body: &str) -> Vec<TreeNode> {
+    // Stack of (indent, path of child indices from the root)
+    let mut roots: Vec<TreeNode> = Vec::new();
+    let mut stack: Vec<(usize, Vec<usize>)> = Vec::new();
+
+    for line in body.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+        let label = match trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            Some(label) => label.trim().to_string(),
+            None => continue,
+        };
+
+        while let Some((top_indent, _)) = stack.last() {
+            if *top_indent >= indent {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+
+        let node = TreeNode { label, children: Vec::new() };
+        let path = match stack.last() {
+            Some((_, parent_path)) => {
+                let mut parent = &mut roots[parent_path[0]];
+                for idx in &parent_path[1..] {
+                    parent = &mut parent.children[*idx];
+                }
+                parent.children.push(node);
+                let mut path = parent_path.clone();
+                path.push(parent.children.len() - 1);
+                path
+            }
+            None => {
+                roots.push(node);
+                vec![roots.len() - 1]
+            }
+        };
+        stack.push((indent, path));
+    }
+
+    roots
+}
+
+/// Render a market map: segments as columns, subsegments as boxes, companies listed inside
+pub fn render_marketmap(body: &str) -> Result<String, String> {
+    let segments = parse_tree(body);
+    if segments.is_empty() {
+        return Err("marketmap block needs an indented list of segments".to_string());
+    }
+    let title = body
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("title:").map(|t| t.trim().to_string()));
+
+    let (gap, company_height, box_header) = (10.0, 16.0, 26.0);
+    let column_width = 180.0_f64.max(640.0 / segments.len() as f64 - gap);
+    let width = segments.len() as f64 * (column_width + gap) + gap;
+
+    // A segment with leaf children shows them as companies in a single unnamed box
+    let boxes_for = |segment: &TreeNode| -> Vec<(Option<String>, Vec<String>)> {
+        if segment.children.iter().all(|child| child.children.is_empty()) {
+            return vec![(None, segment.children.iter().map(|c| c.label.clone()).collect())];
+        }
+        segment
+            .children
+            .iter()
+            .map(|sub| {
+                let companies = sub
+                    .children
+                    .iter()
+                    .flat_map(|c| std::iter::once(c.label.clone()).chain(c.children.iter().map(|g| g.label.clone())))
+                    .collect();
+                (Some(sub.label.clone()), companies)
+            })
+            .collect()
+    };
+    let box_height = |companies: usize| box_header + companies as f64 * company_height + 8.0;
+    let column_height = |segment: &TreeNode| -> f64 {
+        boxes_for(segment).iter().map(|(_, companies)| box_height(companies.len()) + gap).sum()
+    };
+
+    let mut header = String::new();
+    let top = svg_title(&mut header, title.as_deref(), width);
+    let tallest = segments.iter().map(column_height).fold(0.0_f64, f64::max);
+    let height = top + 36.0 + tallest + gap;
+
+    let mut svg = svg_open(width, height);
+    svg.push_str(&header);
+    let max_chars = ((column_width - 20.0) / 6.5) as usize;
+
+    for (idx, segment) in segments.iter().enumerate() {
+        let x = gap + idx as f64 * (column_width + gap);
+        let color = crate::charts::PALETTE[idx % crate::charts::PALETTE.len()];
+        svg.push_str(&format!(
+            r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="28" rx="4" fill="{}"/><text x="{:.1}" y="{:.1}" font-weight="bold" text-anchor="middle" fill="#ffffff">{}</text>"##,
+            x,
+            top,
+            column_width,
+            color,
+            x + column_width / 2.0,
+            top + 18.0,
+            escape_xml(&segment.label.chars().take(max_chars).collect::<String>())
+        ));
+
+        let mut y = top + 36.0;
+        for (name, companies) in boxes_for(segment) {
+            let h = box_height(companies.len());
+            svg.push_str(&format!(
+                r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="4" fill="#fafafa" stroke="{}"/>"##,
+                x,
+                y,
+                column_width,
+                h,
+                color
+            ));
+            let mut text_y = y + 18.0;
+            if let Some(name) = name {
+                svg.push_str(&format!(
+                    r##"<text x="{:.1}" y="{:.1}" font-weight="bold" fill="{}">{}</text>"##,
+                    x + 8.0,
+                    text_y,
+                    color,
+                    escape_xml(&name.chars().take(max_chars).collect::<String>())
+                ));
+                text_y += company_height + 2.0;
+            }
+            for company in &companies {
+                svg.push_str(&format!(
+                    r##"<text x="{:.1}" y="{:.1}" fill="#333">{}</text>"##,
+                    x + 14.0,
+                    text_y,
+                    escape_xml(&company.chars().take(max_chars).collect::<String>())
+                ));
+                text_y += company_height;
+            }
+            y += h + gap;
+        }
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// Dispatch a fenced block to its diagram renderer, if one exists for the language
+fn render_block(language: &str, body: &str) -> Option<Result<String, String>> {
+    match language {
+        "swot" => Some(render_swot(body)),
+        "matrix2x2" => Some(render_matrix2x2(body)),
+        "timeline" => Some(render_timeline(body)),
+        "roadmap" | "gantt" => Some(render_roadmap(body)),
+        "marketmap" => Some(render_marketmap(body)),
+        _ => None,
+    }
+}
+
+/// Replace diagram fenced blocks with inline SVG; blocks that fail to render are left untouched
+pub fn render_fenced_diagrams(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim_start();
+        let fence = if trimmed.starts_with("```") {
+            "```"
+        } else if trimmed.starts_with("~~~") {
+            "~~~"
+        } else {
+            out.push(lines[i].to_string());
+            i += 1;
+            continue;
+        };
+
+        let language = trimmed.trim_start_matches(fence).trim().to_lowercase();
+        let close = (i + 1..lines.len()).find(|&j| lines[j].trim_start().starts_with(fence));
+        let end = close.unwrap_or(lines.len());
+        let body = lines[i + 1..end].join("\n");
+
+        match render_block(&language, &body) {
+            Some(Ok(svg)) => {
+                out.push(format!("<div class=\"diagram diagram-{}\">{}</div>", language, svg));
+                out.push(String::new());
+            }
+            _ => out.extend(lines[i..(end + 1).min(lines.len())].iter().map(|l| l.to_string())),
+        }
+        i = end + 1;
+    }
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Render a diagram block body (e.g. `swot`, `matrix2x2`, `timeline`, `roadmap`, `marketmap`) to SVG
+#[pyfunction]
+pub fn render_diagram(kind: &str, source: &str) -> PyResult<String> {
+    guard("render_diagram", || {
+        match render_block(&kind.trim().to_lowercase(), source) {
+            Some(result) => result.map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>),
+            None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Unknown diagram type: {}", kind)
+            )),
+        }
+    })
+}
@@ -0,0 +1,312 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::Regex;
+
+use crate::convert::{extension_of, html_to_markdown};
+use crate::panics::guard;
+use crate::sha256_hex;
+
+/// Nested multiparts deeper than this are ignored
+const MAX_MIME_DEPTH: usize = 8;
+
+/// One email parsed into a citable source
+#[derive(Clone, Debug)]
+pub struct EmailSource {
+    pub message_id: Option<String>,
+    pub sender: String,
+    pub date: Option<DateTime<FixedOffset>>,
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailSource {
+    /// Stable id for deduplication: the Message-ID when present, otherwise sender, date and subject
+    pub fn source_id(&self) -> String {
+        let key = match &self.message_id {
+            Some(message_id) => message_id.clone(),
+            None => format!(
+                "{}\n{}\n{}",
+                self.sender,
+                self.date.map(|d| d.to_rfc3339()).unwrap_or_default(),
+                self.subject
+            ),
+        };
+        sha256_hex(key.as_bytes())[..16].to_string()
+    }
+}
+
+type Headers = Vec<(String, String)>;
+
+/// Split a message at the first blank line into header block and body
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    let mut start = 0;
+    while start < raw.len() {
+        let end = raw[start..].iter().position(|b| *b == b'\n').map(|p| start + p + 1).unwrap_or(raw.len());
+        let line = &raw[start..end];
+        if line == b"\n" || line == b"\r\n" {
+            return (&raw[..start], &raw[end..]);
+        }
+        start = end;
+    }
+    (raw, &[])
+}
+
+/// Parse headers, unfolding continuation lines; names are lowercased
+fn parse_headers(head: &[u8]) -> Headers {
+    let text = String::from_utf8_lossy(head);
+    let mut headers: Headers = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    headers
+}
+
+fn header<'a>(headers: &'a Headers, name: &str) -> Option<&'a str> {
+    headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/// A `key=value` parameter from a structured header such as Content-Type
+fn header_param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|label| encoding_rs::Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// Decode RFC 2047 encoded words such as `=?utf-8?B?...?=` in a header value
+fn decode_header(value: &str) -> String {
+    let adjacent = Regex::new(r"\?=\s+=\?").unwrap();
+    let encoded = Regex::new(r"=\?([^?]+)\?([bBqQ])\?([^?]*)\?=").unwrap();
+
+    let joined = adjacent.replace_all(value, "?==?");
+    encoded
+        .replace_all(&joined, |caps: &regex::Captures| {
+            let bytes = if caps[2].eq_ignore_ascii_case("b") {
+                base64::engine::general_purpose::STANDARD.decode(&caps[3]).unwrap_or_default()
+            } else {
+                quoted_printable::decode(caps[3].replace('_', " "), quoted_printable::ParseMode::Robust)
+                    .unwrap_or_default()
+            };
+            decode_charset(&bytes, Some(&caps[1]))
+        })
+        .to_string()
+}
+
+fn decode_transfer(headers: &Headers, body: &[u8]) -> Vec<u8> {
+    match header(headers, "content-transfer-encoding").map(|e| e.to_lowercase()).as_deref() {
+        Some("base64") => {
+            let compact: Vec<u8> = body.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD.decode(compact).unwrap_or_else(|_| body.to_vec())
+        }
+        Some("quoted-printable") => {
+            quoted_printable::decode(body, quoted_printable::ParseMode::Robust).unwrap_or_else(|_| body.to_vec())
+        }
+        _ => body.to_vec(),
+    }
+}
+
+/// Split a multipart body on its boundary lines, dropping preamble and epilogue
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut part_start: Option<usize> = None;
+    let mut start = 0;
+
+    while start < body.len() {
+        let end = body[start..].iter().position(|b| *b == b'\n').map(|p| start + p + 1).unwrap_or(body.len());
+        let line = String::from_utf8_lossy(&body[start..end]);
+        let line = line.trim_end();
+        if line.starts_with(&delimiter) {
+            if let Some(part_start) = part_start {
+                parts.push(&body[part_start..start]);
+            }
+            if line[delimiter.len()..].starts_with("--") {
+                return parts;
+            }
+            part_start = Some(end);
+        }
+        start = end;
+    }
+    if let Some(part_start) = part_start {
+        parts.push(&body[part_start..]);
+    }
+    parts
+}
+
+/// Find the readable text of a message, preferring text/plain over text/html; returns (is_html, text)
+fn extract_text(headers: &Headers, body: &[u8], depth: usize) -> Option<(bool, String)> {
+    let content_type = header(headers, "content-type").unwrap_or("text/plain");
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    let attachment = header(headers, "content-disposition")
+        .map(|d| d.trim_start().to_lowercase().starts_with("attachment"))
+        .unwrap_or(false);
+    if attachment {
+        return None;
+    }
+
+    if mime.starts_with("multipart/") {
+        if depth >= MAX_MIME_DEPTH {
+            return None;
+        }
+        let boundary = header_param(content_type, "boundary")?;
+        let mut html = None;
+        for part in split_multipart(body, &boundary) {
+            let (head, part_body) = split_head(part);
+            match extract_text(&parse_headers(head), part_body, depth + 1) {
+                Some((false, text)) => return Some((false, text)),
+                Some(found) if html.is_none() => html = Some(found),
+                _ => {}
+            }
+        }
+        return html;
+    }
+
+    if mime != "text/plain" && mime != "text/html" {
+        return None;
+    }
+    let decoded = decode_transfer(headers, body);
+    let text = decode_charset(&decoded, header_param(content_type, "charset").as_deref());
+    Some((mime == "text/html", text))
+}
+
+/// Strip quoted replies, signatures and newsletter boilerplate, and collapse blank lines
+pub fn clean_body(text: &str) -> String {
+    let boilerplate = ["unsubscribe", "view this email in your browser", "view in browser", "manage your preferences"];
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if line == "-- " {
+            break;
+        }
+        let trimmed = line.trim_end();
+        if trimmed.trim_start().starts_with('>') {
+            continue;
+        }
+        let lower = trimmed.to_lowercase();
+        if boilerplate.iter().any(|phrase| lower.contains(phrase)) {
+            continue;
+        }
+        lines.push(trimmed);
+    }
+
+    let joined = lines.join("\n");
+    Regex::new(r"\n{3,}").unwrap().replace_all(joined.trim(), "\n\n").to_string()
+}
+
+/// Parse a single RFC 822 message
+pub fn parse_message(raw: &[u8]) -> Result<EmailSource> {
+    let (head, body) = split_head(raw);
+    let headers = parse_headers(head);
+    if header(&headers, "from").is_none() && header(&headers, "subject").is_none() {
+        return Err(anyhow!("Not an email message: no From or Subject header"));
+    }
+
+    let date = header(&headers, "date").and_then(|date| {
+        let without_comment = Regex::new(r"\s*\([^)]*\)\s*$").unwrap().replace(date, "");
+        DateTime::parse_from_rfc2822(without_comment.trim()).ok()
+    });
+    let body = match extract_text(&headers, body, 0) {
+        Some((true, html)) => html_to_markdown(&html).markdown,
+        Some((false, text)) => text.replace("\r\n", "\n"),
+        None => String::new(),
+    };
+
+    Ok(EmailSource {
+        message_id: header(&headers, "message-id")
+            .map(|id| id.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+            .filter(|id| !id.is_empty()),
+        sender: decode_header(header(&headers, "from").unwrap_or_default()),
+        date,
+        subject: decode_header(header(&headers, "subject").unwrap_or("(no subject)")),
+        body: clean_body(&body),
+    })
+}
+
+/// Split an mbox file on its `From ` separator lines, unescaping `>From ` in bodies
+pub fn split_mbox(raw: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+    let mut previous_blank = true;
+
+    for line in raw.split_inclusive(|b| *b == b'\n') {
+        if previous_blank && line.starts_with(b"From ") {
+            if let Some(message) = current.take() {
+                messages.push(message);
+            }
+            current = Some(Vec::new());
+            previous_blank = false;
+            continue;
+        }
+        previous_blank = line == b"\n" || line == b"\r\n";
+        if let Some(message) = current.as_mut() {
+            let unescaped = if line.starts_with(b">From ") { &line[1..] } else { line };
+            message.extend_from_slice(unescaped);
+        }
+    }
+    if let Some(message) = current {
+        messages.push(message);
+    }
+    messages
+}
+
+/// Parse an .eml or .mbox file, dropping repeated messages
+pub fn parse_email_file(path: &Path) -> Result<Vec<EmailSource>> {
+    let raw = fs::read(path)?;
+    let messages = match extension_of(path).as_str() {
+        "mbox" | "mbx" => split_mbox(&raw),
+        _ => vec![raw],
+    };
+
+    let mut seen = HashSet::new();
+    let mut sources = Vec::new();
+    for message in messages {
+        let source = parse_message(&message)?;
+        if seen.insert(source.source_id()) {
+            sources.push(source);
+        }
+    }
+    Ok(sources)
+}
+
+/// Parse an .eml or .mbox file into source dicts with id, sender, date, subject and cleaned body
+#[pyfunction]
+pub fn parse_email_sources(path: &str, py: Python) -> PyResult<PyObject> {
+    guard("parse_email_sources", || {
+        let sources = py.allow_threads(|| parse_email_file(Path::new(path)))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to parse email: {}", e)))?;
+
+        let result = PyList::empty(py);
+        for source in &sources {
+            let dict = PyDict::new(py);
+            dict.set_item("id", source.source_id())?;
+            dict.set_item("message_id", &source.message_id)?;
+            dict.set_item("sender", &source.sender)?;
+            dict.set_item("date", source.date.map(|d| d.to_rfc3339()))?;
+            dict.set_item("subject", &source.subject)?;
+            dict.set_item("body", &source.body)?;
+            result.append(dict)?;
+        }
+        Ok(result.into())
+    })
+}
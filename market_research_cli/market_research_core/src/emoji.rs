@@ -0,0 +1,25 @@
+use regex::{Captures, Regex};
+
+use crate::render::map_outside_fences;
+
+/// Replace GitHub-style `:rocket:` shortcodes with their emoji, outside fenced code and code spans. Unknown
+/// shortcodes, and colons in times or ratios, are left as written
+pub fn expand_shortcodes(markdown: &str) -> String {
+    let shortcode = Regex::new(r":([a-z0-9_+-]+):").unwrap();
+    map_outside_fences(markdown, |line| {
+        // Odd segments between backticks are code spans
+        line.split('`')
+            .enumerate()
+            .map(|(index, segment)| match index % 2 {
+                0 => shortcode
+                    .replace_all(segment, |caps: &Captures| match emojis::get_by_shortcode(&caps[1]) {
+                        Some(emoji) => emoji.as_str().to_string(),
+                        None => caps[0].to_string(),
+                    })
+                    .into_owned(),
+                _ => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("`")
+    })
+}
@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::Regex;
+
+use crate::charts::escape_xml;
+use crate::panics::guard;
+use crate::trends::load_sources;
+
+/// Capitalized words that start sentences or headings rather than name anything
+const STOPWORDS: &[&str] = &[
+    "A", "An", "And", "As", "At", "But", "By", "For", "From", "He", "However", "If", "In", "It", "Its", "Key",
+    "Many", "More", "Most", "No", "Not", "Of", "On", "Or", "Our", "She", "So", "Some", "That", "The", "Their",
+    "There", "These", "They", "This", "Those", "To", "We", "What", "When", "Where", "Which", "While", "With",
+    "Yes", "You", "Overview", "Summary", "Conclusion", "Introduction", "Source", "Sources", "Table", "Figure",
+];
+
+/// Text span two entities must share to count as mentioned together
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    Sentence,
+    Paragraph,
+    Source,
+}
+
+impl Window {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "sentence" => Some(Window::Sentence),
+            "paragraph" => Some(Window::Paragraph),
+            "source" | "document" => Some(Window::Source),
+            _ => None,
+        }
+    }
+
+    fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        match self {
+            Window::Sentence => Regex::new(r"[.!?]+\s+|\n\s*\n").unwrap().split(text).collect(),
+            Window::Paragraph => Regex::new(r"\n\s*\n").unwrap().split(text).collect(),
+            Window::Source => vec![text],
+        }
+    }
+}
+
+/// A named entity and the spellings that count as mentioning it
+pub struct EntitySpec {
+    pub name: String,
+    pub aliases: Vec<String>,
+}
+
+/// One entity in the graph
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: String,
+    pub mentions: usize,
+    /// Number of sources mentioning the entity
+    pub sources: usize,
+}
+
+/// Undirected co-occurrence between two entities
+#[derive(Clone, Debug)]
+pub struct Edge {
+    pub source: String,
+    pub target: String,
+    /// Number of windows mentioning both
+    pub weight: usize,
+}
+
+pub struct EntityGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+/// Settings for building the graph
+pub struct GraphOptions {
+    pub window: Window,
+    /// Auto-detected entities must appear in at least this many sources
+    pub min_sources: usize,
+    /// Edges lighter than this are dropped
+    pub min_weight: usize,
+    /// Keep only the most widely mentioned entities
+    pub max_nodes: usize,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        GraphOptions { window: Window::Paragraph, min_sources: 2, min_weight: 1, max_nodes: 50 }
+    }
+}
+
+impl GraphOptions {
+    pub fn from_dict(options: Option<&PyDict>) -> PyResult<Self> {
+        let mut parsed = GraphOptions::default();
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(parsed),
+        };
+        if let Some(value) = options.get_item("window") {
+            let name: String = value.extract()?;
+            parsed.window = Window::parse(&name).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unknown window '{}'. Expected sentence, paragraph or source", name)
+                )
+            })?;
+        }
+        if let Some(value) = options.get_item("min_sources") {
+            parsed.min_sources = value.extract()?;
+        }
+        if let Some(value) = options.get_item("min_weight") {
+            parsed.min_weight = value.extract()?;
+        }
+        if let Some(value) = options.get_item("max_nodes") {
+            parsed.max_nodes = value.extract()?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Capitalized phrases (`Acme Corp`, `OpenAI`, `AWS`) found in at least `min_sources` sources
+pub fn detect_entities(sources: &[String], min_sources: usize) -> Vec<EntitySpec> {
+    let phrase = Regex::new(r"\b[A-Z][A-Za-z0-9&\-]*(?:[ \t]+[A-Z][A-Za-z0-9&\-]*){0,3}\b").unwrap();
+    let mut seen_in: HashMap<String, usize> = HashMap::new();
+    for source in sources {
+        let mut found = HashSet::new();
+        for m in phrase.find_iter(source) {
+            // Drop leading sentence words such as "The" in "The Acme Group"
+            let words: Vec<&str> = m.as_str().split_whitespace().skip_while(|word| STOPWORDS.contains(word)).collect();
+            if words.is_empty() || (words.len() == 1 && words[0].len() < 2) {
+                continue;
+            }
+            found.insert(words.join(" "));
+        }
+        for name in found {
+            *seen_in.entry(name).or_insert(0) += 1;
+        }
+    }
+    let mut names: Vec<String> = seen_in.into_iter().filter(|(_, count)| *count >= min_sources).map(|(name, _)| name).collect();
+    names.sort();
+    names.into_iter().map(|name| EntitySpec { aliases: vec![name.clone()], name }).collect()
+}
+
+/// Build the weighted co-occurrence graph of `entities` across `sources`.
+/// Entities given explicitly match case-insensitively on word boundaries; detected ones match as written.
+pub fn build_graph(sources: &[String], entities: &[EntitySpec], case_insensitive: bool, options: &GraphOptions) -> Result<EntityGraph> {
+    let matchers = entities
+        .iter()
+        .map(|entity| {
+            let alternatives: Vec<String> = entity.aliases.iter().map(|alias| regex::escape(alias.trim())).collect();
+            let flags = if case_insensitive { "(?i)" } else { "" };
+            Regex::new(&format!(r"{}\b(?:{})\b", flags, alternatives.join("|")))
+                .map_err(|e| anyhow!("Invalid entity '{}': {}", entity.name, e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut nodes: Vec<Node> = entities.iter().map(|e| Node { name: e.name.clone(), mentions: 0, sources: 0 }).collect();
+    let mut pairs: HashMap<(usize, usize), usize> = HashMap::new();
+    for source in sources {
+        let mut in_source = vec![false; entities.len()];
+        for window in options.window.split(source) {
+            let mut present = Vec::new();
+            for (i, matcher) in matchers.iter().enumerate() {
+                let count = matcher.find_iter(window).count();
+                if count > 0 {
+                    nodes[i].mentions += count;
+                    in_source[i] = true;
+                    present.push(i);
+                }
+            }
+            for (a, first) in present.iter().enumerate() {
+                for second in &present[a + 1..] {
+                    *pairs.entry((*first, *second)).or_insert(0) += 1;
+                }
+            }
+        }
+        for (node, present) in nodes.iter_mut().zip(in_source) {
+            node.sources += usize::from(present);
+        }
+    }
+
+    // Keep the most widely mentioned entities, then only edges between them
+    let mut order: Vec<usize> = (0..nodes.len()).filter(|&i| nodes[i].mentions > 0).collect();
+    order.sort_by(|&a, &b| {
+        nodes[b].sources.cmp(&nodes[a].sources).then(nodes[b].mentions.cmp(&nodes[a].mentions)).then(nodes[a].name.cmp(&nodes[b].name))
+    });
+    order.truncate(options.max_nodes);
+    let kept: HashSet<usize> = order.iter().copied().collect();
+
+    let mut edges: Vec<Edge> = pairs
+        .into_iter()
+        .filter(|((a, b), weight)| *weight >= options.min_weight && kept.contains(a) && kept.contains(b))
+        .map(|((a, b), weight)| Edge { source: nodes[a].name.clone(), target: nodes[b].name.clone(), weight })
+        .collect();
+    edges.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.source.cmp(&b.source)).then(a.target.cmp(&b.target)));
+
+    Ok(EntityGraph { nodes: order.into_iter().map(|i| nodes[i].clone()).collect(), edges })
+}
+
+impl EntityGraph {
+    pub fn to_json(&self) -> Result<String> {
+        let nodes: Vec<serde_json::Value> = self
+            .nodes
+            .iter()
+            .map(|n| serde_json::json!({ "id": n.name, "mentions": n.mentions, "sources": n.sources }))
+            .collect();
+        let edges: Vec<serde_json::Value> = self
+            .edges
+            .iter()
+            .map(|e| serde_json::json!({ "source": e.source, "target": e.target, "weight": e.weight }))
+            .collect();
+        Ok(serde_json::to_string_pretty(&serde_json::json!({ "nodes": nodes, "edges": edges }))?)
+    }
+
+    /// Graphviz DOT; edge thickness follows the weight
+    pub fn to_dot(&self) -> String {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let max_weight = self.edges.iter().map(|e| e.weight).max().unwrap_or(1) as f64;
+        let mut dot = String::from("graph entities {\n  node [shape=ellipse];\n");
+        for node in &self.nodes {
+            dot.push_str(&format!("  {} [mentions={}, sources={}];\n", quote(&node.name), node.mentions, node.sources));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "  {} -- {} [weight={}, penwidth={:.1}];\n",
+                quote(&edge.source),
+                quote(&edge.target),
+                edge.weight,
+                1.0 + 4.0 * edge.weight as f64 / max_weight
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// GraphML for Gephi, yEd and networkx
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"mentions\" for=\"node\" attr.name=\"mentions\" attr.type=\"int\"/>\n",
+            "  <key id=\"sources\" for=\"node\" attr.name=\"sources\" attr.type=\"int\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"int\"/>\n",
+            "  <graph id=\"entities\" edgedefault=\"undirected\">\n",
+        ));
+        for node in &self.nodes {
+            xml.push_str(&format!(
+                "    <node id=\"{}\"><data key=\"mentions\">{}</data><data key=\"sources\">{}</data></node>\n",
+                escape_xml(&node.name),
+                node.mentions,
+                node.sources
+            ));
+        }
+        for edge in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>\n",
+                escape_xml(&edge.source),
+                escape_xml(&edge.target),
+                edge.weight
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// Entities from a list of names or a `{name: [aliases]}` dict
+fn entities_from_py(entities: &PyAny) -> PyResult<Vec<EntitySpec>> {
+    if let Ok(dict) = entities.downcast::<PyDict>() {
+        let mut specs = BTreeMap::new();
+        for (name, aliases) in dict.iter() {
+            let name: String = name.extract()?;
+            let mut aliases: Vec<String> = if aliases.is_none() { Vec::new() } else { aliases.extract()? };
+            aliases.insert(0, name.clone());
+            specs.insert(name, aliases);
+        }
+        return Ok(specs.into_iter().map(|(name, aliases)| EntitySpec { name, aliases }).collect());
+    }
+    let names: Vec<String> = entities.extract()?;
+    Ok(names.into_iter().map(|name| EntitySpec { aliases: vec![name.clone()], name }).collect())
+}
+
+/// Weighted co-occurrence graph of companies and technologies mentioned together in a session's sources.
+/// `session` is a directory of markdown/text sources or a backup `.tar.gz`. Without `entities`, capitalized
+/// names found in at least `min_sources` sources are used. Options: `window` (sentence, paragraph, source),
+/// `min_sources` (2), `min_weight` (1), `max_nodes` (50) and `output` (a .json, .dot/.gv or .graphml path).
+#[pyfunction]
+#[pyo3(signature = (session, entities=None, options=None))]
+pub fn entity_graph(session: &str, entities: Option<&PyAny>, options: Option<&PyDict>, py: Python) -> PyResult<PyObject> {
+    guard("entity_graph", || {
+        let settings = GraphOptions::from_dict(options)?;
+        let specs = entities.map(entities_from_py).transpose()?;
+        let output: Option<String> = match options.and_then(|o| o.get_item("output")) {
+            Some(value) => Some(value.extract()?),
+            None => None,
+        };
+
+        let graph = py
+            .allow_threads(|| -> Result<EntityGraph> {
+                let sources: Vec<String> = load_sources(Path::new(session))?.into_iter().map(|(_, body)| body).collect();
+                match specs {
+                    Some(specs) => build_graph(&sources, &specs, true, &settings),
+                    None => build_graph(&sources, &detect_entities(&sources, settings.min_sources), false, &settings),
+                }
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to build entity graph: {}", e)))?;
+
+        let json = graph
+            .to_json()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to serialize entity graph: {}", e)))?;
+        let dot = graph.to_dot();
+        let graphml = graph.to_graphml();
+
+        if let Some(output) = &output {
+            let contents = match Path::new(output).extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+                Some("json") => &json,
+                Some("dot") | Some("gv") => &dot,
+                Some("graphml") => &graphml,
+                _ => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Unsupported graph format for {}. Use .json, .dot, .gv or .graphml", output)
+                    ))
+                }
+            };
+            fs::write(output, contents)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write entity graph: {}", e)))?;
+        }
+
+        let nodes = PyList::empty(py);
+        for node in &graph.nodes {
+            let dict = PyDict::new(py);
+            dict.set_item("id", &node.name)?;
+            dict.set_item("mentions", node.mentions)?;
+            dict.set_item("sources", node.sources)?;
+            nodes.append(dict)?;
+        }
+        let edges = PyList::empty(py);
+        for edge in &graph.edges {
+            let dict = PyDict::new(py);
+            dict.set_item("source", &edge.source)?;
+            dict.set_item("target", &edge.target)?;
+            dict.set_item("weight", edge.weight)?;
+            edges.append(dict)?;
+        }
+
+        let dict = PyDict::new(py);
+        dict.set_item("nodes", nodes)?;
+        dict.set_item("edges", edges)?;
+        dict.set_item("json", json)?;
+        dict.set_item("dot", dot)?;
+        dict.set_item("graphml", graphml)?;
+        Ok(dict.into())
+    })
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::charts::compact_number;
+use crate::facts::{facts_from_py, parse_fact_number, Fact, Facts, SOURCE_SEPARATOR};
+use crate::panics::guard;
+
+/// How contributing figures are combined into one estimate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Median,
+    TrimmedMean,
+    Mean,
+}
+
+impl Method {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "median" => Some(Method::Median),
+            "trimmed_mean" | "trimmed" => Some(Method::TrimmedMean),
+            "mean" => Some(Method::Mean),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Median => "median",
+            Method::TrimmedMean => "trimmed_mean",
+            Method::Mean => "mean",
+        }
+    }
+}
+
+/// One source figure and how much it counted
+pub struct Contribution {
+    pub key: String,
+    pub fact: Fact,
+    pub number: f64,
+    pub weight: f64,
+    /// False when trimmed away as an outlier
+    pub included: bool,
+}
+
+/// Consensus estimate with a confidence band
+pub struct Triangulation {
+    pub method: Method,
+    pub estimate: f64,
+    pub low: f64,
+    pub high: f64,
+    pub prefix: String,
+    pub percent: bool,
+    pub contributions: Vec<Contribution>,
+}
+
+impl Triangulation {
+    fn format(&self, value: f64) -> String {
+        format!("{}{}{}", self.prefix, compact_number(value), if self.percent { "%" } else { "" })
+    }
+
+    /// Headline text such as `$4.4B (range $3.9B–$5.0B, 4 sources)`
+    pub fn display(&self) -> String {
+        let sources = self.contributions.iter().filter(|c| c.included).count();
+        format!(
+            "{} (range {}–{}, {} source{})",
+            self.format(self.estimate),
+            self.format(self.low),
+            self.format(self.high),
+            sources,
+            if sources == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// Value at cumulative weight fraction `q` of `points` sorted by value
+fn weighted_quantile(points: &[(f64, f64)], q: f64) -> f64 {
+    let total: f64 = points.iter().map(|(_, w)| w).sum();
+    let target = q * total;
+    let mut cumulative = 0.0;
+    for (value, weight) in points {
+        cumulative += weight;
+        if cumulative >= target - 1e-12 {
+            return *value;
+        }
+    }
+    points.last().map(|(v, _)| *v).unwrap_or(0.0)
+}
+
+/// Combine figures for one metric. Weights express source credibility (default 1.0);
+/// `trim` is the weight fraction dropped from each end for the trimmed mean.
+/// The band is the weighted interquartile range of the contributing figures.
+pub fn triangulate_facts(facts: &Facts, method: Method, weights: &HashMap<String, f64>, trim: f64) -> Result<Triangulation> {
+    if !(0.0..0.5).contains(&trim) {
+        return Err(anyhow!("trim must be at least 0 and below 0.5"));
+    }
+
+    let mut contributions = Vec::new();
+    let mut unit: Option<(String, bool)> = None;
+    for (key, fact) in facts {
+        let parsed = parse_fact_number(&fact.value)
+            .ok_or_else(|| anyhow!("Fact '{}' is not a number: {}", key, fact.value))?;
+        match &unit {
+            Some((prefix, percent)) if *prefix != parsed.prefix || *percent != parsed.percent => {
+                return Err(anyhow!("Fact '{}' ({}) uses different units from the other figures", key, fact.value));
+            }
+            Some(_) => {}
+            None => unit = Some((parsed.prefix.clone(), parsed.percent)),
+        }
+
+        let weight = fact
+            .source
+            .as_ref()
+            .and_then(|source| weights.get(source))
+            .or_else(|| weights.get(key))
+            .copied()
+            .unwrap_or(1.0);
+        if weight < 0.0 {
+            return Err(anyhow!("Weight for '{}' cannot be negative", key));
+        }
+        contributions.push(Contribution { key: key.clone(), fact: fact.clone(), number: parsed.value, weight, included: weight > 0.0 });
+    }
+    let (prefix, percent) = unit.ok_or_else(|| anyhow!("No figures to triangulate"))?;
+
+    contributions.sort_by(|a, b| a.number.total_cmp(&b.number));
+    let total: f64 = contributions.iter().map(|c| c.weight).sum();
+    if total <= 0.0 {
+        return Err(anyhow!("All sources have zero weight"));
+    }
+
+    // Trim by weight from both ends, so a single heavily weighted source is never trimmed entirely
+    if method == Method::TrimmedMean && trim > 0.0 {
+        let cut = trim * total;
+        let mut below = 0.0;
+        for contribution in contributions.iter_mut() {
+            below += contribution.weight;
+            if below > cut {
+                break;
+            }
+            contribution.included = false;
+        }
+        let mut above = 0.0;
+        for contribution in contributions.iter_mut().rev() {
+            above += contribution.weight;
+            if above > cut {
+                break;
+            }
+            contribution.included = false;
+        }
+    }
+
+    let points: Vec<(f64, f64)> = contributions.iter().filter(|c| c.included && c.weight > 0.0).map(|c| (c.number, c.weight)).collect();
+    let weight_sum: f64 = points.iter().map(|(_, w)| w).sum();
+    let estimate = match method {
+        Method::Median => weighted_quantile(&points, 0.5),
+        Method::TrimmedMean | Method::Mean => points.iter().map(|(v, w)| v * w).sum::<f64>() / weight_sum,
+    };
+    let (low, high) = if points.len() < 3 {
+        (points[0].0, points[points.len() - 1].0)
+    } else {
+        (weighted_quantile(&points, 0.25), weighted_quantile(&points, 0.75))
+    };
+
+    Ok(Triangulation { method, estimate, low, high, prefix, percent, contributions })
+}
+
+/// Consensus estimate for one metric from several sources, for use as a headline number.
+/// `metric_facts` is a `FactStore` or a fact dict; options: `metric` (only keys `metric` or `metric@...`),
+/// `weights` (`{source or key: credibility}`) and `trim` (0.2, for the trimmed mean).
+#[pyfunction]
+#[pyo3(signature = (metric_facts, method="median", options=None))]
+pub fn triangulate(metric_facts: &PyAny, method: &str, options: Option<&PyDict>, py: Python) -> PyResult<PyObject> {
+    guard("triangulate", || {
+        let method = Method::parse(method).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Unknown method '{}'. Expected median, trimmed_mean or mean", method)
+            )
+        })?;
+        let mut facts = facts_from_py(metric_facts)?;
+        let mut weights = HashMap::new();
+        let mut trim = 0.2;
+        if let Some(options) = options {
+            if let Some(metric) = options.get_item("metric") {
+                let metric: String = metric.extract()?;
+                facts.retain(|key, _| key.split(SOURCE_SEPARATOR).next() == Some(metric.as_str()));
+            }
+            if let Some(value) = options.get_item("weights") {
+                weights = value.extract()?;
+            }
+            if let Some(value) = options.get_item("trim") {
+                trim = value.extract()?;
+            }
+        }
+
+        let result = triangulate_facts(&facts, method, &weights, trim)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to triangulate: {}", e)))?;
+
+        let sources = PyList::empty(py);
+        for contribution in &result.contributions {
+            let dict = PyDict::new(py);
+            dict.set_item("key", &contribution.key)?;
+            dict.set_item("value", &contribution.fact.value)?;
+            dict.set_item("number", contribution.number)?;
+            dict.set_item("source", &contribution.fact.source)?;
+            dict.set_item("url", &contribution.fact.url)?;
+            dict.set_item("weight", contribution.weight)?;
+            dict.set_item("included", contribution.included)?;
+            sources.append(dict)?;
+        }
+        let dict = PyDict::new(py);
+        dict.set_item("method", result.method.as_str())?;
+        dict.set_item("estimate", result.estimate)?;
+        dict.set_item("low", result.low)?;
+        dict.set_item("high", result.high)?;
+        dict.set_item("display", result.display())?;
+        dict.set_item("sources", sources)?;
+        Ok(dict.into())
+    })
+}
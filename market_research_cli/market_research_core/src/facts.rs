@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::charts::escape_xml;
+use crate::panics::{guard, lock};
+use crate::render::map_outside_fences;
+use crate::tables::{parse_number, ParsedNumber};
+use crate::write_atomic;
+
+/// One recorded data point with its provenance
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Fact {
+    /// Display value as it should appear in prose, e.g. "$4.2B"
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub recorded_at: String,
+}
+
+impl Fact {
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("value", &self.value)?;
+        dict.set_item("source", &self.source)?;
+        dict.set_item("url", &self.url)?;
+        dict.set_item("recorded_at", &self.recorded_at)?;
+        Ok(dict)
+    }
+}
+
+pub type Facts = BTreeMap<String, Fact>;
+
+fn load_facts(path: &Path) -> Result<Facts> {
+    if !path.exists() {
+        return Ok(Facts::new());
+    }
+    let bytes = fs::read(path).context("Failed to read fact store")?;
+    serde_json::from_slice(&bytes).context("Fact store is corrupted")
+}
+
+/// Session store of keyed facts, optionally persisted as JSON
+#[pyclass]
+pub struct FactStore {
+    path: Option<PathBuf>,
+    facts: Mutex<Facts>,
+}
+
+#[pymethods]
+impl FactStore {
+    #[new]
+    #[pyo3(signature = (path=None))]
+    fn new(path: Option<&str>) -> PyResult<Self> {
+        guard("FactStore.new", || {
+            let path = path.map(PathBuf::from);
+            let facts = match &path {
+                Some(path) => load_facts(path)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load fact store: {}", e)))?,
+                None => Facts::new(),
+            };
+            Ok(FactStore { path, facts: Mutex::new(facts) })
+        })
+    }
+
+    /// Record or replace a fact; non-string values are stored as their display text
+    #[pyo3(signature = (key, value, source=None, url=None))]
+    fn set(&self, key: &str, value: &PyAny, source: Option<String>, url: Option<String>) -> PyResult<()> {
+        guard("FactStore.set", || {
+            if key.trim().is_empty() || key.contains(char::is_whitespace) || key.contains("}}") {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid fact key '{}'. Keys may not be empty or contain whitespace", key)
+                ));
+            }
+            let fact = Fact {
+                value: value.str()?.to_string(),
+                source,
+                url,
+                recorded_at: Local::now().to_rfc3339(),
+            };
+            lock(&self.facts).insert(key.to_string(), fact);
+            Ok(())
+        })
+    }
+
+    /// Look up a fact as a dict with value, source, url and recorded_at
+    fn get(&self, key: &str, py: Python) -> PyResult<Option<PyObject>> {
+        guard("FactStore.get", || {
+            match lock(&self.facts).get(key) {
+                Some(fact) => Ok(Some(fact.to_dict(py)?.into())),
+                None => Ok(None),
+            }
+        })
+    }
+
+    /// Remove a fact, returning whether it existed
+    fn remove(&self, key: &str) -> PyResult<bool> {
+        guard("FactStore.remove", || {
+            Ok(lock(&self.facts).remove(key).is_some())
+        })
+    }
+
+    /// List all fact keys in sorted order
+    fn keys(&self) -> PyResult<Vec<String>> {
+        guard("FactStore.keys", || {
+            Ok(lock(&self.facts).keys().cloned().collect())
+        })
+    }
+
+    /// Return every fact as `{key: {value, source, url, recorded_at}}`
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        guard("FactStore.to_dict", || {
+            let result = PyDict::new(py);
+            for (key, fact) in lock(&self.facts).iter() {
+                result.set_item(key, fact.to_dict(py)?)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Write the store to its JSON file (or to `path` if given)
+    #[pyo3(signature = (path=None))]
+    fn save(&self, path: Option<&str>) -> PyResult<()> {
+        guard("FactStore.save", || {
+            let path = path.map(PathBuf::from).or_else(|| self.path.clone()).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>("Fact store has no path; pass one to save()")
+            })?;
+            let bytes = serde_json::to_vec_pretty(&*lock(&self.facts))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to serialize facts: {}", e)))?;
+            write_atomic(&path, &bytes)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to save fact store: {}", e)))
+        })
+    }
+
+    /// Group facts about the same metric (`tam_2024@gartner`, `tam_2024@idc`, ...) and return the
+    /// groups whose values differ by more than `tolerance` (relative to their midpoint), or whose units differ
+    #[pyo3(signature = (tolerance=0.05))]
+    fn find_conflicts(&self, tolerance: f64, py: Python) -> PyResult<PyObject> {
+        guard("FactStore.find_conflicts", || {
+            if tolerance < 0.0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("tolerance cannot be negative"));
+            }
+            let conflicts = find_conflicts(&lock(&self.facts), tolerance);
+
+            let result = PyList::empty(py);
+            for conflict in conflicts {
+                let facts = PyList::empty(py);
+                for (key, fact, number) in &conflict.facts {
+                    let dict = fact.to_dict(py)?;
+                    dict.set_item("key", key)?;
+                    dict.set_item("number", number)?;
+                    facts.append(dict)?;
+                }
+                let dict = PyDict::new(py);
+                dict.set_item("metric", &conflict.metric)?;
+                dict.set_item("kind", conflict.kind)?;
+                dict.set_item("low", conflict.low)?;
+                dict.set_item("high", conflict.high)?;
+                dict.set_item("spread", conflict.spread)?;
+                dict.set_item("summary", &conflict.summary)?;
+                dict.set_item("facts", facts)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    fn __len__(&self) -> PyResult<usize> {
+        guard("FactStore.__len__", || {
+            Ok(lock(&self.facts).len())
+        })
+    }
+}
+
+impl FactStore {
+    /// Snapshot of the current facts for rendering
+    pub fn snapshot(&self) -> Facts {
+        lock(&self.facts).clone()
+    }
+}
+
+/// Accept a `FactStore` or a plain dict of `{key: value}` / `{key: {"value", "source", "url"}}`
+pub fn facts_from_py(obj: &PyAny) -> PyResult<Facts> {
+    if let Ok(store) = obj.extract::<PyRef<FactStore>>() {
+        return Ok(store.snapshot());
+    }
+
+    let dict: &PyDict = obj.downcast()?;
+    let mut facts = Facts::new();
+    for (key, value) in dict.iter() {
+        let fact = match value.downcast::<PyDict>() {
+            Ok(entry) => Fact {
+                value: match entry.get_item("value") {
+                    Some(value) => value.str()?.to_string(),
+                    None => {
+                        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                            format!("Fact '{}' is missing a value", key)
+                        ));
+                    }
+                },
+                source: entry.get_item("source").map(|s| s.extract()).transpose()?,
+                url: entry.get_item("url").map(|u| u.extract()).transpose()?,
+                recorded_at: String::new(),
+            },
+            Err(_) => Fact {
+                value: value.str()?.to_string(),
+                source: None,
+                url: None,
+                recorded_at: String::new(),
+            },
+        };
+        facts.insert(key.str()?.to_string(), fact);
+    }
+    Ok(facts)
+}
+
+/// Replace `{{fact:key}}` with the fact's value and a numbered citation, appending a sources list
+pub fn resolve_fact_refs(markdown: &str, facts: &Facts) -> String {
+    let reference = Regex::new(r"\{\{\s*fact:([^\s}]+)\s*\}\}").unwrap();
+    let mut cited: Vec<&str> = Vec::new();
+
+    let resolved = map_outside_fences(markdown, |line| {
+        reference
+            .replace_all(line, |caps: &Captures| {
+                let key = caps.get(1).unwrap().as_str();
+                let (key, fact) = match facts.get_key_value(key) {
+                    Some(entry) => entry,
+                    None => return caps[0].to_string(),
+                };
+                if fact.source.is_none() && fact.url.is_none() {
+                    return fact.value.clone();
+                }
+
+                let number = match cited.iter().position(|k| *k == key.as_str()) {
+                    Some(idx) => idx + 1,
+                    None => {
+                        cited.push(key.as_str());
+                        cited.len()
+                    }
+                };
+                format!(
+                    "{}<sup class=\"fact-ref\"><a href=\"#fact-source-{}\">[{}]</a></sup>",
+                    fact.value, number, number
+                )
+            })
+            .into_owned()
+    });
+
+    if cited.is_empty() {
+        return resolved;
+    }
+
+    // Sources list in citation order
+    let mut out = resolved.trim_end().to_string();
+    out.push_str("\n\n<section class=\"fact-sources\">\n<ol>\n");
+    for (idx, key) in cited.iter().enumerate() {
+        let fact = &facts[*key];
+        let entry = match (&fact.source, &fact.url) {
+            (Some(source), Some(url)) => format!("{} — <a href=\"{1}\">{1}</a>", escape_xml(source), escape_xml(url)),
+            (Some(source), None) => escape_xml(source),
+            (None, Some(url)) => format!("<a href=\"{0}\">{0}</a>", escape_xml(url)),
+            (None, None) => String::new(),
+        };
+        out.push_str(&format!("<li id=\"fact-source-{}\">{}</li>\n", idx + 1, entry));
+    }
+    out.push_str("</ol>\n</section>\n");
+    out
+}
+
+/// Separates the metric from the source in keys like `tam_2024@gartner`
+pub const SOURCE_SEPARATOR: char = '@';
+
+/// Facts about one metric that disagree
+pub struct Conflict {
+    pub metric: String,
+    /// "value" when the figures differ beyond the tolerance, "units" when they cannot be compared
+    pub kind: &'static str,
+    pub facts: Vec<(String, Fact, Option<f64>)>,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    pub spread: Option<f64>,
+    pub summary: String,
+}
+
+/// Parse a display value such as "$4.2B", "€3.8 billion" or "12.5%"
+pub fn parse_fact_number(value: &str) -> Option<ParsedNumber> {
+    let scale = Regex::new(r"(?i)\s*\b(thousand|million|mn|billion|bn|trillion)\b\.?$").unwrap();
+    let normalized = scale.replace(value.trim(), |caps: &Captures| {
+        match caps[1].to_lowercase().as_str() {
+            "thousand" => "K",
+            "million" | "mn" => "M",
+            "billion" | "bn" => "B",
+            _ => "T",
+        }
+        .to_string()
+    });
+    parse_number(&normalized)
+}
+
+/// Group facts by metric (the key before `@`) and flag groups whose numbers disagree
+pub fn find_conflicts(facts: &Facts, tolerance: f64) -> Vec<Conflict> {
+    let mut groups: BTreeMap<&str, Vec<(&String, &Fact)>> = BTreeMap::new();
+    for (key, fact) in facts {
+        let metric = key.split(SOURCE_SEPARATOR).next().unwrap_or(key);
+        groups.entry(metric).or_default().push((key, fact));
+    }
+
+    let mut conflicts = Vec::new();
+    for (metric, members) in groups {
+        if members.len() < 2 {
+            continue;
+        }
+        let parsed: Vec<(&String, &Fact, Option<ParsedNumber>)> = members
+            .into_iter()
+            .map(|(key, fact)| (key, fact, parse_fact_number(&fact.value)))
+            .collect();
+        let numbers: Vec<(&Fact, &ParsedNumber)> = parsed.iter().filter_map(|(_, fact, n)| n.as_ref().map(|n| (*fact, n))).collect();
+        if numbers.len() < 2 {
+            continue;
+        }
+        let entries = parsed.iter().map(|(key, fact, n)| ((*key).clone(), (*fact).clone(), n.as_ref().map(|n| n.value))).collect();
+
+        // A percentage and an absolute figure, or two currencies, are not the same measurement
+        let (first_prefix, first_percent) = (&numbers[0].1.prefix, numbers[0].1.percent);
+        if numbers.iter().any(|(_, n)| &n.prefix != first_prefix || n.percent != first_percent) {
+            let values: Vec<&str> = numbers.iter().map(|(fact, _)| fact.value.as_str()).collect();
+            conflicts.push(Conflict {
+                metric: metric.to_string(),
+                kind: "units",
+                facts: entries,
+                low: None,
+                high: None,
+                spread: None,
+                summary: format!("sources report {} in different units ({})", metric, values.join(", ")),
+            });
+            continue;
+        }
+
+        let low = numbers.iter().min_by(|a, b| a.1.value.total_cmp(&b.1.value)).unwrap();
+        let high = numbers.iter().max_by(|a, b| a.1.value.total_cmp(&b.1.value)).unwrap();
+        let midpoint = (low.1.value.abs() + high.1.value.abs()) / 2.0;
+        let spread = if midpoint == 0.0 { 0.0 } else { (high.1.value - low.1.value) / midpoint };
+        if spread <= tolerance {
+            continue;
+        }
+        conflicts.push(Conflict {
+            metric: metric.to_string(),
+            kind: "value",
+            facts: entries,
+            low: Some(low.1.value),
+            high: Some(high.1.value),
+            spread: Some(spread),
+            summary: format!("estimates vary between {} and {}", low.0.value, high.0.value),
+        });
+    }
+    conflicts
+}
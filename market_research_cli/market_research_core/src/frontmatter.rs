@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+use serde_yaml::{Mapping, Value};
+
+/// Syntax of a front matter block, told apart by its opening line: `---` YAML, `+++` TOML or ```` ```json ````
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrontMatterFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl FrontMatterFormat {
+    /// The format a block opened by `line` is in, if it opens one
+    fn opened_by(line: &str) -> Option<Self> {
+        match line.trim_end_matches(['\r', '\n']) {
+            "---" => Some(FrontMatterFormat::Yaml),
+            "+++" => Some(FrontMatterFormat::Toml),
+            "```json" => Some(FrontMatterFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// The line that closes a block of this format
+    fn closing(self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "---",
+            FrontMatterFormat::Toml => "+++",
+            FrontMatterFormat::Json => "```",
+        }
+    }
+}
+
+/// Split content into its front matter format, text and body, if a `---`, `+++` or ```` ```json ```` block opens
+/// the file
+pub fn split_front_matter(content: &str) -> Option<(FrontMatterFormat, &str, &str)> {
+    let first = content.split_inclusive('\n').next()?;
+    let format = FrontMatterFormat::opened_by(first).filter(|_| first.ends_with('\n'))?;
+    let rest = &content[first.len()..];
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == format.closing() {
+            let text = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some((format, text.trim_end_matches(['\r', '\n']), body));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// TOML as the YAML the rest of the crate reads; dates and times become their TOML text
+fn toml_to_yaml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => Value::Number(f.into()),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Sequence(items.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(table) => {
+            Value::Mapping(table.into_iter().map(|(key, value)| (Value::String(key), toml_to_yaml(value))).collect())
+        }
+    }
+}
+
+/// Parse front matter text as a mapping. A ```` ```json ```` block that is not a JSON object is an ordinary code
+/// block that happens to open the report, so it gives `None` rather than an error
+fn parse_front_matter(format: FrontMatterFormat, text: &str) -> Result<Option<Mapping>> {
+    if text.trim().is_empty() {
+        return Ok(Some(Mapping::new()));
+    }
+    let value = match format {
+        FrontMatterFormat::Yaml => serde_yaml::from_str::<Value>(text)?,
+        FrontMatterFormat::Toml => toml_to_yaml(toml::Value::Table(
+            toml::from_str(text).map_err(|e| anyhow!("Invalid TOML front matter: {}", e))?,
+        )),
+        FrontMatterFormat::Json => match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(value @ serde_json::Value::Object(_)) => serde_yaml::to_value(value)?,
+            _ => return Ok(None),
+        },
+    };
+    match value {
+        Value::Mapping(mapping) => Ok(Some(mapping)),
+        Value::Null => Ok(Some(Mapping::new())),
+        _ => Err(anyhow!("Front matter is not a key/value mapping")),
+    }
+}
+
+/// Read only the front matter of a file, stopping at the closing delimiter instead of loading the body
+pub fn read_front_matter(path: &Path) -> Result<Mapping> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let format = match FrontMatterFormat::opened_by(&line) {
+        Some(format) => format,
+        None => return Ok(Mapping::new()),
+    };
+
+    let mut text = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            // Unterminated block: treat the file as having no front matter, like split_front_matter
+            return Ok(Mapping::new());
+        }
+        if line.trim_end_matches(['\r', '\n']) == format.closing() {
+            break;
+        }
+        text.push_str(&line);
+    }
+    Ok(parse_front_matter(format, &text)?.unwrap_or_default())
+}
+
+/// Parse the front matter block into a YAML mapping (empty if absent), whichever format it is written in
+pub fn front_matter_mapping(content: &str) -> Result<(Mapping, &str)> {
+    match split_front_matter(content) {
+        Some((format, text, body)) => match parse_front_matter(format, text)? {
+            Some(mapping) => Ok((mapping, body)),
+            None => Ok((Mapping::new(), content)),
+        },
+        None => Ok((Mapping::new(), content)),
+    }
+}
+
+/// Re-serialize a mapping and body into a document with `---` delimiters
+pub fn compose(mapping: &Mapping, body: &str) -> Result<String> {
+    if mapping.is_empty() {
+        return Ok(body.to_string());
+    }
+    let yaml = serde_yaml::to_string(mapping)?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+/// `compose` in the given format, falling back to YAML for what the format cannot hold (TOML nulls, JSON non-string keys)
+fn compose_as(format: FrontMatterFormat, mapping: &Mapping, body: &str) -> Result<String> {
+    if mapping.is_empty() {
+        return Ok(body.to_string());
+    }
+    match format {
+        FrontMatterFormat::Yaml => compose(mapping, body),
+        FrontMatterFormat::Toml => match toml::to_string(mapping) {
+            Ok(text) => Ok(format!("+++\n{}+++\n{}", text, body)),
+            Err(_) => compose(mapping, body),
+        },
+        FrontMatterFormat::Json => match serde_json::to_string_pretty(mapping) {
+            Ok(text) => Ok(format!("```json\n{}\n```\n{}", text, body)),
+            Err(_) => compose(mapping, body),
+        },
+    }
+}
+
+/// Apply a change to the front matter, leaving the body byte-for-byte intact and the front matter in its format
+pub fn update_front_matter<F>(content: &str, change: F) -> Result<String>
+where
+    F: FnOnce(&mut Mapping) -> Result<()>,
+{
+    let (mut mapping, body) = front_matter_mapping(content)?;
+    // A JSON code block that is not front matter stays part of the body, and new front matter is YAML
+    let format = match split_front_matter(content) {
+        Some((format, _, split_body)) if split_body.len() == body.len() => format,
+        _ => FrontMatterFormat::Yaml,
+    };
+    change(&mut mapping)?;
+    compose_as(format, &mapping, body)
+}
+
+/// Read a scalar front matter value as trimmed text, treating blanks as missing
+pub fn mapping_str(mapping: &Mapping, key: &str) -> Option<String> {
+    match mapping.get(key)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Scalar front matter values as text, for callers that only read fields like `title` and `date`; lists, nested
+/// mappings and nulls are left out
+pub fn scalar_metadata(mapping: &Mapping) -> HashMap<String, String> {
+    mapping
+        .iter()
+        .filter_map(|(key, value)| {
+            let text = |value: &Value| match value {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                Value::Bool(b) => Some(b.to_string()),
+                _ => None,
+            };
+            Some((text(key)?, text(value)?))
+        })
+        .collect()
+}
+
+/// Read a tag list that may be written as a YAML sequence or a comma-separated string
+pub fn tags_of(mapping: &Mapping) -> Vec<String> {
+    match mapping.get("tags") {
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The front matter fields shown in report listings
+#[derive(Clone, Debug, Default)]
+pub struct FrontMatterSummary {
+    pub title: Option<String>,
+    pub date: Option<String>,
+    pub id: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl FrontMatterSummary {
+    pub fn from_mapping(mapping: &Mapping) -> Self {
+        FrontMatterSummary {
+            title: mapping_str(mapping, "title"),
+            date: mapping_str(mapping, "date"),
+            id: mapping_str(mapping, "id"),
+            tags: tags_of(mapping),
+        }
+    }
+}
+
+/// Convert a Python value (str, bool, int, float, None, list, tuple, dict) into YAML
+pub fn py_to_yaml(obj: &PyAny) -> PyResult<Value> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(value) = obj.extract::<bool>() {
+        return Ok(Value::Bool(value));
+    }
+    if let Ok(value) = obj.extract::<i64>() {
+        return Ok(Value::Number(value.into()));
+    }
+    if let Ok(value) = obj.extract::<f64>() {
+        return Ok(Value::Number(value.into()));
+    }
+    if let Ok(value) = obj.extract::<String>() {
+        return Ok(Value::String(value));
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut mapping = Mapping::new();
+        for (key, value) in dict.iter() {
+            mapping.insert(Value::String(key.str()?.to_string()), py_to_yaml(value)?);
+        }
+        return Ok(Value::Mapping(mapping));
+    }
+    if obj.downcast::<PyList>().is_ok() || obj.downcast::<PyTuple>().is_ok() {
+        let items = obj.iter()?.map(|item| py_to_yaml(item?)).collect::<PyResult<_>>()?;
+        return Ok(Value::Sequence(items));
+    }
+    Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+        format!("Unsupported front matter value type: {}", obj.get_type().name()?)
+    ))
+}
+
+/// Convert YAML into the matching Python value: mappings to dicts, sequences to lists, integers to int, other
+/// numbers to float. Keys that Python cannot hash (lists, mappings) become their YAML text
+pub fn yaml_to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (_, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Sequence(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(yaml_to_py(py, item)?)?;
+            }
+            list.into()
+        }
+        Value::Mapping(mapping) => {
+            let dict = PyDict::new(py);
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::Sequence(_) | Value::Mapping(_) => {
+                        serde_yaml::to_string(key).map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?
+                            .trim_end()
+                            .into_py(py)
+                    }
+                    _ => yaml_to_py(py, key)?,
+                };
+                dict.set_item(key, yaml_to_py(py, value)?)?;
+            }
+            dict.into()
+        }
+        // `!tag value`: the tag only names a type Python has no counterpart for, so the value stands alone
+        Value::Tagged(tagged) => yaml_to_py(py, &tagged.value)?,
+    })
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::Path;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use similar::{ChangeTag, TextDiff};
+
+use crate::panics::guard;
+use crate::render::RenderOptions;
+use crate::{render_report_html, write_atomic};
+
+/// Put every tag and text run on its own line with collapsed whitespace, so diffs ignore formatting
+pub fn normalize_html(html: &str) -> String {
+    let mut spaced = String::with_capacity(html.len() + html.len() / 8);
+    for c in html.chars() {
+        match c {
+            '<' => {
+                spaced.push('\n');
+                spaced.push(c);
+            }
+            '>' => {
+                spaced.push(c);
+                spaced.push('\n');
+            }
+            _ => spaced.push(c),
+        }
+    }
+
+    let mut out = String::with_capacity(spaced.len());
+    for line in spaced.lines() {
+        let collapsed = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !collapsed.is_empty() {
+            out.push_str(&collapsed);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// One differing line between the golden file and the fresh render
+pub struct LineChange {
+    pub kind: &'static str,
+    pub golden_line: Option<usize>,
+    pub rendered_line: Option<usize>,
+    pub text: String,
+}
+
+/// Line-level comparison of normalized HTML
+pub struct GoldenDiff {
+    pub changes: Vec<LineChange>,
+    pub unified: String,
+}
+
+/// Diff normalized golden HTML against normalized rendered HTML
+pub fn compare(golden: &str, rendered: &str) -> GoldenDiff {
+    let (golden, rendered) = (normalize_html(golden), normalize_html(rendered));
+    let diff = TextDiff::from_lines(&golden, &rendered);
+
+    let changes = diff
+        .iter_all_changes()
+        .filter_map(|change| {
+            let kind = match change.tag() {
+                ChangeTag::Delete => "removed",
+                ChangeTag::Insert => "added",
+                ChangeTag::Equal => return None,
+            };
+            Some(LineChange {
+                kind,
+                golden_line: change.old_index().map(|i| i + 1),
+                rendered_line: change.new_index().map(|i| i + 1),
+                text: change.value().trim_end().to_string(),
+            })
+        })
+        .collect();
+
+    let unified = diff.unified_diff().context_radius(3).header("golden", "rendered").to_string();
+    GoldenDiff { changes, unified }
+}
+
+/// Render markdown and compare it with a golden HTML file, or rewrite the golden file when `update` is set
+#[pyfunction]
+#[pyo3(signature = (markdown, golden_html_path, update=false, options=None))]
+pub fn render_and_compare(
+    markdown: &str,
+    golden_html_path: &str,
+    update: bool,
+    options: Option<&PyDict>,
+    py: Python,
+) -> PyResult<PyObject> {
+    guard("render_and_compare", || {
+        let render_options = RenderOptions::from_dict(options)?;
+        let rendered = render_report_html(markdown, &render_options)?;
+        let golden_path = Path::new(golden_html_path);
+
+        let result = PyDict::new(py);
+        if update {
+            if let Some(parent) = golden_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create directory: {}", e)))?;
+            }
+            write_atomic(golden_path, rendered.as_bytes())
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write golden file: {}", e)))?;
+            result.set_item("matches", true)?;
+            result.set_item("updated", true)?;
+            result.set_item("changes", PyList::empty(py))?;
+            result.set_item("diff", "")?;
+            return Ok(result.into());
+        }
+
+        let golden = fs::read_to_string(golden_path).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("Failed to read golden file {}: {}. Run with update=True to create it", golden_html_path, e)
+            )
+        })?;
+        let diff = compare(&golden, &rendered);
+
+        let changes = PyList::empty(py);
+        for change in &diff.changes {
+            let entry = PyDict::new(py);
+            entry.set_item("kind", change.kind)?;
+            entry.set_item("golden_line", change.golden_line)?;
+            entry.set_item("rendered_line", change.rendered_line)?;
+            entry.set_item("text", &change.text)?;
+            changes.append(entry)?;
+        }
+
+        result.set_item("matches", diff.changes.is_empty())?;
+        result.set_item("updated", false)?;
+        result.set_item("changes", changes)?;
+        result.set_item("diff", diff.unified)?;
+        Ok(result.into())
+    })
+}
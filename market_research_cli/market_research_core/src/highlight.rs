@@ -0,0 +1,141 @@
+#[cfg(feature = "highlighting")]
+use std::sync::OnceLock;
+
+use anyhow::Result;
+#[cfg(feature = "highlighting")]
+use anyhow::anyhow;
+use pyo3::prelude::*;
+#[cfg(feature = "highlighting")]
+use syntect::highlighting::ThemeSet;
+#[cfg(feature = "highlighting")]
+use syntect::html::highlighted_html_for_string;
+#[cfg(feature = "highlighting")]
+use syntect::parsing::SyntaxSet;
+
+#[cfg(not(feature = "highlighting"))]
+use crate::capabilities::feature_missing;
+
+/// Light theme that prints well, used unless another is chosen
+pub const DEFAULT_THEME: &str = "InspiredGitHub";
+
+/// Setting value that turns highlighting off
+const OFF: &str = "none";
+
+/// Theme used when the options do not choose one: `DEFAULT_THEME`, or none without the `highlighting` feature
+pub fn default_theme() -> Option<String> {
+    cfg!(feature = "highlighting").then(|| DEFAULT_THEME.to_string())
+}
+
+/// Loading the bundled definitions takes a while, so it happens once per process
+#[cfg(feature = "highlighting")]
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+#[cfg(feature = "highlighting")]
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// The `highlight` option as a setting string: a theme name, `True` for the default theme, or `False`/`None`/`"none"` for off
+pub fn setting_from_py(value: &PyAny) -> PyResult<String> {
+    if value.is_none() {
+        return Ok(OFF.to_string());
+    }
+    if let Ok(enabled) = value.extract::<bool>() {
+        return Ok(if enabled { DEFAULT_THEME } else { OFF }.to_string());
+    }
+    value.extract()
+}
+
+/// Whether a setting turns highlighting off
+pub fn is_off(setting: &str) -> bool {
+    setting.eq_ignore_ascii_case(OFF)
+}
+
+/// The theme a setting selects, `None` when highlighting is off
+#[cfg(feature = "highlighting")]
+pub fn theme_for(setting: &str) -> Result<Option<String>> {
+    if is_off(setting) {
+        return Ok(None);
+    }
+    match themes().themes.contains_key(setting) {
+        true => Ok(Some(setting.to_string())),
+        false => {
+            let names: Vec<&str> = themes().themes.keys().map(String::as_str).collect();
+            Err(anyhow!("Unknown highlight theme '{}'. Expected none or one of: {}", setting, names.join(", ")))
+        }
+    }
+}
+
+#[cfg(not(feature = "highlighting"))]
+pub fn theme_for(setting: &str) -> Result<Option<String>> {
+    match is_off(setting) {
+        true => Ok(None),
+        false => Err(feature_missing("highlighting")),
+    }
+}
+
+/// `code` as a `<pre>` block with inline colors, or `None` if the language is unknown
+#[cfg(feature = "highlighting")]
+fn highlight(code: &str, language: &str, theme: &str) -> Option<String> {
+    let syntax = syntaxes().find_syntax_by_token(language)?;
+    let theme = themes().themes.get(theme)?;
+    highlighted_html_for_string(code, syntaxes(), syntax, theme).ok()
+}
+
+#[cfg(not(feature = "highlighting"))]
+fn highlight(_code: &str, _language: &str, _theme: &str) -> Option<String> {
+    None
+}
+
+/// Replace fenced code blocks in a known language with highlighted HTML. Colors are inline styles, so they
+/// survive PDF conversion and need no stylesheet; blocks without a language or in an unknown one are left alone
+pub fn highlight_code_blocks(markdown: &str, theme: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim_start();
+        let fence = if trimmed.starts_with("```") {
+            "```"
+        } else if trimmed.starts_with("~~~") {
+            "~~~"
+        } else {
+            out.push(lines[i].to_string());
+            i += 1;
+            continue;
+        };
+
+        // The info string may carry more than the language, e.g. ```python title="etl.py"
+        let language = trimmed.trim_start_matches(fence).split_whitespace().next().unwrap_or_default().to_lowercase();
+        let close = (i + 1..lines.len()).find(|&j| lines[j].trim_start().starts_with(fence));
+        let end = close.unwrap_or(lines.len());
+        let mut code = lines[i + 1..end].join("\n");
+        code.push('\n');
+
+        let highlighted = match language.is_empty() {
+            true => None,
+            false => highlight(&code, &language, theme),
+        };
+        match highlighted {
+            // Kept as a bare `<pre>` block: markdown treats everything up to `</pre>` as HTML, blank lines included
+            Some(html) => {
+                let class: String = language.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+                out.push(html.trim_end().replacen("<pre ", &format!("<pre class=\"code-block code-{}\" ", class), 1));
+                out.push(String::new());
+            }
+            None => out.extend(lines[i..(end + 1).min(lines.len())].iter().map(|l| l.to_string())),
+        }
+        i = end + 1;
+    }
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
@@ -0,0 +1,252 @@
+#[cfg(feature = "history")]
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "history")]
+use anyhow::{anyhow, Context};
+use anyhow::Result;
+use chrono::prelude::*;
+#[cfg(feature = "history")]
+use git2::{ErrorCode, IndexAddOption, Oid, Repository, Signature};
+
+#[cfg(not(feature = "history"))]
+use crate::capabilities::feature_missing;
+#[cfg(feature = "history")]
+use crate::frontmatter::{front_matter_mapping, mapping_str};
+
+/// Sidecar files that never belong in report history
+#[cfg(feature = "history")]
+const GITIGNORE: &str = ".trash/\n.archive/\n.index.json\n.retention.json\n.activity.json\n.qa_index.json\n.artifacts.json\n.artifacts/\n.converter_logs/\n.*.lock\n.*.tmp\n";
+
+/// Committer used when neither the repo nor the user's git config names one
+#[cfg(feature = "history")]
+const FALLBACK_NAME: &str = "market_research_core";
+#[cfg(feature = "history")]
+const FALLBACK_EMAIL: &str = "reports@localhost";
+
+/// One commit that changed a report
+pub struct HistoryEntry {
+    pub commit: String,
+    pub message: String,
+    pub author: String,
+    pub date: DateTime<FixedOffset>,
+}
+
+/// Whether the reports directory is itself a git repository; never without the `history` feature,
+/// so saves work as in a plain directory
+pub fn is_enabled(reports_dir: &str) -> bool {
+    cfg!(feature = "history") && Path::new(reports_dir).join(".git").exists()
+}
+
+/// Version of the bundled libgit2, `None` without the `history` feature
+pub fn libgit2_version() -> Option<String> {
+    #[cfg(feature = "history")]
+    {
+        let (major, minor, patch) = git2::Version::get().libgit2_version();
+        Some(format!("{}.{}.{}", major, minor, patch))
+    }
+    #[cfg(not(feature = "history"))]
+    None
+}
+
+/// Initialize the reports directory as a git repo and commit any reports already in it
+#[cfg(feature = "history")]
+pub fn init(reports_dir: &str, existing: &[String]) -> Result<()> {
+    if is_enabled(reports_dir) {
+        return Ok(());
+    }
+    fs::create_dir_all(reports_dir)?;
+    let repo = Repository::init(reports_dir).context("Failed to initialize git repository")?;
+
+    let gitignore = Path::new(reports_dir).join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, GITIGNORE)?;
+    }
+
+    let mut index = repo.index()?;
+    index.add_path(Path::new(".gitignore"))?;
+    index.add_all(existing, IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    commit_index(&repo, &mut index, "Initialize report history")?;
+    Ok(())
+}
+
+#[cfg(feature = "history")]
+fn open(reports_dir: &str) -> Result<Repository> {
+    Repository::open(reports_dir).map_err(|e| anyhow!("Report history is not enabled ({})", e.message()))
+}
+
+#[cfg(feature = "history")]
+fn signature(repo: &Repository) -> Result<Signature<'static>> {
+    match repo.signature() {
+        Ok(signature) => Ok(signature.to_owned()),
+        Err(_) => Ok(Signature::now(FALLBACK_NAME, FALLBACK_EMAIL)?),
+    }
+}
+
+#[cfg(feature = "history")]
+fn head_commit(repo: &Repository) -> Result<Option<git2::Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(e) if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Commit the staged index on top of HEAD; returns None when nothing changed
+#[cfg(feature = "history")]
+fn commit_index(repo: &Repository, index: &mut git2::Index, message: &str) -> Result<Option<Oid>> {
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = head_commit(repo)?;
+    if parent.as_ref().map(|p| p.tree_id() == tree.id()).unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let signature = signature(repo)?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    Ok(Some(repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?))
+}
+
+/// Whether `filename` is tracked in the HEAD commit
+#[cfg(feature = "history")]
+fn tracked(repo: &Repository, filename: &str) -> Result<bool> {
+    Ok(match head_commit(repo)? {
+        Some(commit) => commit.tree()?.get_path(Path::new(filename)).is_ok(),
+        None => false,
+    })
+}
+
+/// Stage the current state of `filename` (added, changed or removed) and commit it.
+/// The message names the action and the report title, e.g. `Update q3.md: Q3 market scan`.
+#[cfg(feature = "history")]
+pub fn commit_report(reports_dir: &str, filename: &str, action: Option<&str>) -> Result<Option<Oid>> {
+    let repo = open(reports_dir)?;
+    let path = Path::new(reports_dir).join(filename);
+    let exists = path.is_file();
+    let action = match action {
+        Some(action) => action,
+        None if !exists => "Delete",
+        None if tracked(&repo, filename)? => "Update",
+        None => "Add",
+    };
+
+    let mut index = repo.index()?;
+    stage(&mut index, reports_dir, filename)?;
+    index.write()?;
+
+    let title = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| front_matter_mapping(&content).ok().and_then(|(mapping, _)| mapping_str(&mapping, "title")));
+    let message = match title {
+        Some(title) => format!("{} {}: {}", action, filename, title),
+        None => format!("{} {}", action, filename),
+    };
+    commit_index(&repo, &mut index, &message)
+}
+
+/// Stage the current state of `filename`: added or changed if it exists, removed if not
+#[cfg(feature = "history")]
+fn stage(index: &mut git2::Index, reports_dir: &str, filename: &str) -> Result<()> {
+    match Path::new(reports_dir).join(filename).is_file() {
+        true => index.add_path(Path::new(filename))?,
+        false => index.remove_path(Path::new(filename))?,
+    }
+    Ok(())
+}
+
+/// Stage several reports and commit them together, for batch operations. The message names the action and
+/// count, e.g. `Retag 3 reports`, and lists the files in its body
+#[cfg(feature = "history")]
+pub fn commit_reports(reports_dir: &str, filenames: &[String], action: &str) -> Result<Option<Oid>> {
+    let repo = open(reports_dir)?;
+    let mut index = repo.index()?;
+    for filename in filenames {
+        stage(&mut index, reports_dir, filename)?;
+    }
+    index.write()?;
+
+    let noun = if filenames.len() == 1 { "report" } else { "reports" };
+    let message = format!("{} {} {}\n\n{}\n", action, filenames.len(), noun, filenames.join("\n"));
+    commit_index(&repo, &mut index, &message)
+}
+
+/// Commits that changed `filename`, newest first
+#[cfg(feature = "history")]
+pub fn history(reports_dir: &str, filename: &str) -> Result<Vec<HistoryEntry>> {
+    let repo = open(reports_dir)?;
+    if head_commit(&repo)?.is_none() {
+        return Ok(Vec::new());
+    }
+    let path = Path::new(filename);
+    let blob_at = |commit: &git2::Commit| -> Result<Option<Oid>> {
+        Ok(commit.tree()?.get_path(path).ok().map(|entry| entry.id()))
+    };
+
+    let mut walk = repo.revwalk()?;
+    walk.push_head()?;
+    walk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+
+    let mut entries = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let current = blob_at(&commit)?;
+        let previous = match commit.parent(0) {
+            Ok(parent) => blob_at(&parent)?,
+            Err(_) => None,
+        };
+        if current == previous {
+            continue;
+        }
+
+        let time = commit.time();
+        let offset = FixedOffset::east_opt(time.offset_minutes() * 60).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        entries.push(HistoryEntry {
+            commit: commit.id().to_string(),
+            message: commit.summary().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            date: offset.timestamp_opt(time.seconds(), 0).single().unwrap_or_default(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Content of `filename` at any revision git understands (commit id, `HEAD~2`, tag, ...)
+#[cfg(feature = "history")]
+pub fn show_at(reports_dir: &str, filename: &str, rev: &str) -> Result<String> {
+    let repo = open(reports_dir)?;
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| anyhow!("Unknown revision '{}': {}", rev, e.message()))?;
+    let entry = commit
+        .tree()?
+        .get_path(Path::new(filename))
+        .map_err(|_| anyhow!("{} does not exist at revision {}", filename, rev))?;
+    let blob = repo.find_blob(entry.id())?;
+    Ok(String::from_utf8_lossy(blob.content()).to_string())
+}
+
+#[cfg(not(feature = "history"))]
+pub fn init(_reports_dir: &str, _existing: &[String]) -> Result<()> {
+    Err(feature_missing("history"))
+}
+
+#[cfg(not(feature = "history"))]
+pub fn commit_report(_reports_dir: &str, _filename: &str, _action: Option<&str>) -> Result<()> {
+    Err(feature_missing("history"))
+}
+
+#[cfg(not(feature = "history"))]
+pub fn commit_reports(_reports_dir: &str, _filenames: &[String], _action: &str) -> Result<()> {
+    Err(feature_missing("history"))
+}
+
+#[cfg(not(feature = "history"))]
+pub fn history(_reports_dir: &str, _filename: &str) -> Result<Vec<HistoryEntry>> {
+    Err(feature_missing("history"))
+}
+
+#[cfg(not(feature = "history"))]
+pub fn show_at(_reports_dir: &str, _filename: &str, _rev: &str) -> Result<String> {
+    Err(feature_missing("history"))
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde_yaml::Value;
+use ulid::Ulid;
+
+use crate::frontmatter::{front_matter_mapping, mapping_str, update_front_matter};
+
+const MAX_SLUG_LEN: usize = 80;
+
+/// Generate a new sortable, collision-resistant report ID
+pub fn new_report_id() -> String {
+    Ulid::new().to_string()
+}
+
+/// Lowercase, URL-safe slug: ASCII letters and digits separated by single hyphens
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let mut slug: String = slug.trim_end_matches('-').chars().take(MAX_SLUG_LEN).collect();
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        "report".to_string()
+    } else {
+        slug
+    }
+}
+
+fn front_matter_str(content: &str, key: &str) -> Option<String> {
+    let (mapping, _) = front_matter_mapping(content).ok()?;
+    mapping_str(&mapping, key)
+}
+
+/// Whether `target` is unused, or already holds the report with `id`
+fn is_free_for(reports_dir: &str, target: &str, id: &str) -> bool {
+    let existing = Path::new(reports_dir).join(target);
+    !existing.exists() || fs::read_to_string(&existing).ok().and_then(|c| front_matter_str(&c, "id")).as_deref() == Some(id)
+}
+
+/// Ensure the report has an `id` and `slug` in its front matter and return `(filename, content)`
+/// named after the slug. An existing file with the same slug but a different id gets a suffixed name.
+pub fn assign_identity(reports_dir: &str, filename: &str, content: &str) -> Result<(String, String)> {
+    let stem = Path::new(filename).file_stem().and_then(|s| s.to_str()).unwrap_or(filename);
+    let (mapping, body) = front_matter_mapping(content)?;
+
+    let id = front_matter_str(content, "id").unwrap_or_else(new_report_id);
+    let slug = match front_matter_str(content, "slug") {
+        Some(slug) => slugify(&slug),
+        None => {
+            let title = match mapping.get("title") {
+                Some(Value::String(title)) if !title.trim().is_empty() => title.clone(),
+                _ => body
+                    .lines()
+                    .find_map(|line| line.strip_prefix("# "))
+                    .map(|heading| heading.trim().to_string())
+                    .unwrap_or_else(|| stem.to_string()),
+            };
+            slugify(&title)
+        }
+    };
+
+    // Resaving the same report keeps its file; a different report with the same slug gets the id suffix, then a
+    // counter if another report's id ends the same way. Ids come from front matter, so the suffix is slugified
+    let tail: String = id.chars().skip(id.chars().count().saturating_sub(8)).collect();
+    let suffix = slugify(&tail);
+    let mut target = format!("{}.md", slug);
+    let mut collisions = 0;
+    while !is_free_for(reports_dir, &target, &id) {
+        collisions += 1;
+        target = match collisions {
+            1 => format!("{}-{}.md", slug, suffix),
+            n => format!("{}-{}-{}.md", slug, suffix, n),
+        };
+    }
+
+    let content = update_front_matter(content, |mapping| {
+        mapping.insert(Value::String("id".to_string()), Value::String(id));
+        mapping.insert(Value::String("slug".to_string()), Value::String(slug));
+        Ok(())
+    })?;
+    Ok((target, content))
+}
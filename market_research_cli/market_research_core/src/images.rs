@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "http")]
+use base64::Engine;
+use regex::{Captures, Regex};
+
+/// Largest image embedded; bigger ones stay linked
+#[cfg(feature = "http")]
+const MAX_IMAGE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Time allowed for each download
+#[cfg(feature = "http")]
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+#[cfg(feature = "http")]
+const USER_AGENT: &str = concat!("market_research_core/", env!("CARGO_PKG_VERSION"), " (image fetcher)");
+
+fn image_pattern() -> Regex {
+    Regex::new(r#"(<img\b[^>]*?\bsrc=")(https?://[^"]+)(")"#).unwrap()
+}
+
+/// Cached images older than this are downloaded again, and removed at the start of the next export
+#[cfg(feature = "http")]
+const CACHE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Where downloaded images are kept between exports, each named after the SHA-256 of its URL: the user's cache
+/// directory (`$XDG_CACHE_HOME`, `%LOCALAPPDATA%`, `~/Library/Caches` or `~/.cache`), else the temp dir
+#[cfg(feature = "http")]
+fn cache_dir() -> std::path::PathBuf {
+    use std::path::PathBuf;
+
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base = var("XDG_CACHE_HOME")
+        .or_else(|| var("LOCALAPPDATA"))
+        .or_else(|| var("HOME").map(|home| home.join(if cfg!(target_os = "macos") { "Library/Caches" } else { ".cache" })))
+        .unwrap_or_else(std::env::temp_dir);
+    base.join("market_research_core").join("images")
+}
+
+/// Create the cache directory readable only by the current user. Fails when that cannot be ensured, such as
+/// when another user already owns it in a shared temp dir, so nothing is read from or written to it
+#[cfg(feature = "http")]
+fn ensure_cache_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for dir in [dir, dir.parent().unwrap_or(dir)] {
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a cache entry was written within `CACHE_MAX_AGE`
+#[cfg(feature = "http")]
+fn is_fresh(metadata: &std::fs::Metadata) -> bool {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < CACHE_MAX_AGE)
+}
+
+/// Remove cache entries past `CACHE_MAX_AGE`
+#[cfg(feature = "http")]
+fn prune_cache(dir: &std::path::Path) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        if entry.metadata().is_ok_and(|metadata| metadata.is_file() && !is_fresh(&metadata)) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Media type of image data, from its leading bytes; `None` for anything that is not an image
+#[cfg(feature = "http")]
+fn media_type(data: &[u8]) -> Option<&'static str> {
+    let head = String::from_utf8_lossy(&data[..data.len().min(1024)]).to_lowercase();
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() > 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else if head.contains("<svg") {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+/// Image data as a `data:` URI, if it is an image
+#[cfg(feature = "http")]
+fn data_uri(data: &[u8]) -> Option<String> {
+    media_type(data).map(|media_type| {
+        format!("data:{};base64,{}", media_type, base64::engine::general_purpose::STANDARD.encode(data))
+    })
+}
+
+/// Image at `url`, from the cache (when `cache` is usable) or downloaded into it
+#[cfg(feature = "http")]
+fn fetch(agent: &ureq::Agent, url: &str, cache: Option<&std::path::Path>) -> anyhow::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let path = cache.map(|cache| cache.join(crate::sha256_hex(url.as_bytes())));
+    if let Some(path) = path.as_ref().filter(|path| std::fs::metadata(path).is_ok_and(|metadata| is_fresh(&metadata))) {
+        if let Ok(data) = std::fs::read(path) {
+            return Ok(data);
+        }
+    }
+    let response = agent.get(url).call().map_err(|e| anyhow::anyhow!("Failed to fetch {}: {}", url, e))?;
+    let mut data = Vec::new();
+    response.into_reader().take(MAX_IMAGE_BYTES + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_IMAGE_BYTES {
+        return Err(anyhow::anyhow!("{} is larger than {} bytes", url, MAX_IMAGE_BYTES));
+    }
+    if media_type(&data).is_none() {
+        return Err(anyhow::anyhow!("{} is not an image", url));
+    }
+    if let Some(path) = path {
+        // Caching is best effort; the image is embedded either way
+        let _ = crate::write_atomic(&path, &data);
+    }
+    Ok(data)
+}
+
+/// `data:` URIs of the images that could be fetched, by URL
+#[cfg(feature = "http")]
+fn fetch_all(urls: Vec<String>) -> HashMap<String, String> {
+    use rayon::prelude::*;
+
+    let agent = ureq::AgentBuilder::new().timeout(FETCH_TIMEOUT).user_agent(USER_AGENT).build();
+    let cache = cache_dir();
+    let cache = ensure_cache_dir(&cache).is_ok().then_some(cache);
+    if let Some(cache) = &cache {
+        prune_cache(cache);
+    }
+    urls.into_par_iter()
+        .filter_map(|url| {
+            let uri = data_uri(&fetch(&agent, &url, cache.as_deref()).ok()?)?;
+            Some((url, uri))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch_all(_urls: Vec<String>) -> HashMap<String, String> {
+    HashMap::new()
+}
+
+/// URL of an `src` attribute as written in HTML
+fn unescape(url: &str) -> String {
+    url.replace("&amp;", "&")
+}
+
+/// Replace remote `<img>` sources in rendered HTML with `data:` URIs, downloading each image once and caching
+/// it in the user's cache directory for a week, so exports show their images offline. An image that cannot be
+/// fetched stays linked
+pub fn inline_remote_images(html: &str) -> String {
+    let pattern = image_pattern();
+    let mut urls: Vec<String> = pattern.captures_iter(html).map(|caps| unescape(&caps[2])).collect();
+    if urls.is_empty() {
+        return html.to_string();
+    }
+    urls.sort();
+    urls.dedup();
+    let embedded = fetch_all(urls);
+    pattern
+        .replace_all(html, |caps: &Captures| match embedded.get(&unescape(&caps[2])) {
+            Some(uri) => format!("{}{}{}", &caps[1], uri, &caps[3]),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
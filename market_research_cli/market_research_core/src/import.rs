@@ -0,0 +1,334 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use rayon::prelude::*;
+use serde_yaml::{Mapping, Value};
+use walkdir::WalkDir;
+
+use crate::convert::{convert_document, extension_of, DOCUMENT_EXTENSIONS};
+use crate::email::{parse_email_file, EmailSource};
+use crate::frontmatter::{compose, front_matter_mapping, mapping_str, tags_of};
+use crate::ids::{new_report_id, slugify};
+use crate::sandbox::Sandbox;
+use crate::stats::{parse_date, report_date};
+use crate::transcript::{format_timestamp, transcript_markdown, TranscriptChunk};
+use crate::{index, list_reports, lock_report, paths, sha256_hex, write_atomic};
+
+/// Outcome of importing an external folder
+#[derive(Default)]
+pub struct ImportSummary {
+    /// (source path, filename in the reports directory)
+    pub imported: Vec<(String, String)>,
+    /// Source paths whose body already exists in the library
+    pub duplicates: Vec<String>,
+    /// (source path, error)
+    pub failed: Vec<(String, String)>,
+}
+
+/// Hash of the report body only, so re-imports with different front matter are still detected
+fn body_hash(content: &str) -> String {
+    let body = front_matter_mapping(content).map(|(_, body)| body).unwrap_or(content);
+    sha256_hex(body.trim().replace("\r\n", "\n").as_bytes())
+}
+
+/// Fill in title, date, id and tags so imported notes match reports produced by the CLI.
+/// `title_hint` is a title found by a converter; it wins over the first heading but not over front matter.
+fn normalize_front_matter(source: &Path, content: &str, title_hint: Option<String>, extra_tags: &[String]) -> Result<String> {
+    let (mut mapping, body) = front_matter_mapping(content)?;
+    let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("Untitled");
+
+    let title = mapping_str(&mapping, "title")
+        .or(title_hint)
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|heading| heading.trim().to_string())
+        })
+        .unwrap_or_else(|| stem.replace(['_', '-'], " "));
+
+    let date = match mapping_str(&mapping, "date") {
+        Some(date) => parse_date(&date).ok_or_else(|| anyhow!("Unrecognized date '{}'", date))?,
+        None => {
+            let modified = fs::metadata(source).and_then(|m| m.modified()).ok();
+            report_date(stem, "", modified)
+        }
+    };
+
+    let id = mapping_str(&mapping, "id").unwrap_or_else(new_report_id);
+    let mut tags = tags_of(&mapping);
+    for tag in extra_tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    mapping.insert(Value::String("title".to_string()), Value::String(title));
+    mapping.insert(
+        Value::String("date".to_string()),
+        Value::String(date.format("%Y-%m-%d %H:%M:%S").to_string()),
+    );
+    mapping.insert(Value::String("id".to_string()), Value::String(id));
+    if !tags.is_empty() {
+        mapping.insert(
+            Value::String("tags".to_string()),
+            Value::Sequence(tags.into_iter().map(Value::String).collect()),
+        );
+    }
+
+    compose(&mapping, body)
+}
+
+/// Pick a portable `.md` filename in the reports directory that is not taken yet, not even by a name differing
+/// only in case, the same rules `save_report` enforces
+fn unique_filename(reports_dir: &str, source: &Path, taken: &HashSet<String>) -> String {
+    let stem = paths::portable_filename(source.file_stem().and_then(|s| s.to_str()).unwrap_or("report"));
+    // Shorten the stem rather than the suffix, so overlong names still get distinct candidates
+    let name = |suffix: &str| {
+        let mut end = stem.len().min(paths::MAX_NAME_BYTES - suffix.len() - ".md".len());
+        while !stem.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}{}.md", &stem[..end], suffix)
+    };
+    let is_free = |candidate: &str| {
+        let path = Path::new(reports_dir).join(candidate);
+        let folded = candidate.to_lowercase();
+        !taken.iter().any(|other| other.to_lowercase() == folded) && !path.exists() && paths::case_collision(&path).is_none()
+    };
+
+    let mut candidate = name("");
+    let mut counter = 2;
+    while !is_free(&candidate) {
+        candidate = name(&format!("_{}", counter));
+        counter += 1;
+    }
+    candidate
+}
+
+pub fn collect_sources(source_dir: &Path, recursive: bool, extensions: &[&str]) -> Vec<PathBuf> {
+    let walker = WalkDir::new(source_dir)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'));
+
+    walker
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| extensions.contains(&extension_of(path).as_str()))
+        .collect()
+}
+
+/// Hash the bodies of the existing library in parallel
+fn known_hashes(reports_dir: &str) -> Result<HashSet<String>> {
+    Ok(list_reports(reports_dir)?
+        .par_iter()
+        .filter_map(|filename| fs::read_to_string(Path::new(reports_dir).join(filename)).ok())
+        .map(|content| body_hash(&content))
+        .collect())
+}
+
+/// Write prepared reports sequentially so duplicates within the batch and filename collisions are resolved in order,
+/// then track the new files in the integrity index
+fn write_imports(
+    reports_dir: &str,
+    mut known: HashSet<String>,
+    prepared: Vec<(PathBuf, Result<(String, String)>)>,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut taken = HashSet::new();
+    for (source, outcome) in prepared {
+        let display = source.to_string_lossy().to_string();
+        let (hash, normalized) = match outcome {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                summary.failed.push((display, e.to_string()));
+                continue;
+            }
+        };
+        if !known.insert(hash) {
+            summary.duplicates.push(display);
+            continue;
+        }
+
+        let filename = unique_filename(reports_dir, &source, &taken);
+        let path = Path::new(reports_dir).join(&filename);
+        let written = lock_report(&path, true).and_then(|_lock| write_atomic(&path, normalized.as_bytes()));
+        match written {
+            Ok(()) => {
+                taken.insert(filename.clone());
+                summary.imported.push((display, filename));
+            }
+            Err(e) => summary.failed.push((display, format!("Failed to write report: {}", e))),
+        }
+    }
+
+    index::update_index(reports_dir, |index| {
+        for (_, filename) in &summary.imported {
+            let bytes = fs::read(Path::new(reports_dir).join(filename))?;
+            index.record(filename, &bytes);
+        }
+        Ok(())
+    })?;
+
+    Ok(summary)
+}
+
+/// Copy markdown files from an external folder into the reports directory, skipping duplicates
+pub fn import_dir(reports_dir: &str, source_dir: &str, recursive: bool) -> Result<ImportSummary> {
+    let source_dir = Path::new(source_dir);
+    if !source_dir.is_dir() {
+        return Err(anyhow!("Import source is not a directory: {}", source_dir.display()));
+    }
+    fs::create_dir_all(reports_dir)?;
+    let known = known_hashes(reports_dir)?;
+
+    // Read and normalize the candidates in parallel
+    let sources = collect_sources(source_dir, recursive, &["md", "markdown"]);
+    let prepared: Vec<(PathBuf, Result<(String, String)>)> = sources
+        .into_par_iter()
+        .map(|source| {
+            let outcome = fs::read_to_string(&source)
+                .map_err(|e| anyhow!("Failed to read file: {}", e))
+                .and_then(|content| Ok((body_hash(&content), normalize_front_matter(&source, &content, None, &[])?)));
+            (source, outcome)
+        })
+        .collect();
+
+    write_imports(reports_dir, known, prepared)
+}
+
+/// Options for `import_documents`
+#[derive(Default)]
+pub struct DocumentImportOptions {
+    pub recursive: bool,
+    pub tags: Vec<String>,
+    pub sandbox: Option<Sandbox>,
+}
+
+/// Convert legacy .docx/.html/.txt/.pdf (and markdown) documents and add them to the library, skipping duplicates
+pub fn import_documents(reports_dir: &str, paths: &[String], options: &DocumentImportOptions) -> Result<ImportSummary> {
+    fs::create_dir_all(reports_dir)?;
+    let known = known_hashes(reports_dir)?;
+
+    // 1. Expand directories; explicitly listed files are always attempted so unsupported types are reported
+    let mut sources = Vec::new();
+    for path in paths.iter().map(Path::new) {
+        if path.is_dir() {
+            sources.extend(collect_sources(path, options.recursive, &DOCUMENT_EXTENSIONS));
+        } else {
+            sources.push(path.to_path_buf());
+        }
+    }
+
+    // 2. Convert and normalize in parallel
+    let prepared: Vec<(PathBuf, Result<(String, String)>)> = sources
+        .into_par_iter()
+        .map(|source| {
+            let outcome = convert_document(&source, options.sandbox.as_ref()).and_then(|converted| {
+                let format = extension_of(&source);
+                let content = match format.as_str() {
+                    "md" | "markdown" => converted.markdown,
+                    _ => {
+                        let mut provenance = Mapping::new();
+                        let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                        provenance.insert(Value::String("imported_from".to_string()), Value::String(name));
+                        provenance.insert(Value::String("source_format".to_string()), Value::String(format));
+                        compose(&provenance, &converted.markdown)?
+                    }
+                };
+                let normalized = normalize_front_matter(&source, &content, converted.title, &options.tags)?;
+                Ok((body_hash(&content), normalized))
+            });
+            (source, outcome)
+        })
+        .collect();
+
+    // 3. Write in order and index
+    write_imports(reports_dir, known, prepared)
+}
+
+/// Build report content for one email, recording where it came from so it can be cited
+fn email_report(email: &EmailSource, mailbox: &Path) -> Result<String> {
+    let mut mapping = Mapping::new();
+    let mut set = |key: &str, value: String| {
+        mapping.insert(Value::String(key.to_string()), Value::String(value));
+    };
+    set("title", email.subject.clone());
+    let date = email
+        .date
+        .map(|date| date.naive_local())
+        .or_else(|| fs::metadata(mailbox).and_then(|m| m.modified()).ok().map(|time| DateTime::<Local>::from(time).naive_local()));
+    if let Some(date) = date {
+        set("date", date.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    set("author", email.sender.clone());
+    set("source_type", "email".to_string());
+    set("source_id", email.source_id());
+    if let Some(message_id) = &email.message_id {
+        set("message_id", message_id.clone());
+    }
+    set("imported_from", mailbox.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+
+    compose(&mapping, &format!("{}\n", email.body))
+}
+
+/// Ingest .eml and .mbox files as source reports, one per message, skipping messages already in the library
+pub fn ingest_emails(reports_dir: &str, paths: &[String], tags: &[String]) -> Result<ImportSummary> {
+    fs::create_dir_all(reports_dir)?;
+    let known = known_hashes(reports_dir)?;
+
+    let mut prepared: Vec<(PathBuf, Result<(String, String)>)> = Vec::new();
+    for path in paths.iter().map(Path::new) {
+        match parse_email_file(path) {
+            Ok(emails) => {
+                let converted: Vec<(PathBuf, Result<(String, String)>)> = emails
+                    .par_iter()
+                    .map(|email| {
+                        // Name each message after its subject, shown as `mailbox/subject` in the summary
+                        let source = path.join(slugify(&email.subject));
+                        let outcome = email_report(email, path).and_then(|content| {
+                            Ok((body_hash(&content), normalize_front_matter(&source, &content, None, tags)?))
+                        });
+                        (source, outcome)
+                    })
+                    .collect();
+                prepared.extend(converted);
+            }
+            Err(e) => prepared.push((path.to_path_buf(), Err(e))),
+        }
+    }
+
+    write_imports(reports_dir, known, prepared)
+}
+
+/// Save a parsed transcript as a source report; `metadata` supplies title, date, url and any other front matter
+pub fn ingest_transcript(
+    reports_dir: &str,
+    chunks: &[TranscriptChunk],
+    mut metadata: Mapping,
+) -> Result<ImportSummary> {
+    fs::create_dir_all(reports_dir)?;
+    let known = known_hashes(reports_dir)?;
+
+    let title = mapping_str(&metadata, "title").unwrap_or_else(|| "Transcript".to_string());
+    let duration = chunks.last().map(|chunk| format_timestamp(chunk.end)).unwrap_or_default();
+    metadata.insert(Value::String("title".to_string()), Value::String(title.clone()));
+    metadata.insert(Value::String("source_type".to_string()), Value::String("transcript".to_string()));
+    metadata.insert(Value::String("duration".to_string()), Value::String(duration));
+    if mapping_str(&metadata, "date").is_none() {
+        metadata.insert(
+            Value::String("date".to_string()),
+            Value::String(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+        );
+    }
+    let content = compose(&metadata, &transcript_markdown(&title, chunks))?;
+
+    let source = PathBuf::from(format!("{}.md", slugify(&title)));
+    let outcome = normalize_front_matter(&source, &content, None, &[]).map(|normalized| (body_hash(&content), normalized));
+    write_imports(reports_dir, known, vec![(source, outcome)])
+}
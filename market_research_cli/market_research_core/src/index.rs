@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::compat;
+use crate::{list_files, list_reports, lock_report, sha256_hex, write_atomic};
+
+pub const INDEX_FILE: &str = ".index.json";
+pub const INDEX_SCHEMA_VERSION: u32 = 1;
+
+/// Integrity record for one report
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexEntry {
+    pub sha256: String,
+    pub size: u64,
+    pub indexed_at: String,
+}
+
+/// Persistent per-directory report index stored in `.index.json`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportIndex {
+    pub schema_version: u32,
+    pub reports: BTreeMap<String, IndexEntry>,
+}
+
+impl Default for ReportIndex {
+    fn default() -> Self {
+        ReportIndex {
+            schema_version: INDEX_SCHEMA_VERSION,
+            reports: BTreeMap::new(),
+        }
+    }
+}
+
+/// Result of comparing the index against the files on disk
+#[derive(Default)]
+pub struct VerifyReport {
+    pub ok: Vec<String>,
+    pub modified: Vec<String>,
+    pub missing: Vec<String>,
+    pub untracked: Vec<String>,
+}
+
+fn index_path(reports_dir: &str) -> PathBuf {
+    Path::new(reports_dir).join(INDEX_FILE)
+}
+
+impl ReportIndex {
+    /// Load the index, returning an empty one if it does not exist yet
+    pub fn load(reports_dir: &str) -> Result<Self> {
+        let path = index_path(reports_dir);
+        if !path.exists() {
+            return Ok(ReportIndex::default());
+        }
+        let bytes = fs::read(&path).context("Failed to read report index")?;
+        let mut value: serde_json::Value = serde_json::from_slice(&bytes).context("Report index is corrupted")?;
+        // Older indexes are upgraded in memory (and on disk by `migrate`); newer ones are refused
+        compat::upgrade_index(&mut value)?;
+        serde_json::from_value(value).context("Report index is corrupted")
+    }
+
+    /// Write the index atomically via a temp file in the same directory
+    pub fn save(&self, reports_dir: &str) -> Result<()> {
+        write_atomic(&index_path(reports_dir), &serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record the current checksum of a report's content
+    pub fn record(&mut self, filename: &str, bytes: &[u8]) {
+        self.reports.insert(
+            filename.to_string(),
+            IndexEntry {
+                sha256: sha256_hex(bytes),
+                size: bytes.len() as u64,
+                indexed_at: Local::now().to_rfc3339(),
+            },
+        );
+    }
+}
+
+/// Apply a change to the index while holding its lock
+pub fn update_index<F>(reports_dir: &str, change: F) -> Result<()>
+where
+    F: FnOnce(&mut ReportIndex) -> Result<()>,
+{
+    let _lock = lock_report(&index_path(reports_dir), true)?;
+    let mut index = ReportIndex::load(reports_dir)?;
+    change(&mut index)?;
+    index.save(reports_dir)
+}
+
+/// Record the checksum of a report as it currently exists on disk
+pub fn record_file(reports_dir: &str, filename: &str) -> Result<()> {
+    let bytes = fs::read(Path::new(reports_dir).join(filename))?;
+    update_index(reports_dir, |index| {
+        index.record(filename, &bytes);
+        Ok(())
+    })
+}
+
+/// Drop a report from the index
+pub fn forget_file(reports_dir: &str, filename: &str) -> Result<()> {
+    update_index(reports_dir, |index| {
+        index.reports.remove(filename);
+        Ok(())
+    })
+}
+
+/// Recompute checksums for every report currently on disk
+pub fn rebuild(reports_dir: &str) -> Result<usize> {
+    let files = list_reports(reports_dir)?;
+    let mut contents = Vec::with_capacity(files.len());
+    for filename in files {
+        let bytes = fs::read(Path::new(reports_dir).join(&filename))?;
+        contents.push((filename, bytes));
+    }
+
+    update_index(reports_dir, |index| {
+        index.reports.clear();
+        for (filename, bytes) in &contents {
+            index.record(filename, bytes);
+        }
+        Ok(())
+    })?;
+    Ok(contents.len())
+}
+
+/// Changes made by one `sync` pass
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SyncStats {
+    pub added: usize,
+    pub removed: usize,
+    pub updated: usize,
+}
+
+/// Bring the index in line with the files on disk without touching unchanged entries.
+/// Full passes cover files with one of `extensions`; `only` limits the pass to specific filenames; modified files
+/// are re-recorded only with `reindex_modified`, otherwise they stay flagged by `verify`.
+pub fn sync(reports_dir: &str, extensions: &[String], only: Option<&[String]>, reindex_modified: bool) -> Result<SyncStats> {
+    let on_disk: Vec<String> = match only {
+        Some(filenames) => filenames.iter().filter(|f| Path::new(reports_dir).join(f).is_file()).cloned().collect(),
+        None => {
+            let mut files: Vec<String> = list_files(reports_dir, extensions)?.into_iter().map(|(f, _)| f).collect();
+            files.sort();
+            files
+        }
+    };
+
+    // Skip taking the lock and rewriting the index when there is obviously nothing to do
+    if !reindex_modified {
+        let current = ReportIndex::load(reports_dir)?;
+        let untracked = on_disk.iter().any(|f| !current.reports.contains_key(f));
+        let vanished = match only {
+            Some(filenames) => filenames.iter().any(|f| !on_disk.contains(f) && current.reports.contains_key(f)),
+            // Every file on disk is tracked at this point, so any extra entry is a deleted report
+            None => current.reports.len() != on_disk.len(),
+        };
+        if !untracked && !vanished {
+            return Ok(SyncStats::default());
+        }
+    }
+
+    let mut stats = SyncStats::default();
+    update_index(reports_dir, |index| {
+        let gone: Vec<String> = match only {
+            Some(filenames) => filenames.iter().filter(|f| !on_disk.contains(f)).cloned().collect(),
+            None => index.reports.keys().filter(|f| on_disk.binary_search(f).is_err()).cloned().collect(),
+        };
+        for filename in gone {
+            if index.reports.remove(&filename).is_some() {
+                stats.removed += 1;
+            }
+        }
+
+        for filename in &on_disk {
+            let tracked = index.reports.contains_key(filename);
+            if tracked && !reindex_modified {
+                continue;
+            }
+            let bytes = match fs::read(Path::new(reports_dir).join(filename)) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {}", filename)),
+            };
+            match index.reports.get(filename) {
+                Some(entry) if entry.sha256 == sha256_hex(&bytes) => {}
+                Some(_) => {
+                    index.record(filename, &bytes);
+                    stats.updated += 1;
+                }
+                None => {
+                    index.record(filename, &bytes);
+                    stats.added += 1;
+                }
+            }
+        }
+        Ok(())
+    })?;
+    Ok(stats)
+}
+
+/// Compare stored checksums with the files on disk
+pub fn verify(reports_dir: &str) -> Result<VerifyReport> {
+    let index = {
+        let _lock = lock_report(&index_path(reports_dir), false)?;
+        ReportIndex::load(reports_dir)?
+    };
+    let mut report = VerifyReport::default();
+
+    for (filename, entry) in &index.reports {
+        match fs::read(Path::new(reports_dir).join(filename)) {
+            Ok(bytes) if sha256_hex(&bytes) == entry.sha256 => report.ok.push(filename.clone()),
+            Ok(_) => report.modified.push(filename.clone()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => report.missing.push(filename.clone()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", filename)),
+        }
+    }
+
+    for filename in list_reports(reports_dir)? {
+        if !index.reports.contains_key(&filename) {
+            report.untracked.push(filename);
+        }
+    }
+    report.untracked.sort();
+
+    Ok(report)
+}
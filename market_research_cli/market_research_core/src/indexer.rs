@@ -0,0 +1,201 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::index;
+use crate::panics::lock;
+use crate::watcher::translate;
+
+/// How long to wait for a burst of file events to settle before indexing
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Progress of the background indexer, readable from Python at any time
+#[derive(Clone, Default)]
+pub struct IndexerStatus {
+    pub running: bool,
+    pub watching: bool,
+    pub runs: usize,
+    pub last_run: Option<String>,
+    pub last_duration_ms: u64,
+    pub added: usize,
+    pub removed: usize,
+    pub updated: usize,
+    pub pending: usize,
+    pub last_error: Option<String>,
+}
+
+/// Work handed from the watcher and the Python side to the indexing thread
+#[derive(Default)]
+struct Signal {
+    stop: bool,
+    full: bool,
+    pending: BTreeSet<String>,
+}
+
+type SharedSignal = Arc<(Mutex<Signal>, Condvar)>;
+
+/// A running indexer thread; dropping it stops the thread
+pub struct BackgroundIndexer {
+    signal: SharedSignal,
+    status: Arc<Mutex<IndexerStatus>>,
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Run one pass and fold its outcome into the status
+fn run_pass(
+    reports_dir: &str,
+    extensions: &[String],
+    only: Option<&[String]>,
+    reindex_modified: bool,
+    status: &Mutex<IndexerStatus>,
+) {
+    let started = Instant::now();
+    let result = index::sync(reports_dir, extensions, only, reindex_modified);
+
+    let mut status = lock(status);
+    status.runs += 1;
+    status.last_run = Some(Local::now().to_rfc3339());
+    status.last_duration_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(stats) => {
+            status.added += stats.added;
+            status.removed += stats.removed;
+            status.updated += stats.updated;
+            status.last_error = None;
+        }
+        Err(e) => status.last_error = Some(e.to_string()),
+    }
+}
+
+/// Watch the reports directory and queue changed top-level files with one of `extensions` for the indexing thread
+fn start_watcher(reports_dir: &str, extensions: Vec<String>, signal: &SharedSignal) -> Result<RecommendedWatcher> {
+    let dir = PathBuf::from(reports_dir);
+    let mut roots = vec![dir.clone()];
+    if let Ok(canonical) = dir.canonicalize() {
+        roots.push(canonical);
+    }
+
+    let signal = Arc::clone(signal);
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let event = match result {
+            Ok(event) => event,
+            Err(_) => return,
+        };
+        let changed: Vec<String> = translate(&roots, &event)
+            .into_iter()
+            .map(|event| event.filename)
+            .filter(|name| !name.contains(['/', '\\']) && has_extension(name, &extensions))
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        let (state, wake) = &*signal;
+        lock(state).pending.extend(changed);
+        wake.notify_all();
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+/// Whether `name` ends in one of `extensions` (lowercase, without the dot)
+fn has_extension(name: &str, extensions: &[String]) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extensions.contains(&extension.to_lowercase()))
+}
+
+impl BackgroundIndexer {
+    /// Index files with one of `extensions` once immediately, then on every file change (when `watch` is set) and
+    /// every `interval`
+    pub fn start(
+        reports_dir: &str,
+        extensions: Vec<String>,
+        interval: Duration,
+        watch: bool,
+        reindex_modified: bool,
+    ) -> Result<Self> {
+        if !Path::new(reports_dir).is_dir() {
+            return Err(anyhow!("Reports directory does not exist: {}", reports_dir));
+        }
+
+        let signal: SharedSignal = Arc::new((Mutex::new(Signal { full: true, ..Default::default() }), Condvar::new()));
+        let watcher = if watch { Some(start_watcher(reports_dir, extensions.clone(), &signal)?) } else { None };
+        let status = Arc::new(Mutex::new(IndexerStatus { running: true, watching: watch, ..Default::default() }));
+
+        let thread = {
+            let (signal, status, reports_dir) = (Arc::clone(&signal), Arc::clone(&status), reports_dir.to_string());
+            std::thread::Builder::new().name("report-indexer".to_string()).spawn(move || {
+                let (state, wake) = &*signal;
+                let mut guard = lock(state);
+                loop {
+                    if !guard.full && guard.pending.is_empty() && !guard.stop {
+                        let (next, timeout) = wake
+                            .wait_timeout_while(guard, interval, |s| !s.stop && !s.full && s.pending.is_empty())
+                            .unwrap_or_else(PoisonError::into_inner);
+                        guard = next;
+                        guard.full |= timeout.timed_out();
+                    }
+                    if guard.stop {
+                        break;
+                    }
+
+                    // Let a burst of saves settle so one pass covers all of them
+                    if !guard.full {
+                        drop(guard);
+                        std::thread::sleep(DEBOUNCE);
+                        guard = lock(state);
+                    }
+                    let full = std::mem::take(&mut guard.full);
+                    let pending: Vec<String> = std::mem::take(&mut guard.pending).into_iter().collect();
+                    drop(guard);
+
+                    let only = if full { None } else { Some(pending.as_slice()) };
+                    run_pass(&reports_dir, &extensions, only, reindex_modified, &status);
+                    guard = lock(state);
+                }
+                lock(&status).running = false;
+            })?
+        };
+
+        Ok(BackgroundIndexer { signal, status, watcher, thread: Some(thread) })
+    }
+
+    /// Snapshot of the indexer's progress
+    pub fn status(&self) -> IndexerStatus {
+        let mut status = lock(&self.status).clone();
+        status.pending = lock(&self.signal.0).pending.len();
+        status
+    }
+
+    /// Ask for a full pass as soon as possible
+    pub fn trigger(&self) {
+        let (state, wake) = &*self.signal;
+        lock(state).full = true;
+        wake.notify_all();
+    }
+
+    /// Stop watching, finish the current pass and join the thread
+    pub fn stop(&mut self) {
+        self.watcher.take();
+        let (state, wake) = &*self.signal;
+        lock(state).stop = true;
+        wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for BackgroundIndexer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,225 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::{Deserialize, Serialize};
+
+use crate::ids::new_report_id;
+use crate::panics::guard;
+use crate::sandbox::{self, Sandbox};
+
+/// Converter logs of a reports directory, one JSON Lines file per job
+pub const LOG_DIR: &str = ".converter_logs";
+
+/// Lines of converter output quoted in error messages
+pub const TAIL_LINES: usize = 20;
+
+/// Output kept per stream and run; a runaway converter keeps only its last bytes
+const MAX_CAPTURE_BYTES: usize = 1024 * 1024;
+
+/// Job logs kept per directory; the oldest are removed when a new job starts
+const MAX_LOG_FILES: usize = 500;
+
+/// Longest accepted job id, which also names the log file
+const MAX_JOB_ID_LEN: usize = 128;
+
+/// One external converter invocation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConverterRun {
+    pub job_id: String,
+    pub converter: String,
+    pub args: Vec<String>,
+    pub started_at: String,
+    pub duration_seconds: f64,
+    pub success: bool,
+    /// `exit code 1`, `killed by a signal`, `timed out`, or why it could not start
+    pub outcome: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// The export job converter runs are logged under; callers pass their own id to correlate the log with theirs
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub id: String,
+    pub log_dir: PathBuf,
+}
+
+impl Job {
+    /// A job with the given or a fresh id, logging to `log_dir` or the shared log directory under the temp dir
+    pub fn new(id: Option<&str>, log_dir: Option<&str>) -> Self {
+        Job {
+            id: id.map(str::to_string).unwrap_or_else(new_job_id),
+            log_dir: log_dir.map(PathBuf::from).unwrap_or_else(default_log_dir),
+        }
+    }
+
+    pub fn log_path(&self) -> PathBuf {
+        log_path(&self.log_dir, &self.id)
+    }
+}
+
+/// Sortable, unique id for an export job
+pub fn new_job_id() -> String {
+    new_report_id()
+}
+
+/// Where standalone exports log converter output
+pub fn default_log_dir() -> PathBuf {
+    std::env::temp_dir().join("market_research_core").join("converter_logs")
+}
+
+/// Converter log directory of a reports directory
+pub fn reports_log_dir(reports_dir: &str) -> String {
+    Path::new(reports_dir).join(LOG_DIR).to_string_lossy().to_string()
+}
+
+/// Job ids name log files, so only letters, digits, `.`, `_` and `-` are allowed
+pub fn validate_job_id(job_id: &str) -> Result<()> {
+    let valid = !job_id.is_empty()
+        && job_id.len() <= MAX_JOB_ID_LEN
+        && !job_id.starts_with('.')
+        && job_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    match valid {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "Invalid job id '{}': use up to {} letters, digits, '.', '_' or '-', not starting with '.'",
+            job_id,
+            MAX_JOB_ID_LEN
+        )),
+    }
+}
+
+fn log_path(log_dir: &Path, job_id: &str) -> PathBuf {
+    log_dir.join(format!("{}.jsonl", job_id))
+}
+
+/// The last `lines` non-empty lines of converter output
+pub fn tail(output: &str, lines: usize) -> String {
+    let kept: Vec<&str> = output.lines().filter(|line| !line.trim().is_empty()).collect();
+    kept[kept.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Output as text, cut to its last `MAX_CAPTURE_BYTES`
+fn captured(bytes: &[u8]) -> String {
+    let start = bytes.len().saturating_sub(MAX_CAPTURE_BYTES);
+    String::from_utf8_lossy(&bytes[start..]).to_string()
+}
+
+/// Run a converter like `sandbox::run` and append its command line, outcome and output to the job's log.
+/// Logging is best effort: a log that cannot be written never fails the conversion
+pub fn run(command: &mut Command, sandbox: Option<&Sandbox>, job: &Job) -> io::Result<Output> {
+    let started_at = Local::now().to_rfc3339();
+    let started = Instant::now();
+    let result = sandbox::run(command, sandbox);
+
+    let (success, outcome, stdout, stderr) = match &result {
+        Ok(output) => {
+            let outcome = match output.status.code() {
+                Some(code) => format!("exit code {}", code),
+                None => "killed by a signal".to_string(),
+            };
+            (output.status.success(), outcome, captured(&output.stdout), captured(&output.stderr))
+        }
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => (false, format!("timed out ({})", e), String::new(), String::new()),
+        Err(e) => (false, format!("failed to start: {}", e), String::new(), String::new()),
+    };
+    let run = ConverterRun {
+        job_id: job.id.clone(),
+        converter: command.get_program().to_string_lossy().to_string(),
+        args: command.get_args().map(|arg| arg.to_string_lossy().to_string()).collect(),
+        started_at,
+        duration_seconds: started.elapsed().as_secs_f64(),
+        success,
+        outcome,
+        stdout,
+        stderr,
+    };
+    let _ = append(job, &run);
+    result
+}
+
+fn append(job: &Job, run: &ConverterRun) -> Result<()> {
+    fs::create_dir_all(&job.log_dir)?;
+    let path = job.log_path();
+    if !path.exists() {
+        prune(&job.log_dir)?;
+    }
+    let mut line = serde_json::to_vec(run)?;
+    line.push(b'\n');
+    // One write per run, so parallel exports in the same job never interleave within a line
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// Remove the oldest job logs so a new one keeps the directory within `MAX_LOG_FILES`
+fn prune(log_dir: &Path) -> Result<()> {
+    let mut logs: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(log_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "jsonl"))
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .collect();
+    if logs.len() < MAX_LOG_FILES {
+        return Ok(());
+    }
+    logs.sort();
+    for (_, path) in &logs[..logs.len() + 1 - MAX_LOG_FILES] {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Converter runs logged for a job, oldest first
+pub fn load(log_dir: &Path, job_id: &str) -> Result<Vec<ConverterRun>> {
+    validate_job_id(job_id)?;
+    let path = log_path(log_dir, job_id);
+    if !path.exists() {
+        return Err(anyhow!("No converter log for job {}", job_id));
+    }
+    let text = fs::read_to_string(&path).context("Failed to read converter log")?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).context("Converter log is corrupted"))
+        .collect()
+}
+
+/// Converter runs as a list of dicts for Python
+pub fn runs_to_py(py: Python, runs: &[ConverterRun]) -> PyResult<PyObject> {
+    let result = PyList::empty(py);
+    for run in runs {
+        let dict = PyDict::new(py);
+        dict.set_item("job_id", &run.job_id)?;
+        dict.set_item("converter", &run.converter)?;
+        dict.set_item("args", &run.args)?;
+        dict.set_item("started_at", &run.started_at)?;
+        dict.set_item("duration_seconds", run.duration_seconds)?;
+        dict.set_item("success", run.success)?;
+        dict.set_item("outcome", &run.outcome)?;
+        dict.set_item("stdout", &run.stdout)?;
+        dict.set_item("stderr", &run.stderr)?;
+        result.append(dict)?;
+    }
+    Ok(result.into())
+}
+
+/// Read the converter output logged for an export job (`job_id` in the export options, or the id returned by
+/// batch exports). `log_dir` defaults to where standalone exports log; see `ReportManager.get_converter_log`
+#[pyfunction]
+#[pyo3(signature = (job_id, log_dir=None))]
+pub fn read_converter_log(job_id: &str, log_dir: Option<&str>, py: Python) -> PyResult<PyObject> {
+    guard("read_converter_log", || {
+        let log_dir = log_dir.map(PathBuf::from).unwrap_or_else(default_log_dir);
+        let runs = py
+            .allow_threads(|| load(&log_dir, job_id))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to read converter log: {}", e)))?;
+        runs_to_py(py, &runs)
+    })
+}
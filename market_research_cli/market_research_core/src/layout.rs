@@ -0,0 +1,37 @@
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+use handlebars::Handlebars;
+use serde_json::json;
+use serde_yaml::Mapping;
+
+use crate::frontmatter::{front_matter_mapping, mapping_str};
+use crate::sections::split_sections;
+use crate::toc;
+
+/// The document title: front matter `title`, then the first heading, then "Report"
+fn document_title(metadata: &Mapping, body: &str) -> String {
+    mapping_str(metadata, "title")
+        .or_else(|| split_sections(body).into_iter().find(|section| section.level > 0).map(|section| section.heading))
+        .unwrap_or_else(|| "Report".to_string())
+}
+
+/// Fill a Handlebars layout with a rendered report. The template gets `title`, `metadata` (the front matter),
+/// `toc` (a linked list of the headings, as HTML), `body` (the rendered report), `css` (the theme and
+/// stylesheets) and `generator`; HTML slots need triple braces, e.g. `{{{body}}}`, or they are escaped
+pub fn render_layout(template_file: &str, markdown: &str, body_html: &str, css: &str) -> Result<String> {
+    let template =
+        fs::read_to_string(template_file).with_context(|| format!("Failed to read template {}", template_file))?;
+    let (metadata, body) = front_matter_mapping(markdown).unwrap_or_else(|_| (Mapping::new(), markdown));
+    let data = json!({
+        "title": document_title(&metadata, body),
+        "metadata": serde_json::to_value(&metadata)?,
+        "toc": toc::toc_html(markdown, toc::DEFAULT_DEPTH),
+        "body": body_html,
+        "css": css,
+        "generator": concat!("market_research_core ", env!("CARGO_PKG_VERSION")),
+    });
+    Handlebars::new()
+        .render_template(&template, &data)
+        .map_err(|e| anyhow!("Failed to render template {}: {}", template_file, e))
+}
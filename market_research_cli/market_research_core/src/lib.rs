@@ -1,8 +1,9 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use std::fs;
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::Instant;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
@@ -11,291 +12,1756 @@ use chrono::prelude::*;
 use regex::Regex;
 use std::collections::HashMap;
 use serde_yaml;
+use sha2::{Digest, Sha256};
+use fs2::FileExt;
+use rayon::prelude::*;
+
+mod activity;
+mod annotations;
+mod artifacts;
+mod backup;
+mod budget;
+mod builder;
+mod bulk;
+mod capabilities;
+mod charts;
+mod chunks;
+mod citations;
+mod compat;
+mod convert;
+mod csv;
+mod diagrams;
+mod email;
+mod emoji;
+mod entities;
+mod estimates;
+mod facts;
+mod frontmatter;
+mod golden;
+mod highlight;
+mod history;
+mod ids;
+mod images;
+mod import;
+mod index;
+mod indexer;
+mod joblog;
+mod layout;
+mod linkcheck;
+mod lint;
+mod links;
+#[cfg(feature = "charts")]
+mod maps;
+mod math;
+mod mermaid;
+mod metrics;
+mod models;
+mod normalize;
+mod panics;
+pub mod parsers;
+mod paths;
+mod plaintext;
+mod policy;
+mod progress;
+mod qa;
+mod ranking;
+mod redact;
+mod render;
+mod retention;
+mod sandbox;
+mod sanitize;
+mod search;
+mod selftest;
+mod sections;
+mod site;
+mod space;
+mod spreadsheet;
+mod sse;
+mod stats;
+mod supersede;
+mod tables;
+mod takeaways;
+mod templates;
+mod themes;
+mod toc;
+mod transcript;
+mod trends;
+mod video;
+mod watcher;
+mod wikilinks;
 
 /// A Rust module for accelerating market research report generation.
 /// This module provides high-performance alternatives to slow Python operations.
 #[pymodule]
-fn market_research_core(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_class::<ProgressTracker>()?;
+fn market_research_core(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("CapabilityError", py.get_type::<capabilities::CapabilityError>())?;
+    m.add("InternalError", py.get_type::<panics::InternalError>())?;
+    m.add_class::<progress::ProgressTracker>()?;
+    m.add_class::<progress::TrackerRegistry>()?;
     m.add_class::<ReportManager>()?;
+    m.add_class::<watcher::ReportWatcher>()?;
+    m.add_class::<facts::FactStore>()?;
+    m.add_class::<models::ReportTreeModel>()?;
+    m.add_class::<models::SectionTableModel>()?;
+    m.add_class::<chunks::ReportChunks>()?;
+    m.add_class::<templates::TemplateManager>()?;
+    m.add_class::<annotations::Annotations>()?;
+    m.add_class::<linkcheck::LinkCheck>()?;
+    m.add_class::<builder::ReportBuilder>()?;
     m.add_function(wrap_pyfunction!(process_markdown, m)?)?;
     m.add_function(wrap_pyfunction!(format_report, m)?)?;
-    m.add_function(wrap_pyfunction!(parse_report_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_report_metadata, m)?)?;
     m.add_function(wrap_pyfunction!(py_list_reports, m)?)?;
     m.add_function(wrap_pyfunction!(clean_escape_sequences, m)?)?;
     m.add_function(wrap_pyfunction!(export_to_pdf, m)?)?;
+    m.add_function(wrap_pyfunction!(joblog::read_converter_log, m)?)?;
+    m.add_function(wrap_pyfunction!(compat::core_info, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities::capabilities, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities::require, m)?)?;
+    m.add_function(wrap_pyfunction!(selftest::self_test, m)?)?;
+    m.add_function(wrap_pyfunction!(budget::set_memory_budget, m)?)?;
+    m.add_function(wrap_pyfunction!(budget::memory_usage, m)?)?;
     m.add_function(wrap_pyfunction!(open_file, m)?)?;
+    m.add_function(wrap_pyfunction!(tables::format_table_numbers, m)?)?;
+    m.add_function(wrap_pyfunction!(tables::extract_tables, m)?)?;
+    m.add_function(wrap_pyfunction!(csv::csv_to_markdown, m)?)?;
+    #[cfg(feature = "charts")]
+    m.add_function(wrap_pyfunction!(charts::table_to_chart, m)?)?;
+    #[cfg(feature = "charts")]
+    m.add_function(wrap_pyfunction!(maps::render_choropleth, m)?)?;
+    m.add_function(wrap_pyfunction!(diagrams::render_diagram, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::compute_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::scenario_table, m)?)?;
+    m.add_function(wrap_pyfunction!(spreadsheet::export_data_xlsx, m)?)?;
+    m.add_function(wrap_pyfunction!(golden::render_and_compare, m)?)?;
+    m.add_function(wrap_pyfunction!(policy::evaluate_policies, m)?)?;
+    m.add_function(wrap_pyfunction!(email::parse_email_sources, m)?)?;
+    m.add_function(wrap_pyfunction!(transcript::parse_transcript, m)?)?;
+    m.add_function(wrap_pyfunction!(video::extract_video_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(ranking::rank_by_freshness, m)?)?;
+    m.add_function(wrap_pyfunction!(estimates::triangulate, m)?)?;
+    m.add_function(wrap_pyfunction!(trends::term_frequency_over_time, m)?)?;
+    m.add_function(wrap_pyfunction!(entities::entity_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(takeaways::key_takeaways, m)?)?;
+    m.add_function(wrap_pyfunction!(toc::generate_toc, m)?)?;
+    m.add_function(wrap_pyfunction!(toc::extract_outline, m)?)?;
+    m.add_function(wrap_pyfunction!(sections::extract_section, m)?)?;
+    m.add_function(wrap_pyfunction!(sections::replace_section, m)?)?;
+    m.add_function(wrap_pyfunction!(linkcheck::extract_links, m)?)?;
+    m.add_function(wrap_pyfunction!(linkcheck::check_links, m)?)?;
+    m.add_function(wrap_pyfunction!(lint::lint_markdown, m)?)?;
+    m.add_function(wrap_pyfunction!(normalize::normalize_markdown, m)?)?;
+    m.add_function(wrap_pyfunction!(plaintext::markdown_to_text, m)?)?;
     Ok(())
 }
 
-/// Thread-safe progress tracker for report generation
+/// Manager for report files
 #[pyclass]
-struct ProgressTracker {
-    progress: Arc<Mutex<ProgressData>>,
-    start_time: Arc<Mutex<Instant>>,
+struct ReportManager {
+    reports_dir: String,
+    extensions: Vec<String>,
+    indexer: Mutex<Option<indexer::BackgroundIndexer>>,
+    accesses: activity::AccessDebounce,
 }
 
-struct ProgressData {
-    percentage: f32,
-    stage: String,
-    agent: String,
-    activity: String,
+#[derive(Serialize, Deserialize)]
+struct ReportMetadata {
+    title: String,
+    date: String,
+    id: String,
 }
 
 #[pymethods]
-impl ProgressTracker {
+impl ReportManager {
     #[new]
-    fn new() -> Self {
-        ProgressTracker {
-            progress: Arc::new(Mutex::new(ProgressData {
-                percentage: 0.0,
-                stage: "Initializing".to_string(),
-                agent: "System".to_string(),
-                activity: "Starting up".to_string(),
-            })),
-            start_time: Arc::new(Mutex::new(Instant::now())),
-        }
+    #[pyo3(signature = (reports_dir, extensions=None, git_history=false))]
+    fn new(reports_dir: &str, extensions: Option<Vec<String>>, git_history: bool) -> PyResult<Self> {
+        panics::guard("ReportManager.new", || {
+            let extensions = match extensions {
+                Some(extensions) => normalize_extensions(&extensions)?,
+                None => vec![DEFAULT_EXTENSION.to_string()],
+            };
+            // Saves and deletes are committed whenever the directory is a git repo, so this is only needed once
+            if git_history {
+                if !cfg!(feature = "history") {
+                    return Err(capabilities::feature_missing_py("history"));
+                }
+                let existing: Vec<String> = list_files(reports_dir, &extensions)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(filename, _)| filename)
+                    .collect();
+                history::init(reports_dir, &existing)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to enable report history: {}", e)))?;
+            }
+            Ok(ReportManager {
+                reports_dir: reports_dir.to_string(),
+                extensions,
+                indexer: Mutex::new(None),
+                accesses: activity::AccessDebounce::default(),
+            })
+        })
+    }
+
+    /// File extensions included in listings
+    #[getter]
+    fn extensions(&self) -> PyResult<Vec<String>> {
+        panics::guard("ReportManager.extensions", || {
+            Ok(self.extensions.clone())
+        })
+    }
+
+    /// Save a report to disk; with `auto_id`, inject an id and slug into the front matter and name the file after the slug
+    #[pyo3(signature = (filename, content, auto_id=false))]
+    fn save_report(&self, filename: &str, content: &str, auto_id: bool, py: Python) -> PyResult<String> {
+        panics::guard("ReportManager.save_report", || {
+            let (filename, content) = if auto_id {
+                ids::assign_identity(&self.reports_dir, filename, content)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to assign report id: {}", e)))?
+            } else {
+                (filename.to_string(), content.to_string())
+            };
+            let path = store_report(&self.reports_dir, &filename, &content, true, py)?;
+            Ok(path.to_string_lossy().to_string())
+        })
+    }
+
+    /// Merge keys into a report's YAML front matter without touching the body; `None` removes a key
+    fn update_metadata(&self, filename: &str, updates: &PyDict, py: Python) -> PyResult<()> {
+        panics::guard("ReportManager.update_metadata", || {
+            let path = self.report_path(filename)?;
+            if !path.is_file() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Report file not found: {}", filename)
+                ));
+            }
+
+            // Convert while holding the GIL, then edit the file without it
+            let mut changes = Vec::with_capacity(updates.len());
+            for (key, value) in updates.iter() {
+                changes.push((key.str()?.to_string(), frontmatter::py_to_yaml(value)?));
+            }
+
+            let updated = py.allow_threads(|| -> Result<Vec<u8>> {
+                let _lock = lock_report(&path, true)?;
+                let content = fs::read_to_string(&path)?;
+                let updated = frontmatter::update_front_matter(&content, |mapping| {
+                    for (key, value) in changes {
+                        let key = serde_yaml::Value::String(key);
+                        if value.is_null() {
+                            mapping.remove(&key);
+                        } else {
+                            mapping.insert(key, value);
+                        }
+                    }
+                    Ok(())
+                })?;
+                write_atomic(&path, updated.as_bytes())?;
+                Ok(updated.into_bytes())
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update metadata: {}", e)))?;
+
+            index::update_index(&self.reports_dir, |index| {
+                index.record(filename, &updated);
+                Ok(())
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Metadata updated but index update failed: {}", e)))?;
+            self.commit_history(filename, Some("Update metadata of"), py)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Metadata updated but history commit failed: {}", e)))
+        })
+    }
+
+    /// Get a list of all reports
+    fn get_all_reports(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.get_all_reports", || {
+            let reports = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?;
+        
+            let result = PyList::new(py, reports.into_iter().map(|(filename, _)| filename));
+            Ok(result.into())
+        })
+    }
+
+    /// List reports with title, date, size, mtime and tags, reading only each file's front matter in parallel
+    fn list_with_metadata(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.list_with_metadata", || {
+            let (reports_dir, extensions) = (&self.reports_dir, &self.extensions);
+            let listed = py.allow_threads(|| -> Result<Vec<ListedReport>> {
+                Ok(list_files(reports_dir, extensions)?
+                    .into_par_iter()
+                    .map(|(filename, _)| ListedReport::load(reports_dir, filename))
+                    .collect())
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for report in listed {
+                let dict = PyDict::new(py);
+                dict.set_item("filename", &report.filename)?;
+                dict.set_item("title", &report.front_matter.title)?;
+                dict.set_item("date", report.date.format("%Y-%m-%d %H:%M:%S").to_string())?;
+                dict.set_item("id", &report.front_matter.id)?;
+                dict.set_item("tags", &report.front_matter.tags)?;
+                dict.set_item("size", report.size)?;
+                dict.set_item("mtime", report.mtime)?;
+                if let Some(error) = &report.error {
+                    dict.set_item("error", error)?;
+                }
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// List reports and exported artifacts with their format, size and modification time
+    fn list_files(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.list_files", || {
+            let files = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for (filename, format) in files {
+                let metadata = fs::metadata(Path::new(&self.reports_dir).join(&filename)).ok();
+                let modified = metadata
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs_f64());
+
+                let dict = PyDict::new(py);
+                dict.set_item("filename", filename)?;
+                dict.set_item("format", format)?;
+                dict.set_item("size", metadata.map(|m| m.len()))?;
+                dict.set_item("modified", modified)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Read a report from disk, recording the access for `list_recent` at most once a minute per report
+    fn read_report(&self, filename: &str, py: Python) -> PyResult<String> {
+        panics::guard("ReportManager.read_report", || {
+            let path = self.report_path(filename)?;
+        
+            // Check if file exists
+            if !path.exists() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Report file not found: {}", filename)
+                ));
+            }
+        
+            // Check if it's actually a file and not a directory
+            if !path.is_file() {
+                return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(
+                    format!("Path is not a file: {}", filename)
+                ));
+            }
+        
+            // Check file size to prevent loading extremely large files
+            let metadata = fs::metadata(&path).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(
+                    format!("Failed to read file metadata: {}", e)
+                )
+            })?;
+        
+            const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB limit
+            if metadata.len() > MAX_FILE_SIZE {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("File too large ({}MB). Maximum size is 50MB; use read_report_chunks to stream it.", metadata.len() / (1024 * 1024))
+                ));
+            }
+        
+            // Take a shared lock so we never observe a writer's partial output
+            let _lock = py.allow_threads(|| lock_report(&path, false))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to lock report: {}", e)))?;
+        
+            // Read file with informative error
+            let content = fs::read_to_string(&path).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(
+                    format!("Failed to read report file: {}", e)
+                )
+            })?;
+        
+            // Tracking is best effort and debounced; a read must never fail because the activity file could not be written
+            let _ = self.accesses.record(&self.reports_dir, filename);
+            Ok(content)
+        })
+    }
+
+    /// Pin a report to the home screen; returns False if it was already pinned
+    fn pin(&self, filename: &str) -> PyResult<bool> {
+        panics::guard("ReportManager.pin", || {
+            if !self.report_path(filename)?.is_file() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Report file not found: {}", filename)
+                ));
+            }
+            activity::pin(&self.reports_dir, filename)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to pin report: {}", e)))
+        })
+    }
+
+    /// Unpin a report; returns False if it was not pinned
+    fn unpin(&self, filename: &str) -> PyResult<bool> {
+        panics::guard("ReportManager.unpin", || {
+            activity::unpin(&self.reports_dir, filename)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to unpin report: {}", e)))
+        })
+    }
+
+    /// Pinned reports that still exist, in pin order, as `[{filename, pinned_at}]`
+    fn list_pinned(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.list_pinned", || {
+            let activity = activity::Activity::load(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load activity: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for pin in activity.pinned.iter().filter(|pin| Path::new(&self.reports_dir).join(&pin.filename).is_file()) {
+                let dict = PyDict::new(py);
+                dict.set_item("filename", &pin.filename)?;
+                dict.set_item("pinned_at", &pin.pinned_at)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// The `n` most recently opened reports that still exist, newest first, as `[{filename, opened_at, pinned}]`
+    #[pyo3(signature = (n=10))]
+    fn list_recent(&self, n: usize, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.list_recent", || {
+            let activity = activity::Activity::load(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load activity: {}", e)))?;
+
+            let result = PyList::empty(py);
+            let existing = activity.recent.iter().filter(|access| Path::new(&self.reports_dir).join(&access.filename).is_file());
+            for access in existing.take(n) {
+                let dict = PyDict::new(py);
+                dict.set_item("filename", &access.filename)?;
+                dict.set_item("opened_at", &access.opened_at)?;
+                dict.set_item("pinned", activity.pinned.iter().any(|pin| pin.filename == access.filename))?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Iterate over a report in chunks of about `chunk_size` bytes, for files too large for read_report
+    #[pyo3(signature = (filename, chunk_size=chunks::DEFAULT_CHUNK_SIZE))]
+    fn read_report_chunks(&self, filename: &str, chunk_size: usize) -> PyResult<chunks::ReportChunks> {
+        panics::guard("ReportManager.read_report_chunks", || {
+            let path = self.report_path(filename)?;
+            if !path.is_file() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Report file not found: {}", filename)
+                ));
+            }
+
+            chunks::ReportChunks::open(&path, chunk_size)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open report: {}", e)))
+        })
+    }
+
+    /// Delete a report by moving it into the trash
+    fn delete_report(&self, filename: &str, py: Python) -> PyResult<bool> {
+        panics::guard("ReportManager.delete_report", || {
+            self.report_path(filename)?;
+            let trashed = trash_report(&self.reports_dir, filename)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to move file to trash: {}", e)))?;
+            if trashed {
+                index::forget_file(&self.reports_dir, filename)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update index: {}", e)))?;
+                self.commit_history(filename, None, py)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Report deleted but history commit failed: {}", e)))?;
+            }
+            Ok(trashed)
+        })
+    }
+
+    /// List reports currently in the trash, newest first
+    fn list_trash(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.list_trash", || {
+            let entries = list_trash_entries(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list trash: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for entry in entries {
+                let dict = PyDict::new(py);
+                dict.set_item("trash_name", &entry.trash_name)?;
+                dict.set_item("filename", &entry.filename)?;
+                dict.set_item("deleted_at", entry.deleted_at.format("%Y-%m-%d %H:%M:%S").to_string())?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Restore a trashed report to its original location
+    fn restore(&self, trash_name: &str, py: Python) -> PyResult<String> {
+        panics::guard("ReportManager.restore", || {
+            let entry = list_trash_entries(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list trash: {}", e)))?
+                .into_iter()
+                .find(|entry| entry.trash_name == trash_name)
+                .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Trash entry not found: {}", trash_name)
+                ))?;
+
+            let target = Path::new(&self.reports_dir).join(&entry.filename);
+            if target.exists() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileExistsError, _>(
+                    format!("Cannot restore, a report already exists at: {}", entry.filename)
+                ));
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create directory: {}", e)))?;
+            }
+
+            let source = Path::new(&self.reports_dir).join(TRASH_DIR).join(trash_name);
+            let _lock = lock_report(&target, true)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to lock report: {}", e)))?;
+            fs::rename(&source, &target)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to restore report: {}", e)))?;
+            index::record_file(&self.reports_dir, &entry.filename)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update index: {}", e)))?;
+            self.commit_history(&entry.filename, Some("Restore"), py)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Report restored but history commit failed: {}", e)))?;
+
+            Ok(entry.filename)
+        })
+    }
+
+    /// Permanently remove trashed reports, optionally only those older than `older_than` days, and any lock files
+    /// left behind by reports that no longer exist
+    #[pyo3(signature = (older_than=None))]
+    fn purge(&self, older_than: Option<f64>) -> PyResult<usize> {
+        panics::guard("ReportManager.purge", || {
+            let entries = list_trash_entries(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list trash: {}", e)))?;
+
+            let now = Local::now().naive_local();
+            let trash_dir = Path::new(&self.reports_dir).join(TRASH_DIR);
+            let mut purged = 0;
+            for entry in entries {
+                if let Some(days) = older_than {
+                    let age_seconds = (now - entry.deleted_at).num_seconds() as f64;
+                    if age_seconds < days * 86400.0 {
+                        continue;
+                    }
+                }
+                fs::remove_file(trash_dir.join(&entry.trash_name))
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to purge {}: {}", entry.trash_name, e)))?;
+                purged += 1;
+            }
+            remove_orphan_locks(&self.reports_dir);
+
+            Ok(purged)
+        })
+    }
+
+    /// Configure the retention limits stored in `.retention.json`; `action` is "archive" or "delete"
+    #[pyo3(signature = (max_count=None, max_age_days=None, max_total_bytes=None, action="archive"))]
+    fn set_retention_policy(
+        &self,
+        max_count: Option<usize>,
+        max_age_days: Option<f64>,
+        max_total_bytes: Option<u64>,
+        action: &str,
+    ) -> PyResult<()> {
+        panics::guard("ReportManager.set_retention_policy", || {
+            let action = retention::RetentionAction::parse(action)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+            let policy = retention::RetentionPolicy { max_count, max_age_days, max_total_bytes, action };
+            policy.save(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to save retention policy: {}", e)))
+        })
+    }
+
+    /// Return the configured retention policy, or None if none is set
+    fn get_retention_policy(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.get_retention_policy", || {
+            let policy = retention::RetentionPolicy::load(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load retention policy: {}", e)))?;
+
+            match policy {
+                Some(policy) => {
+                    let dict = PyDict::new(py);
+                    dict.set_item("max_count", policy.max_count)?;
+                    dict.set_item("max_age_days", policy.max_age_days)?;
+                    dict.set_item("max_total_bytes", policy.max_total_bytes)?;
+                    dict.set_item("action", policy.action.as_str())?;
+                    Ok(dict.into())
+                }
+                None => Ok(py.None()),
+            }
+        })
+    }
+
+    /// Archive or delete the oldest reports that exceed the retention policy; `dry_run` only reports the plan
+    #[pyo3(signature = (dry_run=true))]
+    fn apply_retention(&self, dry_run: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.apply_retention", || {
+            let policy = retention::RetentionPolicy::load(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load retention policy: {}", e)))?
+                .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "No retention policy configured. Call set_retention_policy first"
+                ))?;
+            let outcome = py.allow_threads(|| retention::apply_retention(&self.reports_dir, &self.extensions, &policy, dry_run))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to apply retention: {}", e)))?;
+            let action = match policy.action {
+                retention::RetentionAction::Archive => "Archive",
+                retention::RetentionAction::Delete => "Delete",
+            };
+            self.commit_history_batch(&outcome.applied, action, py)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Retention applied but history commit failed: {}", e)))?;
+
+            let candidates = PyList::empty(py);
+            for candidate in &outcome.candidates {
+                let entry = PyDict::new(py);
+                entry.set_item("filename", &candidate.filename)?;
+                entry.set_item("size", candidate.size)?;
+                entry.set_item("date", candidate.date.format("%Y-%m-%d %H:%M:%S").to_string())?;
+                entry.set_item("reason", candidate.reason)?;
+                candidates.append(entry)?;
+            }
+
+            let dict = PyDict::new(py);
+            dict.set_item("dry_run", dry_run)?;
+            dict.set_item("action", policy.action.as_str())?;
+            dict.set_item("candidates", candidates)?;
+            dict.set_item("applied", outcome.applied)?;
+            dict.set_item("failed", outcome.failed.into_iter().collect::<HashMap<_, _>>())?;
+            dict.set_item("freed_bytes", outcome.freed_bytes)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Export all reports and a checksummed manifest into a single compressed archive; `deterministic` pins timestamps
+    #[pyo3(signature = (path, deterministic=false))]
+    fn export_backup(&self, path: &str, deterministic: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.export_backup", || {
+            let manifest = backup::export_backup(&self.reports_dir, Path::new(path), &self.extensions, deterministic)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to export backup: {}", e)))?;
+
+            let dict = PyDict::new(py);
+            dict.set_item("path", path)?;
+            dict.set_item("created_at", &manifest.created_at)?;
+            dict.set_item("report_count", manifest.reports.len())?;
+            dict.set_item("total_bytes", manifest.reports.iter().map(|r| r.size).sum::<u64>())?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Verify a backup archive and restore its reports into the reports directory
+    #[pyo3(signature = (path, overwrite=false))]
+    fn import_backup(&self, path: &str, overwrite: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.import_backup", || {
+            let summary = backup::import_backup(&self.reports_dir, Path::new(path), &self.extensions, overwrite)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to import backup: {}", e)))?;
+            for filename in &summary.restored {
+                index::record_file(&self.reports_dir, filename)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update index: {}", e)))?;
+            }
+
+            let dict = PyDict::new(py);
+            dict.set_item("restored", summary.restored)?;
+            dict.set_item("skipped", summary.skipped)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Import markdown files from an external folder, normalizing front matter and skipping duplicate content
+    #[pyo3(signature = (path, recursive=true))]
+    fn import_dir(&self, path: &str, recursive: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.import_dir", || {
+            let summary = py.allow_threads(|| import::import_dir(&self.reports_dir, path, recursive))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to import directory: {}", e)))?;
+
+            let dict = PyDict::new(py);
+            dict.set_item("imported", summary.imported.into_iter().collect::<HashMap<_, _>>())?;
+            dict.set_item("duplicates", summary.duplicates)?;
+            dict.set_item("failed", summary.failed.into_iter().collect::<HashMap<_, _>>())?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Convert and import legacy .docx/.html/.txt/.pdf documents; options: `recursive` (default True), `tags`,
+    /// `sandbox` (True or a dict of limits for the PDF text extractor)
+    #[pyo3(signature = (paths, options=None))]
+    fn import_documents(&self, paths: Vec<String>, options: Option<&PyDict>, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.import_documents", || {
+            let mut import_options = import::DocumentImportOptions { recursive: true, ..Default::default() };
+            if let Some(options) = options {
+                if let Some(recursive) = options.get_item("recursive") {
+                    import_options.recursive = recursive.extract()?;
+                }
+                if let Some(tags) = options.get_item("tags") {
+                    import_options.tags = tags.extract()?;
+                }
+                if let Some(sandbox) = options.get_item("sandbox") {
+                    import_options.sandbox = sandbox::Sandbox::from_py(sandbox)?;
+                }
+            }
+
+            let summary = py.allow_threads(|| import::import_documents(&self.reports_dir, &paths, &import_options))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to import documents: {}", e)))?;
+
+            let dict = PyDict::new(py);
+            dict.set_item("imported", summary.imported.into_iter().collect::<HashMap<_, _>>())?;
+            dict.set_item("duplicates", summary.duplicates)?;
+            dict.set_item("failed", summary.failed.into_iter().collect::<HashMap<_, _>>())?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Ingest newsletters from .eml/.mbox files as source reports with sender, date and subject in the front matter
+    #[pyo3(signature = (paths, tags=None))]
+    fn ingest_emails(&self, paths: Vec<String>, tags: Option<Vec<String>>, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.ingest_emails", || {
+            let tags = tags.unwrap_or_default();
+            let summary = py.allow_threads(|| import::ingest_emails(&self.reports_dir, &paths, &tags))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to ingest emails: {}", e)))?;
+
+            let dict = PyDict::new(py);
+            dict.set_item("imported", summary.imported.into_iter().collect::<HashMap<_, _>>())?;
+            dict.set_item("duplicates", summary.duplicates)?;
+            dict.set_item("failed", summary.failed.into_iter().collect::<HashMap<_, _>>())?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Save a WebVTT/SRT transcript (file path or text) as a source report of timestamped passages.
+    /// `source_meta` becomes front matter (title, date, url, ...); returns the filename and the chunks for citing.
+    #[pyo3(signature = (vtt_or_srt, source_meta=None, chunk_seconds=60.0))]
+    fn ingest_transcript(&self, vtt_or_srt: &str, source_meta: Option<&PyDict>, chunk_seconds: f64, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.ingest_transcript", || {
+            let content = if Path::new(vtt_or_srt).is_file() {
+                fs::read_to_string(vtt_or_srt)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read transcript: {}", e)))?
+            } else {
+                vtt_or_srt.to_string()
+            };
+
+            let mut metadata = serde_yaml::Mapping::new();
+            if let Some(source_meta) = source_meta {
+                for (key, value) in source_meta.iter() {
+                    metadata.insert(serde_yaml::Value::String(key.extract()?), frontmatter::py_to_yaml(value)?);
+                }
+            }
+            let url = frontmatter::mapping_str(&metadata, "url");
+
+            let cues = transcript::parse_cues(&content)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to parse transcript: {}", e)))?;
+            let chunks = transcript::chunk_cues(&cues, chunk_seconds);
+            let summary = py.allow_threads(|| import::ingest_transcript(&self.reports_dir, &chunks, metadata))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to ingest transcript: {}", e)))?;
+            if let Some((_, error)) = summary.failed.first() {
+                return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to ingest transcript: {}", error)));
+            }
+
+            let dict = PyDict::new(py);
+            dict.set_item("filename", summary.imported.first().map(|(_, filename)| filename))?;
+            dict.set_item("duplicate", !summary.duplicates.is_empty())?;
+            dict.set_item("chunks", transcript::chunks_to_py(py, &chunks, url.as_deref())?)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Commits that changed a report, newest first, as `[{commit, message, author, date}]`
+    fn history(&self, filename: &str, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.history", || {
+            if !cfg!(feature = "history") {
+                return Err(capabilities::feature_missing_py("history"));
+            }
+            let entries = py.allow_threads(|| history::history(&self.reports_dir, filename))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read history: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for entry in entries {
+                let dict = PyDict::new(py);
+                dict.set_item("commit", entry.commit)?;
+                dict.set_item("message", entry.message)?;
+                dict.set_item("author", entry.author)?;
+                dict.set_item("date", entry.date.to_rfc3339())?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Content of a report at a past revision (commit id, `HEAD~1`, ...)
+    fn show_at(&self, filename: &str, rev: &str, py: Python) -> PyResult<String> {
+        panics::guard("ReportManager.show_at", || {
+            if !cfg!(feature = "history") {
+                return Err(capabilities::feature_missing_py("history"));
+            }
+            py.allow_threads(|| history::show_at(&self.reports_dir, filename, rev))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to read revision: {}", e)))
+        })
+    }
+
+    /// Check every indexed report against its stored SHA-256 checksum
+    fn verify(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.verify", || {
+            let report = py.allow_threads(|| index::verify(&self.reports_dir))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to verify reports: {}", e)))?;
+
+            let dict = PyDict::new(py);
+            dict.set_item("clean", report.modified.is_empty() && report.missing.is_empty())?;
+            dict.set_item("ok", report.ok)?;
+            dict.set_item("modified", report.modified)?;
+            dict.set_item("missing", report.missing)?;
+            dict.set_item("untracked", report.untracked)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Find report filenames that would break when the archive is synced to another platform, as
+    /// `{non_portable: [{filename, problem, suggestion}], case_collisions: [[filename, ...]]}`
+    fn check_paths(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.check_paths", || {
+            let files: Vec<String> = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?
+                .into_iter()
+                .map(|(filename, _)| filename)
+                .collect();
+
+            let non_portable = PyList::empty(py);
+            for filename in &files {
+                if let Some(problem) = paths::filename_problem(filename) {
+                    let dict = PyDict::new(py);
+                    dict.set_item("filename", filename)?;
+                    dict.set_item("problem", problem)?;
+                    dict.set_item("suggestion", paths::portable_filename(filename))?;
+                    non_portable.append(dict)?;
+                }
+            }
+            let dict = PyDict::new(py);
+            dict.set_item("non_portable", non_portable)?;
+            dict.set_item("case_collisions", paths::case_collisions(&files))?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Map each report to its forward links, backlinks, and unresolved link targets
+    fn link_graph(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.link_graph", || {
+            let graph = py.allow_threads(|| links::link_graph(&self.reports_dir))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to build link graph: {}", e)))?;
+
+            let result = PyDict::new(py);
+            for (filename, links) in graph {
+                let entry = PyDict::new(py);
+                entry.set_item("forward", links.forward.into_iter().collect::<Vec<_>>())?;
+                entry.set_item("backward", links.backward.into_iter().collect::<Vec<_>>())?;
+                entry.set_item("broken", links.broken.into_iter().collect::<Vec<_>>())?;
+                result.set_item(filename, entry)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Keep the report index current on a background thread, reacting to file changes and re-scanning every `interval` seconds
+    #[pyo3(signature = (interval=30.0, watch=true, reindex_modified=false))]
+    fn start_background_indexer(&self, interval: f64, watch: bool, reindex_modified: bool) -> PyResult<()> {
+        panics::guard("ReportManager.start_background_indexer", || {
+            let interval = match std::time::Duration::try_from_secs_f64(interval) {
+                Ok(duration) if interval > 0.0 => duration,
+                _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("interval must be a positive number of seconds")),
+            };
+            let mut guard = panics::lock(&self.indexer);
+            if guard.is_some() {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Background indexer is already running"));
+            }
+
+            let started = indexer::BackgroundIndexer::start(
+                &self.reports_dir,
+                self.extensions.clone(),
+                interval,
+                watch,
+                reindex_modified,
+            )
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to start background indexer: {}", e)))?;
+            *guard = Some(started);
+            Ok(())
+        })
+    }
+
+    /// Stop the background indexer, waiting for an in-progress pass to finish
+    fn stop_background_indexer(&self, py: Python) -> PyResult<bool> {
+        panics::guard("ReportManager.stop_background_indexer", || {
+            let running = panics::lock(&self.indexer).take();
+            let stopped = running.is_some();
+            py.allow_threads(move || drop(running));
+            Ok(stopped)
+        })
+    }
+
+    /// Ask the background indexer for a full pass without waiting for it
+    fn request_reindex(&self) -> PyResult<()> {
+        panics::guard("ReportManager.request_reindex", || {
+            match panics::lock(&self.indexer).as_ref() {
+                Some(running) => {
+                    running.trigger();
+                    Ok(())
+                }
+                None => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Background indexer is not running")),
+            }
+        })
+    }
+
+    /// Status of the background indexer: running, pass count, last run, totals and last error
+    fn indexer_status(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.indexer_status", || {
+            let status = panics::lock(&self.indexer).as_ref().map(|running| running.status()).unwrap_or_default();
+
+            let dict = PyDict::new(py);
+            dict.set_item("running", status.running)?;
+            dict.set_item("watching", status.watching)?;
+            dict.set_item("runs", status.runs)?;
+            dict.set_item("last_run", status.last_run)?;
+            dict.set_item("last_duration_ms", status.last_duration_ms)?;
+            dict.set_item("added", status.added)?;
+            dict.set_item("removed", status.removed)?;
+            dict.set_item("updated", status.updated)?;
+            dict.set_item("pending", status.pending)?;
+            dict.set_item("last_error", status.last_error)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Compute library statistics (sizes, word counts, reports per month, extremes)
+    fn stats(&self, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.stats", || {
+            let stats = py.allow_threads(|| stats::compute(&self.reports_dir, &self.extensions))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to compute stats: {}", e)))?;
+
+            let file_dict = |file: &Option<stats::FileStats>| -> PyResult<PyObject> {
+                match file {
+                    Some(file) => {
+                        let dict = PyDict::new(py);
+                        dict.set_item("filename", &file.filename)?;
+                        dict.set_item("size", file.size)?;
+                        dict.set_item("words", file.words)?;
+                        dict.set_item("date", file.date.format("%Y-%m-%d %H:%M:%S").to_string())?;
+                        Ok(dict.into())
+                    }
+                    None => Ok(py.None()),
+                }
+            };
+
+            let dict = PyDict::new(py);
+            dict.set_item("total_reports", stats.total_reports)?;
+            dict.set_item("total_bytes", stats.total_bytes)?;
+            dict.set_item("total_words", stats.total_words)?;
+            dict.set_item("average_words", stats.total_words.checked_div(stats.total_reports).unwrap_or(0))?;
+            dict.set_item("reports_per_month", stats.reports_per_month)?;
+            dict.set_item("largest", file_dict(&stats.largest)?)?;
+            dict.set_item("oldest", file_dict(&stats.oldest)?)?;
+            dict.set_item("newest", file_dict(&stats.newest)?)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Move many reports to the trash in parallel; `targets` is a list of filenames or a predicate
+    fn delete_many(&self, targets: &PyAny, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.delete_many", || {
+            let targets = self.resolve_targets(py, targets)?;
+            let reports_dir = self.reports_dir.as_str();
+
+            let result = py.allow_threads(|| {
+                let result = bulk::run(&targets, |filename| {
+                    match trash_report(reports_dir, filename)? {
+                        true => Ok(()),
+                        false => Err(anyhow!("Report not found")),
+                    }
+                });
+                index::update_index(reports_dir, |index| {
+                    for filename in &result.succeeded {
+                        index.reports.remove(filename);
+                    }
+                    Ok(())
+                })
+                .map(|_| result)
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update index: {}", e)))?;
+            self.commit_history_batch(&result.succeeded, "Delete", py)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Reports deleted but history commit failed: {}", e)))?;
+
+            bulk_result_dict(py, result)
+        })
+    }
+
+    /// Export many reports in parallel to `output_dir` as md, html, or pdf, recording each file in the
+    /// report's artifact registry (see `list_artifacts`). Exported files are hard links into a content-addressed
+    /// store, and an unchanged report exported again with the same options reuses its stored file. The result names
+    /// the `job_id` converter output is logged under (`job_id` in `options` to choose it; see `get_converter_log`)
+    #[pyo3(signature = (targets, output_dir, format="html", options=None))]
+    fn export_many(&self, targets: &PyAny, output_dir: &str, format: &str, options: Option<&PyDict>, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.export_many", || {
+            let targets = self.resolve_targets(py, targets)?;
+            let render_options = render::RenderOptions::from_dict(options)?;
+            let (job_id, log_dir) = self.converter_job(&render_options);
+            let render_options = render_options.for_job(&job_id, &log_dir);
+            let settings = artifacts::ExportSettings::from_dict(options)?;
+            let format = format.trim().to_lowercase();
+            if !["md", "html", "pdf"].contains(&format.as_str()) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unsupported export format '{}'. Expected md, html or pdf", format)
+                ));
+            }
+            let estimate = targets
+                .iter()
+                .filter_map(|filename| fs::metadata(Path::new(&self.reports_dir).join(filename)).ok())
+                .map(|metadata| space::export_estimate(metadata.len(), &format))
+                .sum();
+            space::ensure_space(Path::new(output_dir), estimate, "the export")
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+            fs::create_dir_all(output_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create output directory: {}", e)))?;
+
+            let reports_dir = self.reports_dir.as_str();
+            let cost = |filename: &str| {
+                budget::render_cost(fs::metadata(Path::new(reports_dir).join(filename)).map(|m| m.len()).unwrap_or(0))
+            };
+            let result = py.allow_threads(|| {
+                bulk::run_budgeted(&targets, cost, |filename| {
+                    let source = Path::new(reports_dir).join(filename);
+                    let content = {
+                        let _lock = lock_report(&source, false)?;
+                        fs::read_to_string(&source)?
+                    };
+                    let stem = Path::new(filename).file_stem().and_then(|s| s.to_str()).unwrap_or(filename);
+                    let target = paths::long_path(&Path::new(output_dir).join(format!("{}.{}", paths::portable_filename(stem), format)));
+
+                    // An unchanged report exported the same way before is linked from the store, not rendered again
+                    let reused = artifacts::reuse_stored(reports_dir, filename, &format, content.as_bytes(), &settings, &target)
+                        .unwrap_or(false);
+                    if !reused {
+                        export_report(&content, &target, &format, &render_options)?;
+                    }
+                    artifacts::record_artifact(reports_dir, filename, &format, &target, content.as_bytes(), &settings, &job_id)
+                        .map_err(|e| anyhow!("exported but failed to record artifact: {}", e))
+                })
+            });
+
+            job_result_dict(py, result, &job_id)
+        })
+    }
+
+    /// Every file exported from a report, least recently exported first, as `[{format, path, sha256, exported_at, profile,
+    /// exists, modified, source_changed}]`; `modified` means the file on disk no longer matches what was exported
+    fn list_artifacts(&self, filename: &str, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.list_artifacts", || {
+            let registry = artifacts::load_artifacts(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load artifacts: {}", e)))?;
+            let current = fs::read(self.report_path(filename)?).ok().map(|content| sha256_hex(&content));
+
+            let result = PyList::empty(py);
+            for artifact in registry.get(filename).into_iter().flatten() {
+                let on_disk = fs::read(&artifact.path).ok().map(|content| sha256_hex(&content));
+                let dict = PyDict::new(py);
+                dict.set_item("format", &artifact.format)?;
+                dict.set_item("path", &artifact.path)?;
+                dict.set_item("sha256", &artifact.sha256)?;
+                dict.set_item("exported_at", &artifact.exported_at)?;
+                dict.set_item("profile", &artifact.settings.redaction)?;
+                dict.set_item("exists", on_disk.is_some())?;
+                dict.set_item("modified", on_disk.is_some_and(|hash| hash != artifact.sha256))?;
+                dict.set_item("source_changed", current.as_ref() != Some(&artifact.source_sha256))?;
+                dict.set_item("job_id", &artifact.job_id)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Regenerate every recorded artifact of a report at its original path with its original options.
+    /// By default each is rebuilt from the report version it was exported from (recovered from report history
+    /// when the report has changed since); `use_current` rebuilds from the report as it is now
+    #[pyo3(signature = (filename, use_current=false))]
+    fn reexport_all(&self, filename: &str, use_current: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.reexport_all", || {
+            let registry = artifacts::load_artifacts(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load artifacts: {}", e)))?;
+            let recorded = match registry.get(filename) {
+                Some(recorded) if !recorded.is_empty() => recorded,
+                _ => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("No exported artifacts recorded for {}", filename)
+                    ))
+                }
+            };
+            let source = self.report_path(filename)?;
+            let current = {
+                let _lock = lock_report(&source, false)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to lock report: {}", e)))?;
+                fs::read_to_string(&source)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(format!("Failed to read report: {}", e)))?
+            };
+
+            let paths: Vec<String> = recorded.iter().map(|artifact| artifact.path.clone()).collect();
+            // Regenerated files land in the artifact store next to the ones they replace
+            let estimate = paths.iter().filter_map(|path| fs::metadata(path).ok()).map(|metadata| metadata.len()).sum();
+            space::ensure_space(Path::new(&self.reports_dir), estimate, "the re-export")
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+            let (job_id, log_dir) = self.converter_job(&render::RenderOptions::default());
+            let reports_dir = self.reports_dir.as_str();
+            let cost = budget::render_cost(current.len() as u64);
+            let result = py.allow_threads(|| {
+                bulk::run_budgeted(&paths, |_| cost, |path| {
+                    let artifact = recorded.iter().find(|artifact| artifact.path == path).ok_or_else(|| anyhow!("unknown artifact"))?;
+                    let content = match use_current {
+                        true => current.clone(),
+                        false => artifacts::exported_source(reports_dir, filename, artifact, &current)?,
+                    };
+                    let target = Path::new(path);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    let render_options = artifact.settings.render_options()?.for_job(&job_id, &log_dir);
+                    export_report(&content, target, &artifact.format, &render_options)?;
+                    artifacts::record_artifact(reports_dir, filename, &artifact.format, target, content.as_bytes(), &artifact.settings, &job_id)
+                        .map_err(|e| anyhow!("exported but failed to record artifact: {}", e))
+                })
+            });
+
+            job_result_dict(py, result, &job_id)
+        })
+    }
+
+    /// Re-export every recorded artifact of the selected reports from their current content, in parallel, with
+    /// `profile` export options (e.g. a new brand `stylesheet` or `redaction`) layered over each artifact's recorded
+    /// options, which are updated. `filter` selects reports like `export_many` targets (all reports with artifacts by
+    /// default); `progress` is updated as reports finish, and cancelling it stops reports not yet started
+    #[pyo3(signature = (profile=None, filter=None, progress=None))]
+    fn rebuild_artifacts(&self, profile: Option<&PyDict>, filter: Option<&PyAny>, progress: Option<progress::ProgressTracker>, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.rebuild_artifacts", || {
+            // Validated like any export options, but applied per artifact
+            let (job_id, log_dir) = self.converter_job(&render::RenderOptions::from_dict(profile)?);
+            let overrides = artifacts::ExportSettings::from_dict(profile)?;
+            let registry = artifacts::load_artifacts(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load artifacts: {}", e)))?;
+            let targets: Vec<String> = match filter {
+                Some(filter) => self.resolve_targets(py, filter)?,
+                None => registry.keys().cloned().collect(),
+            };
+
+            let estimate = targets
+                .iter()
+                .flat_map(|filename| registry.get(filename).into_iter().flatten())
+                .filter_map(|artifact| fs::metadata(&artifact.path).ok())
+                .map(|metadata| metadata.len())
+                .sum();
+            space::ensure_space(Path::new(&self.reports_dir), estimate, "the rebuild")
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+
+            let reports_dir = self.reports_dir.as_str();
+            let total = targets.len().max(1);
+            let done = std::sync::atomic::AtomicUsize::new(0);
+            let cost = |filename: &str| {
+                budget::render_cost(fs::metadata(Path::new(reports_dir).join(filename)).map(|m| m.len()).unwrap_or(0))
+            };
+            let result = py.allow_threads(|| {
+                bulk::run_budgeted(&targets, cost, |filename| {
+                    if progress.as_ref().is_some_and(|tracker| tracker.cancel_requested()) {
+                        return Err(anyhow!("cancelled"));
+                    }
+                    let recorded = registry.get(filename).filter(|recorded| !recorded.is_empty())
+                        .ok_or_else(|| anyhow!("no exported artifacts recorded"))?;
+                    let source = Path::new(reports_dir).join(filename);
+                    let content = {
+                        let _lock = lock_report(&source, false)?;
+                        fs::read_to_string(&source)?
+                    };
+
+                    let mut errors = Vec::new();
+                    for artifact in recorded {
+                        let settings = artifact.settings.merged(&overrides);
+                        let target = Path::new(&artifact.path);
+                        let rebuilt = settings
+                            .render_options()
+                            .and_then(|render_options| {
+                                export_report(&content, target, &artifact.format, &render_options.for_job(&job_id, &log_dir))
+                            })
+                            .and_then(|_| {
+                                artifacts::record_artifact(reports_dir, filename, &artifact.format, target, content.as_bytes(), &settings, &job_id)
+                            });
+                        if let Err(e) = rebuilt {
+                            errors.push(format!("{}: {}", artifact.path, e));
+                        }
+                    }
+
+                    let finished = done.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                    if let Some(tracker) = &progress {
+                        let percentage = finished as f32 * 100.0 / total as f32;
+                        let _ = Python::with_gil(|py| tracker.update(percentage, "Rebuilding artifacts", "ReportManager", filename, py));
+                    }
+                    match errors.is_empty() {
+                        true => Ok(()),
+                        false => Err(anyhow!(errors.join("; "))),
+                    }
+                })
+            });
+
+            job_result_dict(py, result, &job_id)
+        })
+    }
+
+    /// Converter runs logged for an export job of this manager (the `job_id` returned by `export_many`,
+    /// `reexport_all` and `rebuild_artifacts`, or listed by `list_artifacts`): command line, outcome and output
+    fn get_converter_log(&self, job_id: &str, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.get_converter_log", || {
+            let log_dir = PathBuf::from(joblog::reports_log_dir(&self.reports_dir));
+            let runs = py
+                .allow_threads(|| joblog::load(&log_dir, job_id))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to read converter log: {}", e)))?;
+            joblog::runs_to_py(py, &runs)
+        })
+    }
+
+    /// Re-hash the content-addressed artifact store against its manifest, returning
+    /// `{objects, bytes, corrupted, missing: [{filename, path}], unreferenced, removed}`.
+    /// `remove_unreferenced` deletes stored objects no recorded export points to
+    #[pyo3(signature = (remove_unreferenced=false))]
+    fn verify_artifact_store(&self, remove_unreferenced: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.verify_artifact_store", || {
+            let check = py
+                .allow_threads(|| artifacts::check_store(&self.reports_dir, remove_unreferenced))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to verify artifact store: {}", e)))?;
+
+            let missing = PyList::empty(py);
+            for (filename, path) in check.missing {
+                let dict = PyDict::new(py);
+                dict.set_item("filename", filename)?;
+                dict.set_item("path", path)?;
+                missing.append(dict)?;
+            }
+            let result = PyDict::new(py);
+            result.set_item("objects", check.objects)?;
+            result.set_item("bytes", check.bytes)?;
+            result.set_item("corrupted", check.corrupted)?;
+            result.set_item("missing", missing)?;
+            result.set_item("unreferenced", check.unreferenced)?;
+            result.set_item("removed", check.removed)?;
+            Ok(result.into())
+        })
+    }
+
+    /// Extract cited, declarative claims from every report into a question-answering index; returns the claim count.
+    /// `embed`, if given, is called with a list of claim texts and must return one vector per text,
+    /// so `answer_from_archive` can take a question embedding
+    #[pyo3(signature = (embed=None))]
+    fn build_qa_index(&self, embed: Option<&PyAny>, py: Python) -> PyResult<usize> {
+        panics::guard("ReportManager.build_qa_index", || {
+            let files: Vec<String> = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?
+                .into_iter()
+                .map(|(filename, _)| filename)
+                .collect();
+            let previous = qa::QaIndex::load(&self.reports_dir).ok();
+            let mut index = py.allow_threads(|| qa::build_index(&self.reports_dir, &files, previous.as_ref()));
+
+            match embed {
+                Some(embed) => {
+                    // Claims from unchanged reports keep their vectors; only new claims are embedded
+                    let missing: Vec<usize> = (0..index.claims.len()).filter(|&i| index.claims[i].embedding.is_none()).collect();
+                    if !missing.is_empty() {
+                        let texts: Vec<&str> = missing.iter().map(|&i| index.claims[i].text.as_str()).collect();
+                        let vectors: Vec<Vec<f32>> = embed.call1((texts,))?.extract()?;
+                        if vectors.len() != missing.len() {
+                            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                                format!("embed returned {} vectors for {} claims", vectors.len(), missing.len())
+                            ));
+                        }
+                        for (i, vector) in missing.into_iter().zip(vectors) {
+                            index.claims[i].embedding = Some(vector);
+                        }
+                    }
+                }
+                None => index.claims.iter_mut().for_each(|claim| claim.embedding = None),
+            }
+
+            py.allow_threads(|| index.save(&self.reports_dir))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to save QA index: {}", e)))?;
+            Ok(index.claims.len())
+        })
+    }
+
+    /// Best-matching claims from the QA index for a keyword question or a question embedding, as
+    /// `[{claim, score, filename, title, date, heading, line, citations}]`
+    #[pyo3(signature = (query, limit=5))]
+    fn answer_from_archive(&self, query: &PyAny, limit: usize, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.answer_from_archive", || {
+            let query = match query.extract::<String>() {
+                Ok(keywords) => qa::Query::Keywords(keywords),
+                Err(_) => qa::Query::Embedding(query.extract()?),
+            };
+            let index = qa::QaIndex::load(&self.reports_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load QA index: {}", e)))?;
+            let answers = index
+                .answer(&query, limit)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to answer from archive: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for answer in answers {
+                let dict = PyDict::new(py);
+                dict.set_item("claim", &answer.claim.text)?;
+                dict.set_item("score", answer.score)?;
+                dict.set_item("filename", &answer.claim.filename)?;
+                dict.set_item("title", &answer.claim.title)?;
+                dict.set_item("date", &answer.claim.date)?;
+                dict.set_item("heading", &answer.claim.heading)?;
+                dict.set_item("line", answer.claim.line)?;
+                dict.set_item("citations", &answer.claim.citations)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Figures in a report that newer reports restate differently, so it can be annotated or refreshed
+    /// before being re-shared. Returns `[{line, claim, value, newer_filename, newer_date, newer_line,
+    /// newer_claim, newer_value, change, similarity}]`, where `change` is relative to the old figure
+    #[pyo3(signature = (filename, tolerance=0.05, min_similarity=0.5))]
+    fn find_superseded_claims(&self, filename: &str, tolerance: f64, min_similarity: f64, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.find_superseded_claims", || {
+            let files: Vec<String> = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?
+                .into_iter()
+                .map(|(filename, _)| filename)
+                .collect();
+            let superseded = py
+                .allow_threads(|| supersede::find_superseded(&self.reports_dir, filename, &files, tolerance, min_similarity))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(format!("Failed to check claims: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for item in superseded {
+                let dict = PyDict::new(py);
+                dict.set_item("line", item.line)?;
+                dict.set_item("claim", item.claim)?;
+                dict.set_item("value", item.value)?;
+                dict.set_item("newer_filename", item.newer_filename)?;
+                dict.set_item("newer_date", item.newer_date.format("%Y-%m-%d").to_string())?;
+                dict.set_item("newer_line", item.newer_line)?;
+                dict.set_item("newer_claim", item.newer_claim)?;
+                dict.set_item("newer_value", item.newer_value)?;
+                dict.set_item("change", item.change)?;
+                dict.set_item("similarity", item.similarity)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Full-text search; every term must occur. Returns `[{filename, title, score, matches: [{line, snippet}]}]`
+    /// where each snippet is the matching sentence, HTML-escaped, with the terms wrapped in `<mark>`
+    #[pyo3(signature = (query, limit=20, case_sensitive=false, max_snippets=3))]
+    fn search(&self, query: &str, limit: usize, case_sensitive: bool, max_snippets: usize, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.search", || {
+            let files: Vec<String> = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?
+                .into_iter()
+                .map(|(filename, _)| filename)
+                .collect();
+            let hits = py.allow_threads(|| search::search_reports(&self.reports_dir, &files, query, case_sensitive, max_snippets))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to search reports: {}", e)))?;
+
+            let result = PyList::empty(py);
+            for hit in hits.into_iter().take(limit) {
+                let matches = PyList::empty(py);
+                for snippet in hit.snippets {
+                    let dict = PyDict::new(py);
+                    dict.set_item("line", snippet.line)?;
+                    dict.set_item("snippet", snippet.html)?;
+                    matches.append(dict)?;
+                }
+                let dict = PyDict::new(py);
+                dict.set_item("filename", hit.filename)?;
+                dict.set_item("title", hit.title)?;
+                dict.set_item("score", hit.score)?;
+                dict.set_item("matches", matches)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Render the whole library into a browsable static site with a searchable index page.
+    /// `theme` is light, dark, sepia or a path to a CSS file; `options` are the `format_report` render options.
+    #[pyo3(signature = (output_dir, theme="light", options=None))]
+    fn export_site(&self, output_dir: &str, theme: &str, options: Option<&PyDict>, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.export_site", || {
+            let render_options = render::RenderOptions::from_dict(options)?;
+            let files: Vec<String> = list_files(&self.reports_dir, &self.extensions)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?
+                .into_iter()
+                .map(|(filename, _)| filename)
+                .collect();
+            let summary = py.allow_threads(|| site::export_site(&self.reports_dir, &files, output_dir, theme, &render_options))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to export site: {}", e)))?;
+
+            let dict = PyDict::new(py);
+            dict.set_item("index", summary.index.to_string_lossy().to_string())?;
+            dict.set_item("pages", summary.pages)?;
+            dict.set_item("assets", summary.assets)?;
+            dict.set_item("failed", summary.failed.into_iter().collect::<HashMap<_, _>>())?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Add and/or remove front matter tags on many reports in parallel
+    #[pyo3(signature = (targets, add=None, remove=None))]
+    fn retag_many(&self, targets: &PyAny, add: Option<Vec<String>>, remove: Option<Vec<String>>, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.retag_many", || {
+            let targets = self.resolve_targets(py, targets)?;
+            let (add, remove) = (add.unwrap_or_default(), remove.unwrap_or_default());
+            let reports_dir = self.reports_dir.as_str();
+
+            let result = py.allow_threads(|| {
+                let result = bulk::run(&targets, |filename| {
+                    bulk::retag(&Path::new(reports_dir).join(filename), &add, &remove)
+                });
+                index::update_index(reports_dir, |index| {
+                    for filename in &result.succeeded {
+                        let bytes = fs::read(Path::new(reports_dir).join(filename))?;
+                        index.record(filename, &bytes);
+                    }
+                    Ok(())
+                })
+                .map(|_| result)
+            })
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to update index: {}", e)))?;
+            self.commit_history_batch(&result.succeeded, "Retag", py)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Reports retagged but history commit failed: {}", e)))?;
+
+            bulk_result_dict(py, result)
+        })
+    }
+
+    /// Recompute checksums for all reports, accepting their current content as correct
+    fn rebuild_index(&self, py: Python) -> PyResult<usize> {
+        panics::guard("ReportManager.rebuild_index", || {
+            py.allow_threads(|| index::rebuild(&self.reports_dir))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to rebuild index: {}", e)))
+        })
+    }
+
+    /// Upgrade versioned sidecar files written by an older build to the current schemas (see `core_info`),
+    /// returning `[{format, file, from_version, to_version, migrated}]`; `dry_run` reports without writing.
+    /// Files from a newer build are refused with a ValueError instead of being read and overwritten
+    #[pyo3(signature = (dry_run=false))]
+    fn migrate(&self, dry_run: bool, py: Python) -> PyResult<PyObject> {
+        panics::guard("ReportManager.migrate", || {
+            let migrations = py
+                .allow_threads(|| compat::migrate_index(&self.reports_dir, dry_run).map(|migration| vec![migration]))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to migrate: {}", e)))?;
+            let result = PyList::empty(py);
+            for migration in migrations {
+                let dict = PyDict::new(py);
+                dict.set_item("format", migration.format)?;
+                dict.set_item("file", migration.file)?;
+                dict.set_item("from_version", migration.from_version)?;
+                dict.set_item("to_version", migration.to_version)?;
+                dict.set_item("migrated", migration.migrated)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+}
+
+impl ReportManager {
+    /// Commit the current state of a report when the reports directory is a git repo
+    fn commit_history(&self, filename: &str, action: Option<&str>, py: Python) -> Result<()> {
+        commit_history(&self.reports_dir, filename, action, py)
     }
 
-    /// Update the progress of report generation
-    fn update(&self, percentage: f32, stage: &str, agent: &str, activity: &str) -> PyResult<()> {
-        let mut data = self.progress.lock().unwrap();
-        data.percentage = percentage;
-        data.stage = stage.to_string();
-        data.agent = agent.to_string();
-        data.activity = activity.to_string();
+    /// Record a batch operation on `filenames` as a single commit, when history is enabled and anything changed
+    fn commit_history_batch(&self, filenames: &[String], action: &str, py: Python) -> Result<()> {
+        if !filenames.is_empty() && history::is_enabled(&self.reports_dir) {
+            py.allow_threads(|| history::commit_reports(&self.reports_dir, filenames, action))?;
+        }
         Ok(())
     }
 
-    /// Get the current progress data
-    fn get_progress(&self, py: Python) -> PyResult<PyObject> {
-        let data = self.progress.lock().unwrap();
-        let dict = PyDict::new(py);
-        dict.set_item("percentage", data.percentage)?;
-        dict.set_item("stage", &data.stage)?;
-        dict.set_item("agent", &data.agent)?;
-        dict.set_item("activity", &data.activity)?;
-        dict.set_item("elapsed_seconds", self.get_elapsed_seconds())?;
-        Ok(dict.into())
+    /// Path of a report inside the reports directory; a filename reaching outside it is a ValueError
+    fn report_path(&self, filename: &str) -> PyResult<PathBuf> {
+        paths::report_path(&self.reports_dir, filename)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
     }
 
-    /// Get elapsed time in seconds
-    fn get_elapsed_seconds(&self) -> f32 {
-        let start = self.start_time.lock().unwrap();
-        start.elapsed().as_secs_f32()
+    /// Job id and log directory for an export: as given in the options, else a new id logging to the reports directory
+    fn converter_job(&self, options: &render::RenderOptions) -> (String, String) {
+        let job_id = options.job_id.clone().unwrap_or_else(joblog::new_job_id);
+        let log_dir = options.log_dir.clone().unwrap_or_else(|| joblog::reports_log_dir(&self.reports_dir));
+        (job_id, log_dir)
     }
 
-    /// Reset the progress tracker
-    fn reset(&self) -> PyResult<()> {
-        let mut data = self.progress.lock().unwrap();
-        data.percentage = 0.0;
-        data.stage = "Initializing".to_string();
-        data.agent = "System".to_string();
-        data.activity = "Starting up".to_string();
-        
-        let mut start = self.start_time.lock().unwrap();
-        *start = Instant::now();
-        Ok(())
+    /// Resolve a list of filenames, or a predicate called with `{"filename", "metadata"}` per report
+    fn resolve_targets(&self, py: Python, targets: &PyAny) -> PyResult<Vec<String>> {
+        if !targets.is_callable() {
+            return targets.extract();
+        }
+
+        let reports = list_files(&self.reports_dir, &self.extensions)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?;
+        let mut selected = Vec::new();
+        for (filename, _) in reports {
+            let content = fs::read_to_string(Path::new(&self.reports_dir).join(&filename)).unwrap_or_default();
+            let metadata = report_front_matter(&content).map(|(mapping, _)| mapping).unwrap_or_default();
+            let metadata = frontmatter::yaml_to_py(py, &serde_yaml::Value::Mapping(metadata))?;
+
+            let info = PyDict::new(py);
+            info.set_item("filename", &filename)?;
+            info.set_item("metadata", metadata)?;
+            if targets.call1((info,))?.is_true()? {
+                selected.push(filename);
+            }
+        }
+        Ok(selected)
     }
 }
 
-/// Manager for report files
-#[pyclass]
-struct ReportManager {
-    reports_dir: String,
+/// One row of `list_with_metadata`
+struct ListedReport {
+    filename: String,
+    front_matter: frontmatter::FrontMatterSummary,
+    date: NaiveDateTime,
+    size: u64,
+    mtime: Option<f64>,
+    error: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-struct ReportMetadata {
-    title: String,
-    date: String,
-    id: String,
-}
+impl ListedReport {
+    /// Stat the file and parse its front matter; the date falls back to the filename stamp, then mtime
+    fn load(reports_dir: &str, filename: String) -> Self {
+        let path = Path::new(reports_dir).join(&filename);
+        let metadata = fs::metadata(&path).ok();
+        let modified = metadata.as_ref().and_then(|m| m.modified().ok());
 
-#[pymethods]
-impl ReportManager {
-    #[new]
-    fn new(reports_dir: &str) -> Self {
-        ReportManager {
-            reports_dir: reports_dir.to_string(),
-        }
-    }
+        let (front_matter, error) = match frontmatter::read_front_matter(&path) {
+            Ok(mapping) => (frontmatter::FrontMatterSummary::from_mapping(&mapping), None),
+            Err(e) => (Default::default(), Some(e.to_string())),
+        };
+        let date = front_matter
+            .date
+            .as_deref()
+            .and_then(stats::parse_date)
+            .unwrap_or_else(|| stats::report_date(&filename, "", modified));
 
-    /// Save a report to disk
-    fn save_report(&self, filename: &str, content: &str) -> PyResult<String> {
-        let path = Path::new(&self.reports_dir).join(filename);
-        
-        // Create directory if it doesn't exist
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create directory: {}", e)))?;
-            }
+        ListedReport {
+            mtime: modified
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs_f64()),
+            size: metadata.map(|m| m.len()).unwrap_or(0),
+            filename,
+            front_matter,
+            date,
+            error,
         }
-        
-        // Use atomic write pattern to prevent corruption
-        let temp_filename = format!("{}.tmp", filename);
-        
-        // Write to temporary file first
-        fs::write(&temp_filename, content).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(
-                format!("Failed to write temporary file: {}", e)
-            )
-        })?;
-        
-        // Rename temporary file to final filename
-        fs::rename(&temp_filename, &path).map_err(|e| {
-            // Try to clean up temp file
-            let _ = fs::remove_file(&temp_filename);
-            
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(
-                format!("Failed to save report: {}", e)
-            )
-        })?;
-        
-        Ok(path.to_string_lossy().to_string())
     }
+}
 
-    /// Get a list of all reports
-    fn get_all_reports(&self, py: Python) -> PyResult<PyObject> {
-        let reports = list_reports(&self.reports_dir)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))?;
-        
-        let result = PyList::new(py, &reports);
-        Ok(result.into())
-    }
+const DEFAULT_EXTENSION: &str = "md";
 
-    /// Read a report from disk
-    fn read_report(&self, filename: &str) -> PyResult<String> {
-        let path = Path::new(&self.reports_dir).join(filename);
-        
-        // Check if file exists
-        if !path.exists() {
-            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
-                format!("Report file not found: {}", filename)
-            ));
-        }
-        
-        // Check if it's actually a file and not a directory
-        if !path.is_file() {
-            return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(
-                format!("Path is not a file: {}", filename)
-            ));
+/// Lowercase extensions and strip leading dots, rejecting empty entries
+fn normalize_extensions(extensions: &[String]) -> PyResult<Vec<String>> {
+    let mut normalized = Vec::with_capacity(extensions.len());
+    for extension in extensions {
+        let extension = extension.trim().trim_start_matches('.').to_lowercase();
+        if extension.is_empty() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("File extensions must not be empty"));
         }
-        
-        // Check file size to prevent loading extremely large files
-        let metadata = fs::metadata(&path).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(
-                format!("Failed to read file metadata: {}", e)
-            )
-        })?;
-        
-        const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024; // 50MB limit
-        if metadata.len() > MAX_FILE_SIZE {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                format!("File too large ({}MB). Maximum size is 50MB.", metadata.len() / (1024 * 1024))
-            ));
+        if !normalized.contains(&extension) {
+            normalized.push(extension);
         }
-        
-        // Read file with informative error
-        fs::read_to_string(&path).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(
-                format!("Failed to read report file: {}", e)
-            )
-        })
     }
-
-    /// Delete a report
-    fn delete_report(&self, filename: &str) -> PyResult<bool> {
-        let path = Path::new(&self.reports_dir).join(filename);
-        
-        if path.exists() {
-            fs::remove_file(&path)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to delete file: {}", e)))?;
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+    if normalized.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("At least one file extension is required"));
     }
+    Ok(normalized)
 }
 
-/// Process markdown content and extract metadata
-#[pyfunction]
-fn process_markdown(content: &str) -> PyResult<(HashMap<String, String>, String)> {
-    // Validate input is not empty
-    if content.trim().is_empty() {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "Markdown content cannot be empty"
-        ));
+/// Convert a batch result into `{"succeeded": [...], "failed": {filename: error}}`
+fn bulk_result_dict(py: Python, result: bulk::BulkResult) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("succeeded", result.succeeded)?;
+    dict.set_item("failed", result.failed.into_iter().collect::<HashMap<_, _>>())?;
+    Ok(dict.into())
+}
+
+/// `bulk_result_dict` of an export job, plus the `job_id` its converter output is logged under
+fn job_result_dict(py: Python, result: bulk::BulkResult, job_id: &str) -> PyResult<PyObject> {
+    let dict = bulk_result_dict(py, result)?;
+    dict.as_ref(py).downcast::<PyDict>()?.set_item("job_id", job_id)?;
+    Ok(dict)
+}
+
+const TRASH_DIR: &str = ".trash";
+const TRASH_SEPARATOR: &str = "__";
+const TRASH_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%.3f";
+
+/// A report that has been moved to the trash
+struct TrashEntry {
+    trash_name: String,
+    filename: String,
+    deleted_at: NaiveDateTime,
+}
+
+/// Move a report into the trash under a timestamped name, holding its exclusive lock so no writer is mid-save;
+/// returns false if it does not exist
+fn trash_report(reports_dir: &str, filename: &str) -> Result<bool> {
+    let path = Path::new(reports_dir).join(filename);
+    if !path.exists() {
+        return Ok(false);
+    }
+    let _lock = lock_report(&path, true)?;
+    if !path.exists() {
+        remove_lock_file(&path);
+        return Ok(false);
     }
 
-    // Limit the size of the input to prevent processing extremely large markdown
-    const MAX_CONTENT_LENGTH: usize = 10 * 1024 * 1024; // 10MB limit
-    if content.len() > MAX_CONTENT_LENGTH {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            format!("Markdown content too large ({}MB). Maximum size is 10MB.", content.len() / (1024 * 1024))
-        ));
+    let trash_dir = Path::new(reports_dir).join(TRASH_DIR);
+    fs::create_dir_all(&trash_dir)?;
+    let trash_name = format!(
+        "{}{}{}",
+        Local::now().format(TRASH_TIMESTAMP_FORMAT),
+        TRASH_SEPARATOR,
+        // Escape `%` first so a literal `%2F` in the name comes back unchanged
+        filename.replace('%', "%25").replace(['/', '\\'], "%2F")
+    );
+    fs::rename(&path, trash_dir.join(&trash_name))?;
+    remove_lock_file(&path);
+    Ok(true)
+}
+
+/// Read the trash directory, decoding original filenames and deletion times
+fn list_trash_entries(reports_dir: &str) -> Result<Vec<TrashEntry>> {
+    let trash_dir = Path::new(reports_dir).join(TRASH_DIR);
+    if !trash_dir.exists() {
+        return Ok(Vec::new());
     }
 
-    // Extract metadata and markdown content
-    let (metadata, markdown_content) = match parse_report_metadata(content) {
-        Ok(result) => result,
-        Err(err) => {
+    let mut entries: Vec<TrashEntry> = fs::read_dir(&trash_dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let trash_name = entry.file_name().to_str()?.to_string();
+            let (stamp, encoded) = trash_name.split_once(TRASH_SEPARATOR)?;
+            let deleted_at = NaiveDateTime::parse_from_str(stamp, TRASH_TIMESTAMP_FORMAT).ok()?;
+
+            Some(TrashEntry {
+                filename: encoded.replace("%2F", "/").replace("%25", "%"),
+                trash_name,
+                deleted_at,
+            })
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    Ok(entries)
+}
+
+/// Process markdown content and extract metadata, keeping YAML types as `parse_report_metadata` does
+#[pyfunction]
+fn process_markdown(content: &str, py: Python) -> PyResult<(PyObject, String)> {
+    panics::guard("process_markdown", || {
+        // Validate input is not empty
+        if content.trim().is_empty() {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                format!("Failed to parse markdown metadata: {}", err)
+                "Markdown content cannot be empty"
             ));
         }
-    };
 
-    // Validate that required metadata fields are present
-    let required_fields = ["title", "date"];
-    for field in required_fields.iter() {
-        if !metadata.contains_key(*field) {
+        // Limit the size of the input to prevent processing extremely large markdown
+        const MAX_CONTENT_LENGTH: usize = 10 * 1024 * 1024; // 10MB limit
+        if content.len() > MAX_CONTENT_LENGTH {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                format!("Missing required metadata field: {}", field)
+                format!("Markdown content too large ({}MB). Maximum size is 10MB.", content.len() / (1024 * 1024))
             ));
         }
-    }
 
-    Ok((metadata, markdown_content))
+        // Extract metadata and markdown content
+        let (metadata, markdown_content) = match report_front_matter(content) {
+            Ok(result) => result,
+            Err(err) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Failed to parse markdown metadata: {}", err)
+                ));
+            }
+        };
+
+        // Validate that required metadata fields are present
+        let required_fields = ["title", "date"];
+        for field in required_fields.iter() {
+            if !metadata.contains_key(*field) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Missing required metadata field: {}", field)
+                ));
+            }
+        }
+
+        Ok((frontmatter::yaml_to_py(py, &serde_yaml::Value::Mapping(metadata))?, markdown_content.to_string()))
+    })
 }
 
 /// Clean terminal escape sequences from the content
 #[pyfunction]
 fn clean_escape_sequences(content: &str) -> PyResult<String> {
-    // Handle various forms of escape sequences
+    panics::guard("clean_escape_sequences", || {
+        // Handle various forms of escape sequences
     
-    // 1. ANSI/VT100 escape sequence regex pattern for real escape codes
-    let ansi_pattern = Regex::new(r"\x1B\[([0-9]{1,2}(;[0-9]{1,2})*)?[m|K|G|A|B|C|D|H|J|s|u|h|l]").unwrap();
-    let mut cleaned = ansi_pattern.replace_all(content, "").to_string();
+        // 1. ANSI/VT100 escape sequence regex pattern for real escape codes
+        let ansi_pattern = Regex::new(r"\x1B\[([0-9]{1,2}(;[0-9]{1,2})*)?[m|K|G|A|B|C|D|H|J|s|u|h|l]").unwrap();
+        let mut cleaned = ansi_pattern.replace_all(content, "").to_string();
     
-    // 2. Literal "ESC[" followed by formatting codes
-    let literal_esc_pattern = Regex::new(r"ESC\[([0-9]{1,2}(;[0-9]{1,2})*)?[m|K|G|A|B|C|D|H|J|s|u|h|l]").unwrap();
-    cleaned = literal_esc_pattern.replace_all(&cleaned, "").to_string();
+        // 2. Literal "ESC[" followed by formatting codes
+        let literal_esc_pattern = Regex::new(r"ESC\[([0-9]{1,2}(;[0-9]{1,2})*)?[m|K|G|A|B|C|D|H|J|s|u|h|l]").unwrap();
+        cleaned = literal_esc_pattern.replace_all(&cleaned, "").to_string();
     
-    // 3. Simple common patterns
-    let simple_patterns = [
-        Regex::new(r"ESC\[0m").unwrap(),         // Reset
-        Regex::new(r"ESC\[1m").unwrap(),         // Bold
-        Regex::new(r"ESC\[1;33m").unwrap(),      // Yellow bold
-        Regex::new(r"ESC\[\d+m").unwrap(),       // Any single number format
-        Regex::new(r"ESC\[\d+;\d+m").unwrap(),   // Any compound format
-    ];
+        // 3. Simple common patterns
+        let simple_patterns = [
+            Regex::new(r"ESC\[0m").unwrap(),         // Reset
+            Regex::new(r"ESC\[1m").unwrap(),         // Bold
+            Regex::new(r"ESC\[1;33m").unwrap(),      // Yellow bold
+            Regex::new(r"ESC\[\d+m").unwrap(),       // Any single number format
+            Regex::new(r"ESC\[\d+;\d+m").unwrap(),   // Any compound format
+        ];
     
-    for pattern in simple_patterns.iter() {
-        cleaned = pattern.replace_all(&cleaned, "").to_string();
-    }
+        for pattern in simple_patterns.iter() {
+            cleaned = pattern.replace_all(&cleaned, "").to_string();
+        }
     
-    // 4. Catch-all for other forms
-    let catchall = Regex::new(r"(?:\x1B|\bESC)(?:\[|\(|\))[^@-Z\\^_`a-z{|}~]*[@-Z\\^_`a-z{|}~]").unwrap();
-    cleaned = catchall.replace_all(&cleaned, "").to_string();
+        // 4. Catch-all for other forms
+        let catchall = Regex::new(r"(?:\x1B|\bESC)(?:\[|\(|\))[^@-Z\\^_`a-z{|}~]*[@-Z\\^_`a-z{|}~]").unwrap();
+        cleaned = catchall.replace_all(&cleaned, "").to_string();
     
-    Ok(cleaned)
+        Ok(cleaned)
+    })
 }
 
-/// Format a market research report from markdown to HTML
+/// Format a market research report from markdown to an HTML fragment, or to a full document when the `template`
+/// option names a layout
 #[pyfunction]
-fn format_report(markdown: &str) -> PyResult<String> {
+#[pyo3(signature = (markdown, options=None))]
+fn format_report(markdown: &str, options: Option<&PyDict>) -> PyResult<String> {
+    panics::guard("format_report", || {
+        let render_options = render::RenderOptions::from_dict(options)?;
+        let html = render_report_html(markdown, &render_options)?;
+        if render_options.template.is_none() {
+            return Ok(html);
+        }
+        let css = render_options.document_css()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read stylesheet: {}", e)))?;
+        export_document(markdown, &html, &css, &render_options)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+    })
+}
+
+/// Render markdown to an HTML fragment (shared by `format_report` and batch exports)
+fn render_report_html(markdown: &str, render_options: &render::RenderOptions) -> PyResult<String> {
     // Validate input is not empty
     if markdown.trim().is_empty() {
         return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
@@ -305,6 +1771,10 @@ fn format_report(markdown: &str) -> PyResult<String> {
 
     // Clean any terminal escape sequences that might be present
     let cleaned_markdown = clean_escape_sequences(markdown)?;
+    if let Some(policy_file) = &render_options.policy_file {
+        policy::preflight(&cleaned_markdown, policy_file)?;
+    }
+    let cleaned_markdown = render::preprocess(&cleaned_markdown, render_options);
 
     // Create options for markdown processing
     let mut options = ComrakOptions::default();
@@ -314,14 +1784,21 @@ fn format_report(markdown: &str) -> PyResult<String> {
     options.extension.autolink = true;
     options.extension.tasklist = true;
     options.extension.superscript = true;
-    options.extension.header_ids = Some("section-".to_string());
+    options.extension.header_ids = Some(toc::ANCHOR_PREFIX.to_string());
+    options.extension.footnotes = render_options.footnotes;
+    options.parse.smart = render_options.smart_punctuation;
     options.render.github_pre_lang = true;
     options.render.hardbreaks = false;
-    options.render.unsafe_ = true;  // Allow HTML passthrough
+    options.render.unsafe_ = true;  // Allow HTML passthrough, cleaned afterwards by the `sanitize` mode
+
+    let sanitize = render_options.sanitize;
+    let math = render_options.math;
+    let mermaid = render_options.mermaid;
+    let inline_images = render_options.inline_images;
 
     // Use a thread with timeout to prevent potential hangs
     let result = std::thread::spawn(move || {
-        comrak::markdown_to_html(&cleaned_markdown, &options)
+        sanitize::sanitize_html(&render::titled_endnotes(&comrak::markdown_to_html(&cleaned_markdown, &options)), sanitize)
     })
     .join()
     .map_err(|_| {
@@ -337,67 +1814,314 @@ fn format_report(markdown: &str) -> PyResult<String> {
         ));
     }
     
-    Ok(result)
+    let result = if inline_images { images::inline_remote_images(&result) } else { result };
+    let result = if math { math::with_katex(result) } else { result };
+    Ok(if mermaid { mermaid::with_mermaid(result) } else { result })
 }
 
-/// Parse report metadata from markdown content
-#[pyfunction]
+/// Split a report into its front matter and body; no front matter gives an empty mapping and the full content
+fn report_front_matter(content: &str) -> PyResult<(serde_yaml::Mapping, &str)> {
+    frontmatter::front_matter_mapping(content)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to parse front matter: {}", e)))
+}
+
+/// Report metadata with scalar values as text, and the body (for callers that only read fields like title and date)
 fn parse_report_metadata(content: &str) -> PyResult<(HashMap<String, String>, String)> {
-    let re = Regex::new(r"^---\n(.*?)\n---\n(.*)").unwrap();
-    
-    match re.captures(content) {
-        Some(caps) => {
-            let yaml_str = caps.get(1).unwrap().as_str();
-            let markdown_content = caps.get(2).unwrap().as_str();
-            
-            let metadata: HashMap<String, String> = serde_yaml::from_str(yaml_str)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    format!("Failed to parse YAML metadata: {}", e)
-                ))?;
-            
-            Ok((metadata, markdown_content.to_string()))
-        },
-        None => {
-            // If no metadata section found, return empty metadata and full content
-            Ok((HashMap::new(), content.to_string()))
-        }
-    }
+    let (mapping, body) = report_front_matter(content)?;
+    Ok((frontmatter::scalar_metadata(&mapping), body.to_string()))
+}
+
+/// Parse report metadata from YAML (`---`), TOML (`+++`) or ```` ```json ```` front matter, keeping its types: lists, nested mappings, ints, floats and bools
+/// come back as the matching Python values
+#[pyfunction]
+#[pyo3(name = "parse_report_metadata")]
+fn py_parse_report_metadata(content: &str, py: Python) -> PyResult<(PyObject, String)> {
+    panics::guard("parse_report_metadata", || {
+        let (mapping, body) = report_front_matter(content)?;
+        Ok((frontmatter::yaml_to_py(py, &serde_yaml::Value::Mapping(mapping))?, body.to_string()))
+    })
 }
 
 /// Python-exposed function for listing reports
 #[pyfunction]
 fn py_list_reports(dir_path: &str) -> PyResult<Vec<String>> {
-    list_reports(dir_path)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))
+    panics::guard("py_list_reports", || {
+        list_reports(dir_path)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list reports: {}", e)))
+    })
 }
 
 /// List all report files (internal implementation)
 fn list_reports(dir_path: &str) -> Result<Vec<String>> {
+    Ok(list_files(dir_path, &[DEFAULT_EXTENSION.to_string()])?
+        .into_iter()
+        .map(|(filename, _)| filename)
+        .collect())
+}
+
+/// List files whose extension is in `extensions`, returning (filename, format) pairs. Hidden files are the
+/// library's own state (`.index.json`, `.retention.json`, ...), never reports
+fn list_files(dir_path: &str, extensions: &[String]) -> Result<Vec<(String, String)>> {
     let path = Path::new(dir_path);
     
     if !path.exists() {
         return Err(anyhow!("Reports directory does not exist"));
     }
     
-    let entries = fs::read_dir(path)?
+    let mut entries = fs::read_dir(path)?
         .filter_map(|entry| {
             let entry = entry.ok()?;
             let path = entry.path();
-            
-            if path.is_file() && path.extension()?.to_str()? == "md" {
-                Some(path.file_name()?.to_str()?.to_string())
+            let format = path.extension()?.to_str()?.to_lowercase();
+            let name = path.file_name()?.to_str()?;
+
+            if path.is_file() && !name.starts_with('.') && extensions.contains(&format) {
+                Some((name.to_string(), format))
             } else {
                 None
             }
         })
-        .collect();
+        .collect::<Vec<_>>();
     
+    // Sort by stem, then format, so exported artifacts sit next to their source report
+    entries.sort_by(|(a, a_format), (b, b_format)| {
+        let stem = |name: &str| Path::new(name).file_stem().map(|s| s.to_os_string());
+        stem(a).cmp(&stem(b)).then_with(|| a_format.cmp(b_format)).then_with(|| a.cmp(b))
+    });
     Ok(entries)
 }
 
+/// Advisory lock on a report, released when dropped. Holds no file when a reader could not create the sidecar
+struct ReportLock {
+    file: Option<fs::File>,
+}
+
+impl Drop for ReportLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            let _ = FileExt::unlock(file);
+        }
+    }
+}
+
+/// The hidden `.{name}.lock` sidecar guarding a report
+fn lock_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!(".{}.lock", name))
+}
+
+/// Remove a report's lock sidecar once the report itself is gone; call it while still holding the lock
+fn remove_lock_file(path: &Path) {
+    let _ = fs::remove_file(lock_path(path));
+}
+
+/// Remove lock sidecars whose report no longer exists, such as those left by older versions after a delete.
+/// Sidecars another process holds are kept
+fn remove_orphan_locks(reports_dir: &str) {
+    let entries = walkdir::WalkDir::new(reports_dir)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || entry.file_type().is_file() || !entry.file_name().to_string_lossy().starts_with('.'))
+        .flatten();
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let report = match name.strip_prefix('.').and_then(|name| name.strip_suffix(".lock")) {
+            Some(report) if !report.is_empty() && entry.file_type().is_file() => entry.path().with_file_name(report),
+            _ => continue,
+        };
+        if report.exists() {
+            continue;
+        }
+        if let Ok(file) = fs::OpenOptions::new().write(true).open(entry.path()) {
+            if FileExt::try_lock_exclusive(&file).is_ok() {
+                if !report.exists() {
+                    let _ = fs::remove_file(entry.path());
+                }
+                let _ = FileExt::unlock(&file);
+            }
+        }
+    }
+}
+
+/// Lock the hidden `.{name}.lock` sidecar of a report, waiting up to `LOCK_TIMEOUT`. Shared locks open the sidecar
+/// read-only, and go without a lock when it does not exist and cannot be created, so read-only and shared report
+/// directories stay readable
+fn lock_report(path: &Path, exclusive: bool) -> std::io::Result<ReportLock> {
+    const LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+    const LOCK_RETRY: std::time::Duration = std::time::Duration::from_millis(20);
+
+    let lock_path = lock_path(path);
+    let create = || fs::OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path);
+    let file = match exclusive {
+        true => create()?,
+        false => match fs::File::open(&lock_path).or_else(|_| create()) {
+            Ok(file) => file,
+            Err(_) => return Ok(ReportLock { file: None }),
+        },
+    };
+
+    let started = Instant::now();
+    loop {
+        let result = if exclusive {
+            FileExt::try_lock_exclusive(&file)
+        } else {
+            FileExt::try_lock_shared(&file)
+        };
+        match result {
+            Ok(()) => return Ok(ReportLock { file: Some(file) }),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() && started.elapsed() < LOCK_TIMEOUT => {
+                std::thread::sleep(LOCK_RETRY);
+            }
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for lock on {}", path.display()),
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Save one report the way `ReportManager.save_report` does: check the name is portable and free of case
+/// collisions, create its directory, write it atomically under the exclusive lock, then update the index and
+/// history. With `overwrite` false an existing report raises `FileExistsError` instead
+fn store_report(reports_dir: &str, filename: &str, content: &str, overwrite: bool, py: Python) -> PyResult<PathBuf> {
+    let path = paths::report_path(reports_dir, filename)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    // Archives are synced between Windows, macOS and Linux; never create a name one of them cannot handle
+    if let Some(problem) = paths::filename_problem(filename) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Report filename is not portable: {}; try '{}'", problem, paths::portable_filename(filename))
+        ));
+    }
+    if let Some(other) = paths::case_collision(&path) {
+        return Err(PyErr::new::<pyo3::exceptions::PyFileExistsError, _>(
+            format!("{} differs from the existing {} only by case, which macOS and Windows treat as the same file", filename, other)
+        ));
+    }
+
+    // Create directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create directory: {}", e)))?;
+        }
+    }
+
+    // Hold an exclusive lock so concurrent writers cannot interleave
+    let _lock = py.allow_threads(|| lock_report(&path, true))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to lock report: {}", e)))?;
+
+    // Never clobber an existing report unless asked to
+    if !overwrite && path.exists() {
+        return Err(PyErr::new::<pyo3::exceptions::PyFileExistsError, _>(
+            format!("Report '{}' already exists", filename)
+        ));
+    }
+
+    // Write a temp file next to the destination, fsync it, and rename it into place
+    py.allow_threads(|| write_atomic(&path, content.as_bytes())).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(
+            format!("Failed to save report: {}", e)
+        )
+    })?;
+
+    // Record the checksum so external modification can be detected later
+    index::update_index(reports_dir, |index| {
+        index.record(filename, content.as_bytes());
+        Ok(())
+    })
+    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Report saved but index update failed: {}", e)))?;
+    commit_history(reports_dir, filename, None, py)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Report saved but history commit failed: {}", e)))?;
+
+    Ok(path)
+}
+
+/// Commit the current state of a report when the reports directory is a git repo
+fn commit_history(reports_dir: &str, filename: &str, action: Option<&str>, py: Python) -> Result<()> {
+    if history::is_enabled(reports_dir) {
+        py.allow_threads(|| history::commit_report(reports_dir, filename, action))?;
+    }
+    Ok(())
+}
+
+/// Replace a file durably: write and fsync a sibling temp file, rename it into place, then fsync the directory
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp_path = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, path)?;
+        sync_dir(dir)
+    })();
+
+    // Never leave a half-written temp file behind
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Flush a directory entry so a completed rename survives a crash
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    if cfg!(unix) {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Hex-encoded SHA-256 digest of a byte slice
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// How long to block for a Python `timeout` in seconds. `None` means no limit, and so does a value too large for a
+/// `Duration` (such as `float("inf")`); negative values don't wait at all
+fn wait_limit(timeout: Option<f64>) -> Option<std::time::Duration> {
+    timeout.and_then(|seconds| std::time::Duration::try_from_secs_f64(seconds.max(0.0)).ok())
+}
+
 /// Convert markdown report to PDF format
 #[pyfunction]
-fn export_to_pdf(content: &str, output_path: &str) -> PyResult<String> {
+#[pyo3(signature = (content, output_path, options=None))]
+fn export_to_pdf(content: &str, output_path: &str, options: Option<&PyDict>) -> PyResult<String> {
+    panics::guard("export_to_pdf", || {
+        let render_options = render::RenderOptions::from_dict(options)?;
+        write_pdf(content, output_path, &render_options)
+    })
+}
+
+/// Write a report to `target` as md, html or pdf (shared by batch exports and artifact re-exports)
+fn export_report(content: &str, target: &Path, format: &str, render_options: &render::RenderOptions) -> Result<()> {
+    // A previous export may be a hard link into the artifact store; writing through it would corrupt the stored copy
+    if target.exists() {
+        fs::remove_file(target)?;
+    }
+    match format {
+        "md" => fs::write(target, render::redacted(content, render_options))?,
+        "html" => {
+            let html = render_report_html(content, render_options).map_err(|e| anyhow!(e.to_string()))?;
+            fs::write(target, export_document(content, &html, &render_options.document_css()?, render_options)?)?;
+        }
+        "pdf" => {
+            write_pdf(content, &target.to_string_lossy(), render_options).map_err(|e| anyhow!(e.to_string()))?;
+        }
+        _ => return Err(anyhow!("Unsupported export format '{}'", format)),
+    }
+    Ok(())
+}
+
+/// Render markdown to a PDF file via wkhtmltopdf (shared by `export_to_pdf` and batch exports)
+fn write_pdf(content: &str, output_path: &str, render_options: &render::RenderOptions) -> PyResult<String> {
     // First, convert markdown to HTML
     // Clean any terminal escape sequences
     let cleaned_content = clean_escape_sequences(content)?;
@@ -408,10 +2132,23 @@ fn export_to_pdf(content: &str, output_path: &str) -> PyResult<String> {
             "Markdown content cannot be empty for PDF conversion"
         ));
     }
+    if let Some(policy_file) = &render_options.policy_file {
+        policy::preflight(&cleaned_content, policy_file)?;
+    }
     
-    // Create a temporary HTML file
-    let temp_dir = std::env::temp_dir();
-    let temp_html_path = temp_dir.join("report_temp.html");
+    // Create a temporary HTML file in a directory unique per call so parallel exports don't collide;
+    // deterministic exports name the file itself after the content so nothing about the run leaks into the PDF
+    static TEMP_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let temp_dir = std::env::temp_dir().join(format!(
+        "report_export_{}_{}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    ));
+    let temp_html_path = if render_options.deterministic {
+        temp_dir.join(format!("report_{}.html", &sha256_hex(cleaned_content.as_bytes())[..16]))
+    } else {
+        temp_dir.join("report.html")
+    };
     
     // Create HTML with proper styling for PDF output
     let mut options = ComrakOptions::default();
@@ -421,98 +2158,150 @@ fn export_to_pdf(content: &str, output_path: &str) -> PyResult<String> {
     options.extension.autolink = true;
     options.extension.tasklist = true;
     options.extension.superscript = true;
+    options.extension.header_ids = Some(toc::ANCHOR_PREFIX.to_string());  // Targets for table of contents links
+    options.extension.footnotes = render_options.footnotes;
+    options.parse.smart = render_options.smart_punctuation;
     options.render.github_pre_lang = true;
-    options.render.unsafe_ = true;  // Allow HTML passthrough
+    options.render.unsafe_ = true;  // Allow HTML passthrough, cleaned afterwards by the `sanitize` mode
     
-    let html_content = comrak::markdown_to_html(&cleaned_content, &options);
+    let mut html_content = sanitize::sanitize_html(
+        &render::titled_endnotes(&comrak::markdown_to_html(&render::preprocess(&cleaned_content, render_options), &options)),
+        render_options.sanitize,
+    );
+    if render_options.wikilinks.is_some() {
+        html_content = wikilinks::wikilinks_as_endnotes(&html_content);
+    }
+    let job = joblog::Job::new(render_options.job_id.as_deref(), render_options.log_dir.as_deref());
+    if render_options.inline_images {
+        html_content = images::inline_remote_images(&html_content);
+    }
+    if render_options.math {
+        html_content = math::with_katex(html_content);
+    }
+    if render_options.mermaid {
+        html_content = mermaid::prerender(&html_content, render_options.sandbox.as_ref(), &job);
+    }
+    let css = render_options.document_css()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read stylesheet: {}", e)))?;
+    let full_html = export_document(&cleaned_content, &html_content, &css, render_options)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+    // Write HTML to temp file
+    fs::create_dir(&temp_dir)
+        .and_then(|_| fs::write(&temp_html_path, full_html))
+        .map_err(|e| {
+            let _ = fs::remove_dir_all(&temp_dir);
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write temporary HTML file: {}", e))
+        })?;
     
-    // Add CSS styling for PDF output
-    let full_html = format!(r#"<!DOCTYPE html>
+    // Convert, then remove the temporary directory whatever the outcome
+    let title = match render_options.deterministic {
+        true => Some(
+            parse_report_metadata(&cleaned_content)
+                .ok()
+                .and_then(|(metadata, _)| metadata.get("title").cloned())
+                .unwrap_or_else(|| "Report".to_string()),
+        ),
+        false => None,
+    };
+    // KaTeX typesets after the page loads, so the converter has to wait for it
+    let javascript_delay = (render_options.math && math::has_math(&html_content)).then_some(math::RENDER_DELAY_MS);
+    let result = run_wkhtmltopdf(
+        &temp_html_path,
+        output_path,
+        title.as_deref(),
+        render_options.sandbox.as_ref(),
+        javascript_delay,
+        &job,
+    );
+    let _ = fs::remove_dir_all(&temp_dir);
+    let output = result?;
+
+    if render_options.deterministic {
+        pin_pdf_dates(Path::new(output_path), render::pinned_timestamp(&cleaned_content))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to pin PDF timestamps: {}", e)))?;
+    }
+    Ok(output)
+}
+
+/// Overwrite the PDF creation/modification dates in place, keeping byte lengths so xref offsets stay valid
+fn pin_pdf_dates(path: &Path, timestamp: NaiveDateTime) -> std::io::Result<()> {
+    let date = regex::bytes::Regex::new(r"/(CreationDate|ModDate)\s*\(D:(\d{14})([+-]\d{2}'\d{2}'?)?").unwrap();
+    let content = fs::read(path)?;
+    let stamp = timestamp.format("%Y%m%d%H%M%S").to_string();
+
+    let mut pinned = content.clone();
+    for caps in date.captures_iter(&content) {
+        let digits = caps.get(2).unwrap();
+        pinned[digits.range()].copy_from_slice(stamp.as_bytes());
+        if let Some(offset) = caps.get(3) {
+            let normalized = format!("+00'00{}", if offset.as_bytes().ends_with(b"'") { "'" } else { "" });
+            pinned[offset.range()].copy_from_slice(normalized.as_bytes());
+        }
+    }
+
+    if pinned != content {
+        write_atomic(path, &pinned)?;
+    }
+    Ok(())
+}
+
+/// Wrap an HTML fragment in a standalone document styled with `css`, the export's theme and stylesheets
+fn html_document(html_content: &str, css: &str) -> String {
+    format!(r#"<!DOCTYPE html>
 <html>
 <head>
     <meta charset="UTF-8">
     <style>
-        body {{
-            font-family: Arial, sans-serif;
-            font-size: 12pt;
-            line-height: 1.5;
-            margin: 2cm;
-        }}
-        h1, h2, h3, h4, h5, h6 {{
-            color: #333;
-            margin-top: 1.5em;
-            margin-bottom: 0.5em;
-        }}
-        h1 {{ font-size: 24pt; }}
-        h2 {{ font-size: 20pt; }}
-        h3 {{ font-size: 16pt; }}
-        table {{
-            width: 100%;
-            border-collapse: collapse;
-            margin: 1em 0;
-        }}
-        th, td {{
-            border: 1px solid #ddd;
-            padding: 8px;
-            text-align: left;
-        }}
-        th {{
-            background-color: #f2f2f2;
-        }}
-        .report-metadata {{
-            margin-bottom: 2em;
-            color: #666;
-            font-style: italic;
-        }}
-        ul, ol {{
-            margin: 0.5em 0;
-            padding-left: 2em;
-        }}
-        code {{
-            font-family: monospace;
-            background-color: #f5f5f5;
-            padding: 2px 4px;
-            border-radius: 3px;
-        }}
-        pre {{
-            background-color: #f5f5f5;
-            padding: 1em;
-            border-radius: 5px;
-            overflow-x: auto;
-        }}
-        blockquote {{
-            background-color: #f9f9f9;
-            border-left: 4px solid #ccc;
-            margin: 1em 0;
-            padding: 0.5em 1em;
-        }}
+        {css}
     </style>
 </head>
 <body>
     {html_content}
 </body>
-</html>"#);
+</html>"#)
+}
 
-    // Write HTML to temp file
-    fs::write(&temp_html_path, full_html)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(
-            format!("Failed to write temporary HTML file: {}", e)
-        ))?;
-    
+/// The standalone HTML document of an export: the caller's layout template when one is set, else `html_document`
+fn export_document(content: &str, html: &str, css: &str, render_options: &render::RenderOptions) -> Result<String> {
+    match &render_options.template {
+        Some(template) => layout::render_layout(template, &render::redacted(content, render_options), html, css),
+        None => Ok(html_document(html, css)),
+    }
+}
+
+/// Convert an HTML file to PDF with wkhtmltopdf
+fn run_wkhtmltopdf(
+    temp_html_path: &Path,
+    output_path: &str,
+    title: Option<&str>,
+    sandbox: Option<&sandbox::Sandbox>,
+    javascript_delay: Option<u32>,
+    job: &joblog::Job,
+) -> PyResult<String> {
     // Check if wkhtmltopdf is installed and available
-    let wkhtmltopdf_check = std::process::Command::new("wkhtmltopdf")
-        .arg("--version")
-        .output();
-    
-    if let Err(_) = wkhtmltopdf_check {
-        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-            "wkhtmltopdf not found. Please install wkhtmltopdf to use PDF export functionality."
-        ));
+    if let Ok(Some(missing)) = capabilities::check("pdf_export") {
+        return Err(missing.to_py());
     }
     
     // Convert HTML to PDF using wkhtmltopdf
-    let output = std::process::Command::new("wkhtmltopdf")
-        .arg("--enable-local-file-access")
+    let mut command = std::process::Command::new("wkhtmltopdf");
+    if let Some(title) = title {
+        command.arg("--title").arg(title);
+    }
+    if let Some(delay) = javascript_delay {
+        command.arg("--javascript-delay").arg(delay.to_string());
+    }
+    match sandbox {
+        Some(sandbox) => {
+            let input_dir = temp_html_path.parent().map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default();
+            command.args(sandbox.wkhtmltopdf_args(&input_dir));
+        }
+        None => {
+            command.arg("--enable-local-file-access");
+        }
+    }
+    command
         .arg("--page-size")
         .arg("A4")
         .arg("--margin-top")
@@ -526,17 +2315,22 @@ fn export_to_pdf(content: &str, output_path: &str) -> PyResult<String> {
         .arg("--encoding")
         .arg("UTF-8")
         .arg(temp_html_path.to_string_lossy().to_string())
-        .arg(output_path)
-        .output()
+        .arg(output_path);
+    let output = joblog::run(&mut command, sandbox, job)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-            format!("Failed to execute wkhtmltopdf: {}", e)
+            format!("Failed to execute wkhtmltopdf (job {}): {}", job.id, e)
         ))?;
     
-    // Check if wkhtmltopdf succeeded
+    // Check if wkhtmltopdf succeeded; its full output stays in the job log
     if !output.status.success() {
-        let error_output = String::from_utf8_lossy(&output.stderr);
+        let error_output = joblog::tail(&String::from_utf8_lossy(&output.stderr), joblog::TAIL_LINES);
         return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-            format!("wkhtmltopdf failed: {}", error_output)
+            format!(
+                "wkhtmltopdf failed (job {}): {}\nFull converter output: {}",
+                job.id,
+                error_output,
+                job.log_path().display()
+            )
         ));
     }
     
@@ -553,30 +2347,34 @@ fn export_to_pdf(content: &str, output_path: &str) -> PyResult<String> {
 /// Open a file with the default system application
 #[pyfunction]
 fn open_file(file_path: &str) -> PyResult<bool> {
-    // Determine which command to use based on platform
-    let command = if cfg!(target_os = "windows") {
-        ("cmd", ["/c", "start", "", file_path].to_vec())
-    } else if cfg!(target_os = "macos") {
-        ("open", [file_path].to_vec())
-    } else {
-        ("xdg-open", [file_path].to_vec())  // Linux/Unix
-    };
+    panics::guard("open_file", || {
+        // Determine which command to use based on platform
+        let command = if cfg!(target_os = "windows") {
+            ("cmd", ["/c", "start", "", file_path].to_vec())
+        } else if cfg!(target_os = "macos") {
+            ("open", [file_path].to_vec())
+        } else {
+            ("xdg-open", [file_path].to_vec())  // Linux/Unix
+        };
     
-    // Execute the command
-    let output = std::process::Command::new(command.0)
-        .args(command.1)
-        .output()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-            format!("Failed to open file: {}", e)
-        ))?;
+        // Execute the command
+        let output = std::process::Command::new(command.0)
+            .args(command.1)
+            .output()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                format!("Failed to open file: {}", e)
+            ))?;
     
-    // Check if command succeeded
-    if !output.status.success() {
-        let error_output = String::from_utf8_lossy(&output.stderr);
-        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-            format!("Failed to open file: {}", error_output)
-        ));
-    }
+        // Check if command succeeded
+        if !output.status.success() {
+            let error_output = String::from_utf8_lossy(&output.stderr);
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                format!("Failed to open file: {}", error_output)
+            ));
+        }
     
-    Ok(true)
+        // Opening a report from a managed directory counts as recent access; never fail the open over it
+        let _ = activity::record_path_access(Path::new(file_path));
+        Ok(true)
+    })
 }
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+#[cfg(feature = "http")]
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::capabilities::feature_missing_py;
+use crate::panics::{guard, internal_error, lock, panic_message};
+use crate::wait_limit;
+
+/// Requests in flight at once when checking links
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Sent with every check; some sites refuse requests without a user agent
+#[cfg(feature = "http")]
+const USER_AGENT: &str = concat!("market_research_core/", env!("CARGO_PKG_VERSION"), " (link checker)");
+
+/// A link written in a report
+#[derive(Clone, Debug)]
+pub struct Link {
+    pub url: String,
+    /// Link text, image alt text, or the reference label of a definition
+    pub text: String,
+    pub image: bool,
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl Link {
+    /// `external` (http/https), `email`, `anchor` (`#section`), `relative` or `other` (another scheme)
+    pub fn kind(&self) -> &'static str {
+        let lower = self.url.to_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            "external"
+        } else if lower.starts_with("mailto:") {
+            "email"
+        } else if lower.starts_with('#') {
+            "anchor"
+        } else if lower.contains(':') {
+            "other"
+        } else {
+            "relative"
+        }
+    }
+}
+
+/// Remove inline code spans so URLs shown as code are not taken for links
+fn without_code_spans(line: &str) -> String {
+    let code = Regex::new(r"(`+)[^`]+?(`+)").unwrap();
+    code.replace_all(line, |caps: &regex::Captures| " ".repeat(caps[0].len())).into_owned()
+}
+
+/// Every link outside fenced code blocks and code spans: inline links and images, `<url>` autolinks, reference
+/// definitions and bare `http(s)://` URLs, in document order
+pub fn find_links(markdown: &str) -> Vec<Link> {
+    let inline = Regex::new(r#"(!?)\[([^\]]*)\]\(\s*<?([^)\s>]+)>?(?:\s+(?:"[^"]*"|'[^']*'))?\s*\)"#).unwrap();
+    let autolink = Regex::new(r"<((?:https?|mailto):[^<>\s]+)>").unwrap();
+    let definition = Regex::new(r"^\s{0,3}\[([^\]]+)\]:\s*<?(\S+?)>?(?:\s|$)").unwrap();
+    let bare = Regex::new(r"https?://[^\s<>()\[\]]+").unwrap();
+
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    for (index, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let line = without_code_spans(line);
+        let mut found: Vec<(usize, usize, Link)> = Vec::new();
+        let link = |url: &str, text: &str, image: bool| Link {
+            url: url.to_string(),
+            text: text.trim().to_string(),
+            image,
+            line: index + 1,
+        };
+        if let Some(caps) = definition.captures(&line) {
+            let span = caps.get(0).unwrap();
+            found.push((span.start(), span.end(), link(&caps[2], &caps[1], false)));
+        }
+        for caps in inline.captures_iter(&line) {
+            let span = caps.get(0).unwrap();
+            found.push((span.start(), span.end(), link(&caps[3], &caps[2], &caps[1] == "!")));
+        }
+        for caps in autolink.captures_iter(&line) {
+            let span = caps.get(0).unwrap();
+            found.push((span.start(), span.end(), link(&caps[1], "", false)));
+        }
+        for url in bare.find_iter(&line) {
+            if found.iter().any(|(start, end, _)| url.start() < *end && url.end() > *start) {
+                continue;
+            }
+            // Sentence punctuation after a URL is not part of it
+            let trimmed = url.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"', '*', '_']);
+            found.push((url.start(), url.start() + trimmed.len(), link(trimmed, "", false)));
+        }
+        found.sort_by_key(|(start, _, _)| *start);
+        links.extend(found.into_iter().map(|(_, _, link)| link));
+    }
+    links
+}
+
+/// Outcome of checking one external URL
+pub struct LinkStatus {
+    pub url: String,
+    /// Final HTTP status, `None` when no response came back
+    pub status: Option<u16>,
+    pub ok: bool,
+    /// Where redirects led, if somewhere else
+    pub final_url: Option<String>,
+    pub error: Option<String>,
+    /// Lines the URL appears on
+    pub lines: Vec<usize>,
+    pub seconds: f64,
+}
+
+/// Request `url` with `method`, returning the status and the URL redirects ended at; 4xx/5xx are responses too
+#[cfg(feature = "http")]
+fn request(agent: &ureq::Agent, method: &str, url: &str) -> Result<(u16, String), String> {
+    match agent.request(method, url).call() {
+        Ok(response) => Ok((response.status(), response.get_url().to_string())),
+        Err(ureq::Error::Status(status, response)) => Ok((status, response.get_url().to_string())),
+        Err(ureq::Error::Transport(e)) => Err(e.to_string()),
+    }
+}
+
+/// HEAD the URL, retrying with GET when that fails, since many servers reject or mishandle HEAD
+#[cfg(feature = "http")]
+fn fetch_status(agent: &ureq::Agent, url: &str) -> Result<(u16, String), String> {
+    match request(agent, "HEAD", url) {
+        Ok((status, final_url)) if status < 400 => Ok((status, final_url)),
+        _ => request(agent, "GET", url),
+    }
+}
+
+#[cfg(feature = "http")]
+fn check_urls(urls: Vec<(String, Vec<usize>)>, timeout: Duration, concurrency: usize) -> Vec<LinkStatus> {
+    let agent = ureq::AgentBuilder::new().timeout(timeout).user_agent(USER_AGENT).build();
+    let check = |(url, lines): (String, Vec<usize>)| {
+        let started = Instant::now();
+        let (status, final_url, error) = match fetch_status(&agent, &url) {
+            // `http://host` comes back as `http://host/` without any redirect
+            Ok((status, final_url)) => {
+                let moved = final_url.trim_end_matches('/') != url.trim_end_matches('/');
+                (Some(status), moved.then_some(final_url), None)
+            }
+            Err(error) => (None, None, Some(error)),
+        };
+        let ok = status.is_some_and(|status| (200..400).contains(&status));
+        LinkStatus { url, status, ok, final_url, error, lines, seconds: started.elapsed().as_secs_f64() }
+    };
+    // Requests mostly wait on the network, so they get their own pool rather than the CPU-sized global one
+    match rayon::ThreadPoolBuilder::new().num_threads(concurrency).build() {
+        Ok(pool) => pool.install(|| urls.into_par_iter().map(check).collect()),
+        Err(_) => urls.into_iter().map(check).collect(),
+    }
+}
+
+/// Results of a `check_links` run, filled in by a background thread
+type Outcome = Arc<(Mutex<Option<Result<Vec<LinkStatus>, String>>>, Condvar)>;
+
+/// A link check running in the background. `await` it from asyncio, or call `result()` to block until done
+#[pyclass]
+pub struct LinkCheck {
+    outcome: Outcome,
+}
+
+impl LinkCheck {
+    fn start(urls: Vec<(String, Vec<usize>)>, timeout: Duration, concurrency: usize) -> Self {
+        let outcome: Outcome = Arc::new((Mutex::new(None), Condvar::new()));
+        let shared = Arc::clone(&outcome);
+        std::thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| run_checks(urls, timeout, concurrency)))
+                .map_err(|payload| panic_message(payload.as_ref()));
+            let (slot, finished) = &*shared;
+            *lock(slot) = Some(result);
+            finished.notify_all();
+        });
+        LinkCheck { outcome }
+    }
+}
+
+#[cfg(feature = "http")]
+fn run_checks(urls: Vec<(String, Vec<usize>)>, timeout: Duration, concurrency: usize) -> Vec<LinkStatus> {
+    check_urls(urls, timeout, concurrency)
+}
+
+#[cfg(not(feature = "http"))]
+fn run_checks(_urls: Vec<(String, Vec<usize>)>, _timeout: Duration, _concurrency: usize) -> Vec<LinkStatus> {
+    Vec::new()
+}
+
+fn statuses_to_py(py: Python, statuses: &[LinkStatus]) -> PyResult<PyObject> {
+    let list = PyList::empty(py);
+    for status in statuses {
+        let dict = PyDict::new(py);
+        dict.set_item("url", &status.url)?;
+        dict.set_item("status", status.status)?;
+        dict.set_item("ok", status.ok)?;
+        dict.set_item("final_url", &status.final_url)?;
+        dict.set_item("error", &status.error)?;
+        dict.set_item("lines", &status.lines)?;
+        dict.set_item("seconds", status.seconds)?;
+        list.append(dict)?;
+    }
+    Ok(list.into())
+}
+
+#[pymethods]
+impl LinkCheck {
+    /// Whether every URL has been checked
+    fn done(&self) -> PyResult<bool> {
+        guard("LinkCheck.done", || {
+            Ok(lock(&self.outcome.0).is_some())
+        })
+    }
+
+    /// Wait up to `timeout` seconds (forever if `None` or infinite) and return `[{url, status, ok, final_url, error, lines,
+    /// seconds}]` in the order the URLs first appear; TimeoutError if the check is still running
+    #[pyo3(signature = (timeout=None))]
+    fn result(&self, timeout: Option<f64>, py: Python) -> PyResult<PyObject> {
+        guard("LinkCheck.result", || {
+            let finished = py.allow_threads(|| {
+                let (slot, finished) = &*self.outcome;
+                let mut outcome = lock(slot);
+                // An infinite or oversized timeout has no deadline, the same as none at all
+                let deadline = wait_limit(timeout).and_then(|limit| Instant::now().checked_add(limit));
+                while outcome.is_none() {
+                    outcome = match deadline {
+                        Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                            Some(left) => finished.wait_timeout(outcome, left).unwrap_or_else(|e| e.into_inner()).0,
+                            None => return false,
+                        },
+                        None => finished.wait(outcome).unwrap_or_else(|e| e.into_inner()),
+                    };
+                }
+                true
+            });
+            if !finished {
+                return Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>("Link check is still running"));
+            }
+            match lock(&self.outcome.0).as_ref() {
+                Some(Ok(statuses)) => statuses_to_py(py, statuses),
+                Some(Err(message)) => Err(internal_error("check_links", message.clone())),
+                None => unreachable!("the outcome was set before waiting ended"),
+            }
+        })
+    }
+
+    /// Wait for the results in the event loop's default executor, so other tasks keep running meanwhile
+    fn __await__(slf: &PyCell<Self>, py: Python) -> PyResult<PyObject> {
+        guard("LinkCheck.__await__", || {
+            let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+            let waiting = event_loop.call_method1("run_in_executor", (py.None(), slf.getattr("result")?))?;
+            Ok(waiting.call_method0("__await__")?.into())
+        })
+    }
+}
+
+/// Every link in a report as `[{url, text, kind, image, line}]`, where `kind` is external, email, anchor, relative
+/// or other; fenced code and code spans are skipped
+#[pyfunction]
+pub fn extract_links(markdown: &str, py: Python) -> PyResult<PyObject> {
+    guard("extract_links", || {
+        let list = PyList::empty(py);
+        for link in find_links(markdown) {
+            let dict = PyDict::new(py);
+            dict.set_item("url", &link.url)?;
+            dict.set_item("text", &link.text)?;
+            dict.set_item("kind", link.kind())?;
+            dict.set_item("image", link.image)?;
+            dict.set_item("line", link.line)?;
+            list.append(dict)?;
+        }
+        Ok(list.into())
+    })
+}
+
+/// Check every external URL in a report, `concurrency` at a time with `timeout` seconds each, in the background.
+/// Returns a `LinkCheck`: `await` it, or call `.result()`, for `[{url, status, ok, final_url, error, lines,
+/// seconds}]`. `ok` means a 2xx or 3xx response after redirects; `error` says why there was no response
+#[pyfunction]
+#[pyo3(signature = (markdown, timeout=10.0, concurrency=DEFAULT_CONCURRENCY))]
+pub fn check_links(markdown: &str, timeout: f64, concurrency: usize) -> PyResult<LinkCheck> {
+    guard("check_links", || {
+        if !cfg!(feature = "http") {
+            return Err(feature_missing_py("http"));
+        }
+        let timeout = match Duration::try_from_secs_f64(timeout) {
+            Ok(duration) if timeout > 0.0 => duration,
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("timeout must be a positive number of seconds")),
+        };
+        let mut urls: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for link in find_links(markdown).into_iter().filter(|link| link.kind() == "external") {
+            match positions.get(&link.url) {
+                Some(&position) => urls[position].1.push(link.line),
+                None => {
+                    positions.insert(link.url.clone(), urls.len());
+                    urls.push((link.url, vec![link.line]));
+                }
+            }
+        }
+        Ok(LinkCheck::start(urls, timeout, concurrency.max(1)))
+    })
+}
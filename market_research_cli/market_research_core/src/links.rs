@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::ids::slugify;
+use crate::list_reports;
+
+/// A link found in a report, before it is resolved against the library
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkTarget {
+    /// `[[other-report]]` or `[[other-report|label]]`
+    Wiki(String),
+    /// `[text](other.md)` with a relative path
+    Relative(String),
+}
+
+/// Forward, backward and unresolved links for one report
+#[derive(Default, Debug)]
+pub struct ReportLinks {
+    pub forward: BTreeSet<String>,
+    pub backward: BTreeSet<String>,
+    pub broken: BTreeSet<String>,
+}
+
+/// Find wikilinks and relative `.md` links outside fenced code blocks
+pub fn extract_links(content: &str) -> Vec<LinkTarget> {
+    let wiki = Regex::new(r"\[\[([^\[\]|#]+)(?:#[^\[\]|]*)?(?:\|[^\[\]]*)?\]\]").unwrap();
+    let markdown = Regex::new(r"\]\(\s*<?([^)\s>]+\.md)(?:#[^)\s>]*)?>?(?:\s+[^)]*)?\)").unwrap();
+
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        for caps in wiki.captures_iter(line) {
+            links.push(LinkTarget::Wiki(caps[1].trim().to_string()));
+        }
+        for caps in markdown.captures_iter(line) {
+            let target = &caps[1];
+            if !target.contains("://") && !target.starts_with('/') {
+                links.push(LinkTarget::Relative(target.to_string()));
+            }
+        }
+    }
+    links
+}
+
+/// Lookup from the names a report can be linked by to its filename
+#[derive(Debug)]
+pub struct LinkResolver {
+    by_filename: HashMap<String, String>,
+    by_slug: HashMap<String, String>,
+}
+
+impl LinkResolver {
+    /// Index reports by filename and by the slug of their filename stem
+    pub fn new(filenames: &[String]) -> Self {
+        let mut by_filename = HashMap::new();
+        let mut by_slug = HashMap::new();
+        for filename in filenames {
+            by_filename.insert(filename.to_lowercase(), filename.clone());
+            let stem = filename.strip_suffix(".md").unwrap_or(filename);
+            by_slug.entry(slugify(stem)).or_insert_with(|| filename.clone());
+        }
+        LinkResolver { by_filename, by_slug }
+    }
+
+    /// Also accept a report's title as a wikilink name; filenames take precedence
+    pub fn add_title(&mut self, title: &str, filename: &str) {
+        self.by_slug.entry(slugify(title)).or_insert_with(|| filename.to_string());
+    }
+
+    /// Resolve a link to a report filename, matching filenames exactly and wikilinks by slug
+    pub fn resolve(&self, target: &LinkTarget) -> Option<String> {
+        match target {
+            LinkTarget::Relative(path) => {
+                let name = Path::new(path).file_name()?.to_str()?;
+                self.by_filename.get(&name.to_lowercase()).cloned()
+            }
+            LinkTarget::Wiki(name) => {
+                let name = name.strip_suffix(".md").unwrap_or(name);
+                self.by_filename
+                    .get(&format!("{}.md", name.to_lowercase()))
+                    .or_else(|| self.by_slug.get(&slugify(name)))
+                    .cloned()
+            }
+        }
+    }
+}
+
+fn target_label(target: &LinkTarget) -> String {
+    match target {
+        LinkTarget::Wiki(name) => format!("[[{}]]", name),
+        LinkTarget::Relative(path) => path.clone(),
+    }
+}
+
+/// Build the forward/backward link graph for every report in the library
+pub fn link_graph(reports_dir: &str) -> Result<BTreeMap<String, ReportLinks>> {
+    let filenames = list_reports(reports_dir)?;
+    let mut resolver = LinkResolver::new(&filenames);
+
+    let scanned: Vec<(String, Option<String>, Vec<LinkTarget>)> = filenames
+        .par_iter()
+        .map(|filename| {
+            let content = fs::read_to_string(Path::new(reports_dir).join(filename)).unwrap_or_default();
+            let title = crate::parse_report_metadata(&content)
+                .ok()
+                .and_then(|(metadata, _)| metadata.get("title").cloned());
+            (filename.clone(), title, extract_links(&content))
+        })
+        .collect();
+    for (filename, title, _) in &scanned {
+        if let Some(title) = title {
+            resolver.add_title(title, filename);
+        }
+    }
+
+    let mut graph: BTreeMap<String, ReportLinks> =
+        filenames.iter().map(|f| (f.clone(), ReportLinks::default())).collect();
+    for (source, _, targets) in scanned {
+        for target in targets {
+            match resolver.resolve(&target) {
+                Some(resolved) if resolved == source => {}
+                Some(resolved) => {
+                    graph.get_mut(&source).unwrap().forward.insert(resolved.clone());
+                    graph.get_mut(&resolved).unwrap().backward.insert(source.clone());
+                }
+                None => {
+                    graph.get_mut(&source).unwrap().broken.insert(target_label(&target));
+                }
+            }
+        }
+    }
+    Ok(graph)
+}
@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::{Captures, Regex};
+
+use crate::frontmatter::split_front_matter;
+use crate::panics::guard;
+use crate::policy::Severity;
+use crate::sections::parse_heading;
+use crate::tables::{parse_delimiter_row, split_row};
+
+/// One problem found in a report, at a 1-based line and column of the original file
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Emphasis delimiters that have been opened in the current paragraph: `*`, `**`, `_`, `__`
+#[derive(Default)]
+struct OpenEmphasis {
+    open: [Option<(usize, usize)>; 4],
+}
+
+const EMPHASIS_DELIMITERS: [&str; 4] = ["*", "**", "_", "__"];
+
+impl OpenEmphasis {
+    fn toggle(&mut self, kind: usize, line: usize, column: usize) {
+        self.open[kind] = match self.open[kind] {
+            Some(_) => None,
+            None => Some((line, column)),
+        };
+    }
+
+    /// Report delimiters still open when a paragraph ends
+    fn close(&mut self, diagnostics: &mut Vec<Diagnostic>) {
+        for (kind, open) in self.open.iter_mut().enumerate() {
+            if let Some((line, column)) = open.take() {
+                diagnostics.push(Diagnostic {
+                    rule: "unbalanced-emphasis",
+                    severity: Severity::Warning,
+                    message: format!("`{}` is never closed in this paragraph", EMPHASIS_DELIMITERS[kind]),
+                    line,
+                    column,
+                });
+            }
+        }
+    }
+}
+
+/// Normalize a link reference label the way CommonMark matches them: case and inner whitespace don't matter
+fn reference_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// The line with code spans blanked out, keeping every other character in its column
+fn mask_code_spans(line: &str) -> String {
+    let mut in_code = false;
+    line.chars()
+        .map(|c| {
+            if c == '`' {
+                in_code = !in_code;
+                ' '
+            } else if in_code {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// The 1-based column of a byte offset
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+pub fn is_thematic_break(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && ['*', '-', '_'].iter().any(|marker| compact.chars().all(|c| c == *marker))
+}
+
+/// Track `*` and `_` runs in one line of a paragraph. Runs with whitespace on both sides (list bullets, `a * b`)
+/// and underscores inside words (`snake_case`) are not emphasis
+fn scan_emphasis(text: &str, number: usize, open: &mut OpenEmphasis) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' {
+            i += 2;
+            continue;
+        }
+        if c != '*' && c != '_' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && chars[i] == c {
+            i += 1;
+        }
+        let before = if start == 0 { ' ' } else { chars[start - 1] };
+        let after = chars.get(i).copied().unwrap_or(' ');
+        if before.is_whitespace() && after.is_whitespace() {
+            continue;
+        }
+        if c == '_' && before.is_alphanumeric() && after.is_alphanumeric() {
+            continue;
+        }
+        let base = if c == '*' { 0 } else { 2 };
+        match i - start {
+            1 => open.toggle(base, number, start + 1),
+            2 => open.toggle(base + 1, number, start + 1),
+            3 => {
+                open.toggle(base, number, start + 1);
+                open.toggle(base + 1, number, start + 1);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Check a report for problems that render badly or not at all: tables whose rows don't match their header,
+/// unclosed emphasis, heading levels that skip a level, bare URLs and references to undefined link or footnote
+/// labels. Front matter and fenced code are skipped; lines are numbered from the top of the file
+pub fn lint(content: &str) -> Vec<Diagnostic> {
+    let body = split_front_matter(content).map_or(content, |(_, _, body)| body);
+    let first_line = content[..content.len() - body.len()].matches('\n').count() + 1;
+    let lines: Vec<&str> = body.lines().collect();
+
+    let definition = Regex::new(r"^ {0,3}\[([^\[\]]+)\]:").unwrap();
+    let full_reference = Regex::new(r"\[([^\[\]]+)\]\[([^\[\]]*)\]").unwrap();
+    let footnote_reference = Regex::new(r"\[\^([^\[\]]+)\]").unwrap();
+    let url = Regex::new(r"https?://[^\s<>]+").unwrap();
+
+    let mut in_fence = false;
+    let mut defined = HashSet::new();
+    for line in &lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some(caps) = definition.captures(line) {
+                defined.insert(reference_label(&caps[1]));
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut emphasis = OpenEmphasis::default();
+    let mut previous_level: Option<usize> = None;
+    let mut table_columns: Option<usize> = None;
+    in_fence = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        let number = first_line + index;
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            emphasis.close(&mut diagnostics);
+            table_columns = None;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if trimmed.is_empty() {
+            emphasis.close(&mut diagnostics);
+            table_columns = None;
+            continue;
+        }
+
+        let masked = mask_code_spans(line);
+
+        // Tables: a delimiter row must match its header, and each body row the header
+        if let Some(columns) = table_columns {
+            if line.contains('|') {
+                let cells = split_row(line).len();
+                if cells != columns {
+                    diagnostics.push(Diagnostic {
+                        rule: "table-columns",
+                        severity: Severity::Warning,
+                        message: format!("Table row has {} cells but the header has {}", cells, columns),
+                        line: number,
+                        column: 1,
+                    });
+                }
+            } else {
+                table_columns = None;
+            }
+        } else if line.contains('|') {
+            if let Some(delimiter) = lines.get(index + 1).filter(|next| next.contains('|')) {
+                if let Some(alignments) = parse_delimiter_row(delimiter) {
+                    let columns = split_row(line).len();
+                    if columns != alignments.len() {
+                        diagnostics.push(Diagnostic {
+                            rule: "table-structure",
+                            severity: Severity::Error,
+                            message: format!(
+                                "Table delimiter row has {} columns but the header has {}, so it is not a table",
+                                alignments.len(),
+                                columns
+                            ),
+                            line: number + 1,
+                            column: 1,
+                        });
+                    } else {
+                        table_columns = Some(columns);
+                    }
+                }
+            }
+        }
+
+        if let Some((level, _)) = parse_heading(line) {
+            emphasis.close(&mut diagnostics);
+            if let Some(previous) = previous_level.filter(|previous| level > previous + 1) {
+                diagnostics.push(Diagnostic {
+                    rule: "heading-increment",
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Heading level {} follows level {}; expected level {} at most",
+                        level,
+                        previous,
+                        previous + 1
+                    ),
+                    line: number,
+                    column: line.len() - trimmed.len() + 1,
+                });
+            }
+            previous_level = Some(level);
+        }
+
+        let is_definition = definition.is_match(line);
+        for found in url.find_iter(&masked) {
+            let before = masked[..found.start()].chars().last();
+            if is_definition || matches!(before, Some('(' | '<' | '"' | '\'' | '=' | '[')) {
+                continue;
+            }
+            diagnostics.push(Diagnostic {
+                rule: "bare-url",
+                severity: Severity::Warning,
+                message: format!("Bare URL {0}; write it as <{0}> or [text]({0})", found.as_str()),
+                line: number,
+                column: column(&masked, found.start()),
+            });
+        }
+
+        if !is_definition {
+            for caps in full_reference.captures_iter(&masked) {
+                let label = match caps[2].trim() {
+                    "" => &caps[1],
+                    label => label,
+                };
+                if !defined.contains(&reference_label(label)) {
+                    diagnostics.push(Diagnostic {
+                        rule: "undefined-reference",
+                        severity: Severity::Error,
+                        message: format!("Link reference [{}] is not defined", label),
+                        line: number,
+                        column: column(&masked, caps.get(0).unwrap().start()),
+                    });
+                }
+            }
+        }
+        for caps in footnote_reference.captures_iter(&masked) {
+            let found = caps.get(0).unwrap();
+            if masked[found.end()..].starts_with(':') && found.start() == masked.len() - masked.trim_start().len() {
+                continue;
+            }
+            if !defined.contains(&reference_label(&format!("^{}", &caps[1]))) {
+                diagnostics.push(Diagnostic {
+                    rule: "undefined-reference",
+                    severity: Severity::Error,
+                    message: format!("Footnote [^{}] is not defined", &caps[1]),
+                    line: number,
+                    column: column(&masked, found.start()),
+                });
+            }
+        }
+
+        // Emphasis is scoped to a paragraph; headings and table rows are paragraphs of their own
+        if is_thematic_break(line) || is_definition {
+            emphasis.close(&mut diagnostics);
+            continue;
+        }
+        let single_line = table_columns.is_some() || parse_heading(line).is_some();
+        if single_line {
+            emphasis.close(&mut diagnostics);
+        }
+        let without_urls = url.replace_all(&masked, |caps: &Captures| " ".repeat(caps[0].chars().count()));
+        scan_emphasis(&without_urls, number, &mut emphasis);
+        if single_line {
+            emphasis.close(&mut diagnostics);
+        }
+    }
+    emphasis.close(&mut diagnostics);
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+/// Lint a report as `[{rule, severity, message, line, column}]`, empty when it is clean. Rules are
+/// `table-structure` and `undefined-reference` (errors: the markup will not render as intended) and
+/// `table-columns`, `unbalanced-emphasis`, `heading-increment` and `bare-url` (warnings)
+#[pyfunction]
+pub fn lint_markdown(content: &str, py: Python) -> PyResult<PyObject> {
+    guard("lint_markdown", || {
+        let list = PyList::empty(py);
+        for diagnostic in lint(content) {
+            let entry = PyDict::new(py);
+            entry.set_item("rule", diagnostic.rule)?;
+            entry.set_item("severity", diagnostic.severity.as_str())?;
+            entry.set_item("message", &diagnostic.message)?;
+            entry.set_item("line", diagnostic.line)?;
+            entry.set_item("column", diagnostic.column)?;
+            list.append(entry)?;
+        }
+        Ok(list.into())
+    })
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::fs;
+
+use pyo3::prelude::*;
+
+use crate::charts::{compact_number, escape_xml};
+use crate::panics::guard;
+
+const TILE: f64 = 34.0;
+const GAP: f64 = 3.0;
+const MAP_MARGIN: f64 = 20.0;
+const LEGEND_HEIGHT: f64 = 50.0;
+
+/// Sequential shades from lowest to highest value bucket
+const SHADES: [&str; 5] = ["#deebf7", "#9ecae1", "#6baed6", "#3182bd", "#08519c"];
+const NO_DATA: &str = "#eeeeee";
+
+/// A country tile: ISO alpha-2, ISO alpha-3, name, region, grid column, grid row
+type CountryTile = (&'static str, &'static str, &'static str, &'static str, u32, u32);
+
+/// Tile-grid world layout; every country is the same size so small markets stay visible
+const COUNTRIES: &[CountryTile] = &[
+    // North America
+    ("CA", "CAN", "Canada", "North America", 2, 1),
+    ("US", "USA", "United States", "North America", 2, 2),
+    ("MX", "MEX", "Mexico", "Latin America", 2, 3),
+    // Latin America
+    ("GT", "GTM", "Guatemala", "Latin America", 2, 4),
+    ("CU", "CUB", "Cuba", "Latin America", 3, 3),
+    ("CR", "CRI", "Costa Rica", "Latin America", 3, 4),
+    ("PA", "PAN", "Panama", "Latin America", 3, 5),
+    ("CO", "COL", "Colombia", "Latin America", 4, 5),
+    ("VE", "VEN", "Venezuela", "Latin America", 5, 5),
+    ("EC", "ECU", "Ecuador", "Latin America", 3, 6),
+    ("PE", "PER", "Peru", "Latin America", 4, 6),
+    ("BR", "BRA", "Brazil", "Latin America", 5, 6),
+    ("BO", "BOL", "Bolivia", "Latin America", 4, 7),
+    ("PY", "PRY", "Paraguay", "Latin America", 5, 7),
+    ("CL", "CHL", "Chile", "Latin America", 4, 8),
+    ("AR", "ARG", "Argentina", "Latin America", 5, 8),
+    ("UY", "URY", "Uruguay", "Latin America", 6, 8),
+    // Europe
+    ("IS", "ISL", "Iceland", "Europe", 8, 0),
+    ("NO", "NOR", "Norway", "Europe", 10, 0),
+    ("SE", "SWE", "Sweden", "Europe", 11, 0),
+    ("FI", "FIN", "Finland", "Europe", 12, 0),
+    ("IE", "IRL", "Ireland", "Europe", 8, 1),
+    ("GB", "GBR", "United Kingdom", "Europe", 9, 1),
+    ("DK", "DNK", "Denmark", "Europe", 10, 1),
+    ("EE", "EST", "Estonia", "Europe", 12, 1),
+    ("NL", "NLD", "Netherlands", "Europe", 10, 2),
+    ("DE", "DEU", "Germany", "Europe", 11, 2),
+    ("PL", "POL", "Poland", "Europe", 12, 2),
+    ("UA", "UKR", "Ukraine", "Europe", 13, 2),
+    ("FR", "FRA", "France", "Europe", 9, 3),
+    ("BE", "BEL", "Belgium", "Europe", 10, 3),
+    ("CZ", "CZE", "Czechia", "Europe", 11, 3),
+    ("AT", "AUT", "Austria", "Europe", 12, 3),
+    ("RO", "ROU", "Romania", "Europe", 13, 3),
+    ("PT", "PRT", "Portugal", "Europe", 8, 4),
+    ("ES", "ESP", "Spain", "Europe", 9, 4),
+    ("CH", "CHE", "Switzerland", "Europe", 10, 4),
+    ("IT", "ITA", "Italy", "Europe", 11, 4),
+    ("HU", "HUN", "Hungary", "Europe", 12, 4),
+    ("GR", "GRC", "Greece", "Europe", 13, 4),
+    ("RU", "RUS", "Russia", "Europe", 15, 1),
+    // Middle East & Africa
+    ("TR", "TUR", "Turkey", "Middle East & Africa", 14, 4),
+    ("MA", "MAR", "Morocco", "Middle East & Africa", 9, 5),
+    ("DZ", "DZA", "Algeria", "Middle East & Africa", 10, 5),
+    ("TN", "TUN", "Tunisia", "Middle East & Africa", 11, 5),
+    ("EG", "EGY", "Egypt", "Middle East & Africa", 13, 5),
+    ("IL", "ISR", "Israel", "Middle East & Africa", 14, 5),
+    ("IR", "IRN", "Iran", "Middle East & Africa", 16, 4),
+    ("SA", "SAU", "Saudi Arabia", "Middle East & Africa", 15, 5),
+    ("AE", "ARE", "United Arab Emirates", "Middle East & Africa", 16, 5),
+    ("QA", "QAT", "Qatar", "Middle East & Africa", 15, 6),
+    ("NG", "NGA", "Nigeria", "Middle East & Africa", 10, 6),
+    ("GH", "GHA", "Ghana", "Middle East & Africa", 9, 6),
+    ("ET", "ETH", "Ethiopia", "Middle East & Africa", 13, 6),
+    ("KE", "KEN", "Kenya", "Middle East & Africa", 13, 7),
+    ("CD", "COD", "DR Congo", "Middle East & Africa", 11, 7),
+    ("TZ", "TZA", "Tanzania", "Middle East & Africa", 12, 7),
+    ("AO", "AGO", "Angola", "Middle East & Africa", 11, 8),
+    ("ZA", "ZAF", "South Africa", "Middle East & Africa", 12, 9),
+    // Asia Pacific
+    ("KZ", "KAZ", "Kazakhstan", "Asia Pacific", 16, 2),
+    ("MN", "MNG", "Mongolia", "Asia Pacific", 18, 2),
+    ("PK", "PAK", "Pakistan", "Asia Pacific", 17, 4),
+    ("CN", "CHN", "China", "Asia Pacific", 18, 3),
+    ("KR", "KOR", "South Korea", "Asia Pacific", 20, 3),
+    ("JP", "JPN", "Japan", "Asia Pacific", 21, 3),
+    ("IN", "IND", "India", "Asia Pacific", 17, 5),
+    ("BD", "BGD", "Bangladesh", "Asia Pacific", 18, 5),
+    ("TW", "TWN", "Taiwan", "Asia Pacific", 20, 4),
+    ("HK", "HKG", "Hong Kong", "Asia Pacific", 19, 4),
+    ("TH", "THA", "Thailand", "Asia Pacific", 18, 6),
+    ("VN", "VNM", "Vietnam", "Asia Pacific", 19, 6),
+    ("PH", "PHL", "Philippines", "Asia Pacific", 20, 6),
+    ("MY", "MYS", "Malaysia", "Asia Pacific", 18, 7),
+    ("SG", "SGP", "Singapore", "Asia Pacific", 19, 7),
+    ("ID", "IDN", "Indonesia", "Asia Pacific", 20, 7),
+    ("AU", "AUS", "Australia", "Asia Pacific", 20, 9),
+    ("NZ", "NZL", "New Zealand", "Asia Pacific", 22, 10),
+];
+
+/// Aliases for region keys commonly used in market sizing tables
+fn region_for_key(key: &str) -> Option<&'static str> {
+    match key.trim().to_lowercase().as_str() {
+        "north america" | "na" | "namer" => Some("North America"),
+        "latin america" | "latam" | "south america" => Some("Latin America"),
+        "europe" | "eu" | "emea europe" => Some("Europe"),
+        "middle east & africa" | "middle east and africa" | "mea" => Some("Middle East & Africa"),
+        "asia pacific" | "asia-pacific" | "apac" | "asia" => Some("Asia Pacific"),
+        _ => None,
+    }
+}
+
+/// Find the tile for an ISO alpha-2/alpha-3 code or a country name
+fn country_for_key(key: &str) -> Option<&'static CountryTile> {
+    let key = key.trim();
+    COUNTRIES.iter().find(|(iso2, iso3, name, _, _, _)| {
+        iso2.eq_ignore_ascii_case(key) || iso3.eq_ignore_ascii_case(key) || name.eq_ignore_ascii_case(key)
+    })
+}
+
+/// Resolve input values to one value per tile; country values win over region values
+fn tile_values(country_values: &HashMap<String, f64>) -> Result<HashMap<&'static str, f64>, String> {
+    let mut by_region: HashMap<&'static str, f64> = HashMap::new();
+    let mut by_country: HashMap<&'static str, f64> = HashMap::new();
+    let mut unknown = Vec::new();
+
+    for (key, value) in country_values {
+        if let Some((iso2, ..)) = country_for_key(key) {
+            by_country.insert(iso2, *value);
+        } else if let Some(region) = region_for_key(key) {
+            by_region.insert(region, *value);
+        } else {
+            unknown.push(key.clone());
+        }
+    }
+
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(format!("Unknown countries or regions: {}", unknown.join(", ")));
+    }
+
+    let mut values = HashMap::new();
+    for (iso2, _, _, region, _, _) in COUNTRIES {
+        if let Some(value) = by_country.get(iso2).or_else(|| by_region.get(region)) {
+            values.insert(*iso2, *value);
+        }
+    }
+    Ok(values)
+}
+
+/// Map a value to a shade using equal-width buckets between min and max
+fn shade(value: f64, min: f64, max: f64) -> &'static str {
+    if max <= min {
+        return SHADES[SHADES.len() - 1];
+    }
+    let position = (value - min) / (max - min);
+    let bucket = ((position * SHADES.len() as f64) as usize).min(SHADES.len() - 1);
+    SHADES[bucket]
+}
+
+/// Render a tile-grid choropleth SVG for country or region values
+pub fn choropleth_svg(country_values: &HashMap<String, f64>, title: &str) -> Result<String, String> {
+    let values = tile_values(country_values)?;
+    if values.is_empty() {
+        return Err("No values to plot".to_string());
+    }
+
+    let min = values.values().copied().fold(f64::INFINITY, f64::min);
+    let max = values.values().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let columns = COUNTRIES.iter().map(|c| c.4).max().unwrap_or(0) + 1;
+    let rows = COUNTRIES.iter().map(|c| c.5).max().unwrap_or(0) + 1;
+    let width = MAP_MARGIN * 2.0 + columns as f64 * (TILE + GAP);
+    let top = if title.is_empty() { MAP_MARGIN } else { MAP_MARGIN + 24.0 };
+    let height = top + rows as f64 * (TILE + GAP) + LEGEND_HEIGHT;
+
+    let mut out = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="Arial, sans-serif" font-size="10">"#,
+        w = width,
+        h = height
+    );
+    out.push_str(r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+    if !title.is_empty() {
+        out.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="14" font-weight="bold" text-anchor="middle" fill="#333">{}</text>"##,
+            width / 2.0,
+            MAP_MARGIN + 8.0,
+            escape_xml(title)
+        ));
+    }
+
+    for (iso2, _, name, _, col, row) in COUNTRIES {
+        let x = MAP_MARGIN + *col as f64 * (TILE + GAP);
+        let y = top + *row as f64 * (TILE + GAP);
+        let (fill, tooltip) = match values.get(iso2) {
+            Some(value) => (shade(*value, min, max), format!("{}: {}", name, compact_number(*value))),
+            None => (NO_DATA, format!("{}: no data", name)),
+        };
+        let text_color = if fill == SHADES[3] || fill == SHADES[4] { "#ffffff" } else { "#333333" };
+        out.push_str(&format!(
+            r#"<g><title>{}</title><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="3" fill="{}"/><text x="{:.1}" y="{:.1}" text-anchor="middle" fill="{}">{}</text></g>"#,
+            escape_xml(&tooltip),
+            x,
+            y,
+            TILE,
+            TILE,
+            fill,
+            x + TILE / 2.0,
+            y + TILE / 2.0 + 3.5,
+            text_color,
+            iso2
+        ));
+    }
+
+    // Legend with the value range covered by each shade
+    let legend_y = top + rows as f64 * (TILE + GAP) + 14.0;
+    let step = (max - min) / SHADES.len() as f64;
+    for (idx, color) in SHADES.iter().enumerate() {
+        let x = MAP_MARGIN + idx as f64 * 90.0;
+        let low = min + step * idx as f64;
+        out.push_str(&format!(
+            r##"<rect x="{:.1}" y="{:.1}" width="14" height="14" fill="{}"/><text x="{:.1}" y="{:.1}" fill="#333">{}+</text>"##,
+            x,
+            legend_y,
+            color,
+            x + 18.0,
+            legend_y + 11.0,
+            compact_number(low)
+        ));
+    }
+    out.push_str(&format!(
+        r##"<rect x="{:.1}" y="{:.1}" width="14" height="14" fill="{}"/><text x="{:.1}" y="{:.1}" fill="#333">No data</text>"##,
+        MAP_MARGIN + SHADES.len() as f64 * 90.0,
+        legend_y,
+        NO_DATA,
+        MAP_MARGIN + SHADES.len() as f64 * 90.0 + 18.0,
+        legend_y + 11.0
+    ));
+
+    out.push_str("</svg>");
+    Ok(out)
+}
+
+/// Render a world tile map shaded by value per country (or region) as SVG
+#[pyfunction]
+#[pyo3(signature = (country_values, output=None, title=""))]
+pub fn render_choropleth(country_values: HashMap<String, f64>, output: Option<&str>, title: &str) -> PyResult<String> {
+    guard("render_choropleth", || {
+        let svg = choropleth_svg(&country_values, title)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+
+        if let Some(output) = output {
+            fs::write(output, &svg).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write map: {}", e))
+            })?;
+        }
+
+        Ok(svg)
+    })
+}
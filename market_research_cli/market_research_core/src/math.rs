@@ -0,0 +1,196 @@
+/// KaTeX release the rendered HTML loads
+const KATEX_URL: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.9/dist";
+
+/// Class on every element holding TeX, which the KaTeX loader looks for
+const MATH_CLASS: &str = "math";
+
+/// How long wkhtmltopdf waits for KaTeX to load and typeset before printing
+pub const RENDER_DELAY_MS: u32 = 2000;
+
+/// TeX as element content that neither comrak nor the browser will reinterpret: ASCII punctuation becomes numeric
+/// character references, so `_`, `*` and `\` are not read as markdown and the element's text is the TeX again
+fn encode_tex(tex: &str) -> String {
+    tex.chars()
+        .map(|c| match c.is_ascii_punctuation() {
+            true => format!("&#{};", c as u32),
+            false => c.to_string(),
+        })
+        .collect()
+}
+
+fn inline_math(tex: &str) -> String {
+    format!("<span class=\"{} math-inline\">{}</span>", MATH_CLASS, encode_tex(tex))
+}
+
+fn display_math(tex: &str, block: bool) -> String {
+    let tag = if block { "div" } else { "span" };
+    format!("<{} class=\"{} math-display\">{}</{}>", tag, MATH_CLASS, encode_tex(tex.trim()), tag)
+}
+
+/// Position just past the closing `$` of inline math opened at `open`. As in pandoc, the opening `$` is followed by
+/// a non-space and the closing one preceded by a non-space and not followed by a digit; stricter than pandoc, the
+/// opening `$` may not be followed by a digit either, since reports are full of amounts like `$5M`
+fn inline_close(chars: &[char], open: usize) -> Option<usize> {
+    let first = *chars.get(open + 1)?;
+    if first.is_whitespace() || first == '$' || first.is_ascii_digit() {
+        return None;
+    }
+    let mut i = open + 1;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied().unwrap_or(' ');
+        match chars[i] {
+            '\\' => i += 1,
+            '$' if !chars[i - 1].is_whitespace() && chars[i - 1] != '$' && !next.is_ascii_digit() && next != '$' => {
+                return Some(i + 1)
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Replace `$...$` and same-line `$$...$$` in one line of prose, leaving code spans and `\$` untouched
+fn replace_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                out.extend(&chars[i..(i + 2).min(chars.len())]);
+                i += 2;
+            }
+            '`' => {
+                // A code span runs to the next run of as many backticks; an unmatched run is literal
+                let run = chars[i..].iter().take_while(|c| **c == '`').count();
+                let close = (i + run..chars.len()).find(|&j| {
+                    chars[j..].iter().take_while(|c| **c == '`').count() == run && chars[j - 1] != '`'
+                });
+                let end = close.map_or(i + run, |j| j + run);
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '$' if chars.get(i + 1) == Some(&'$') => {
+                let close = (i + 2..chars.len().saturating_sub(1)).find(|&j| chars[j] == '$' && chars[j + 1] == '$');
+                match close {
+                    Some(j) if j > i + 2 => {
+                        out.push_str(&display_math(&chars[i + 2..j].iter().collect::<String>(), false));
+                        i = j + 2;
+                    }
+                    _ => {
+                        out.push_str("$$");
+                        i += 2;
+                    }
+                }
+            }
+            '$' => match inline_close(&chars, i) {
+                Some(end) => {
+                    out.push_str(&inline_math(&chars[i + 1..end - 1].iter().collect::<String>()));
+                    i = end;
+                }
+                None => {
+                    out.push('$');
+                    i += 1;
+                }
+            },
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Mark up `$...$` (inline) and `$$...$$` (display, on its own lines or inline) math outside fenced code blocks
+/// for KaTeX. Display blocks become their own HTML block, so blank lines are added around them
+pub fn mark_math(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut in_fence = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        let fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if fence {
+            in_fence = !in_fence;
+        }
+        if fence || in_fence {
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        }
+
+        // A display block opens a line and closes at the first line ending in `$$`
+        let opened = trimmed.strip_prefix("$$").filter(|rest| !rest.contains("$$"));
+        let close = opened.and_then(|_| (i + 1..lines.len()).find(|&j| lines[j].trim_end().ends_with("$$")));
+        match (opened, close) {
+            (Some(first), Some(end)) => {
+                let mut tex: Vec<&str> = vec![first];
+                tex.extend(lines[i + 1..end].iter());
+                tex.push(lines[end].trim_end().strip_suffix("$$").unwrap_or_default());
+                if out.last().is_some_and(|line| !line.trim().is_empty()) {
+                    out.push(String::new());
+                }
+                out.push(display_math(&tex.join(" "), true));
+                out.push(String::new());
+                i = end + 1;
+            }
+            _ => {
+                // `$$...$$` alone on a line is a display block too
+                let single = trimmed.strip_prefix("$$").and_then(|rest| rest.strip_suffix("$$"));
+                out.push(match single.filter(|tex| !tex.trim().is_empty() && !tex.contains("$$")) {
+                    Some(tex) => format!("\n{}\n", display_math(tex, true)),
+                    None => replace_inline(line),
+                });
+                i += 1;
+            }
+        }
+    }
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Whether rendered HTML has math marked up by `mark_math`
+pub fn has_math(html: &str) -> bool {
+    html.contains(&format!("class=\"{} ", MATH_CLASS))
+}
+
+/// Append the KaTeX stylesheet and scripts to rendered HTML that has math in it. If KaTeX cannot load (offline,
+/// scripts disabled), the TeX source stays visible
+pub fn with_katex(html: String) -> String {
+    if !has_math(&html) {
+        return html;
+    }
+    // Plain loops and className, since wkhtmltopdf's WebKit predates NodeList.forEach and classList
+    format!(
+        r#"{html}<link rel="stylesheet" href="{url}/katex.min.css">
+<script src="{url}/katex.min.js"></script>
+<script>
+(function () {{
+  if (typeof katex === "undefined") return;
+  var elements = document.querySelectorAll(".{class}");
+  for (var i = 0; i < elements.length; i++) {{
+    var element = elements[i];
+    try {{
+      katex.render(element.textContent, element, {{
+        displayMode: element.className.indexOf("math-display") !== -1,
+        throwOnError: false
+      }});
+    }} catch (e) {{}}
+  }}
+}})();
+</script>
+"#,
+        html = html,
+        url = KATEX_URL,
+        class = MATH_CLASS
+    )
+}
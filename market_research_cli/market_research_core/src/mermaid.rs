@@ -0,0 +1,159 @@
+use std::fs;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex};
+
+use crate::capabilities;
+use crate::charts::escape_xml;
+use crate::joblog::{self, Job};
+use crate::sandbox::Sandbox;
+
+/// Mermaid release the rendered HTML loads
+const MERMAID_URL: &str = "https://cdn.jsdelivr.net/npm/mermaid@10.9.1/dist/mermaid.min.js";
+
+/// Class of the element holding a diagram's source, which the Mermaid loader looks for
+const MERMAID_CLASS: &str = "mermaid";
+
+/// Plain SVG text labels instead of HTML in `<foreignObject>`, which wkhtmltopdf's WebKit draws badly
+const MMDC_CONFIG: &str = r#"{"htmlLabels": false, "flowchart": {"htmlLabels": false}}"#;
+
+/// Replace ```` ```mermaid ```` fenced blocks with `<pre class="mermaid">` holding the escaped source, which the
+/// Mermaid loader draws in HTML output and `prerender` turns into SVG for PDF
+pub fn mark_mermaid(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim_start();
+        let fence = if trimmed.starts_with("```") {
+            "```"
+        } else if trimmed.starts_with("~~~") {
+            "~~~"
+        } else {
+            out.push(lines[i].to_string());
+            i += 1;
+            continue;
+        };
+
+        let language = trimmed.trim_start_matches(fence).split_whitespace().next().unwrap_or_default().to_lowercase();
+        let close = (i + 1..lines.len()).find(|&j| lines[j].trim_start().starts_with(fence));
+        let end = close.unwrap_or(lines.len());
+        match language == "mermaid" && end > i + 1 {
+            // Kept as a bare `<pre>` block: markdown treats everything up to `</pre>` as HTML, blank lines included
+            true => {
+                out.push(format!("<pre class=\"{}\">{}</pre>", MERMAID_CLASS, escape_xml(&lines[i + 1..end].join("\n"))));
+                out.push(String::new());
+            }
+            false => out.extend(lines[i..(end + 1).min(lines.len())].iter().map(|l| l.to_string())),
+        }
+        i = end + 1;
+    }
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+fn diagram_pattern() -> Regex {
+    Regex::new(&format!(r#"(?s)<pre class="{}">(.*?)</pre>"#, MERMAID_CLASS)).unwrap()
+}
+
+/// Whether rendered HTML has diagrams marked up by `mark_mermaid`
+pub fn has_mermaid(html: &str) -> bool {
+    html.contains(&format!("<pre class=\"{}\">", MERMAID_CLASS))
+}
+
+/// Append the Mermaid loader to rendered HTML that has diagrams in it. If Mermaid cannot load (offline, scripts
+/// disabled), the diagram source stays visible
+pub fn with_mermaid(html: String) -> String {
+    if !has_mermaid(&html) {
+        return html;
+    }
+    format!(
+        r#"{html}<script src="{url}"></script>
+<script>
+if (typeof mermaid !== "undefined") mermaid.initialize({{ startOnLoad: true, securityLevel: "strict" }});
+</script>
+"#,
+        html = html,
+        url = MERMAID_URL
+    )
+}
+
+/// Source of a marked-up diagram, as it was in the fenced block
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+/// Render one diagram to SVG with mermaid-cli, giving it its own id so the styles of several diagrams on a page
+/// do not apply to each other
+fn render_svg(source: &str, svg_id: &str, sandbox: Option<&Sandbox>, job: &Job) -> Result<String> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join("market_research_core");
+    fs::create_dir_all(&dir)?;
+    let stem = format!("mermaid_{}_{}", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+    let input = dir.join(format!("{}.mmd", stem));
+    let output = dir.join(format!("{}.svg", stem));
+    let config = dir.join(format!("{}.json", stem));
+    fs::write(&input, source)?;
+    fs::write(&config, MMDC_CONFIG)?;
+
+    let mut command = Command::new("mmdc");
+    command
+        .arg("--quiet")
+        .arg("--input")
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .arg("--configFile")
+        .arg(&config)
+        .arg("--backgroundColor")
+        .arg("transparent")
+        .arg("--svgId")
+        .arg(svg_id);
+    let result = joblog::run(&mut command, sandbox, job);
+    let svg = fs::read_to_string(&output);
+    for path in [&input, &output, &config] {
+        let _ = fs::remove_file(path);
+    }
+
+    let run = result.map_err(|e| anyhow!("Failed to run mmdc: {}", e))?;
+    if !run.status.success() {
+        return Err(anyhow!("mmdc failed: {}", joblog::tail(&String::from_utf8_lossy(&run.stderr), joblog::TAIL_LINES)));
+    }
+    let svg = svg?;
+    // Inline SVG needs no XML declaration
+    Ok(match svg.find("<svg") {
+        Some(start) => svg[start..].to_string(),
+        None => svg,
+    })
+}
+
+/// Replace diagrams marked up by `mark_mermaid` with SVG pre-rendered by mermaid-cli (`mmdc`), for PDF export,
+/// whose converter cannot run Mermaid itself. Without mmdc, or when a diagram fails to render, its source is
+/// shown as a code block instead; failures are in the job log
+pub fn prerender(html: &str, sandbox: Option<&Sandbox>, job: &Job) -> String {
+    if !has_mermaid(html) {
+        return html.to_string();
+    }
+    let installed = matches!(capabilities::check("mermaid_pdf"), Ok(None));
+    let mut index = 0;
+    diagram_pattern()
+        .replace_all(html, |caps: &Captures| {
+            index += 1;
+            let rendered = match installed {
+                true => render_svg(&unescape(&caps[1]), &format!("mermaid-{}", index), sandbox, job).ok(),
+                false => None,
+            };
+            match rendered {
+                Some(svg) => format!("<div class=\"diagram diagram-mermaid\">{}</div>", svg),
+                None => format!("<pre class=\"{}-source\"><code>{}</code></pre>", MERMAID_CLASS, &caps[1]),
+            }
+        })
+        .into_owned()
+}
@@ -0,0 +1,255 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::{Captures, Regex};
+
+use crate::charts::{render_chart, ChartType};
+use crate::panics::guard;
+use crate::render::map_outside_fences;
+use crate::tables::{format_table, group_thousands, parse_number, render_table, Alignment, MarkdownTable, NumberFormatRules};
+
+/// Compound annual growth rate between two values over `periods` years, as a fraction
+pub fn cagr(start: f64, end: f64, periods: f64) -> Option<f64> {
+    if start <= 0.0 || end < 0.0 || periods <= 0.0 {
+        return None;
+    }
+    Some((end / start).powf(1.0 / periods) - 1.0)
+}
+
+/// Simple growth from one value to the next, as a fraction
+pub fn growth(previous: f64, current: f64) -> Option<f64> {
+    if previous == 0.0 {
+        return None;
+    }
+    Some((current - previous) / previous.abs())
+}
+
+/// Period-over-period growth for each consecutive pair in a series
+pub fn yoy_growth(values: &[f64]) -> Vec<Option<f64>> {
+    values.windows(2).map(|pair| growth(pair[0], pair[1])).collect()
+}
+
+/// Each participant's share of the total, as fractions
+pub fn market_shares(values: &[f64]) -> Option<Vec<f64>> {
+    if values.iter().any(|v| *v < 0.0) {
+        return None;
+    }
+    let total: f64 = values.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    Some(values.iter().map(|v| v / total).collect())
+}
+
+/// Herfindahl-Hirschman index on the 0-10,000 scale from raw participant values
+pub fn hhi(values: &[f64]) -> Option<f64> {
+    let shares = market_shares(values)?;
+    Some(shares.iter().map(|s| (s * 100.0).powi(2)).sum())
+}
+
+/// Concentration band for an HHI value, using the US merger guideline thresholds
+pub fn concentration_level(hhi: f64) -> &'static str {
+    if hhi < 1500.0 {
+        "unconcentrated"
+    } else if hhi <= 2500.0 {
+        "moderately concentrated"
+    } else {
+        "highly concentrated"
+    }
+}
+
+fn format_percent(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => format!("{:.1}%", value * 100.0),
+        _ => "n/a".to_string(),
+    }
+}
+
+/// Evaluate one template expression such as `cagr(120, 310, 5)`
+fn evaluate(function: &str, args: &[f64]) -> Option<String> {
+    let formatted = match (function, args) {
+        ("cagr", [start, end, periods]) => format_percent(cagr(*start, *end, *periods)),
+        ("growth", [previous, current]) => format_percent(growth(*previous, *current)),
+        ("share", [part, total]) => format_percent(if *total > 0.0 { Some(part / total) } else { None }),
+        ("hhi", values) if !values.is_empty() => match hhi(values) {
+            Some(value) => group_thousands(&format!("{:.0}", value)),
+            None => "n/a".to_string(),
+        },
+        _ => return None,
+    };
+    Some(formatted)
+}
+
+/// Split expression arguments on `;` or a comma followed by whitespace, so "1,200" stays one number
+fn split_args(args: &str) -> Vec<&str> {
+    let separator = Regex::new(r";|,\s+").unwrap();
+    separator.split(args.trim()).map(str::trim).collect()
+}
+
+/// Replace `{{ cagr(...) }}`, `{{ growth(...) }}`, `{{ share(...) }}` and `{{ hhi(...) }}` outside code blocks
+pub fn expand_expressions(markdown: &str) -> String {
+    let expression = Regex::new(r"\{\{\s*(cagr|growth|share|hhi)\s*\(([^)]*)\)\s*\}\}").unwrap();
+
+    map_outside_fences(markdown, |line| {
+        expression
+            .replace_all(line, |caps: &Captures| {
+                let args: Option<Vec<f64>> = split_args(&caps[2])
+                    .into_iter()
+                    .map(|arg| parse_number(arg).map(|n| n.value))
+                    .collect();
+                args.and_then(|args| evaluate(&caps[1], &args))
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    })
+}
+
+/// Compute growth and concentration metrics for a list of values or a `{label: value}` dict
+#[pyfunction]
+#[pyo3(signature = (values, periods=None))]
+pub fn compute_metrics(values: &PyAny, periods: Option<f64>, py: Python) -> PyResult<PyObject> {
+    guard("compute_metrics", || {
+        // 1. Accept either an ordered series or labelled participants
+        let (labels, series): (Option<Vec<String>>, Vec<f64>) = if let Ok(dict) = values.downcast::<PyDict>() {
+            let mut labels = Vec::with_capacity(dict.len());
+            let mut series = Vec::with_capacity(dict.len());
+            for (key, value) in dict.iter() {
+                labels.push(key.str()?.to_string());
+                series.push(value.extract()?);
+            }
+            (Some(labels), series)
+        } else {
+            (None, values.extract()?)
+        };
+
+        if series.iter().any(|v| !v.is_finite()) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Values must be finite numbers"));
+        }
+
+        // 2. Growth metrics treat the values as a time series, first to last
+        let (first, last) = (series.first().copied(), series.last().copied());
+        let periods = periods.unwrap_or(series.len().saturating_sub(1) as f64);
+        let series_cagr = match (first, last) {
+            (Some(first), Some(last)) => cagr(first, last, periods),
+            _ => None,
+        };
+        let total_growth = match (first, last) {
+            (Some(first), Some(last)) if series.len() > 1 => growth(first, last),
+            _ => None,
+        };
+
+        // 3. Concentration metrics treat the values as market participants
+        let shares = market_shares(&series);
+        let index = hhi(&series);
+
+        let result = PyDict::new(py);
+        result.set_item("count", series.len())?;
+        result.set_item("total", series.iter().sum::<f64>())?;
+        result.set_item("cagr", series_cagr)?;
+        result.set_item("total_growth", total_growth)?;
+        result.set_item("yoy_growth", PyList::new(py, yoy_growth(&series)))?;
+
+        match (&labels, &shares) {
+            (Some(labels), Some(shares)) => {
+                let by_label = PyDict::new(py);
+                for (label, share) in labels.iter().zip(shares) {
+                    by_label.set_item(label, share)?;
+                }
+                result.set_item("market_share", by_label)?;
+            }
+            _ => result.set_item("market_share", shares)?,
+        }
+        result.set_item("hhi", index)?;
+        result.set_item("concentration", index.map(concentration_level))?;
+
+        Ok(result.into())
+    })
+}
+
+/// Project a base value forward under each named growth rate, one row per year
+pub fn project_scenarios(base_value: f64, scenarios: &[(String, f64)], years: usize, start_year: Option<i32>) -> MarkdownTable {
+    let mut header = vec!["Year".to_string()];
+    header.extend(scenarios.iter().map(|(name, _)| name.clone()));
+
+    let rows = (0..=years)
+        .map(|year| {
+            let label = match start_year {
+                Some(start) => (start + year as i32).to_string(),
+                None => format!("Y{}", year),
+            };
+            let mut row = vec![label];
+            row.extend(
+                scenarios
+                    .iter()
+                    .map(|(_, rate)| format!("{:.2}", base_value * (1.0 + rate).powi(year as i32))),
+            );
+            row
+        })
+        .collect();
+
+    MarkdownTable {
+        start_line: 0,
+        end_line: 0,
+        alignments: vec![Alignment::None; header.len()],
+        header,
+        rows,
+    }
+}
+
+/// Build a best/base/worst projection table and a matching line chart
+#[pyfunction]
+#[pyo3(signature = (base_value, growth_rates, years, start_year=None, rules=None))]
+pub fn scenario_table(
+    base_value: f64,
+    growth_rates: &PyAny,
+    years: usize,
+    start_year: Option<i32>,
+    rules: Option<&PyDict>,
+    py: Python,
+) -> PyResult<PyObject> {
+    guard("scenario_table", || {
+        // 1. Accept {"Best": 0.15, ...} or a list of rates ordered best, base, worst
+        let scenarios: Vec<(String, f64)> = if let Ok(dict) = growth_rates.downcast::<PyDict>() {
+            dict.iter()
+                .map(|(name, rate)| Ok((name.str()?.to_string(), rate.extract()?)))
+                .collect::<PyResult<_>>()?
+        } else {
+            let rates: Vec<f64> = growth_rates.extract()?;
+            let names: Vec<String> = if rates.len() == 3 {
+                ["Best", "Base", "Worst"].iter().map(|n| n.to_string()).collect()
+            } else {
+                (1..=rates.len()).map(|i| format!("Scenario {}", i)).collect()
+            };
+            names.into_iter().zip(rates).collect()
+        };
+
+        if scenarios.is_empty() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("At least one growth rate is required"));
+        }
+        if years == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Years must be at least 1"));
+        }
+        if !base_value.is_finite() || scenarios.iter().any(|(_, rate)| !rate.is_finite() || *rate <= -1.0) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Base value must be finite and growth rates must be fractions greater than -1 (e.g. 0.12 for 12%)"
+            ));
+        }
+
+        // 2. Chart the raw projections, then format the value columns for the table
+        let table = project_scenarios(base_value, &scenarios, years, start_year);
+        let chart = render_chart(&table, ChartType::Line).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+
+        let rules = NumberFormatRules::from_dict(rules)?;
+        let formatted = format_table(&table, &rules);
+
+        let final_values = PyDict::new(py);
+        for (name, rate) in &scenarios {
+            final_values.set_item(name, base_value * (1.0 + rate).powi(years as i32))?;
+        }
+
+        let result = PyDict::new(py);
+        result.set_item("table", render_table(&formatted))?;
+        result.set_item("chart", chart)?;
+        result.set_item("final_values", final_values)?;
+        Ok(result.into())
+    })
+}
@@ -0,0 +1,407 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::frontmatter::{front_matter_mapping, read_front_matter, FrontMatterSummary};
+use crate::panics::{guard, lock};
+use crate::sections::{section_body, split_sections};
+use crate::tables::find_tables;
+
+/// One directory entry in the tree, without its metadata
+#[derive(Clone)]
+struct TreeEntry {
+    name: String,
+    path: String,
+    is_dir: bool,
+    size: u64,
+    modified: f64,
+}
+
+/// Lazily loaded directory tree of reports for TUI frontends.
+/// Directories are listed on first access and front matter is read only for the rows requested.
+#[pyclass]
+pub struct ReportTreeModel {
+    root: PathBuf,
+    listings: Mutex<HashMap<String, Arc<Vec<TreeEntry>>>>,
+    metadata: Mutex<HashMap<String, FrontMatterSummary>>,
+}
+
+/// Resolve a path relative to the root, refusing anything that escapes it
+fn relative_path(root: &Path, path: &str) -> PyResult<PathBuf> {
+    let relative = Path::new(path);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Path must be relative to the tree root: {}", path)
+        ));
+    }
+    Ok(root.join(relative))
+}
+
+fn list_directory(root: &Path, path: &str) -> std::io::Result<Vec<TreeEntry>> {
+    let dir = root.join(path);
+    let mut entries: Vec<TreeEntry> = fs::read_dir(&dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().to_str()?.to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let is_dir = metadata.is_dir();
+            if !is_dir && Path::new(&name).extension().and_then(|e| e.to_str()) != Some("md") {
+                return None;
+            }
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs_f64())
+                .unwrap_or_default();
+            let relative = Path::new(path).join(&name).to_string_lossy().replace('\\', "/");
+            Some(TreeEntry { name, path: relative, is_dir, size: if is_dir { 0 } else { metadata.len() }, modified })
+        })
+        .collect();
+
+    // Directories first, then files, each alphabetically
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    Ok(entries)
+}
+
+fn load_metadata(root: &Path, path: &str) -> FrontMatterSummary {
+    read_front_matter(&root.join(path))
+        .map(|mapping| FrontMatterSummary::from_mapping(&mapping))
+        .unwrap_or_default()
+}
+
+impl ReportTreeModel {
+    fn listing(&self, path: &str) -> PyResult<Arc<Vec<TreeEntry>>> {
+        let key = path.trim_matches('/').to_string();
+        if let Some(listing) = lock(&self.listings).get(&key) {
+            return Ok(Arc::clone(listing));
+        }
+
+        relative_path(&self.root, &key)?;
+        let listing = Arc::new(list_directory(&self.root, &key).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list directory '{}': {}", key, e))
+        })?);
+        lock(&self.listings).insert(key, Arc::clone(&listing));
+        Ok(listing)
+    }
+}
+
+#[pymethods]
+impl ReportTreeModel {
+    #[new]
+    fn new(root: &str) -> PyResult<Self> {
+        guard("ReportTreeModel.new", || {
+            let root = PathBuf::from(root);
+            if !root.is_dir() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Tree root is not a directory: {}", root.display())
+                ));
+            }
+            Ok(ReportTreeModel {
+                root,
+                listings: Mutex::new(HashMap::new()),
+                metadata: Mutex::new(HashMap::new()),
+            })
+        })
+    }
+
+    /// Number of visible children (subdirectories and `.md` files) under `path`
+    #[pyo3(signature = (path=""))]
+    fn child_count(&self, path: &str) -> PyResult<usize> {
+        guard("ReportTreeModel.child_count", || {
+            Ok(self.listing(path)?.len())
+        })
+    }
+
+    /// One page of children under `path`; report front matter is loaded in parallel for just this page
+    #[pyo3(signature = (path="", offset=0, limit=None, with_metadata=true))]
+    fn children(&self, path: &str, offset: usize, limit: Option<usize>, with_metadata: bool, py: Python) -> PyResult<PyObject> {
+        guard("ReportTreeModel.children", || {
+            let listing = self.listing(path)?;
+            let end = limit.map(|limit| offset.saturating_add(limit)).unwrap_or(listing.len()).min(listing.len());
+            let page = &listing[offset.min(end)..end];
+
+            if with_metadata {
+                let missing: Vec<String> = {
+                    let cache = lock(&self.metadata);
+                    page.iter().filter(|e| !e.is_dir && !cache.contains_key(&e.path)).map(|e| e.path.clone()).collect()
+                };
+                let root = &self.root;
+                let loaded: Vec<(String, FrontMatterSummary)> = py.allow_threads(|| {
+                    missing.into_par_iter().map(|path| {
+                        let metadata = load_metadata(root, &path);
+                        (path, metadata)
+                    }).collect()
+                });
+                lock(&self.metadata).extend(loaded);
+            }
+
+            let cache = lock(&self.metadata);
+            let result = PyList::empty(py);
+            for entry in page {
+                let dict = PyDict::new(py);
+                dict.set_item("name", &entry.name)?;
+                dict.set_item("path", &entry.path)?;
+                dict.set_item("is_dir", entry.is_dir)?;
+                dict.set_item("size", entry.size)?;
+                dict.set_item("mtime", entry.modified)?;
+                if let Some(metadata) = cache.get(&entry.path).filter(|_| with_metadata) {
+                    dict.set_item("title", &metadata.title)?;
+                    dict.set_item("date", &metadata.date)?;
+                    dict.set_item("id", &metadata.id)?;
+                    dict.set_item("tags", &metadata.tags)?;
+                }
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Front matter summary (title, date, id, tags) for one report
+    fn metadata(&self, path: &str, py: Python) -> PyResult<PyObject> {
+        guard("ReportTreeModel.metadata", || {
+            let full = relative_path(&self.root, path)?;
+            if !full.is_file() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(format!("Report not found: {}", path)));
+            }
+            let metadata = {
+                let cached = lock(&self.metadata).get(path).cloned();
+                match cached {
+                    Some(metadata) => metadata,
+                    None => {
+                        let loaded = load_metadata(&self.root, path);
+                        lock(&self.metadata).insert(path.to_string(), loaded.clone());
+                        loaded
+                    }
+                }
+            };
+
+            let dict = PyDict::new(py);
+            dict.set_item("title", metadata.title)?;
+            dict.set_item("date", metadata.date)?;
+            dict.set_item("id", metadata.id)?;
+            dict.set_item("tags", metadata.tags)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Drop cached listings and metadata for `path` and below, or everything when omitted
+    #[pyo3(signature = (path=None))]
+    fn refresh(&self, path: Option<&str>) -> PyResult<()> {
+        guard("ReportTreeModel.refresh", || {
+            match path.map(|p| p.trim_matches('/')) {
+                None | Some("") => {
+                    lock(&self.listings).clear();
+                    lock(&self.metadata).clear();
+                }
+                Some(prefix) => {
+                    let nested = format!("{}/", prefix);
+                    let stale = |key: &String| key == prefix || key.starts_with(&nested);
+                    lock(&self.listings).retain(|key, _| !stale(key));
+                    lock(&self.metadata).retain(|key, _| !stale(key));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Per-section statistics shown as one table row
+#[derive(Clone)]
+struct SectionRow {
+    index: usize,
+    level: usize,
+    heading: String,
+    words: usize,
+    lines: usize,
+    tables: usize,
+    links: usize,
+    numbers: usize,
+}
+
+const SECTION_COLUMNS: [&str; 8] = ["index", "level", "heading", "words", "lines", "tables", "links", "numbers"];
+
+/// Current sort and filter applied to a `SectionTableModel`
+#[derive(Default)]
+struct SectionView {
+    sort: Option<(String, bool)>,
+    query: Option<String>,
+    min_level: Option<usize>,
+    max_level: Option<usize>,
+    rows: Vec<usize>,
+}
+
+/// Sortable, filterable table of section statistics for one report
+#[pyclass]
+pub struct SectionTableModel {
+    rows: Vec<SectionRow>,
+    view: Mutex<SectionView>,
+}
+
+fn section_rows(markdown: &str) -> Vec<SectionRow> {
+    let body = front_matter_mapping(markdown).map(|(_, body)| body).unwrap_or(markdown);
+    let lines: Vec<&str> = body.lines().collect();
+    let number = Regex::new(r"\d[\d,]*(?:\.\d+)?%?").unwrap();
+
+    split_sections(body)
+        .iter()
+        .enumerate()
+        .map(|(index, section)| {
+            let content = section_body(&lines, section).join("\n");
+            SectionRow {
+                index,
+                level: section.level,
+                heading: section.heading.clone(),
+                words: content.split_whitespace().count(),
+                lines: section.end_line - section.start_line,
+                tables: find_tables(&content).len(),
+                links: content.matches("](").count(),
+                numbers: number.find_iter(&content).count(),
+            }
+        })
+        .collect()
+}
+
+fn compare_rows(a: &SectionRow, b: &SectionRow, column: &str) -> Ordering {
+    match column {
+        "level" => a.level.cmp(&b.level),
+        "heading" => a.heading.to_lowercase().cmp(&b.heading.to_lowercase()),
+        "words" => a.words.cmp(&b.words),
+        "lines" => a.lines.cmp(&b.lines),
+        "tables" => a.tables.cmp(&b.tables),
+        "links" => a.links.cmp(&b.links),
+        "numbers" => a.numbers.cmp(&b.numbers),
+        _ => a.index.cmp(&b.index),
+    }
+}
+
+impl SectionTableModel {
+    /// Recompute the visible row order from the stored filter and sort
+    fn rebuild(&self, view: &mut SectionView) {
+        let query = view.query.as_ref().map(|q| q.to_lowercase());
+        let mut visible: Vec<usize> = self
+            .rows
+            .iter()
+            .filter(|row| query.as_ref().map(|q| row.heading.to_lowercase().contains(q)).unwrap_or(true))
+            .filter(|row| view.min_level.map(|min| row.level >= min).unwrap_or(true))
+            .filter(|row| view.max_level.map(|max| row.level <= max).unwrap_or(true))
+            .map(|row| row.index)
+            .collect();
+
+        if let Some((column, descending)) = &view.sort {
+            visible.sort_by(|a, b| {
+                let ordering = compare_rows(&self.rows[*a], &self.rows[*b], column).then(a.cmp(b));
+                if *descending { ordering.reverse() } else { ordering }
+            });
+        }
+        view.rows = visible;
+    }
+}
+
+#[pymethods]
+impl SectionTableModel {
+    #[new]
+    fn new(markdown: &str) -> PyResult<Self> {
+        guard("SectionTableModel.new", || {
+            let rows = section_rows(markdown);
+            let view = SectionView { rows: (0..rows.len()).collect(), ..Default::default() };
+            Ok(SectionTableModel { rows, view: Mutex::new(view) })
+        })
+    }
+
+    /// Build the model from a markdown file
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        guard("SectionTableModel.from_file", || {
+            let content = fs::read_to_string(path)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read file: {}", e)))?;
+            SectionTableModel::new(&content)
+        })
+    }
+
+    /// Column names available for sorting and display
+    fn columns(&self) -> PyResult<Vec<&'static str>> {
+        guard("SectionTableModel.columns", || {
+            Ok(SECTION_COLUMNS.to_vec())
+        })
+    }
+
+    /// Number of rows after filtering
+    fn row_count(&self) -> PyResult<usize> {
+        guard("SectionTableModel.row_count", || {
+            Ok(lock(&self.view).rows.len())
+        })
+    }
+
+    /// Number of sections before filtering
+    fn total_count(&self) -> PyResult<usize> {
+        guard("SectionTableModel.total_count", || {
+            Ok(self.rows.len())
+        })
+    }
+
+    /// One page of visible rows as dicts
+    #[pyo3(signature = (offset=0, limit=None))]
+    fn rows(&self, offset: usize, limit: Option<usize>, py: Python) -> PyResult<PyObject> {
+        guard("SectionTableModel.rows", || {
+            let view = lock(&self.view);
+            let end = limit.map(|limit| offset.saturating_add(limit)).unwrap_or(view.rows.len()).min(view.rows.len());
+
+            let result = PyList::empty(py);
+            for index in &view.rows[offset.min(end)..end] {
+                let row = &self.rows[*index];
+                let dict = PyDict::new(py);
+                dict.set_item("index", row.index)?;
+                dict.set_item("level", row.level)?;
+                dict.set_item("heading", &row.heading)?;
+                dict.set_item("words", row.words)?;
+                dict.set_item("lines", row.lines)?;
+                dict.set_item("tables", row.tables)?;
+                dict.set_item("links", row.links)?;
+                dict.set_item("numbers", row.numbers)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Sort visible rows by a column; ties keep document order
+    #[pyo3(signature = (column, descending=false))]
+    fn sort_by(&self, column: &str, descending: bool) -> PyResult<()> {
+        guard("SectionTableModel.sort_by", || {
+            if !SECTION_COLUMNS.contains(&column) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unknown column '{}'. Expected one of: {}", column, SECTION_COLUMNS.join(", "))
+                ));
+            }
+            let mut view = lock(&self.view);
+            view.sort = Some((column.to_string(), descending));
+            self.rebuild(&mut view);
+            Ok(())
+        })
+    }
+
+    /// Filter by heading substring and/or heading level range; pass no arguments to clear
+    #[pyo3(signature = (query=None, min_level=None, max_level=None))]
+    fn set_filter(&self, query: Option<String>, min_level: Option<usize>, max_level: Option<usize>) -> PyResult<()> {
+        guard("SectionTableModel.set_filter", || {
+            let mut view = lock(&self.view);
+            view.query = query.filter(|q| !q.trim().is_empty());
+            view.min_level = min_level;
+            view.max_level = max_level;
+            self.rebuild(&mut view);
+            Ok(())
+        })
+    }
+}
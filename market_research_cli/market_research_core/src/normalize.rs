@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+
+use pyo3::prelude::*;
+use regex::Regex;
+
+use crate::frontmatter::split_front_matter;
+use crate::lint::is_thematic_break;
+use crate::panics::guard;
+use crate::sections::parse_heading;
+use crate::tables::{find_tables, render_table, MarkdownTable};
+
+/// Column paragraphs and list items are wrapped at unless told otherwise
+pub const DEFAULT_WIDTH: usize = 80;
+
+struct Patterns {
+    list_item: Regex,
+    setext: Regex,
+    definition: Regex,
+    /// Words that would start a new block if a wrapped line began with them
+    block_start: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        Patterns {
+            list_item: Regex::new(r"^( *)([-*+]|\d{1,9}[.)])(?: +(.*))?$").unwrap(),
+            setext: Regex::new(r"^ {0,3}(=+|-+) *$").unwrap(),
+            definition: Regex::new(r"^ {0,3}\[[^\[\]]+\]:").unwrap(),
+            block_start: Regex::new(r"^(?:[-*+=_]+|\d{1,9}[.)]|#{1,6}|[>|<].*|```.*|~~~.*|\$\$.*|\[[^\[\]]+\]:.*)$")
+                .unwrap(),
+        }
+    }
+}
+
+fn is_fence(trimmed: &str) -> bool {
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+fn push_blank(out: &mut Vec<String>) {
+    if out.last().is_some_and(|line| !line.is_empty()) {
+        out.push(String::new());
+    }
+}
+
+/// Split text into words at whitespace outside code spans, whose inner spacing is significant
+fn words(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '`' {
+            let run = chars[i..].iter().take_while(|c| **c == '`').count();
+            let mut j = i + run;
+            let mut end = i + run;
+            while j < chars.len() {
+                if chars[j] != '`' {
+                    j += 1;
+                    continue;
+                }
+                let closing = chars[j..].iter().take_while(|c| **c == '`').count();
+                j += closing;
+                if closing == run {
+                    end = j;
+                    break;
+                }
+            }
+            current.extend(&chars[i..end]);
+            i = end;
+        } else if c.is_whitespace() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            i += 1;
+        } else {
+            current.push(c);
+            i += 1;
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Fill lines up to `width` columns (0 for no limit), the first starting with `first` and the rest with `rest`.
+/// A line never starts with a word that would read as a list marker, heading, quote or other block
+fn wrap(words: &[String], first: &str, rest: &str, width: usize, patterns: &Patterns) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first.to_string();
+    let mut empty = true;
+    for word in words {
+        let fits = line.chars().count() + 1 + word.chars().count() <= width;
+        if !empty && width > 0 && !fits && !patterns.block_start.is_match(word) {
+            lines.push(std::mem::take(&mut line));
+            line = rest.to_string();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    lines.push(line);
+    lines
+}
+
+/// Wrap a paragraph, keeping its hard line breaks (trailing spaces or backslash) as backslashes
+fn fill(source: &[String], first: &str, rest: &str, width: usize, patterns: &Patterns) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut segment = String::new();
+    for (index, line) in source.iter().enumerate() {
+        let last = index + 1 == source.len();
+        let hard_break = !last && (line.ends_with("  ") || line.trim_end().ends_with('\\'));
+        segment.push(' ');
+        segment.push_str(line.trim().trim_end_matches('\\'));
+        if hard_break || last {
+            let prefix = if out.is_empty() { first } else { rest };
+            let mut lines = wrap(&words(&segment), prefix, rest, width, patterns);
+            if hard_break {
+                if let Some(end) = lines.last_mut() {
+                    end.push('\\');
+                }
+            }
+            out.append(&mut lines);
+            segment.clear();
+        }
+    }
+    out
+}
+
+/// Re-serialize a report in one consistent style, so runs that produce the same content produce the same text:
+/// ATX headings (`## Title`, setext underlines converted), `-` bullets and `1.` numbers, padded and aligned
+/// tables, `---` rules, `\` hard breaks, single blank lines, and paragraphs and list items re-wrapped at `width`
+/// columns (0 puts each paragraph on one line). Front matter, fenced and indented code, display math, HTML
+/// blocks and link definitions are kept as written
+pub fn normalize(markdown: &str, width: usize) -> String {
+    let (front_matter, body) = match split_front_matter(markdown) {
+        Some((_, _, body)) => markdown.split_at(markdown.len() - body.len()),
+        None => ("", markdown),
+    };
+    let patterns = Patterns::new();
+    let lines: Vec<&str> = body.lines().collect();
+    let tables: HashMap<usize, MarkdownTable> =
+        find_tables(body).into_iter().map(|table| (table.start_line, table)).collect();
+    let table_lines: HashSet<usize> = tables.values().flat_map(|table| table.start_line..table.end_line).collect();
+
+    // A line that ends the paragraph before it
+    let interrupts = |index: usize, in_list: bool| {
+        let line = lines[index];
+        let trimmed = line.trim_start();
+        trimmed.is_empty()
+            || is_fence(trimmed)
+            || parse_heading(line).is_some()
+            || is_thematic_break(line)
+            || patterns.setext.is_match(line)
+            || patterns.list_item.captures(line).is_some_and(|caps| {
+                // Outside a list only bullets and lists starting at 1 can interrupt a paragraph, and not when empty
+                in_list
+                    || caps.get(3).is_some_and(|text| !text.as_str().trim().is_empty())
+                        && matches!(&caps[2], "-" | "*" | "+" | "1." | "1)")
+            })
+            || patterns.definition.is_match(line)
+            || table_lines.contains(&index)
+            || ['>', '<'].iter().any(|c| trimmed.starts_with(*c))
+            || trimmed.starts_with("$$")
+    };
+
+    let mut out: Vec<String> = Vec::new();
+    let mut in_list = false;
+    let mut list_content = 0;
+    // Indent and source marker of the open lists, innermost last
+    let mut list_markers: Vec<(usize, char)> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        let after_blank = out.last().is_none_or(|last| last.is_empty());
+        if trimmed.is_empty() {
+            push_blank(&mut out);
+            i += 1;
+            continue;
+        }
+        if indent == 0 && !patterns.list_item.is_match(line) {
+            in_list = false;
+            list_markers.clear();
+        }
+
+        if is_fence(trimmed) {
+            let marker = &trimmed[..3];
+            out.push(line.to_string());
+            i += 1;
+            while i < lines.len() {
+                out.push(lines[i].to_string());
+                i += 1;
+                if lines[i - 1].trim_start().starts_with(marker) {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        // A display math block runs to the first line ending in `$$`, as `math::mark_math` reads it
+        if trimmed.strip_prefix("$$").is_some_and(|rest| !rest.contains("$$")) {
+            let close = (i + 1..lines.len()).find(|&j| lines[j].trim_end().ends_with("$$")).unwrap_or(i);
+            out.extend(lines[i..=close].iter().map(|line| line.to_string()));
+            i = close + 1;
+            continue;
+        }
+
+        if let Some(table) = tables.get(&i) {
+            match indent {
+                0 => out.push(render_table(table)),
+                _ => out.extend(lines[table.start_line..table.end_line].iter().map(|line| line.trim_end().to_string())),
+            }
+            i = table.end_line;
+            continue;
+        }
+
+        // Indented code, outside a list where indentation nests content instead
+        let code_indent = if in_list { list_content + 4 } else { 4 };
+        if indent >= code_indent && after_blank {
+            let mut end = i;
+            let in_code = |line: &str| line.trim().is_empty() || line.len() - line.trim_start().len() >= code_indent;
+            while end < lines.len() && in_code(lines[end]) {
+                end += 1;
+            }
+            while lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            out.extend(lines[i..end].iter().map(|line| line.to_string()));
+            i = end;
+            continue;
+        }
+
+        if let Some((level, text)) = parse_heading(line).filter(|_| !in_list || indent == 0) {
+            push_blank(&mut out);
+            out.push(match text.is_empty() {
+                true => "#".repeat(level),
+                false => format!("{} {}", "#".repeat(level), text),
+            });
+            out.push(String::new());
+            i += 1;
+            continue;
+        }
+
+        if is_thematic_break(line) && indent <= 3 {
+            push_blank(&mut out);
+            out.push("---".to_string());
+            out.push(String::new());
+            i += 1;
+            continue;
+        }
+
+        if let Some(quoted) = trimmed.strip_prefix('>') {
+            let quoted = match quoted.starts_with([' ', '>']) || quoted.is_empty() {
+                true => quoted.to_string(),
+                false => format!(" {}", quoted),
+            };
+            out.push(format!("{}>{}", &line[..indent], quoted));
+            i += 1;
+            continue;
+        }
+
+        // HTML blocks and link definitions are passed through
+        if (trimmed.starts_with('<') && after_blank) || patterns.definition.is_match(line) {
+            let html = trimmed.starts_with('<');
+            out.push(line.to_string());
+            i += 1;
+            while html && i < lines.len() && !lines[i].trim().is_empty() {
+                out.push(lines[i].trim_end().to_string());
+                i += 1;
+            }
+            continue;
+        }
+
+        let (first, rest, text) = match patterns.list_item.captures(line) {
+            Some(caps) => {
+                let marker = match &caps[2] {
+                    "-" | "*" | "+" => "-".to_string(),
+                    number => format!("{}.", &number[..number.len() - 1]),
+                };
+                // Switching bullet character or number delimiter starts a new list, which stays separate once
+                // both use the same marker only if something comes between them
+                let kind = caps[2].chars().last().unwrap_or('-');
+                let level = caps[1].len();
+                list_markers.retain(|(indent, _)| *indent < level || (*indent == level && in_list));
+                match list_markers.last_mut() {
+                    Some((indent, previous)) if *indent == level => {
+                        if *previous != kind {
+                            push_blank(&mut out);
+                            out.push("<!-- end list -->".to_string());
+                            out.push(String::new());
+                            *previous = kind;
+                        }
+                    }
+                    _ => list_markers.push((level, kind)),
+                }
+                let first = format!("{}{} ", &caps[1], marker);
+                in_list = true;
+                list_content = first.len();
+                (first.clone(), " ".repeat(first.len()), caps.get(3).map_or("", |text| text.as_str()))
+            }
+            None => {
+                let prefix = if in_list { line[..indent].to_string() } else { String::new() };
+                (prefix.clone(), prefix, trimmed)
+            }
+        };
+        // Keep the trailing spaces of a hard break
+        let mut paragraph = vec![format!("{}{}", text, &lines[i][line.len()..])];
+        i += 1;
+        while i < lines.len() && !interrupts(i, in_list) {
+            paragraph.push(lines[i].to_string());
+            i += 1;
+        }
+
+        // A paragraph underlined with `===` or `---` is a heading
+        let underline = lines.get(i).and_then(|next| patterns.setext.captures(next));
+        if let Some(underline) = underline.filter(|_| first.trim().is_empty() && !in_list) {
+            let level = if underline[1].starts_with('=') { 1 } else { 2 };
+            let text: Vec<&str> = paragraph.iter().map(|line| line.trim()).collect();
+            push_blank(&mut out);
+            out.push(format!("{} {}", "#".repeat(level), text.join(" ")));
+            out.push(String::new());
+            i += 1;
+            continue;
+        }
+        out.extend(fill(&paragraph, &first, &rest, width, &patterns));
+    }
+
+    while out.last().is_some_and(|line| line.is_empty()) {
+        out.pop();
+    }
+    let mut normalized = front_matter.to_string();
+    if !out.is_empty() {
+        normalized.push_str(&out.join("\n"));
+        normalized.push('\n');
+    }
+    normalized
+}
+
+/// Reformat a report consistently (headings, list markers, tables, line wrapping) so output from different runs
+/// can be diffed; see `normalize` for what changes. `width` is the wrap column, 0 to unwrap paragraphs
+#[pyfunction]
+#[pyo3(signature = (content, width=DEFAULT_WIDTH))]
+pub fn normalize_markdown(content: &str, width: usize) -> PyResult<String> {
+    guard("normalize_markdown", || {
+        Ok(normalize(content, width))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    /// HTML comrak renders for `markdown` with the extensions reports use, with whitespace runs collapsed (wrapping
+    /// only moves soft line breaks) and list separators dropped
+    fn html(markdown: &str) -> String {
+        let mut options = comrak::ComrakOptions::default();
+        options.extension.table = true;
+        options.extension.strikethrough = true;
+        options.extension.front_matter_delimiter = Some("---".to_string());
+        options.render.unsafe_ = true;
+        let html = comrak::markdown_to_html(markdown, &options).replace("<!-- end list -->", "");
+        html.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Normalized at `width`, checking it renders the same as the input and is unchanged by normalizing again
+    fn normalized(markdown: &str, width: usize) -> String {
+        let once = normalize(markdown, width);
+        assert_eq!(html(&once), html(markdown), "rendering changed for {:?}", markdown);
+        assert_eq!(normalize(&once, width), once, "not idempotent for {:?}", markdown);
+        once
+    }
+
+    #[test]
+    fn uses_one_style_for_headings_lists_rules_and_breaks() {
+        let markdown = "Title\n=====\n\nSub\n---\n\n* one\n* two\n\n***\n\nline one  \nline two\n\n\n\nEnd\n";
+        assert_eq!(
+            normalized(markdown, 80),
+            "# Title\n\n## Sub\n\n- one\n- two\n\n---\n\nline one\\\nline two\n\nEnd\n"
+        );
+    }
+
+    #[test]
+    fn pads_and_aligns_tables() {
+        assert_eq!(normalized("|a|bb|\n|:-|-:|\n|ccc|d|\n", 80), "| a   |  bb |\n| :-- | --: |\n| ccc |   d |\n");
+    }
+
+    #[test]
+    fn wraps_paragraphs_at_the_width_and_unwraps_at_zero() {
+        let markdown = "The market grew quickly in the last year and analysts expect it to keep growing.\n";
+        let wrapped = normalized(markdown, 40);
+        assert!(wrapped.lines().all(|line| line.chars().count() <= 40), "{}", wrapped);
+        assert_eq!(normalized(&wrapped, 0), markdown);
+    }
+
+    #[test]
+    fn never_wraps_a_word_into_a_block_start() {
+        let wrapped = normalized("Growth was strong in the year - and in 2019. it fell back # again\n", 10);
+        for line in wrapped.lines() {
+            assert!(!line.starts_with("- ") && !line.starts_with("# ") && !line.starts_with("2019."), "{}", wrapped);
+        }
+    }
+
+    #[test]
+    fn keeps_a_year_after_a_line_break_in_its_paragraph() {
+        assert_eq!(normalized("Revenue rose in\n2019. Then it fell.\n", 80), "Revenue rose in 2019. Then it fell.\n");
+    }
+
+    #[test]
+    fn keeps_lists_apart_when_the_marker_changes() {
+        let normalized = normalized("- a\n- b\n\n+ c\n", 80);
+        assert_eq!(normalized.matches("<!-- end list -->").count(), 1, "{}", normalized);
+        assert!(normalized.ends_with("- c\n"), "{}", normalized);
+    }
+
+    #[test]
+    fn keeps_code_front_matter_and_start_numbers() {
+        let markdown = "---\ntitle: X\n---\n\n```\n*  keep   me\n```\n\n    indented   code\n\n3) third\n4) fourth\n";
+        assert_eq!(
+            normalized(markdown, 80),
+            "---\ntitle: X\n---\n```\n*  keep   me\n```\n\n    indented   code\n\n3. third\n4. fourth\n"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use pyo3::prelude::*;
+
+pyo3::create_exception!(
+    market_research_core,
+    InternalError,
+    pyo3::exceptions::PyRuntimeError,
+    "A bug in market_research_core was hit (a Rust panic); `function` and `panic_message` say where and what"
+);
+
+/// The message a panic was raised with
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => "unknown panic".to_string(),
+    }
+}
+
+/// Run the body of a Python entry point, turning a panic into an `InternalError` naming `function`. Without
+/// this, pyo3 raises `PanicException`, which derives from `BaseException` and so gets past `except Exception`
+/// handlers and ends the caller's whole run
+pub fn guard<T, F>(function: &str, body: F) -> PyResult<T>
+where
+    F: FnOnce() -> PyResult<T>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => result,
+        Err(payload) => Err(internal_error(function, panic_message(payload.as_ref()))),
+    }
+}
+
+/// `InternalError` for a panic with `message` in `function`, including one caught on a worker thread
+pub fn internal_error(function: &str, message: String) -> PyErr {
+    let error = InternalError::new_err(format!(
+        "Internal error in {}: {}. This is a bug; please report it with the input that caused it",
+        function, message
+    ));
+    Python::with_gil(|py| {
+        let value = error.value(py);
+        // Setting attributes on a fresh exception instance cannot fail in practice
+        let _ = value.setattr("function", function);
+        let _ = value.setattr("panic_message", message);
+    });
+    error
+}
+
+/// Lock a mutex even if a panic poisoned it while held. For state that stays usable after an interrupted update
+/// (progress counters, registries), so one failed call does not make every later one panic too
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use crate::charts::ChartMode;
+use crate::convert;
+use crate::csv::{self, CsvOptions};
+use crate::facts::{Fact, Facts};
+use crate::frontmatter;
+use crate::redact::RedactionProfile;
+use crate::render::{self, RenderOptions};
+use crate::wikilinks::Library;
+
+// Pure entry points to the parsers that see scraped and imported content, for the harnesses under `fuzz/`.
+// None of them needs Python or the filesystem, and none may panic on any input: a panic inside the extension
+// takes the whole Python process down with it.
+
+/// Report metadata and body as `parse_report_metadata` reads them, or `None` for invalid front matter
+pub fn report_metadata(input: &str) -> Option<(HashMap<String, String>, String)> {
+    crate::parse_report_metadata(input).ok()
+}
+
+/// Front matter as a YAML mapping and the body after it, or `None` for invalid front matter
+pub fn front_matter_mapping(input: &str) -> Option<(serde_yaml::Mapping, &str)> {
+    frontmatter::front_matter_mapping(input).ok()
+}
+
+/// Content with terminal escape sequences removed
+pub fn clean_escapes(input: &str) -> String {
+    // Only fails by construction of a Python error, which cleaning never does
+    crate::clean_escape_sequences(input).unwrap_or_default()
+}
+
+/// Markdown after every pre-processing step `format_report` can apply, all of them enabled. Facts and the wikilink
+/// library are small fixed sets built in memory, so references to them both resolve and miss. Redaction keeps
+/// comments, which would otherwise take the chart markers with them
+pub fn preprocess_markdown(input: &str) -> String {
+    let fact = Fact { value: "$4.2B".to_string(), source: None, url: None, recorded_at: String::new() };
+    let filenames = vec!["market-overview.md".to_string(), "q3 review.md".to_string()];
+    let titles = HashMap::from([("market-overview.md".to_string(), "Market Overview".to_string())]);
+    let options = RenderOptions {
+        charts: ChartMode::Beside,
+        facts: Some(Facts::from([("tam_2024".to_string(), fact)])),
+        redaction: RedactionProfile::builtin("external").map(|profile| RedactionProfile { comments: false, ..profile }),
+        toc: 6,
+        emoji: true,
+        smart_punctuation: true,
+        wikilinks: Some(Library::new(&filenames, titles)),
+        citations: true,
+        math: true,
+        mermaid: true,
+        ..RenderOptions::default()
+    };
+    render::preprocess(input, &options)
+}
+
+/// Markdown and title extracted from an HTML document
+pub fn extract_html(input: &str) -> (String, Option<String>) {
+    let converted = convert::html_to_markdown(input);
+    (converted.markdown, converted.title)
+}
+
+/// A markdown table converted from delimited text, with the delimiter detected, or `None` when there are no rows
+pub fn csv_table(input: &str) -> Option<String> {
+    csv::csv_markdown(input, &CsvOptions::default())
+}
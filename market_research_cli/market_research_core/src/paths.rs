@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+
+/// Characters Windows does not allow in file names; `\` is a separator there
+const RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*', '\\'];
+
+/// Device names Windows reserves regardless of extension (`con.md` is as unusable as `CON`)
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+    "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Longest file name (one path component) most file systems accept
+pub const MAX_NAME_BYTES: usize = 255;
+
+/// Why a single path component cannot be used on every platform, if it cannot
+fn component_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("has an empty path component".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| RESERVED_CHARS.contains(c) || c.is_control()) {
+        return Some(format!("contains {:?}, which Windows does not allow in file names", c));
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Some("ends with a dot or space, which Windows strips".to_string());
+    }
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        return Some(format!("uses the reserved Windows device name {}", stem.to_uppercase()));
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return Some(format!("has a name longer than {} bytes", MAX_NAME_BYTES));
+    }
+    None
+}
+
+/// Why `filename` (relative to the reports directory, `/`-separated) would not survive syncing between
+/// Windows, macOS and Linux, if it would not
+pub fn filename_problem(filename: &str) -> Option<String> {
+    filename.split('/').find_map(component_problem).map(|problem| format!("{} {}", filename, problem))
+}
+
+/// A version of `filename` that is valid everywhere: reserved and control characters become `_`, trailing dots
+/// and spaces are dropped, device names get a `_` prefix, and overlong names are shortened keeping the extension
+pub fn portable_filename(filename: &str) -> String {
+    filename.split('/').map(portable_component).collect::<Vec<_>>().join("/")
+}
+
+fn portable_component(name: &str) -> String {
+    let replaced: String = name.chars().map(|c| if RESERVED_CHARS.contains(&c) || c.is_control() { '_' } else { c }).collect();
+    let mut name = replaced.trim_end_matches(['.', ' ']).to_string();
+    if name.is_empty() {
+        name = "_".to_string();
+    }
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        name.insert(0, '_');
+    }
+    if name.len() > MAX_NAME_BYTES {
+        let extension = match name.rfind('.') {
+            Some(dot) if name.len() - dot <= 16 => name[dot..].to_string(),
+            _ => String::new(),
+        };
+        let mut end = MAX_NAME_BYTES - extension.len();
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name = format!("{}{}", name[..end].trim_end_matches(['.', ' ']), extension);
+    }
+    name
+}
+
+/// Join a report filename onto the reports directory, rejecting absolute paths and `..` so a filename can never
+/// reach outside it
+pub fn report_path(reports_dir: &str, filename: &str) -> Result<PathBuf> {
+    let relative = Path::new(filename);
+    if filename.is_empty() || !relative.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+        return Err(anyhow!("Invalid report filename '{}': must be a path inside the reports directory", filename));
+    }
+    Ok(long_path(&Path::new(reports_dir).join(relative)))
+}
+
+/// Another entry in the same directory whose name differs from `path`'s only by case, which case-insensitive
+/// file systems (macOS, Windows) treat as the same file
+pub fn case_collision(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let folded = name.to_lowercase();
+    fs::read_dir(parent).ok()?.filter_map(|entry| entry.ok()).find_map(|entry| {
+        let other = entry.file_name().to_string_lossy().to_string();
+        (other != name && other.to_lowercase() == folded).then_some(other)
+    })
+}
+
+/// Groups of filenames that only differ by case, sorted
+pub fn case_collisions(filenames: &[String]) -> Vec<Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for filename in filenames {
+        groups.entry(filename.to_lowercase()).or_default().push(filename.clone());
+    }
+    groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect()
+}
+
+/// On Windows, paths past `MAX_PATH` can only be opened with the `\\?\` prefix, which in turn needs an absolute,
+/// backslash-separated path without `.` or `..`
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    const MAX_PATH: usize = 260;
+    let text = path.as_os_str().to_string_lossy();
+    if text.len() < MAX_PATH || text.starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+    let absolute = match std::env::current_dir() {
+        Ok(dir) if !path.is_absolute() => dir.join(path),
+        _ => path.to_path_buf(),
+    };
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    let text = normalized.to_string_lossy().replace('/', "\\");
+    match text.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{}", unc)),
+        None => PathBuf::from(format!(r"\\?\{}", text)),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+use regex::{Captures, Regex};
+
+use crate::citations::citation_pattern;
+use crate::frontmatter::split_front_matter;
+use crate::normalize::normalize;
+use crate::panics::guard;
+use crate::sections::parse_heading;
+use crate::tables::{find_tables, Alignment, MarkdownTable};
+use crate::toc;
+use crate::wikilinks::wikilink_pattern;
+
+/// What unordered list items start with in plain text
+const BULLET: &str = "•";
+
+/// Private-use characters standing in for escaped punctuation and code spans while inline markup is removed
+const ESCAPE_BASE: u32 = 0xE000;
+const CODE_OPEN: char = '\u{E100}';
+const CODE_CLOSE: char = '\u{E101}';
+
+struct Patterns {
+    escape: Regex,
+    code_span: Regex,
+    code_placeholder: Regex,
+    image: Regex,
+    wikilink: Regex,
+    citation: Regex,
+    footnote: Regex,
+    link: Regex,
+    reference_link: Regex,
+    autolink: Regex,
+    line_break: Regex,
+    comment: Regex,
+    tag: Regex,
+    strong: Regex,
+    strong_underscore: Regex,
+    emphasis: Regex,
+    emphasis_underscore: Regex,
+    strikethrough: Regex,
+    numeric_entity: Regex,
+    list_item: Regex,
+    footnote_definition: Regex,
+    definition: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        Patterns {
+            escape: Regex::new(r"\\([!-/:-@\[-`{-~])").unwrap(),
+            code_span: Regex::new(r"``(.+?)``|`([^`]+)`").unwrap(),
+            code_placeholder: Regex::new("\u{E100}(\\d+)\u{E101}").unwrap(),
+            image: Regex::new(r"!\[([^\[\]]*)\]\([^)]*\)").unwrap(),
+            wikilink: wikilink_pattern(),
+            citation: Regex::new(&format!(r"\s?{}", citation_pattern().as_str())).unwrap(),
+            footnote: Regex::new(r"\[\^([^\[\]]+)\]").unwrap(),
+            link: Regex::new(r#"\[([^\[\]]*)\]\(\s*<?([^)\s>]*)>?(?:\s+"[^"]*")?\s*\)"#).unwrap(),
+            reference_link: Regex::new(r"\[([^\[\]]+)\]\[[^\[\]]*\]").unwrap(),
+            autolink: Regex::new(r"<((?:https?|mailto):[^>\s]+|[^@<>\s]+@[^@<>\s]+\.[^@<>\s]+)>").unwrap(),
+            line_break: Regex::new(r"(?i)<br\s*/?>").unwrap(),
+            comment: Regex::new(r"(?s)<!--.*?-->").unwrap(),
+            tag: Regex::new(r"</?[A-Za-z][^<>]*>").unwrap(),
+            strong: Regex::new(r"\*\*(\S(?:.*?\S)?)\*\*").unwrap(),
+            strong_underscore: Regex::new(r"(^|\W)__(\S(?:.*?\S)?)__(\W|$)").unwrap(),
+            emphasis: Regex::new(r"\*(\S(?:.*?\S)?)\*").unwrap(),
+            emphasis_underscore: Regex::new(r"(^|\W)_(\S(?:.*?\S)?)_(\W|$)").unwrap(),
+            strikethrough: Regex::new(r"~~(\S(?:.*?\S)?)~~").unwrap(),
+            numeric_entity: Regex::new(r"&#(?:(\d+)|[xX]([0-9a-fA-F]+));").unwrap(),
+            list_item: Regex::new(r"^( *)(-|\d{1,9}\.)(?: (.*))?$").unwrap(),
+            footnote_definition: Regex::new(r"^ {0,3}\[\^([^\[\]]+)\]:\s*(.*)$").unwrap(),
+            definition: Regex::new(r"^ {0,3}\[[^\[\]]+\]:").unwrap(),
+        }
+    }
+}
+
+fn decode_entities(text: &str, patterns: &Patterns) -> String {
+    let text = patterns.numeric_entity.replace_all(text, |caps: &Captures| {
+        let code = match (caps.get(1), caps.get(2)) {
+            (Some(decimal), _) => decimal.as_str().parse().ok(),
+            (_, Some(hex)) => u32::from_str_radix(hex.as_str(), 16).ok(),
+            _ => None,
+        };
+        code.and_then(char::from_u32).map_or_else(|| caps[0].to_string(), String::from)
+    });
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text of one line of markdown with inline markup removed: emphasis and code delimiters dropped, links and
+/// images reduced to their text (with the URL in parentheses when `link_urls` is set), wikilinks to their label,
+/// citations dropped, footnote references as `[1]` and HTML tags stripped
+fn inline_text(text: &str, link_urls: bool, patterns: &Patterns) -> String {
+    let text = patterns.escape.replace_all(text, |caps: &Captures| {
+        char::from_u32(ESCAPE_BASE + caps[1].chars().next().unwrap_or_default() as u32).unwrap_or_default().to_string()
+    });
+    let mut code: Vec<String> = Vec::new();
+    let text = patterns.code_span.replace_all(&text, |caps: &Captures| {
+        let content = caps.get(1).or(caps.get(2)).map_or("", |content| content.as_str());
+        code.push(content.trim().to_string());
+        format!("{}{}{}", CODE_OPEN, code.len() - 1, CODE_CLOSE)
+    });
+
+    let text = patterns.image.replace_all(&text, "$1");
+    let text = patterns.wikilink.replace_all(&text, |caps: &Captures| {
+        caps.get(3).map_or(&caps[1], |label| label.as_str()).trim().to_string()
+    });
+    let text = patterns.citation.replace_all(&text, "");
+    let text = patterns.footnote.replace_all(&text, "[$1]");
+    let text = patterns.link.replace_all(&text, |caps: &Captures| {
+        let (label, url) = (&caps[1], &caps[2]);
+        match link_urls && !url.is_empty() && !url.starts_with('#') && label != url {
+            true => format!("{} ({})", label, url),
+            false => label.to_string(),
+        }
+    });
+    let text = patterns.reference_link.replace_all(&text, "$1");
+    let text = patterns.autolink.replace_all(&text, "$1");
+    let text = patterns.line_break.replace_all(&text, "\n");
+    let text = patterns.comment.replace_all(&text, "");
+    let mut text = patterns.tag.replace_all(&text, "").into_owned();
+
+    // Underscore delimiters share their surrounding character with the next match, so a second pass is needed
+    for _ in 0..2 {
+        text = patterns.strong.replace_all(&text, "$1").into_owned();
+        text = patterns.strong_underscore.replace_all(&text, "$1$2$3").into_owned();
+    }
+    for _ in 0..2 {
+        text = patterns.emphasis.replace_all(&text, "$1").into_owned();
+        text = patterns.emphasis_underscore.replace_all(&text, "$1$2$3").into_owned();
+    }
+    let text = patterns.strikethrough.replace_all(&text, "$1");
+
+    let text = decode_entities(&text, patterns);
+    let text = patterns.code_placeholder.replace_all(&text, |caps: &Captures| {
+        caps[1].parse::<usize>().ok().and_then(|idx| code.get(idx)).cloned().unwrap_or_default()
+    });
+    text.chars()
+        .map(|c| match c as u32 {
+            escaped if (ESCAPE_BASE..ESCAPE_BASE + 0x80).contains(&escaped) => {
+                char::from_u32(escaped - ESCAPE_BASE).unwrap_or(c)
+            }
+            _ => c,
+        })
+        .collect()
+}
+
+/// A table as space-separated columns padded to line up, with a rule under the header
+fn table_text(table: &MarkdownTable, link_urls: bool, patterns: &Patterns) -> Vec<String> {
+    let cell = |cell: &String| inline_text(&cell.replace("\\|", "|"), link_urls, patterns).replace('\n', " ");
+    let header: Vec<String> = table.header.iter().map(cell).collect();
+    let rows: Vec<Vec<String>> = table.rows.iter().map(|row| row.iter().map(cell).collect()).collect();
+
+    let mut widths = vec![0usize; header.len()];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (idx, cell) in row.iter().enumerate().take(widths.len()) {
+            widths[idx] = widths[idx].max(cell.chars().count());
+        }
+    }
+    let line = |row: &[String]| -> String {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .take(widths.len())
+            .map(|(idx, cell)| {
+                let width = widths[idx];
+                match table.alignments[idx] {
+                    Alignment::Right => format!("{:>width$}", cell, width = width),
+                    Alignment::Center => format!("{:^width$}", cell, width = width),
+                    _ => format!("{:<width$}", cell, width = width),
+                }
+            })
+            .collect();
+        cells.join("  ").trim_end().to_string()
+    };
+
+    let mut out = vec![line(&header)];
+    out.push(widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("  "));
+    out.extend(rows.iter().map(|row| line(row)));
+    out
+}
+
+fn push_blank(out: &mut Vec<String>) {
+    if out.last().is_some_and(|line| !line.is_empty()) {
+        out.push(String::new());
+    }
+}
+
+/// Plain text of a report: one line per paragraph with blank lines between blocks, headings on their own,
+/// `•` bullets and numbers kept with list nesting, tables laid out in aligned columns, code blocks kept as written
+/// (Mermaid sources dropped) and front matter, link definitions and HTML comments left out
+pub fn to_text(markdown: &str, link_urls: bool) -> String {
+    let normalized = normalize(markdown, 0);
+    let body = split_front_matter(&normalized).map_or(normalized.as_str(), |(_, _, body)| body);
+    let patterns = Patterns::new();
+    let lines: Vec<&str> = body.lines().collect();
+    let tables: HashMap<usize, MarkdownTable> =
+        find_tables(body).into_iter().map(|table| (table.start_line, table)).collect();
+
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        let index = i;
+        i += 1;
+        let link_definition = patterns.definition.is_match(line) && !patterns.footnote_definition.is_match(line);
+        if trimmed.is_empty() || trimmed == "---" || trimmed == toc::MARKER || link_definition {
+            push_blank(&mut out);
+            continue;
+        }
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker = &trimmed[..3];
+            let close = (i..lines.len()).find(|&j| lines[j].trim_start().starts_with(marker)).unwrap_or(lines.len());
+            if trimmed[3..].trim().to_lowercase() != "mermaid" {
+                push_blank(&mut out);
+                out.extend(lines[i..close].iter().map(|line| line.trim_end().to_string()));
+                push_blank(&mut out);
+            }
+            i = close + 1;
+            continue;
+        }
+        // Display math keeps its TeX, as the closest thing to text it has
+        let opened = trimmed.strip_prefix("$$").filter(|rest| !rest.contains("$$"));
+        let close = opened.and_then(|_| (i..lines.len()).find(|&j| lines[j].trim_end().ends_with("$$")));
+        if let (Some(opened), Some(close)) = (opened, close) {
+            let last = lines[close].trim_end().strip_suffix("$$").unwrap_or_default();
+            let tex = std::iter::once(opened).chain(lines[i..close].iter().copied()).chain(std::iter::once(last));
+            push_blank(&mut out);
+            out.extend(tex.map(|line| line.trim().to_string()).filter(|line| !line.is_empty()));
+            push_blank(&mut out);
+            i = close + 1;
+            continue;
+        }
+        if trimmed.starts_with("<!--") && !trimmed.contains("-->") {
+            i = (i..lines.len()).find(|&j| lines[j].contains("-->")).map_or(lines.len(), |j| j + 1);
+            continue;
+        }
+        if let Some(table) = tables.get(&index) {
+            push_blank(&mut out);
+            out.extend(table_text(table, link_urls, &patterns));
+            push_blank(&mut out);
+            i = table.end_line;
+            continue;
+        }
+        if let Some((_, heading)) = parse_heading(line) {
+            push_blank(&mut out);
+            out.push(inline_text(&heading, link_urls, &patterns));
+            push_blank(&mut out);
+            continue;
+        }
+
+        let text = if let Some(caps) = patterns.footnote_definition.captures(line) {
+            format!("[{}] {}", &caps[1], inline_text(&caps[2], link_urls, &patterns))
+        } else if let Some(quoted) = trimmed.strip_prefix('>') {
+            let depth = 1 + quoted.chars().filter(|c| *c == '>').count();
+            let quoted = quoted.trim_start_matches(['>', ' ']);
+            match quoted.is_empty() {
+                true => String::new(),
+                false => format!("{}{}", "  ".repeat(depth), inline_text(quoted, link_urls, &patterns)),
+            }
+        } else if let Some(caps) = patterns.list_item.captures(line) {
+            let marker = match &caps[2] {
+                "-" => BULLET,
+                number => number,
+            };
+            let text = caps.get(3).map_or("", |text| text.as_str());
+            format!("{}{} {}", &caps[1], marker, inline_text(text, link_urls, &patterns))
+        } else {
+            format!("{}{}", indent, inline_text(trimmed.trim_end_matches('\\'), link_urls, &patterns))
+        };
+
+        match text.trim().is_empty() {
+            true if trimmed.starts_with('>') => push_blank(&mut out),
+            true => {}
+            false => out.push(text.trim_end().to_string()),
+        }
+    }
+
+    while out.last().is_some_and(|line| line.is_empty()) {
+        out.pop();
+    }
+    match out.is_empty() {
+        true => String::new(),
+        false => format!("{}\n", out.join("\n")),
+    }
+}
+
+/// Plain text of a report for speech, embeddings or email bodies: formatting removed, paragraphs on one line
+/// each, list bullets and numbers kept and tables laid out in aligned columns. With `link_urls`, links read
+/// "text (url)" instead of just their text
+#[pyfunction]
+#[pyo3(signature = (content, link_urls=false))]
+pub fn markdown_to_text(content: &str, link_urls: bool) -> PyResult<String> {
+    guard("markdown_to_text", || {
+        Ok(to_text(content, link_urls))
+    })
+}
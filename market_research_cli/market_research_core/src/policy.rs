@@ -0,0 +1,279 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::RegexBuilder;
+use serde::Deserialize;
+
+use crate::frontmatter::front_matter_mapping;
+use crate::panics::guard;
+
+/// How serious a failed rule is; only errors fail the policy
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+fn default_severity() -> Severity {
+    Severity::Error
+}
+
+fn default_min_mentions() -> usize {
+    1
+}
+
+/// The condition a rule checks, selected by its `check` field
+#[derive(Deserialize, Debug)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum Check {
+    /// The body must match a regex
+    RequirePattern { pattern: String },
+    /// The body must not match a regex
+    ForbidPattern { pattern: String },
+    /// Literal text (e.g. a disclaimer) must appear, ignoring case and whitespace differences
+    RequireText { text: String },
+    /// Body word count must fall within bounds
+    WordCount { min: Option<usize>, max: Option<usize> },
+    /// A heading with this text must exist
+    RequireSection { heading: String },
+    /// Some line must mention the metric together with a number
+    RequireMetric { metric: String },
+    /// At least `min` of the listed entities must be mentioned
+    RequireEntities {
+        names: Vec<String>,
+        #[serde(default = "default_min_mentions")]
+        min: usize,
+    },
+    /// These front matter keys must be present and non-empty
+    RequireFrontMatter { keys: Vec<String> },
+}
+
+/// One named rule in a policy file
+#[derive(Deserialize, Debug)]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+/// A set of rules loaded from YAML or JSON
+#[derive(Deserialize, Debug)]
+pub struct Policy {
+    pub rules: Vec<Rule>,
+}
+
+/// Outcome of evaluating one rule
+pub struct RuleResult {
+    pub id: String,
+    pub severity: Severity,
+    pub passed: bool,
+    pub message: String,
+}
+
+impl Policy {
+    /// Load a policy file; JSON is accepted because it is valid YAML
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("Failed to read policy file {}", path.display()))?;
+        let policy: Policy = serde_yaml::from_str(&text).context("Invalid policy file")?;
+        for rule in &policy.rules {
+            rule.validate()?;
+        }
+        Ok(policy)
+    }
+
+    /// Evaluate every rule against a report
+    pub fn evaluate(&self, markdown: &str) -> Vec<RuleResult> {
+        let (metadata, body) = match front_matter_mapping(markdown) {
+            Ok(parsed) => parsed,
+            Err(_) => (Default::default(), markdown),
+        };
+
+        self.rules
+            .iter()
+            .map(|rule| {
+                let (passed, detail) = rule.check.run(body, &metadata);
+                let message = match (&rule.description, passed) {
+                    (Some(description), false) => format!("{}: {}", description, detail),
+                    _ => detail,
+                };
+                RuleResult { id: rule.id.clone(), severity: rule.severity, passed, message }
+            })
+            .collect()
+    }
+}
+
+impl Rule {
+    /// Reject rules whose regexes do not compile, before any report is checked
+    fn validate(&self) -> Result<()> {
+        match &self.check {
+            Check::RequirePattern { pattern } | Check::ForbidPattern { pattern } => {
+                compile(pattern).map(|_| ()).map_err(|e| anyhow!("Rule '{}' has an invalid pattern: {}", self.id, e))
+            }
+            Check::WordCount { min: None, max: None } => {
+                Err(anyhow!("Rule '{}' needs at least one of min or max", self.id))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn compile(pattern: &str) -> std::result::Result<regex::Regex, regex::Error> {
+    RegexBuilder::new(pattern).multi_line(true).build()
+}
+
+fn normalize_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+impl Check {
+    /// Return whether the check passed and a human-readable explanation
+    fn run(&self, body: &str, metadata: &serde_yaml::Mapping) -> (bool, String) {
+        match self {
+            Check::RequirePattern { pattern } => match compile(pattern) {
+                Ok(regex) if regex.is_match(body) => (true, format!("Found /{}/", pattern)),
+                Ok(_) => (false, format!("Required pattern /{}/ not found", pattern)),
+                Err(e) => (false, format!("Invalid pattern: {}", e)),
+            },
+            Check::ForbidPattern { pattern } => match compile(pattern) {
+                Ok(regex) => match regex.find(body) {
+                    Some(found) => (false, format!("Forbidden pattern matched \"{}\"", found.as_str())),
+                    None => (true, format!("No match for /{}/", pattern)),
+                },
+                Err(e) => (false, format!("Invalid pattern: {}", e)),
+            },
+            Check::RequireText { text } => {
+                if normalize_space(body).contains(&normalize_space(text)) {
+                    (true, "Required text present".to_string())
+                } else {
+                    (false, format!("Required text missing: \"{}\"", text))
+                }
+            }
+            Check::WordCount { min, max } => {
+                let words = body.split_whitespace().count();
+                let too_short = min.map(|min| words < min).unwrap_or(false);
+                let too_long = max.map(|max| words > max).unwrap_or(false);
+                let bounds = format!(
+                    "{}..{}",
+                    min.map(|m| m.to_string()).unwrap_or_default(),
+                    max.map(|m| m.to_string()).unwrap_or_default()
+                );
+                (!too_short && !too_long, format!("{} words (allowed {})", words, bounds))
+            }
+            Check::RequireSection { heading } => {
+                let wanted = normalize_space(heading);
+                let found = body.lines().any(|line| {
+                    let trimmed = line.trim_start();
+                    trimmed.starts_with('#') && normalize_space(trimmed.trim_start_matches('#')).replace("**", "") == wanted
+                });
+                match found {
+                    true => (true, format!("Section \"{}\" present", heading)),
+                    false => (false, format!("Missing section \"{}\"", heading)),
+                }
+            }
+            Check::RequireMetric { metric } => {
+                let found = body
+                    .lines()
+                    .any(|line| contains_ignore_case(line, metric) && line.chars().any(|c| c.is_ascii_digit()));
+                match found {
+                    true => (true, format!("Metric \"{}\" reported", metric)),
+                    false => (false, format!("No figure given for \"{}\"", metric)),
+                }
+            }
+            Check::RequireEntities { names, min } => {
+                let mentioned: Vec<&String> = names.iter().filter(|name| contains_ignore_case(body, name)).collect();
+                (
+                    mentioned.len() >= *min,
+                    format!("{} of {} entities mentioned (need {})", mentioned.len(), names.len(), min),
+                )
+            }
+            Check::RequireFrontMatter { keys } => {
+                let missing: Vec<&str> = keys
+                    .iter()
+                    .filter(|key| match metadata.get(key.as_str()) {
+                        None | Some(serde_yaml::Value::Null) => true,
+                        Some(serde_yaml::Value::String(s)) => s.trim().is_empty(),
+                        _ => false,
+                    })
+                    .map(|key| key.as_str())
+                    .collect();
+                match missing.is_empty() {
+                    true => (true, "All required front matter present".to_string()),
+                    false => (false, format!("Missing front matter: {}", missing.join(", "))),
+                }
+            }
+        }
+    }
+}
+
+/// Fail an export when any error-severity rule does not pass
+pub fn preflight(markdown: &str, policy_file: &str) -> PyResult<()> {
+    let policy = Policy::load(Path::new(policy_file))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to load policy: {}", e)))?;
+    let failures: Vec<String> = policy
+        .evaluate(markdown)
+        .into_iter()
+        .filter(|result| !result.passed && result.severity == Severity::Error)
+        .map(|result| format!("[{}] {}", result.id, result.message))
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Report failed policy checks:\n{}", failures.join("\n"))
+        ))
+    }
+}
+
+/// Evaluate a report against a YAML/JSON policy file and return a pass/fail report
+#[pyfunction]
+pub fn evaluate_policies(markdown: &str, policy_file: &str, py: Python) -> PyResult<PyObject> {
+    guard("evaluate_policies", || {
+        let policy = Policy::load(Path::new(policy_file))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to load policy: {}", e)))?;
+        let results = policy.evaluate(markdown);
+
+        let count = |severity: Severity| results.iter().filter(|r| !r.passed && r.severity == severity).count();
+        let errors = count(Severity::Error);
+
+        let list = PyList::empty(py);
+        for result in &results {
+            let entry = PyDict::new(py);
+            entry.set_item("id", &result.id)?;
+            entry.set_item("severity", result.severity.as_str())?;
+            entry.set_item("passed", result.passed)?;
+            entry.set_item("message", &result.message)?;
+            list.append(entry)?;
+        }
+
+        let report = PyDict::new(py);
+        report.set_item("passed", errors == 0)?;
+        report.set_item("errors", errors)?;
+        report.set_item("warnings", count(Severity::Warning))?;
+        report.set_item("results", list)?;
+        Ok(report.into())
+    })
+}
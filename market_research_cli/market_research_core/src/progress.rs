@@ -0,0 +1,1164 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities;
+use crate::panics::{guard, lock};
+use crate::sse;
+use crate::write_atomic;
+
+/// Updates kept in the history by default
+const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// Percentage step between callback notifications when no thresholds are given
+const DEFAULT_THRESHOLD_STEP: f32 = 10.0;
+
+/// Narrowest bar `render_bar` draws, however little room is left
+const MIN_BAR_WIDTH: usize = 10;
+
+/// Version of the `save_state` file format
+pub const STATE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ProgressData {
+    percentage: f32,
+    stage: String,
+    agent: String,
+    activity: String,
+}
+
+impl ProgressData {
+    fn initial() -> Self {
+        ProgressData {
+            percentage: 0.0,
+            stage: "Initializing".to_string(),
+            agent: "System".to_string(),
+            activity: "Starting up".to_string(),
+        }
+    }
+}
+
+/// One recorded `update()` call
+#[derive(Serialize, Deserialize, Clone)]
+struct ProgressEvent {
+    timestamp: String,
+    /// Seconds since the root tracker started
+    elapsed_seconds: f32,
+    /// Seconds since the previous recorded update, i.e. how long the previous step took
+    since_previous: f32,
+    /// Path of the tracker that was updated, e.g. `Overall/Research`
+    tracker: String,
+    percentage: f32,
+    stage: String,
+    agent: String,
+    activity: String,
+}
+
+impl ProgressEvent {
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("timestamp", &self.timestamp)?;
+        dict.set_item("elapsed_seconds", self.elapsed_seconds)?;
+        dict.set_item("since_previous", self.since_previous)?;
+        dict.set_item("tracker", &self.tracker)?;
+        dict.set_item("percentage", self.percentage)?;
+        dict.set_item("stage", &self.stage)?;
+        dict.set_item("agent", &self.agent)?;
+        dict.set_item("activity", &self.activity)?;
+        Ok(dict)
+    }
+}
+
+/// One tracker in the hierarchy; a node with children reports their weighted progress
+struct ProgressNode {
+    name: String,
+    parent: Option<usize>,
+    weight: f32,
+    data: ProgressData,
+    children: Vec<usize>,
+    /// Whether the children are a stage plan declared with `set_stages`, updated through this tracker
+    staged: bool,
+    /// When the clock last started; `None` while the run is paused
+    running_since: Option<Instant>,
+    /// Seconds accumulated before `running_since`: time before a pause, or a run restored with `load_state`
+    carried_seconds: f32,
+    /// Tree clock value at the last update, to find the most recent activity in a subtree
+    updated: u64,
+    /// Work units counted with `increment_items`, e.g. pages searched
+    items: u64,
+    item_unit: Option<String>,
+    tokens: u64,
+}
+
+impl ProgressNode {
+    fn new(name: &str, parent: Option<usize>, weight: f32, data: ProgressData, paused: bool) -> Self {
+        ProgressNode {
+            name: name.to_string(),
+            parent,
+            weight,
+            data,
+            children: Vec::new(),
+            staged: false,
+            running_since: (!paused).then(Instant::now),
+            carried_seconds: 0.0,
+            updated: 0,
+            items: 0,
+            item_unit: None,
+            tokens: 0,
+        }
+    }
+
+    fn elapsed_seconds(&self) -> f32 {
+        self.carried_seconds + self.running_since.map(|since| since.elapsed().as_secs_f32()).unwrap_or(0.0)
+    }
+
+    /// Stop the clock, keeping the time so far
+    fn pause(&mut self) {
+        self.carried_seconds = self.elapsed_seconds();
+        self.running_since = None;
+    }
+}
+
+/// Time spent in each stage and by each agent, in order of first appearance. Between two updates anywhere in the
+/// tree, the time goes to the stage and agent of the earlier one; time before the first update counts as
+/// `Initializing` by `System`
+#[derive(Serialize, Deserialize, Clone)]
+struct Timings {
+    stages: Vec<(String, f32)>,
+    agents: Vec<(String, f32)>,
+    /// Stage and agent of the latest update, still accumulating time
+    current: (String, String),
+    /// Root elapsed seconds when `current` began
+    since: f32,
+}
+
+impl Timings {
+    fn new() -> Self {
+        let initial = ProgressData::initial();
+        Timings { stages: Vec::new(), agents: Vec::new(), current: (initial.stage, initial.agent), since: 0.0 }
+    }
+
+    fn add(totals: &mut Vec<(String, f32)>, name: &str, seconds: f32) {
+        match totals.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, total)) => *total += seconds,
+            None => totals.push((name.to_string(), seconds)),
+        }
+    }
+
+    /// Close the current period at root elapsed `now` and start one for `stage` and `agent`
+    fn switch(&mut self, now: f32, stage: &str, agent: &str) {
+        let seconds = (now - self.since).max(0.0);
+        Timings::add(&mut self.stages, &self.current.0, seconds);
+        Timings::add(&mut self.agents, &self.current.1, seconds);
+        self.current = (stage.to_string(), agent.to_string());
+        self.since = now;
+    }
+}
+
+/// Rows of a timings table: name, seconds and share of `total`
+fn timing_rows<'py>(totals: &[(String, f32)], total: f32, py: Python<'py>) -> PyResult<&'py PyList> {
+    let rows = PyList::empty(py);
+    for (name, seconds) in totals {
+        let row = PyDict::new(py);
+        row.set_item("name", name)?;
+        row.set_item("seconds", seconds)?;
+        row.set_item("share", if total > 0.0 { seconds / total } else { 0.0 })?;
+        rows.append(row)?;
+    }
+    Ok(rows)
+}
+
+/// Markdown table of one timings breakdown, longest first
+fn timings_table(heading: &str, totals: &[(String, f32)], total: f32) -> String {
+    let mut sorted: Vec<&(String, f32)> = totals.iter().collect();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut markdown = format!("| {} | Time | Share |\n|---|---:|---:|\n", heading);
+    for (name, seconds) in sorted {
+        let share = if total > 0.0 { seconds / total * 100.0 } else { 0.0 };
+        // Short stages keep a decimal so they do not all read `0s`
+        let time = if *seconds < 60.0 { format!("{:.1}s", seconds) } else { format_duration(*seconds) };
+        markdown.push_str(&format!("| {} | {} | {:.1}% |\n", name.replace('|', "\\|"), time, share));
+    }
+    markdown
+}
+
+/// A tracker as written by `save_state`
+#[derive(Serialize, Deserialize)]
+struct SavedNode {
+    name: String,
+    parent: Option<usize>,
+    weight: f32,
+    #[serde(flatten)]
+    data: ProgressData,
+    children: Vec<usize>,
+    #[serde(default)]
+    staged: bool,
+    elapsed_seconds: f32,
+    updated: u64,
+    #[serde(default)]
+    items: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    item_unit: Option<String>,
+    #[serde(default)]
+    tokens: u64,
+}
+
+/// The `save_state` file
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    version: u32,
+    saved_at: String,
+    clock: u64,
+    #[serde(default)]
+    paused: bool,
+    #[serde(default = "Timings::new")]
+    timings: Timings,
+    nodes: Vec<SavedNode>,
+    history: Vec<ProgressEvent>,
+}
+
+/// Whether saved `parent`/`children` links form a single tree rooted at node 0: every child names its parent back,
+/// every other node is listed by exactly one parent, and all nodes are reachable from the root. Anything else
+/// (a cycle, a stray link) would send the recursive roll-ups into endless recursion
+fn is_tree(parents: &[Option<usize>], children: &[&[usize]]) -> bool {
+    let count = parents.len();
+    if count == 0 || children.len() != count || parents[0].is_some() {
+        return false;
+    }
+    let mut listed = vec![0usize; count];
+    for (node, kids) in children.iter().enumerate() {
+        for &child in kids.iter() {
+            if child == 0 || child >= count || parents[child] != Some(node) {
+                return false;
+            }
+            listed[child] += 1;
+        }
+    }
+    if listed.iter().skip(1).any(|&times| times != 1) {
+        return false;
+    }
+
+    let mut seen = vec![false; count];
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        if std::mem::replace(&mut seen[node], true) {
+            return false;
+        }
+        stack.extend_from_slice(children[node]);
+    }
+    seen.iter().all(|&reached| reached)
+}
+
+/// A Python callable notified when a tracker's stage changes or its percentage crosses a threshold
+struct ProgressCallback {
+    id: usize,
+    /// Tracker whose rolled-up progress is watched
+    node: usize,
+    callback: PyObject,
+    thresholds: Vec<f32>,
+    last_stage: Option<String>,
+    last_percentage: f32,
+}
+
+/// All trackers created from one root, shared by every handle
+struct ProgressTree {
+    nodes: Vec<ProgressNode>,
+    clock: u64,
+    /// Bounded log of updates across the whole tree, oldest first
+    history: VecDeque<ProgressEvent>,
+    history_size: usize,
+    /// Root elapsed seconds at the last recorded update
+    last_recorded: Option<f32>,
+    /// Set by `pause`; every clock in the tree is stopped
+    paused: bool,
+    timings: Timings,
+    callbacks: Vec<ProgressCallback>,
+    next_callback: usize,
+    /// Stop flag of the running `serve()` endpoint
+    server: Option<Arc<AtomicBool>>,
+    /// Set by `request_cancel`; shared with every handle so workers can poll it without the lock
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProgressTree {
+    /// Names from the root down to `id`, joined with `/`
+    fn path(&self, id: usize) -> String {
+        let mut names = vec![self.nodes[id].name.as_str()];
+        let mut current = self.nodes[id].parent;
+        while let Some(parent) = current {
+            names.push(self.nodes[parent].name.as_str());
+            current = self.nodes[parent].parent;
+        }
+        names.reverse();
+        names.join("/")
+    }
+
+    fn record(&mut self, id: usize) {
+        if self.history_size == 0 {
+            return;
+        }
+        // Measured on the root clock, so time spent paused does not count towards a step
+        let elapsed_seconds = self.nodes[0].elapsed_seconds();
+        let since_previous = self.last_recorded.map(|last| (elapsed_seconds - last).max(0.0)).unwrap_or(elapsed_seconds);
+        self.last_recorded = Some(elapsed_seconds);
+        let data = &self.nodes[id].data;
+        let event = ProgressEvent {
+            timestamp: Local::now().to_rfc3339(),
+            elapsed_seconds,
+            since_previous,
+            tracker: self.path(id),
+            percentage: data.percentage,
+            stage: data.stage.clone(),
+            agent: data.agent.clone(),
+            activity: data.activity.clone(),
+        };
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+        self.history.push_back(event);
+    }
+
+    /// Own percentage for leaves, weighted mean of the children otherwise
+    fn percentage(&self, id: usize) -> f32 {
+        let node = &self.nodes[id];
+        let total: f32 = node.children.iter().map(|&child| self.nodes[child].weight).sum();
+        if node.children.is_empty() || total <= 0.0 {
+            return node.data.percentage;
+        }
+        node.children
+            .iter()
+            .map(|&child| self.nodes[child].weight * self.percentage(child).clamp(0.0, 100.0))
+            .sum::<f32>()
+            / total
+    }
+
+    /// The child of staged tracker `id` for `stage`; stages declared before it are marked finished, so the
+    /// overall percentage never falls back when the run moves on
+    fn stage_node(&mut self, id: usize, stage: &str) -> PyResult<usize> {
+        let children = self.nodes[id].children.clone();
+        let position = match children.iter().position(|&child| self.nodes[child].name == stage) {
+            Some(position) => position,
+            None => {
+                let names: Vec<&str> = children.iter().map(|&child| self.nodes[child].name.as_str()).collect();
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Stage '{}' is not in the declared stage plan ({})",
+                    stage,
+                    names.join(", ")
+                )));
+            }
+        };
+        for &earlier in &children[..position] {
+            // Stages with their own children roll up from them and are left alone
+            if self.nodes[earlier].children.is_empty() && self.nodes[earlier].data.percentage < 100.0 {
+                self.nodes[earlier].data.percentage = 100.0;
+            }
+        }
+        Ok(children[position])
+    }
+
+    /// Whether `ancestor` is `id` or one of its parents
+    fn contains(&self, ancestor: usize, id: usize) -> bool {
+        let mut current = Some(id);
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.nodes[node].parent;
+        }
+        false
+    }
+
+    /// Items and tokens counted in a subtree, with the unit of the first tracker that names one
+    fn counts(&self, id: usize) -> (u64, u64, Option<String>) {
+        let node = &self.nodes[id];
+        node.children.iter().fold((node.items, node.tokens, node.item_unit.clone()), |(items, tokens, unit), &child| {
+            let (child_items, child_tokens, child_unit) = self.counts(child);
+            (items + child_items, tokens + child_tokens, unit.or(child_unit))
+        })
+    }
+
+    /// Counts and average rates since the tracker started, excluding time spent paused
+    fn set_throughput(&self, id: usize, dict: &PyDict) -> PyResult<()> {
+        let (items, tokens, unit) = self.counts(id);
+        let elapsed = self.nodes[id].elapsed_seconds();
+        let rate = |count: u64| if elapsed > 0.0 { count as f32 / elapsed } else { 0.0 };
+        dict.set_item("items", items)?;
+        dict.set_item("item_unit", unit.unwrap_or_else(|| "items".to_string()))?;
+        dict.set_item("items_per_second", rate(items))?;
+        dict.set_item("tokens", tokens)?;
+        dict.set_item("tokens_per_second", rate(tokens))?;
+        Ok(())
+    }
+
+    /// Seconds left at the average pace so far, unknown before any progress
+    fn eta_seconds(&self, id: usize) -> Option<f32> {
+        let percentage = self.percentage(id).clamp(0.0, 100.0);
+        if percentage <= 0.0 {
+            return None;
+        }
+        Some(self.nodes[id].elapsed_seconds() * (100.0 - percentage) / percentage)
+    }
+
+    /// The dict returned by `get_progress` for one tracker
+    fn progress_dict<'py>(&self, id: usize, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let data = &self.nodes[self.latest(id)].data;
+        let dict = PyDict::new(py);
+        dict.set_item("percentage", self.percentage(id))?;
+        dict.set_item("stage", &data.stage)?;
+        dict.set_item("agent", &data.agent)?;
+        dict.set_item("activity", &data.activity)?;
+        dict.set_item("elapsed_seconds", self.nodes[id].elapsed_seconds())?;
+        dict.set_item("eta_seconds", self.eta_seconds(id))?;
+        dict.set_item("cancelled", self.cancelled.load(Ordering::SeqCst))?;
+        dict.set_item("paused", self.paused)?;
+        self.set_throughput(id, dict)?;
+        Ok(dict)
+    }
+
+    /// The `get_progress` fields of one tracker as JSON, for the `serve()` endpoint
+    fn progress_json(&self, id: usize) -> serde_json::Value {
+        let data = &self.nodes[self.latest(id)].data;
+        let (items, tokens, unit) = self.counts(id);
+        serde_json::json!({
+            "tracker": self.path(id),
+            "percentage": self.percentage(id),
+            "stage": data.stage,
+            "agent": data.agent,
+            "activity": data.activity,
+            "elapsed_seconds": self.nodes[id].elapsed_seconds(),
+            "eta_seconds": self.eta_seconds(id),
+            "cancelled": self.cancelled.load(Ordering::SeqCst),
+            "paused": self.paused,
+            "items": items,
+            "item_unit": unit.unwrap_or_else(|| "items".to_string()),
+            "tokens": tokens,
+        })
+    }
+
+    /// Callbacks due after `id` was updated, with the progress to pass them
+    fn due_callbacks<'py>(&mut self, id: usize, py: Python<'py>) -> PyResult<Vec<(PyObject, &'py PyDict)>> {
+        let mut due = Vec::new();
+        for index in 0..self.callbacks.len() {
+            let node = self.callbacks[index].node;
+            if !self.contains(node, id) {
+                continue;
+            }
+            let percentage = self.percentage(node);
+            let stage = self.nodes[self.latest(node)].data.stage.clone();
+            let callback = &mut self.callbacks[index];
+            let stage_changed = callback.last_stage.as_ref() != Some(&stage);
+            let crossed = callback.thresholds.iter().any(|&t| callback.last_percentage < t && percentage >= t);
+            callback.last_stage = Some(stage);
+            callback.last_percentage = percentage;
+            if stage_changed || crossed {
+                let callback = callback.callback.clone_ref(py);
+                due.push((callback, self.progress_dict(node, py)?));
+            }
+        }
+        Ok(due)
+    }
+
+    /// The most recently updated node in a subtree, whose stage and activity describe it best
+    fn latest(&self, id: usize) -> usize {
+        self.nodes[id]
+            .children
+            .iter()
+            .map(|&child| self.latest(child))
+            .fold(id, |best, candidate| if self.nodes[candidate].updated > self.nodes[best].updated { candidate } else { best })
+    }
+
+    fn to_dict<'py>(&self, id: usize, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let node = &self.nodes[id];
+        let dict = PyDict::new(py);
+        dict.set_item("name", &node.name)?;
+        dict.set_item("weight", node.weight)?;
+        dict.set_item("percentage", self.percentage(id))?;
+        dict.set_item("stage", &node.data.stage)?;
+        dict.set_item("agent", &node.data.agent)?;
+        dict.set_item("activity", &node.data.activity)?;
+        dict.set_item("elapsed_seconds", node.elapsed_seconds())?;
+        self.set_throughput(id, dict)?;
+        let children = PyList::empty(py);
+        for &child in &node.children {
+            children.append(self.to_dict(child, py)?)?;
+        }
+        dict.set_item("children", children)?;
+        Ok(dict)
+    }
+}
+
+/// Thread-safe progress tracker for report generation
+#[pyclass]
+#[derive(Clone)]
+pub struct ProgressTracker {
+    tree: Arc<Mutex<ProgressTree>>,
+    node: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProgressTracker {
+    /// A new root tracker keeping `history_size` events
+    fn root(history_size: usize) -> Self {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        ProgressTracker {
+            tree: Arc::new(Mutex::new(ProgressTree {
+                nodes: vec![ProgressNode::new("Overall", None, 1.0, ProgressData::initial(), false)],
+                clock: 0,
+                history: VecDeque::new(),
+                history_size,
+                last_recorded: None,
+                paused: false,
+                timings: Timings::new(),
+                callbacks: Vec::new(),
+                next_callback: 0,
+                server: None,
+                cancelled: Arc::clone(&cancelled),
+            })),
+            node: 0,
+            cancelled,
+        }
+    }
+
+    /// Whether cancellation was requested, for workers on the Rust side
+    pub fn cancel_requested(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+#[pymethods]
+impl ProgressTracker {
+    #[new]
+    #[pyo3(signature = (history_size=DEFAULT_HISTORY_SIZE))]
+    fn new(history_size: usize) -> PyResult<Self> {
+        guard("ProgressTracker.new", || Ok(ProgressTracker::root(history_size)))
+    }
+
+    /// Update the progress of report generation. After `set_stages`, `percentage` is progress within `stage`,
+    /// which must be one of the declared stages; the stages before it count as finished
+    pub fn update(&self, percentage: f32, stage: &str, agent: &str, activity: &str, py: Python) -> PyResult<()> {
+        guard("ProgressTracker.update", || {
+            let due = {
+                let mut tree = lock(&self.tree);
+                let target = match tree.nodes[self.node].staged {
+                    true => tree.stage_node(self.node, stage)?,
+                    false => self.node,
+                };
+                tree.clock += 1;
+                let clock = tree.clock;
+                let node = &mut tree.nodes[target];
+                node.data.percentage = percentage;
+                node.data.stage = stage.to_string();
+                node.data.agent = agent.to_string();
+                node.data.activity = activity.to_string();
+                node.updated = clock;
+                let now = tree.nodes[0].elapsed_seconds();
+                tree.timings.switch(now, stage, agent);
+                tree.record(target);
+                tree.due_callbacks(target, py)?
+            };
+
+            // Call back without the lock held, so callbacks can query the tracker.
+            // A failing callback is reported but never interrupts the run it observes.
+            for (callback, progress) in due {
+                if let Err(e) = callback.call1(py, (progress,)) {
+                    e.print(py);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Count `n` finished work units (pages searched, sources read, ...), optionally naming the unit;
+    /// `get_progress` reports the total and the rate per second, summed over child trackers
+    #[pyo3(signature = (n=1, unit=None))]
+    fn increment_items(&self, n: u64, unit: Option<String>) -> PyResult<()> {
+        guard("ProgressTracker.increment_items", || {
+            let mut tree = lock(&self.tree);
+            let node = &mut tree.nodes[self.node];
+            node.items += n;
+            if unit.is_some() {
+                node.item_unit = unit;
+            }
+            Ok(())
+        })
+    }
+
+    /// Count `n` LLM tokens processed, reported as `tokens` and `tokens_per_second` by `get_progress`
+    fn add_tokens(&self, n: u64) -> PyResult<()> {
+        guard("ProgressTracker.add_tokens", || {
+            lock(&self.tree).nodes[self.node].tokens += n;
+            Ok(())
+        })
+    }
+
+    /// Call `callback(progress)` whenever this tracker's stage changes or its percentage crosses one of
+    /// `thresholds` (every 10% by default); `progress` is the `get_progress()` dict. Returns an id for `remove_callback`
+    #[pyo3(signature = (callback, thresholds=None))]
+    fn add_callback(&self, callback: PyObject, thresholds: Option<Vec<f32>>, py: Python) -> PyResult<usize> {
+        guard("ProgressTracker.add_callback", || {
+            if !callback.as_ref(py).is_callable() {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("callback must be callable"));
+            }
+            let thresholds = thresholds.unwrap_or_else(|| (1..=10).map(|step| step as f32 * DEFAULT_THRESHOLD_STEP).collect());
+            let mut tree = lock(&self.tree);
+            let id = tree.next_callback;
+            tree.next_callback += 1;
+            let last_percentage = tree.percentage(self.node);
+            let last_stage = Some(tree.nodes[tree.latest(self.node)].data.stage.clone());
+            tree.callbacks.push(ProgressCallback { id, node: self.node, callback, thresholds, last_stage, last_percentage });
+            Ok(id)
+        })
+    }
+
+    /// Stop calling a callback; returns false if the id is unknown
+    fn remove_callback(&self, callback_id: usize) -> PyResult<bool> {
+        guard("ProgressTracker.remove_callback", || {
+            let mut tree = lock(&self.tree);
+            let before = tree.callbacks.len();
+            tree.callbacks.retain(|callback| callback.id != callback_id);
+            Ok(tree.callbacks.len() != before)
+        })
+    }
+
+    /// Create (or return the existing) child tracker for an agent or stage.
+    /// Once a tracker has children, its percentage is their weighted average.
+    #[pyo3(signature = (name, weight=1.0))]
+    fn create_child(&self, name: &str, weight: f32) -> PyResult<ProgressTracker> {
+        guard("ProgressTracker.create_child", || {
+            if !(weight >= 0.0 && weight.is_finite()) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Weight must be a non-negative number"));
+            }
+            let mut tree = lock(&self.tree);
+            let existing = tree.nodes[self.node].children.iter().copied().find(|&child| tree.nodes[child].name == name);
+            let child = match existing {
+                Some(child) => {
+                    tree.nodes[child].weight = weight;
+                    child
+                }
+                None => {
+                    let data = ProgressData {
+                        percentage: 0.0,
+                        stage: name.to_string(),
+                        agent: name.to_string(),
+                        activity: "Waiting".to_string(),
+                    };
+                    let paused = tree.paused;
+                    tree.nodes.push(ProgressNode::new(name, Some(self.node), weight, data, paused));
+                    let child = tree.nodes.len() - 1;
+                    tree.nodes[self.node].children.push(child);
+                    child
+                }
+            };
+            Ok(ProgressTracker { tree: Arc::clone(&self.tree), node: child, cancelled: Arc::clone(&self.cancelled) })
+        })
+    }
+
+    /// Declare the stages of this tracker's run up front as `(name, weight)` pairs in order, e.g.
+    /// `[("search", 0.4), ("analysis", 0.4), ("writing", 0.2)]`. Each stage becomes a child tracker (returned in
+    /// order), `update()` then takes per-stage percentages, and the overall percentage is their weighted sum.
+    /// Redeclaring replaces the plan; children not in it are detached
+    fn set_stages(&self, stages: Vec<(String, f32)>) -> PyResult<Vec<ProgressTracker>> {
+        guard("ProgressTracker.set_stages", || {
+            if stages.is_empty() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("A stage plan needs at least one stage"));
+            }
+            for (index, (name, weight)) in stages.iter().enumerate() {
+                if !(*weight >= 0.0 && weight.is_finite()) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Weight of stage '{}' must be a non-negative number",
+                        name
+                    )));
+                }
+                if stages[..index].iter().any(|(other, _)| other == name) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Stage '{}' is declared twice", name)));
+                }
+            }
+            if stages.iter().map(|(_, weight)| weight).sum::<f32>() <= 0.0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Stage weights must not all be zero"));
+            }
+
+            let children = stages
+                .iter()
+                .map(|(name, weight)| self.create_child(name, *weight))
+                .collect::<PyResult<Vec<_>>>()?;
+            let mut tree = lock(&self.tree);
+            let node = &mut tree.nodes[self.node];
+            node.children = children.iter().map(|child| child.node).collect();
+            node.staged = true;
+            Ok(children)
+        })
+    }
+
+    /// Get the current progress data; with children, stage, agent and activity come from the most recent update
+    fn get_progress(&self, py: Python) -> PyResult<PyObject> {
+        guard("ProgressTracker.get_progress", || {
+            let tree = lock(&self.tree);
+            Ok(tree.progress_dict(self.node, py)?.into())
+        })
+    }
+
+    /// Get this tracker and its children as a nested dict with rolled-up percentages
+    fn get_tree(&self, py: Python) -> PyResult<PyObject> {
+        guard("ProgressTracker.get_tree", || {
+            let tree = lock(&self.tree);
+            Ok(tree.to_dict(self.node, py)?.into())
+        })
+    }
+
+    /// Get the last `n` recorded updates (all kept updates by default), oldest first
+    #[pyo3(signature = (n=None))]
+    fn get_history(&self, n: Option<usize>, py: Python) -> PyResult<PyObject> {
+        guard("ProgressTracker.get_history", || {
+            let tree = lock(&self.tree);
+            let skip = n.map(|n| tree.history.len().saturating_sub(n)).unwrap_or(0);
+            let result = PyList::empty(py);
+            for event in tree.history.iter().skip(skip) {
+                result.append(event.to_dict(py)?)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Write the recorded updates to a JSON file; returns the number of events written
+    fn dump_history_json(&self, path: &str, py: Python) -> PyResult<usize> {
+        guard("ProgressTracker.dump_history_json", || {
+            let events: Vec<ProgressEvent> = lock(&self.tree).history.iter().cloned().collect();
+            let json = serde_json::to_vec_pretty(&events)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to serialize progress history: {}", e)))?;
+            py.allow_threads(|| write_atomic(Path::new(path), &json))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write progress history: {}", e)))?;
+            Ok(events.len())
+        })
+    }
+
+    /// Ask the run to stop; workers polling `is_cancelled()` on any tracker in the tree wind down gracefully
+    fn request_cancel(&self) -> PyResult<()> {
+        guard("ProgressTracker.request_cancel", || {
+            self.cancelled.store(true, Ordering::SeqCst);
+            Ok(())
+        })
+    }
+
+    /// Whether cancellation was requested; lock-free, so cheap enough to poll in tight loops
+    fn is_cancelled(&self) -> PyResult<bool> {
+        guard("ProgressTracker.is_cancelled", || Ok(self.cancel_requested()))
+    }
+
+    /// Pause the whole run: elapsed times on every tracker in the tree stop until `resume()`.
+    /// Updates are still recorded while paused; returns false if the run was already paused
+    fn pause(&self) -> PyResult<bool> {
+        guard("ProgressTracker.pause", || {
+            let mut tree = lock(&self.tree);
+            if tree.paused {
+                return Ok(false);
+            }
+            tree.paused = true;
+            tree.nodes.iter_mut().for_each(ProgressNode::pause);
+            Ok(true)
+        })
+    }
+
+    /// Restart the clocks stopped by `pause()`; returns false if the run was not paused
+    fn resume(&self) -> PyResult<bool> {
+        guard("ProgressTracker.resume", || {
+            let mut tree = lock(&self.tree);
+            if !tree.paused {
+                return Ok(false);
+            }
+            tree.paused = false;
+            let now = Instant::now();
+            tree.nodes.iter_mut().for_each(|node| node.running_since = Some(now));
+            Ok(true)
+        })
+    }
+
+    /// Whether the run is paused
+    fn is_paused(&self) -> PyResult<bool> {
+        guard("ProgressTracker.is_paused", || {
+            Ok(lock(&self.tree).paused)
+        })
+    }
+
+    /// Save every tracker in the tree, with elapsed times and history, so a restarted run can resume
+    fn save_state(&self, path: &str, py: Python) -> PyResult<()> {
+        guard("ProgressTracker.save_state", || {
+            let json = {
+                let tree = lock(&self.tree);
+                let state = SavedState {
+                    version: STATE_VERSION,
+                    saved_at: Local::now().to_rfc3339(),
+                    clock: tree.clock,
+                    paused: tree.paused,
+                    timings: tree.timings.clone(),
+                    nodes: tree
+                        .nodes
+                        .iter()
+                        .map(|node| SavedNode {
+                            name: node.name.clone(),
+                            parent: node.parent,
+                            weight: node.weight,
+                            data: node.data.clone(),
+                            children: node.children.clone(),
+                            staged: node.staged,
+                            elapsed_seconds: node.elapsed_seconds(),
+                            updated: node.updated,
+                            items: node.items,
+                            item_unit: node.item_unit.clone(),
+                            tokens: node.tokens,
+                        })
+                        .collect(),
+                    history: tree.history.iter().cloned().collect(),
+                };
+                serde_json::to_vec_pretty(&state)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to serialize progress state: {}", e)))?
+            };
+            py.allow_threads(|| write_atomic(Path::new(path), &json))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to save progress state: {}", e)))
+        })
+    }
+
+    /// Restore a tree written by `save_state`; elapsed times continue from the saved values, paused if the run was.
+    /// Call on the root tracker, then get child handles again with `create_child`, which returns existing children
+    fn load_state(&self, path: &str, py: Python) -> PyResult<()> {
+        guard("ProgressTracker.load_state", || {
+            if self.node != 0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("State can only be loaded into the root tracker"));
+            }
+            let bytes = py
+                .allow_threads(|| fs::read(path))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read progress state: {}", e)))?;
+            let state: SavedState = serde_json::from_slice(&bytes)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Progress state is corrupted: {}", e)))?;
+
+            let count = state.nodes.len();
+            let parents: Vec<Option<usize>> = state.nodes.iter().map(|node| node.parent).collect();
+            let children: Vec<&[usize]> = state.nodes.iter().map(|node| node.children.as_slice()).collect();
+            if state.version != STATE_VERSION || !is_tree(&parents, &children) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unsupported or inconsistent progress state in {}", path)
+                ));
+            }
+
+            let mut tree = lock(&self.tree);
+            let previous_count = tree.nodes.len();
+            let now = Instant::now();
+            tree.nodes = state
+                .nodes
+                .into_iter()
+                .map(|saved| ProgressNode {
+                    name: saved.name,
+                    parent: saved.parent,
+                    weight: saved.weight,
+                    data: saved.data,
+                    children: saved.children,
+                    staged: saved.staged,
+                    running_since: (!state.paused).then_some(now),
+                    carried_seconds: saved.elapsed_seconds,
+                    updated: saved.updated,
+                    items: saved.items,
+                    item_unit: saved.item_unit,
+                    tokens: saved.tokens,
+                })
+                .collect();
+            // Handles created before loading keep pointing at valid, if detached, trackers
+            while tree.nodes.len() < previous_count {
+                tree.nodes.push(ProgressNode::new("Detached", None, 0.0, ProgressData::initial(), state.paused));
+            }
+            tree.clock = state.clock;
+            tree.paused = state.paused;
+            tree.timings = state.timings;
+            let skip = state.history.len().saturating_sub(tree.history_size);
+            tree.history = state.history.into_iter().skip(skip).collect();
+            tree.last_recorded = None;
+            tree.callbacks.retain(|callback| callback.node < count);
+            Ok(())
+        })
+    }
+
+    /// One-line progress bar fitted to `width` characters (the bar never shrinks below 10), e.g.
+    /// `Research · WebSearchAgent [██████░░░░░░] 45.0% ETA 1m20s`; `ascii` draws it with `#` and `-`
+    #[pyo3(signature = (width=80, ascii=false))]
+    fn render_bar(&self, width: usize, ascii: bool) -> PyResult<String> {
+        guard("ProgressTracker.render_bar", || {
+            let tree = lock(&self.tree);
+            let percentage = tree.percentage(self.node).clamp(0.0, 100.0);
+            let data = &tree.nodes[tree.latest(self.node)].data;
+            let status = match tree.paused {
+                true => "paused".to_string(),
+                false if percentage >= 100.0 => "done".to_string(),
+                false => format!("ETA {}", tree.eta_seconds(self.node).map(format_duration).unwrap_or_else(|| "--".to_string())),
+            };
+            let (filled_char, empty_char) = if ascii { ('#', '-') } else { ('█', '░') };
+            let separator = if ascii { " - " } else { " · " };
+
+            // The bar and figures get their room first; the label is shortened to fit
+            let figures = format!(" {:5.1}% {}", percentage, status);
+            let room = width.saturating_sub(figures.chars().count() + 2);
+            let mut label = format!("{}{}{}", data.stage, separator, data.agent);
+            let label_room = room.saturating_sub(MIN_BAR_WIDTH + 1);
+            if label.chars().count() > label_room {
+                label = match label_room {
+                    0 => String::new(),
+                    n => format!("{}{}", label.chars().take(n - 1).collect::<String>(), if ascii { '~' } else { '…' }),
+                };
+            }
+            let bar_width = match label.is_empty() {
+                true => room.max(MIN_BAR_WIDTH),
+                false => room.saturating_sub(label.chars().count() + 1).max(MIN_BAR_WIDTH),
+            };
+            let filled = ((percentage / 100.0) * bar_width as f32).round() as usize;
+            let bar = format!("{}{}", filled_char.to_string().repeat(filled), empty_char.to_string().repeat(bar_width - filled));
+            Ok(match label.is_empty() {
+                true => format!("[{}]{}", bar, figures),
+                false => format!("{} [{}]{}", label, bar, figures),
+            })
+        })
+    }
+
+    /// How long each stage and each agent took over the whole run, excluding time spent paused:
+    /// `{"total_seconds", "stages": [{"name", "seconds", "share"}], "agents": [...], "markdown"}`, where
+    /// `markdown` holds both breakdowns as tables, longest first, ready to append to a report
+    fn get_timings(&self, py: Python) -> PyResult<PyObject> {
+        guard("ProgressTracker.get_timings", || {
+            let tree = lock(&self.tree);
+            let total = tree.nodes[0].elapsed_seconds();
+            // The stage in progress has run until now
+            let mut timings = tree.timings.clone();
+            let (stage, agent) = timings.current.clone();
+            timings.switch(total, &stage, &agent);
+
+            let dict = PyDict::new(py);
+            dict.set_item("total_seconds", total)?;
+            dict.set_item("stages", timing_rows(&timings.stages, total, py)?)?;
+            dict.set_item("agents", timing_rows(&timings.agents, total, py)?)?;
+            let markdown = format!(
+                "{}\n{}",
+                timings_table("Stage", &timings.stages, total),
+                timings_table("Agent", &timings.agents, total)
+            );
+            dict.set_item("markdown", markdown)?;
+            Ok(dict.into())
+        })
+    }
+
+    /// Stream this tracker's progress over HTTP so a browser dashboard can follow the run without Python:
+    /// `GET /events` is a server-sent event stream with a JSON message (the `get_progress` fields) on every
+    /// change, checked every `interval` seconds, and `GET /progress` returns the current state. Port 0 picks a
+    /// free port; returns the bound port. Serving again replaces the previous endpoint; it runs until `stop_serving()`.
+    /// Other sites can read the endpoint from a browser only if their origin (or `*`) is given as `allow_origin`.
+    /// Requests must name `localhost`, a loopback address or the bound address in their `Host` header
+    #[pyo3(signature = (port=0, host="127.0.0.1", interval=0.5, allow_origin=None))]
+    fn serve(&self, port: u16, host: &str, interval: f64, allow_origin: Option<String>) -> PyResult<u16> {
+        guard("ProgressTracker.serve", || {
+            if !cfg!(feature = "server") {
+                return Err(capabilities::feature_missing_py("server"));
+            }
+            // `try_from` also rejects finite values too large for a `Duration`, which would panic
+            let interval = match std::time::Duration::try_from_secs_f64(interval) {
+                Ok(duration) if interval > 0.0 => duration,
+                _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Interval must be a positive number of seconds")),
+            };
+            if let Some(origin) = allow_origin.as_deref().filter(|origin| origin.is_empty() || origin.contains(['\r', '\n'])) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid allow_origin {:?}", origin)));
+            }
+            let listener = std::net::TcpListener::bind((host, port))
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to start progress server: {}", e)))?;
+            let bound = listener
+                .local_addr()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to start progress server: {}", e)))?
+                .port();
+
+            let tree = Arc::clone(&self.tree);
+            let node = self.node;
+            let snapshot: sse::Snapshot = Arc::new(move || {
+                let tree = lock(&tree);
+                let key = format!("{}:{}:{}", tree.clock, tree.paused, tree.cancelled.load(Ordering::SeqCst));
+                (key, tree.progress_json(node).to_string())
+            });
+            let stop = Arc::new(AtomicBool::new(false));
+            sse::spawn(listener, Arc::clone(&stop), interval, allow_origin, snapshot)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to start progress server: {}", e)))?;
+            if let Some(previous) = lock(&self.tree).server.replace(stop) {
+                previous.store(true, Ordering::SeqCst);
+            }
+            Ok(bound)
+        })
+    }
+
+    /// Stop the `serve()` endpoint and close its streams; returns false if none was running
+    fn stop_serving(&self) -> PyResult<bool> {
+        guard("ProgressTracker.stop_serving", || {
+            Ok(match lock(&self.tree).server.take() {
+                Some(stop) => {
+                    stop.store(true, Ordering::SeqCst);
+                    true
+                }
+                None => false,
+            })
+        })
+    }
+
+    /// Get elapsed time in seconds, excluding time spent paused
+    fn get_elapsed_seconds(&self) -> PyResult<f32> {
+        guard("ProgressTracker.get_elapsed_seconds", || {
+            let tree = lock(&self.tree);
+            Ok(tree.nodes[self.node].elapsed_seconds())
+        })
+    }
+
+    /// Reset the progress tracker, detaching its children; resetting the root also clears a cancellation request
+    fn reset(&self) -> PyResult<()> {
+        guard("ProgressTracker.reset", || {
+            if self.node == 0 {
+                self.cancelled.store(false, Ordering::SeqCst);
+            }
+            let mut tree = lock(&self.tree);
+            let paused = tree.paused;
+            let node = &mut tree.nodes[self.node];
+            node.data = ProgressData::initial();
+            node.children.clear();
+            node.staged = false;
+            node.running_since = (!paused).then(Instant::now);
+            node.carried_seconds = 0.0;
+            node.updated = 0;
+            node.items = 0;
+            node.item_unit = None;
+            node.tokens = 0;
+            if self.node == 0 {
+                tree.last_recorded = None;
+                tree.timings = Timings::new();
+            }
+            // Callbacks watching this tracker start counting thresholds again
+            let node = self.node;
+            for callback in tree.callbacks.iter_mut().filter(|callback| callback.node == node) {
+                callback.last_percentage = 0.0;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// `42s`, `3m05s` or `1h02m`
+fn format_duration(seconds: f32) -> String {
+    let seconds = seconds.max(0.0).round() as u64;
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+/// Root trackers by name, shared by every `TrackerRegistry` in the process
+static REGISTRY: Mutex<BTreeMap<String, ProgressTracker>> = Mutex::new(BTreeMap::new());
+
+/// Process-wide named trackers, so concurrent runs (one per topic) can be monitored together
+#[pyclass]
+pub struct TrackerRegistry;
+
+#[pymethods]
+impl TrackerRegistry {
+    #[new]
+    fn new() -> PyResult<Self> {
+        guard("TrackerRegistry.new", || {
+            Ok(TrackerRegistry)
+        })
+    }
+
+    /// The root tracker registered under `name`, created on first use
+    #[pyo3(signature = (name, history_size=DEFAULT_HISTORY_SIZE))]
+    fn get_or_create(&self, name: &str, history_size: usize) -> PyResult<ProgressTracker> {
+        guard("TrackerRegistry.get_or_create", || {
+            let mut registry = lock(&REGISTRY);
+            Ok(registry.entry(name.to_string()).or_insert_with(|| ProgressTracker::root(history_size)).clone())
+        })
+    }
+
+    /// The tracker registered under `name`, if any
+    fn get(&self, name: &str) -> PyResult<Option<ProgressTracker>> {
+        guard("TrackerRegistry.get", || {
+            Ok(lock(&REGISTRY).get(name).cloned())
+        })
+    }
+
+    /// Register an existing root tracker under `name`, replacing any tracker already there
+    fn register(&self, name: &str, tracker: &ProgressTracker) -> PyResult<()> {
+        guard("TrackerRegistry.register", || {
+            if tracker.node != 0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Only root trackers can be registered"));
+            }
+            lock(&REGISTRY).insert(name.to_string(), tracker.clone());
+            Ok(())
+        })
+    }
+
+    /// Forget a tracker, e.g. once its run is finished; returns false if the name is unknown.
+    /// Handles already given out keep working
+    fn remove(&self, name: &str) -> PyResult<bool> {
+        guard("TrackerRegistry.remove", || {
+            Ok(lock(&REGISTRY).remove(name).is_some())
+        })
+    }
+
+    /// Names of registered trackers, sorted
+    fn names(&self) -> PyResult<Vec<String>> {
+        guard("TrackerRegistry.names", || {
+            Ok(lock(&REGISTRY).keys().cloned().collect())
+        })
+    }
+
+    /// `get_progress()` of every registered tracker keyed by name, with its `get_tree()` under `tree` if asked
+    #[pyo3(signature = (include_tree=false))]
+    fn get_all_progress(&self, include_tree: bool, py: Python) -> PyResult<PyObject> {
+        guard("TrackerRegistry.get_all_progress", || {
+            // Snapshot the handles first so no tree lock is taken while holding the registry lock
+            let trackers: Vec<(String, ProgressTracker)> =
+                lock(&REGISTRY).iter().map(|(name, tracker)| (name.clone(), tracker.clone())).collect();
+            let result = PyDict::new(py);
+            for (name, tracker) in trackers {
+                let tree = lock(&tracker.tree);
+                let progress = tree.progress_dict(tracker.node, py)?;
+                if include_tree {
+                    progress.set_item("tree", tree.to_dict(tracker.node, py)?)?;
+                }
+                result.set_item(name, progress)?;
+            }
+            Ok(result.into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_tree;
+
+    #[test]
+    fn accepts_a_consistent_tree() {
+        assert!(is_tree(&[None, Some(0), Some(0), Some(1)], &[&[1, 2], &[3], &[], &[]]));
+        assert!(is_tree(&[None], &[&[]]));
+    }
+
+    #[test]
+    fn rejects_cycles_and_mismatched_links() {
+        // 1 and 2 list each other and neither hangs off the root
+        assert!(!is_tree(&[None, Some(2), Some(1)], &[&[], &[2], &[1]]));
+        // Child whose parent link points elsewhere
+        assert!(!is_tree(&[None, Some(0), Some(0)], &[&[1], &[2], &[]]));
+        // Node listed by two parents
+        assert!(!is_tree(&[None, Some(0), Some(1)], &[&[1, 2], &[2], &[]]));
+        // Node with a parent link that no parent lists
+        assert!(!is_tree(&[None, Some(0)], &[&[], &[]]));
+        // Root listed as a child, out-of-range child, parent on the root, empty tree
+        assert!(!is_tree(&[None, Some(0)], &[&[1], &[0]]));
+        assert!(!is_tree(&[None], &[&[5]]));
+        assert!(!is_tree(&[Some(0)], &[&[]]));
+        assert!(!is_tree(&[], &[]));
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::sections::parse_heading;
+use crate::stats::report_date;
+use crate::{lock_report, write_atomic};
+
+pub const QA_INDEX_FILE: &str = ".qa_index.json";
+
+/// Headings whose lists define numbered sources rather than make claims
+const REFERENCE_HEADINGS: &[&str] = &["sources", "references", "bibliography", "citations"];
+
+/// Words too common to tell claims apart
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "was", "were", "with", "that", "this", "from", "have", "has", "had", "its", "their",
+    "what", "which", "who", "how", "why", "when", "where", "does", "did", "into", "than", "about", "our", "not",
+];
+
+/// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// A declarative, cited sentence from a report
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Claim {
+    pub filename: String,
+    pub title: Option<String>,
+    pub date: String,
+    /// Nearest heading above the claim
+    pub heading: Option<String>,
+    /// 1-based line number in the report file
+    pub line: usize,
+    pub text: String,
+    /// Citation markers resolved to their reference entries where the report defines them
+    pub citations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// Every claim in the archive, with the hash of each report it came from
+#[derive(Serialize, Deserialize, Default)]
+pub struct QaIndex {
+    pub built_at: String,
+    pub files: HashMap<String, String>,
+    pub claims: Vec<Claim>,
+}
+
+/// How a question is matched against claims
+pub enum Query {
+    Keywords(String),
+    Embedding(Vec<f32>),
+}
+
+/// A claim and how well it answers the question
+pub struct Answer<'a> {
+    pub claim: &'a Claim,
+    pub score: f64,
+}
+
+fn index_path(reports_dir: &str) -> PathBuf {
+    Path::new(reports_dir).join(QA_INDEX_FILE)
+}
+
+impl QaIndex {
+    pub fn load(reports_dir: &str) -> Result<Self> {
+        let path = index_path(reports_dir);
+        if !path.exists() {
+            return Err(anyhow!("QA index has not been built; call build_qa_index() first"));
+        }
+        let _lock = lock_report(&path, false)?;
+        let bytes = fs::read(&path).context("Failed to read QA index")?;
+        serde_json::from_slice(&bytes).context("QA index is corrupted")
+    }
+
+    pub fn save(&self, reports_dir: &str) -> Result<()> {
+        let path = index_path(reports_dir);
+        let _lock = lock_report(&path, true)?;
+        write_atomic(&path, &serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+}
+
+pub fn terms(text: &str) -> Vec<String> {
+    let word = Regex::new(r"[A-Za-z0-9][A-Za-z0-9'\-]*").unwrap();
+    word.find_iter(text)
+        .map(|m| m.as_str().to_lowercase())
+        .filter(|w| w.len() > 1 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Reference definitions (`[1]: url`, `[^1]: text`) and numbered entries under a Sources heading
+fn reference_entries(body: &str) -> HashMap<String, String> {
+    let definition = Regex::new(r"^\s*\[\^?([^\]]+)\]:\s*(.+)$").unwrap();
+    let numbered = Regex::new(r"^\s*(?:\[(\d+)\]|(\d+)[.)])\s+(.+)$").unwrap();
+    let mut entries = HashMap::new();
+    let mut in_references = false;
+    for line in body.lines() {
+        if let Some((_, heading)) = parse_heading(line) {
+            let heading = heading.to_lowercase();
+            in_references = REFERENCE_HEADINGS.iter().any(|name| heading.contains(name));
+            continue;
+        }
+        if let Some(caps) = definition.captures(line) {
+            entries.insert(caps[1].to_string(), caps[2].trim().to_string());
+        } else if in_references {
+            if let Some(caps) = numbered.captures(line) {
+                let number = caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str().to_string()).unwrap_or_default();
+                entries.insert(number, caps[3].trim().to_string());
+            }
+        }
+    }
+    entries
+}
+
+/// A declarative sentence of report prose
+pub struct ProseSentence {
+    /// 1-based line number in the report file
+    pub line: usize,
+    /// Nearest heading above the sentence
+    pub heading: Option<String>,
+    pub text: String,
+}
+
+/// Statements in a report body: no headings, code, tables, reference lists, questions or fragments
+pub fn prose_sentences(content: &str) -> Vec<ProseSentence> {
+    let body = crate::parse_report_metadata(content)
+        .map(|(_, body)| body)
+        .unwrap_or_else(|_| content.to_string());
+    // Line numbers are reported against the file, so account for the front matter
+    let offset = content.lines().count() - body.lines().count();
+    let sentence_end = Regex::new(r"[.!?](?:\[\^?\d+\])*\s+").unwrap();
+    let list_marker = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+").unwrap();
+
+    let mut sentences = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut in_fence = false;
+    let mut in_references = false;
+    for (idx, line) in body.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || trimmed.is_empty() || trimmed.starts_with('|') {
+            continue;
+        }
+        if let Some((_, text)) = parse_heading(line) {
+            in_references = REFERENCE_HEADINGS.iter().any(|name| text.to_lowercase().contains(name));
+            heading = Some(text);
+            continue;
+        }
+        if in_references || trimmed.starts_with('[') && trimmed.contains("]:") {
+            continue;
+        }
+
+        let text = list_marker.replace(trimmed, "");
+        let text = text.trim_start_matches('>').trim();
+        let mut start = 0;
+        let mut pieces = Vec::new();
+        for end in sentence_end.find_iter(text) {
+            pieces.push(&text[start..end.end()]);
+            start = end.end();
+        }
+        pieces.push(&text[start..]);
+
+        for sentence in pieces.into_iter().map(str::trim) {
+            if sentence.ends_with('?') || sentence.split_whitespace().count() < 5 {
+                continue;
+            }
+            sentences.push(ProseSentence { line: offset + idx + 1, heading: heading.clone(), text: sentence.to_string() });
+        }
+    }
+    sentences
+}
+
+/// Declarative sentences that carry a citation, with their provenance
+pub fn extract_claims(filename: &str, content: &str, modified: Option<std::time::SystemTime>) -> Vec<Claim> {
+    let (metadata, body) = crate::parse_report_metadata(content).unwrap_or_else(|_| (HashMap::new(), content.to_string()));
+    let title = metadata.get("title").cloned();
+    let date = report_date(filename, content, modified).format("%Y-%m-%d").to_string();
+    let references = reference_entries(&body);
+
+    let marker = Regex::new(r"\[\^?(\d+)\]").unwrap();
+    let link = Regex::new(r"\[[^\]]+\]\((https?://[^)\s]+)[^)]*\)").unwrap();
+    let attribution = Regex::new(r"\((?:[Ss]ource|[Pp]er|[Aa]ccording to):?\s*([^()]+)\)").unwrap();
+
+    prose_sentences(content)
+        .into_iter()
+        .filter_map(|sentence| {
+            let mut citations: Vec<String> = marker
+                .captures_iter(&sentence.text)
+                .map(|caps| references.get(&caps[1]).cloned().unwrap_or_else(|| format!("[{}]", &caps[1])))
+                .collect();
+            citations.extend(link.captures_iter(&sentence.text).map(|caps| caps[1].to_string()));
+            citations.extend(attribution.captures_iter(&sentence.text).map(|caps| caps[1].trim().to_string()));
+            if citations.is_empty() {
+                return None;
+            }
+            citations.dedup();
+            Some(Claim {
+                filename: filename.to_string(),
+                title: title.clone(),
+                date: date.clone(),
+                heading: sentence.heading,
+                line: sentence.line,
+                text: sentence.text,
+                citations,
+                embedding: None,
+            })
+        })
+        .collect()
+}
+
+/// Extract claims from every report; unchanged reports reuse their claims (and embeddings) from `previous`
+pub fn build_index(reports_dir: &str, files: &[String], previous: Option<&QaIndex>) -> QaIndex {
+    let results: Vec<(String, String, Vec<Claim>)> = files
+        .par_iter()
+        .filter_map(|filename| {
+            let path = Path::new(reports_dir).join(filename);
+            let content = {
+                let _lock = lock_report(&path, false).ok()?;
+                fs::read_to_string(&path).ok()?
+            };
+            let hash = crate::sha256_hex(content.as_bytes());
+            if let Some(previous) = previous.filter(|p| p.files.get(filename) == Some(&hash)) {
+                let claims = previous.claims.iter().filter(|c| &c.filename == filename).cloned().collect();
+                return Some((filename.clone(), hash, claims));
+            }
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            Some((filename.clone(), hash, extract_claims(filename, &content, modified)))
+        })
+        .collect();
+
+    let mut index = QaIndex { built_at: Local::now().to_rfc3339(), ..Default::default() };
+    for (filename, hash, claims) in results {
+        index.files.insert(filename, hash);
+        index.claims.extend(claims);
+    }
+    index
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| (*x as f64) * (*y as f64)).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+impl QaIndex {
+    /// Best-matching claims, highest score first. Keywords are ranked with BM25;
+    /// an embedding is compared by cosine similarity with the embeddings stored at build time.
+    pub fn answer(&self, query: &Query, limit: usize) -> Result<Vec<Answer<'_>>> {
+        let mut answers: Vec<Answer> = match query {
+            Query::Keywords(question) => {
+                let question: HashSet<String> = terms(question).into_iter().collect();
+                if question.is_empty() {
+                    return Err(anyhow!("Question has no searchable words"));
+                }
+                let documents: Vec<Vec<String>> = self.claims.iter().map(|claim| terms(&claim.text)).collect();
+                let count = documents.len().max(1) as f64;
+                let average_length = documents.iter().map(|d| d.len()).sum::<usize>() as f64 / count;
+                let frequency: HashMap<&String, usize> = question
+                    .iter()
+                    .map(|term| (term, documents.iter().filter(|d| d.contains(term)).count()))
+                    .collect();
+
+                self.claims
+                    .iter()
+                    .zip(&documents)
+                    .map(|(claim, document)| {
+                        let length_norm = 1.0 - B + B * document.len() as f64 / average_length.max(1.0);
+                        let score = question
+                            .iter()
+                            .map(|term| {
+                                let tf = document.iter().filter(|word| *word == term).count() as f64;
+                                let df = frequency[term] as f64;
+                                let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                                idf * tf * (K1 + 1.0) / (tf + K1 * length_norm)
+                            })
+                            .sum();
+                        Answer { claim, score }
+                    })
+                    .filter(|answer| answer.score > 0.0)
+                    .collect()
+            }
+            Query::Embedding(vector) => {
+                if !self.claims.iter().any(|claim| claim.embedding.is_some()) {
+                    return Err(anyhow!("QA index has no embeddings; rebuild it with an embed function"));
+                }
+                self.claims
+                    .iter()
+                    .filter_map(|claim| {
+                        let embedding = claim.embedding.as_ref()?;
+                        if embedding.len() != vector.len() {
+                            return None;
+                        }
+                        Some(Answer { claim, score: cosine(vector, embedding) })
+                    })
+                    .collect()
+            }
+        };
+        // Newer reports win ties, since they usually carry the more current figure
+        answers.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| b.claim.date.cmp(&a.claim.date)));
+        answers.truncate(limit);
+        Ok(answers)
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::panics::guard;
+use crate::stats::parse_date;
+
+/// Exponential recency decay applied on top of a relevance score
+#[derive(Clone, Copy, Debug)]
+pub struct FreshnessDecay {
+    /// Age at which a source keeps half its weight; `None` disables decay (e.g. for history sections)
+    pub half_life_days: Option<f64>,
+    /// Weight old sources decay towards instead of zero, so a highly relevant old source can still rank
+    pub floor: f64,
+    /// Weight for sources without a usable date
+    pub undated_weight: f64,
+}
+
+impl FreshnessDecay {
+    /// Weight in `[floor, 1]` for a source published `age_days` ago; future dates count as brand new
+    pub fn weight(&self, age_days: Option<f64>) -> f64 {
+        let half_life = match self.half_life_days {
+            Some(half_life) if half_life > 0.0 => half_life,
+            _ => return 1.0,
+        };
+        match age_days {
+            Some(age) => self.floor + (1.0 - self.floor) * 0.5f64.powf(age.max(0.0) / half_life),
+            None => self.undated_weight,
+        }
+    }
+}
+
+/// Age in fractional days of a date string, relative to `now`
+pub fn age_days(date: &str, now: NaiveDateTime) -> Option<f64> {
+    let date = parse_date(date)?;
+    Some((now - date).num_seconds() as f64 / 86400.0)
+}
+
+/// Per-query ranking options; see `rank_by_freshness`
+struct RankOptions {
+    date_key: String,
+    score_key: String,
+    floor: f64,
+    undated_weight: f64,
+    now: NaiveDateTime,
+}
+
+impl RankOptions {
+    fn from_dict(options: Option<&PyDict>) -> PyResult<Self> {
+        let mut rank_options = RankOptions {
+            date_key: "date".to_string(),
+            score_key: "score".to_string(),
+            floor: 0.0,
+            undated_weight: 0.5,
+            now: Local::now().naive_local(),
+        };
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(rank_options),
+        };
+
+        if let Some(value) = options.get_item("date_key") {
+            rank_options.date_key = value.extract()?;
+        }
+        if let Some(value) = options.get_item("score_key") {
+            rank_options.score_key = value.extract()?;
+        }
+        if let Some(value) = options.get_item("floor") {
+            rank_options.floor = value.extract()?;
+        }
+        if let Some(value) = options.get_item("undated_weight") {
+            rank_options.undated_weight = value.extract()?;
+        }
+        if let Some(value) = options.get_item("now") {
+            let now: String = value.str()?.to_string();
+            rank_options.now = parse_date(&now).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid date for now: {}", now))
+            })?;
+        }
+        if !(0.0..=1.0).contains(&rank_options.floor) || !(0.0..=1.0).contains(&rank_options.undated_weight) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "floor and undated_weight must be between 0 and 1"
+            ));
+        }
+        Ok(rank_options)
+    }
+}
+
+/// Re-rank retrieval results by `score × freshness`, newest first on ties. `half_life_days=None`
+/// disables decay. Options: `date_key` ("date"), `score_key` ("score"), `floor` (0.0),
+/// `undated_weight` (0.5) and `now`. Returns copies of the result dicts with `freshness` and
+/// `final_score` added; results without a score count as 1.0, so plain search hits are ordered by recency.
+#[pyfunction]
+#[pyo3(signature = (results, half_life_days=None, options=None))]
+pub fn rank_by_freshness(results: &PyList, half_life_days: Option<f64>, options: Option<&PyDict>, py: Python) -> PyResult<PyObject> {
+    guard("rank_by_freshness", || {
+        let options = RankOptions::from_dict(options)?;
+        let decay = FreshnessDecay { half_life_days, floor: options.floor, undated_weight: options.undated_weight };
+
+        let mut ranked = Vec::with_capacity(results.len());
+        for result in results.iter() {
+            let result: &PyDict = result.downcast()?;
+            let score: f64 = match result.get_item(options.score_key.as_str()) {
+                Some(score) if !score.is_none() => score.extract()?,
+                _ => 1.0,
+            };
+            let date = match result.get_item(options.date_key.as_str()) {
+                Some(date) if !date.is_none() => Some(date.str()?.to_string()),
+                _ => None,
+            };
+            let age = date.as_deref().and_then(|date| age_days(date, options.now));
+            let freshness = decay.weight(age);
+
+            let ranked_result = result.copy()?;
+            ranked_result.set_item("freshness", freshness)?;
+            ranked_result.set_item("final_score", score * freshness)?;
+            ranked.push((score * freshness, age, ranked_result));
+        }
+
+        ranked.sort_by(|(a_score, a_age, _), (b_score, b_age, _)| {
+            let newer = |age: &Option<f64>| age.map(|age| -age).unwrap_or(f64::NEG_INFINITY);
+            b_score.total_cmp(a_score).then_with(|| newer(b_age).total_cmp(&newer(a_age)))
+        });
+
+        let list = PyList::empty(py);
+        for (_, _, result) in ranked {
+            list.append(result)?;
+        }
+        Ok(list.into())
+    })
+}
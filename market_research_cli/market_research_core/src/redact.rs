@@ -0,0 +1,212 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::frontmatter::{compose, front_matter_mapping, split_front_matter};
+use crate::sections::split_sections;
+
+/// Replacement for redacted names when a profile does not set one
+const DEFAULT_REPLACEMENT: &str = "[redacted]";
+
+/// Front matter key a report can use to list its own internal names (people, codenames, clients)
+const INTERNAL_NAMES_KEY: &str = "internal_names";
+
+/// What to remove from a report before it leaves the team. Loaded from YAML/JSON or built in:
+/// `external` strips internal-only sections, comments, provenance, cost data and internal names.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedactionProfile {
+    /// Built-in profile to start from
+    pub extends: Option<String>,
+    /// Headings whose sections (and subsections) are removed; a trailing `*` matches any heading starting with the text
+    pub sections: Vec<String>,
+    pub front_matter_keys: Vec<String>,
+    /// Remove HTML comments; `<!-- internal -->...<!-- /internal -->` blocks and ```` ```internal ```` fences always go
+    pub comments: bool,
+    /// Names replaced wherever they appear, in addition to the report's own `internal_names`
+    pub names: Vec<String>,
+    /// Regexes; lines matching any of them are removed
+    pub patterns: Vec<String>,
+    pub replacement: Option<String>,
+}
+
+impl RedactionProfile {
+    fn external() -> Self {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        RedactionProfile {
+            extends: None,
+            sections: strings(&[
+                "internal*", "provenance*", "research log", "agent notes", "reviewer notes", "cost data", "costs",
+                "cost breakdown", "run cost*", "token usage",
+            ]),
+            front_matter_keys: strings(&[
+                "cost", "costs", "tokens", "token_usage", "model", "models", "internal", "internal_notes", "provenance",
+                "reviewer", "reviewers", "notes", INTERNAL_NAMES_KEY,
+            ]),
+            comments: true,
+            names: Vec::new(),
+            patterns: strings(&[
+                r"(?i)\b(?:api|llm|token|inference|run|research)\s+(?:cost|spend)s?\b",
+                r"(?i)\btokens?\s+used\b",
+            ]),
+            replacement: None,
+        }
+    }
+
+    /// A built-in profile by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "external" => Some(RedactionProfile::external()),
+            "none" | "internal" => Some(RedactionProfile::default()),
+            _ => None,
+        }
+    }
+
+    /// A built-in profile name or the path of a YAML/JSON profile file
+    pub fn load(name_or_path: &str) -> Result<Self> {
+        if let Some(profile) = RedactionProfile::builtin(name_or_path) {
+            return Ok(profile);
+        }
+        let path = Path::new(name_or_path);
+        if !path.is_file() {
+            return Err(anyhow!("Unknown redaction profile '{}'. Expected external, none or a profile file", name_or_path));
+        }
+        let text = fs::read_to_string(path).with_context(|| format!("Failed to read redaction profile {}", path.display()))?;
+        let profile: RedactionProfile = serde_yaml::from_str(&text).context("Invalid redaction profile")?;
+        for pattern in &profile.patterns {
+            Regex::new(pattern).with_context(|| format!("Invalid pattern in redaction profile: {}", pattern))?;
+        }
+        match &profile.extends {
+            Some(base) => {
+                let base = RedactionProfile::builtin(base).ok_or_else(|| anyhow!("Unknown base profile '{}'", base))?;
+                Ok(base.merge(profile))
+            }
+            None => Ok(profile),
+        }
+    }
+
+    fn merge(mut self, other: RedactionProfile) -> Self {
+        self.sections.extend(other.sections);
+        self.front_matter_keys.extend(other.front_matter_keys);
+        self.comments |= other.comments;
+        self.names.extend(other.names);
+        self.patterns.extend(other.patterns);
+        self.replacement = other.replacement.or(self.replacement);
+        self
+    }
+
+    fn section_matches(&self, heading: &str) -> bool {
+        // "Appendix B: Provenance" is judged by its name, not its numbering
+        let appendix = Regex::new(r"(?i)^appendix\s*[a-z0-9]*\s*[:.\-–]\s*").unwrap();
+        let heading = appendix.replace(heading.trim(), "").to_lowercase();
+        self.sections.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            match pattern.strip_suffix('*') {
+                Some(prefix) => heading.starts_with(prefix.trim_end()),
+                None => heading == pattern,
+            }
+        })
+    }
+
+    /// Redacted copy of a report; the input is never modified
+    pub fn apply(&self, markdown: &str) -> String {
+        // Front matter that cannot be parsed cannot be filtered key by key, so none of it is kept
+        let (mut mapping, body) = match front_matter_mapping(markdown) {
+            Ok(parsed) => parsed,
+            Err(_) => (Mapping::new(), split_front_matter(markdown).map(|(_, _, body)| body).unwrap_or(markdown)),
+        };
+
+        let mut names = self.names.clone();
+        if let Some(Value::Sequence(items)) = mapping.get(INTERNAL_NAMES_KEY) {
+            names.extend(items.iter().filter_map(|item| item.as_str().map(str::to_string)));
+        }
+        for key in &self.front_matter_keys {
+            mapping.remove(key.as_str());
+        }
+
+        // 1. Internal blocks and comments
+        let internal_block = Regex::new(r"(?is)<!--\s*internal\s*-->.*?<!--\s*/internal\s*-->").unwrap();
+        let mut body = remove_blocks(body, &internal_block);
+        body = remove_internal_fences(&body);
+        if self.comments {
+            body = remove_blocks(&body, &Regex::new(r"(?s)<!--.*?-->").unwrap());
+        }
+
+        // 2. Internal sections, each with its subsections
+        let lines: Vec<&str> = body.lines().collect();
+        let mut keep = vec![true; lines.len()];
+        let sections = split_sections(&body);
+        for (i, section) in sections.iter().enumerate() {
+            if section.level == 0 || !self.section_matches(&section.heading) {
+                continue;
+            }
+            let end = sections[i + 1..]
+                .iter()
+                .find(|next| next.level > 0 && next.level <= section.level)
+                .map(|next| next.start_line)
+                .unwrap_or(lines.len());
+            keep[section.start_line..end].iter_mut().for_each(|k| *k = false);
+        }
+
+        // 3. Lines carrying cost data or other profile patterns (validated when the profile was loaded)
+        let patterns: Vec<Regex> = self.patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
+        let mut body: String = lines
+            .iter()
+            .zip(&keep)
+            .filter(|(line, keep)| **keep && !patterns.iter().any(|p| p.is_match(line)))
+            .map(|(line, _)| format!("{}\n", line))
+            .collect();
+
+        // 4. Internal names
+        let replacement = self.replacement.as_deref().unwrap_or(DEFAULT_REPLACEMENT);
+        for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            if let Ok(pattern) = RegexBuilder::new(&format!(r"\b{}\b", regex::escape(name))).case_insensitive(true).build() {
+                body = pattern.replace_all(&body, regex::NoExpand(replacement)).to_string();
+            }
+        }
+
+        let collapsed = format!("{}\n", Regex::new(r"\n{3,}").unwrap().replace_all(body.trim_end(), "\n\n"));
+        compose(&mapping, &collapsed).unwrap_or(collapsed)
+    }
+}
+
+/// Remove every match, taking the whole line with it when the match is all the line holds
+fn remove_blocks(body: &str, block: &Regex) -> String {
+    let whole_lines = Regex::new(&format!(r"(?m)^[ \t]*(?:{})[ \t]*(?:\r?\n|$)", block.as_str())).unwrap();
+    let body = whole_lines.replace_all(body, "");
+    block.replace_all(&body, "").to_string()
+}
+
+/// Drop ```` ```internal ```` fenced blocks
+fn remove_internal_fences(body: &str) -> String {
+    let mut result = String::with_capacity(body.len());
+    let mut fence: Option<(String, bool)> = None;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker: String = trimmed.chars().take_while(|c| *c == '`' || *c == '~').collect();
+        match &fence {
+            Some((open, internal)) => {
+                let internal = *internal;
+                if marker.len() >= open.len() && trimmed.trim_end().len() == marker.len() && marker.starts_with(&open[..1]) {
+                    fence = None;
+                }
+                if !internal {
+                    result.push_str(line);
+                }
+            }
+            None if marker.len() >= 3 => {
+                let internal = trimmed[marker.len()..].trim().eq_ignore_ascii_case("internal");
+                fence = Some((marker, internal));
+                if !internal {
+                    result.push_str(line);
+                }
+            }
+            None => result.push_str(line),
+        }
+    }
+    result
+}
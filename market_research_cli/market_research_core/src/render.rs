@@ -0,0 +1,400 @@
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use regex::{Captures, Regex};
+
+use crate::capabilities;
+use crate::charts::{embed_charts, ChartMode};
+use crate::citations::resolve_citations;
+use crate::diagrams::render_fenced_diagrams;
+use crate::emoji::expand_shortcodes;
+use crate::facts::{facts_from_py, resolve_fact_refs, Facts};
+use crate::highlight::{self, highlight_code_blocks};
+use crate::joblog;
+use crate::math::mark_math;
+use crate::mermaid::mark_mermaid;
+use crate::metrics::expand_expressions;
+use crate::redact::RedactionProfile;
+use crate::sandbox::Sandbox;
+use crate::sanitize::SanitizeMode;
+use crate::stats::parse_date;
+use crate::themes::{theme_css, Theme};
+use crate::toc::{self, insert_toc};
+use crate::wikilinks::{link_wikilinks, Library};
+
+/// Rendering options shared by `format_report` and `export_to_pdf`
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub charts: ChartMode,
+    /// Render diagram fenced blocks (`swot`, `matrix2x2`, ...) as inline SVG
+    pub diagrams: bool,
+    /// Evaluate `{{ cagr(...) }}`-style metric expressions
+    pub metrics: bool,
+    /// Facts used to resolve `{{fact:key}}` references
+    pub facts: Option<Facts>,
+    /// Produce byte-identical output for identical input (fixed timestamps and temp names)
+    pub deterministic: bool,
+    /// Policy file whose error-severity rules must pass before export
+    pub policy_file: Option<String>,
+    /// Profile applied to the exported copy (`external` or a profile file); the stored report is untouched
+    pub redaction: Option<RedactionProfile>,
+    /// Look of HTML and PDF exports
+    pub theme: Theme,
+    /// CSS file applied after the theme in HTML and PDF exports, e.g. a brand kit
+    pub stylesheet: Option<String>,
+    /// CSS applied last, after the theme and stylesheet
+    pub css: Option<String>,
+    /// Handlebars file laying out the HTML document, in place of the built-in skeleton
+    pub template: Option<String>,
+    /// Restrictions for the PDF converter when rendering untrusted content
+    pub sandbox: Option<Sandbox>,
+    /// What raw HTML in the report survives rendering
+    pub sanitize: SanitizeMode,
+    /// Theme for syntax highlighting of fenced code blocks; `None` leaves them plain
+    pub highlight: Option<String>,
+    /// Deepest heading level in a table of contents injected at `[[TOC]]` or after the first heading; 0 for none
+    pub toc: usize,
+    /// Render `[^1]` references and their definitions as linked endnotes
+    pub footnotes: bool,
+    /// Replace `:rocket:`-style shortcodes with emoji
+    pub emoji: bool,
+    /// Curly quotes, en and em dashes and ellipses for their ASCII forms
+    pub smart_punctuation: bool,
+    /// Library `[[Report]]` links resolve against: linked in HTML, numbered references in PDF
+    pub wikilinks: Option<Library>,
+    /// Render `[@id]` citations of front matter `sources:` as numbered links to a references section
+    pub citations: bool,
+    /// Typeset `$...$` and `$$...$$` TeX with KaTeX, loaded by the rendered HTML
+    pub math: bool,
+    /// Draw ```` ```mermaid ```` blocks: with the Mermaid loader in HTML, pre-rendered by mmdc in PDF
+    pub mermaid: bool,
+    /// Embed remote images as `data:` URIs, so HTML and PDF exports show them offline
+    pub inline_images: bool,
+    /// Export job converter output is logged under, to correlate it with the caller's own logs; generated if unset
+    pub job_id: Option<String>,
+    /// Directory for converter logs; the shared one under the temp dir if unset
+    pub log_dir: Option<String>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            charts: ChartMode::None,
+            diagrams: true,
+            metrics: true,
+            facts: None,
+            deterministic: false,
+            policy_file: None,
+            redaction: None,
+            theme: Theme::Corporate,
+            stylesheet: None,
+            css: None,
+            template: None,
+            sandbox: None,
+            sanitize: SanitizeMode::Safe,
+            highlight: highlight::default_theme(),
+            toc: 0,
+            footnotes: true,
+            emoji: false,
+            smart_punctuation: false,
+            wikilinks: None,
+            citations: true,
+            math: false,
+            mermaid: false,
+            inline_images: false,
+            job_id: None,
+            log_dir: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Build options from an optional Python dict, rejecting unknown values
+    pub fn from_dict(options: Option<&PyDict>) -> PyResult<Self> {
+        let mut parsed = RenderOptions::default();
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(parsed),
+        };
+
+        if let Some(value) = options.get_item("charts") {
+            let mode: String = value.extract()?;
+            parsed.charts = ChartMode::parse(&mode).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unknown charts mode '{}'. Expected none, beside or replace", mode)
+                )
+            })?;
+            if parsed.charts != ChartMode::None && !cfg!(feature = "charts") {
+                return Err(capabilities::feature_missing_py("charts"));
+            }
+        }
+
+        if let Some(value) = options.get_item("diagrams") {
+            parsed.diagrams = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("metrics") {
+            parsed.metrics = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("facts") {
+            parsed.facts = Some(facts_from_py(value)?);
+        }
+
+        if let Some(value) = options.get_item("deterministic") {
+            parsed.deterministic = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("policy_file") {
+            parsed.policy_file = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("redaction") {
+            let profile: Option<String> = value.extract()?;
+            parsed.redaction = match profile {
+                Some(profile) => Some(RedactionProfile::load(&profile).map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to load redaction profile: {}", e))
+                })?),
+                None => None,
+            };
+        }
+
+        if let Some(value) = options.get_item("stylesheet") {
+            let stylesheet: Option<String> = value.extract()?;
+            if let Some(path) = &stylesheet {
+                if !std::path::Path::new(path).is_file() {
+                    return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(format!("Stylesheet not found: {}", path)));
+                }
+            }
+            parsed.stylesheet = stylesheet;
+        }
+
+        if let Some(value) = options.get_item("theme") {
+            let name: String = value.extract()?;
+            parsed.theme = Theme::parse(&name).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unknown theme '{}'. Expected corporate, minimal, dark or print", name)
+                )
+            })?;
+        }
+
+        if let Some(value) = options.get_item("css") {
+            parsed.css = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("template") {
+            let template: Option<String> = value.extract()?;
+            if let Some(path) = &template {
+                if !std::path::Path::new(path).is_file() {
+                    return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(format!("Template not found: {}", path)));
+                }
+            }
+            parsed.template = template;
+        }
+
+        if let Some(value) = options.get_item("sandbox") {
+            parsed.sandbox = Sandbox::from_py(value)?;
+        }
+
+        if let Some(value) = options.get_item("sanitize") {
+            let mode: String = value.extract()?;
+            parsed.sanitize = SanitizeMode::parse(&mode).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unknown sanitize mode '{}'. Expected safe, strict or off", mode)
+                )
+            })?;
+        }
+
+        if let Some(value) = options.get_item("highlight") {
+            let setting = highlight::setting_from_py(value)?;
+            if !cfg!(feature = "highlighting") && !highlight::is_off(&setting) {
+                return Err(capabilities::feature_missing_py("highlighting"));
+            }
+            parsed.highlight = highlight::theme_for(&setting)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        }
+
+        if let Some(value) = options.get_item("toc") {
+            parsed.toc = toc::depth_from_py(value)?;
+        }
+
+        if let Some(value) = options.get_item("footnotes") {
+            parsed.footnotes = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("emoji") {
+            parsed.emoji = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("smart_punctuation") {
+            parsed.smart_punctuation = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("wikilinks") {
+            let reports_dir: Option<String> = value.extract()?;
+            parsed.wikilinks = match reports_dir {
+                Some(dir) if !std::path::Path::new(&dir).is_dir() => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(format!("Reports directory not found: {}", dir)));
+                }
+                Some(dir) => Some(Library::load(&dir).map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load report library: {:#}", e))
+                })?),
+                None => None,
+            };
+        }
+
+        if let Some(value) = options.get_item("citations") {
+            parsed.citations = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("math") {
+            parsed.math = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("mermaid") {
+            parsed.mermaid = value.extract()?;
+        }
+
+        if let Some(value) = options.get_item("inline_images") {
+            parsed.inline_images = value.extract()?;
+            if parsed.inline_images && !cfg!(feature = "http") {
+                return Err(capabilities::feature_missing_py("http"));
+            }
+        }
+
+        if let Some(value) = options.get_item("job_id") {
+            let job_id: Option<String> = value.extract()?;
+            if let Some(job_id) = &job_id {
+                joblog::validate_job_id(job_id).map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+            }
+            parsed.job_id = job_id;
+        }
+
+        if let Some(value) = options.get_item("log_dir") {
+            parsed.log_dir = value.extract()?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// The same options logging converter output under `job_id` in `log_dir`
+    pub fn for_job(mut self, job_id: &str, log_dir: &str) -> Self {
+        self.job_id = Some(job_id.to_string());
+        self.log_dir = Some(log_dir.to_string());
+        self
+    }
+
+    /// Contents of the stylesheet, empty when none is set
+    pub fn stylesheet_css(&self) -> std::io::Result<String> {
+        match &self.stylesheet {
+            Some(path) => std::fs::read_to_string(path),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Full stylesheet of an exported document: the theme, then the stylesheet file, then the inline CSS
+    pub fn document_css(&self) -> std::io::Result<String> {
+        Ok(format!("{}
+{}
+{}", theme_css(self.theme), self.stylesheet_css()?, self.css.as_deref().unwrap_or_default()))
+    }
+}
+
+/// Apply markdown-level transformations before handing content to comrak
+pub fn preprocess(markdown: &str, options: &RenderOptions) -> String {
+    let markdown = redacted(markdown, options);
+    let mut markdown = match &options.facts {
+        Some(facts) => resolve_fact_refs(&markdown, facts),
+        None => markdown,
+    };
+    if options.citations {
+        markdown = resolve_citations(&markdown);
+    }
+    if options.metrics {
+        markdown = expand_expressions(&markdown);
+    }
+    if options.emoji {
+        markdown = expand_shortcodes(&markdown);
+    }
+    // After facts and metrics, so the listed headings read as rendered
+    if options.toc > 0 {
+        markdown = insert_toc(&markdown, options.toc);
+    }
+    // After the table of contents, so its `[[TOC]]` marker is gone
+    if let Some(library) = &options.wikilinks {
+        markdown = link_wikilinks(&markdown, library);
+    }
+    // Before charts and diagrams, whose generated markup may contain dollar amounts
+    if options.math {
+        markdown = mark_math(&markdown);
+    }
+    // Before highlighting, which would otherwise color the diagram source as code
+    if options.mermaid {
+        markdown = mark_mermaid(&markdown);
+    }
+    markdown = embed_charts(&markdown, options.charts);
+    if options.diagrams {
+        markdown = render_fenced_diagrams(&markdown);
+    }
+    // Last, so chart and diagram blocks are already rendered rather than highlighted as source
+    if let Some(theme) = &options.highlight {
+        markdown = highlight_code_blocks(&markdown, theme);
+    }
+    markdown
+}
+
+/// Give comrak's footnote list a heading, so it reads as the report's endnotes rather than a stray list
+pub fn titled_endnotes(html: &str) -> String {
+    let section = Regex::new(r#"<section class="footnotes"[^>]*>"#).unwrap();
+    section
+        .replace(html, |caps: &Captures| format!("{}\n<h2 class=\"footnotes-title\">Notes</h2>", &caps[0]))
+        .into_owned()
+}
+
+/// The report as it may be shared under the options' redaction profile
+pub fn redacted(markdown: &str, options: &RenderOptions) -> String {
+    match &options.redaction {
+        Some(profile) => profile.apply(markdown),
+        None => markdown.to_string(),
+    }
+}
+
+/// `SOURCE_DATE_EPOCH` from the environment, the reproducible-builds convention for pinned timestamps
+pub fn source_date_epoch() -> Option<NaiveDateTime> {
+    let seconds: i64 = std::env::var("SOURCE_DATE_EPOCH").ok()?.trim().parse().ok()?;
+    DateTime::from_timestamp(seconds, 0).map(|dt| dt.naive_utc())
+}
+
+/// Timestamp to embed in deterministic output: front matter `date`, then `SOURCE_DATE_EPOCH`, then the Unix epoch
+pub fn pinned_timestamp(markdown: &str) -> NaiveDateTime {
+    crate::parse_report_metadata(markdown)
+        .ok()
+        .and_then(|(metadata, _)| metadata.get("date").and_then(|date| parse_date(date)))
+        .or_else(source_date_epoch)
+        .unwrap_or_default()
+}
+
+/// Apply a line transformation everywhere except inside fenced code blocks
+pub fn map_outside_fences<F>(markdown: &str, mut transform: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    let mut in_fence = false;
+    let lines: Vec<String> = markdown
+        .split('\n')
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            if in_fence {
+                line.to_string()
+            } else {
+                transform(line)
+            }
+        })
+        .collect();
+
+    lines.join("\n")
+}
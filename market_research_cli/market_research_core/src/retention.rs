@@ -0,0 +1,208 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{scan_file, FileStats};
+use crate::{index, list_files, lock_report, remove_lock_file, write_atomic};
+
+pub const RETENTION_FILE: &str = ".retention.json";
+pub const ARCHIVE_DIR: &str = ".archive";
+const ARCHIVE_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%.3f";
+
+/// What happens to reports that fall outside the retention limits
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    /// Gzip into `.archive/` and remove from the library
+    Archive,
+    /// Remove permanently
+    Delete,
+}
+
+impl RetentionAction {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "archive" => Ok(RetentionAction::Archive),
+            "delete" => Ok(RetentionAction::Delete),
+            other => Err(anyhow!("Unknown retention action '{}'. Expected 'archive' or 'delete'", other)),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RetentionAction::Archive => "archive",
+            RetentionAction::Delete => "delete",
+        }
+    }
+}
+
+/// Limits persisted in `.retention.json`; any limit left unset is not enforced
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RetentionPolicy {
+    pub max_count: Option<usize>,
+    pub max_age_days: Option<f64>,
+    pub max_total_bytes: Option<u64>,
+    pub action: RetentionAction,
+}
+
+/// A report selected for removal and the first limit it broke
+pub struct RetentionCandidate {
+    pub filename: String,
+    pub size: u64,
+    pub date: NaiveDateTime,
+    pub reason: &'static str,
+}
+
+/// Result of applying (or previewing) a retention policy
+#[derive(Default)]
+pub struct RetentionOutcome {
+    pub candidates: Vec<RetentionCandidate>,
+    pub applied: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    pub freed_bytes: u64,
+}
+
+fn policy_path(reports_dir: &str) -> PathBuf {
+    Path::new(reports_dir).join(RETENTION_FILE)
+}
+
+impl RetentionPolicy {
+    /// Load the configured policy, or `None` when retention has not been set up
+    pub fn load(reports_dir: &str) -> Result<Option<Self>> {
+        let path = policy_path(reports_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path).context("Failed to read retention policy")?;
+        serde_json::from_slice(&bytes).map(Some).context("Retention policy is corrupted")
+    }
+
+    /// Persist the policy next to the reports
+    pub fn save(&self, reports_dir: &str) -> Result<()> {
+        fs::create_dir_all(reports_dir)?;
+        write_atomic(&policy_path(reports_dir), &serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Choose reports to remove, keeping the newest ones that fit within every limit
+    pub fn plan(&self, files: Vec<FileStats>, now: NaiveDateTime) -> Vec<RetentionCandidate> {
+        let mut files = files;
+        files.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.filename.cmp(&b.filename)));
+
+        let mut kept = 0;
+        let mut kept_bytes = 0u64;
+        let mut candidates = Vec::new();
+        for file in files {
+            let age_days = (now - file.date).num_seconds() as f64 / 86400.0;
+            let reason = if self.max_age_days.map(|max| age_days > max).unwrap_or(false) {
+                Some("max_age")
+            } else if self.max_count.map(|max| kept >= max).unwrap_or(false) {
+                Some("max_count")
+            } else if self.max_total_bytes.map(|max| kept_bytes + file.size > max).unwrap_or(false) {
+                Some("max_total_size")
+            } else {
+                None
+            };
+
+            match reason {
+                Some(reason) => candidates.push(RetentionCandidate {
+                    filename: file.filename,
+                    size: file.size,
+                    date: file.date,
+                    reason,
+                }),
+                None => {
+                    kept += 1;
+                    kept_bytes += file.size;
+                }
+            }
+        }
+
+        // Oldest first, the order they are removed in
+        candidates.reverse();
+        candidates
+    }
+}
+
+/// Gzip a report into the archive directory, keeping its relative path and stamping the name with the archive
+/// time, so archiving a later report of the same name keeps the earlier one
+fn archive_report(reports_dir: &str, filename: &str) -> Result<()> {
+    let source = Path::new(reports_dir).join(filename);
+    let stamp = Local::now().format(ARCHIVE_TIMESTAMP_FORMAT);
+    let base = Path::new(reports_dir).join(ARCHIVE_DIR).join(format!("{}.{}", filename, stamp));
+    if let Some(parent) = base.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let archive_path = |suffix: &str| {
+        let mut name = base.clone().into_os_string();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    let mut target = archive_path(".gz");
+    let mut n = 2;
+    while target.exists() {
+        target = archive_path(&format!("-{}.gz", n));
+        n += 1;
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&fs::read(&source)?)?;
+    write_atomic(&target, &encoder.finish()?)?;
+    fs::remove_file(&source)?;
+    Ok(())
+}
+
+/// Apply the retention policy to reports with one of `extensions`; with `dry_run`, only report what would be removed
+pub fn apply_retention(
+    reports_dir: &str,
+    extensions: &[String],
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<RetentionOutcome> {
+    let files: Vec<FileStats> = list_files(reports_dir, extensions)?
+        .par_iter()
+        .filter_map(|(filename, _)| scan_file(reports_dir, filename))
+        .collect();
+
+    let mut outcome = RetentionOutcome {
+        candidates: policy.plan(files, Local::now().naive_local()),
+        ..Default::default()
+    };
+    if dry_run {
+        return Ok(outcome);
+    }
+
+    for candidate in &outcome.candidates {
+        let path = Path::new(reports_dir).join(&candidate.filename);
+        let removed = lock_report(&path, true).map_err(anyhow::Error::from).and_then(|_lock| {
+            match policy.action {
+                RetentionAction::Archive => archive_report(reports_dir, &candidate.filename)?,
+                RetentionAction::Delete => fs::remove_file(&path)?,
+            }
+            remove_lock_file(&path);
+            Ok(())
+        });
+        match removed {
+            Ok(()) => {
+                outcome.applied.push(candidate.filename.clone());
+                outcome.freed_bytes += candidate.size;
+            }
+            Err(e) => outcome.failed.push((candidate.filename.clone(), e.to_string())),
+        }
+    }
+
+    index::update_index(reports_dir, |index| {
+        for filename in &outcome.applied {
+            index.reports.remove(filename);
+        }
+        Ok(())
+    })?;
+    Ok(outcome)
+}
@@ -0,0 +1,212 @@
+use std::io::{self, Read};
+use std::process::{Child, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize};
+
+/// Proxy address nothing listens on (the discard port), so every request a converter makes fails locally
+const DEAD_PROXY: &str = "http://127.0.0.1:9";
+
+/// How often a running converter is checked against its timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Restrictions for the external converters this crate runs (wkhtmltopdf for PDF export, pdftotext for imports),
+/// for rendering untrusted scraped content. Resource limits are rlimits and apply on Unix; the timeout applies everywhere.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sandbox {
+    /// Route all requests to a dead proxy so nothing can be fetched or sent
+    pub no_network: bool,
+    /// Turn off JavaScript in rendered HTML
+    pub no_javascript: bool,
+    /// Directories local files may be read from besides the converter's input; `None` leaves local access open
+    pub allow_paths: Option<Vec<String>>,
+    pub max_memory_mb: Option<u64>,
+    pub max_cpu_seconds: Option<u64>,
+    /// Largest file the converter may write
+    pub max_output_mb: Option<u64>,
+    /// Wall-clock limit; the converter is killed once it passes
+    pub timeout_seconds: Option<f64>,
+}
+
+impl Sandbox {
+    /// Settings used for `sandbox=True`: offline, no scripts, no local files beyond the input, bounded resources
+    pub fn strict() -> Self {
+        Sandbox {
+            no_network: true,
+            no_javascript: true,
+            allow_paths: Some(Vec::new()),
+            max_memory_mb: Some(2048),
+            max_cpu_seconds: Some(120),
+            max_output_mb: Some(512),
+            timeout_seconds: Some(300.0),
+        }
+    }
+
+    /// `True` for the strict preset, `False`/`None` for no sandbox, or a dict of fields (missing fields are unrestricted)
+    pub fn from_py(value: &PyAny) -> PyResult<Option<Self>> {
+        if value.is_none() {
+            return Ok(None);
+        }
+        if let Ok(enabled) = value.extract::<bool>() {
+            return Ok(enabled.then(Sandbox::strict));
+        }
+        let dict: &PyDict = value.downcast().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>("sandbox must be True, False or a dict of sandbox settings")
+        })?;
+        let mut sandbox = Sandbox::default();
+        for (key, value) in dict.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "no_network" => sandbox.no_network = value.extract()?,
+                "no_javascript" => sandbox.no_javascript = value.extract()?,
+                "allow_paths" => sandbox.allow_paths = value.extract()?,
+                "max_memory_mb" => sandbox.max_memory_mb = value.extract()?,
+                "max_cpu_seconds" => sandbox.max_cpu_seconds = value.extract()?,
+                "max_output_mb" => sandbox.max_output_mb = value.extract()?,
+                "timeout_seconds" => sandbox.timeout_seconds = value.extract()?,
+                other => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown sandbox setting '{}'", other)));
+                }
+            }
+        }
+        Ok(Some(sandbox))
+    }
+
+    /// wkhtmltopdf flags enforcing the network, script and file restrictions; `input_dir` holds the page itself
+    pub fn wkhtmltopdf_args(&self, input_dir: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.no_network {
+            args.extend(["--proxy".to_string(), DEAD_PROXY.to_string()]);
+        }
+        if self.no_javascript {
+            args.push("--disable-javascript".to_string());
+        }
+        match &self.allow_paths {
+            Some(paths) => {
+                args.push("--disable-local-file-access".to_string());
+                for path in std::iter::once(input_dir).chain(paths.iter().map(String::as_str)) {
+                    args.extend(["--allow".to_string(), path.to_string()]);
+                }
+            }
+            None => args.push("--enable-local-file-access".to_string()),
+        }
+        args
+    }
+
+    /// Proxy variables that send a converter's HTTP(S) traffic nowhere
+    fn network_env(&self) -> Vec<(&'static str, &'static str)> {
+        match self.no_network {
+            true => ["http_proxy", "https_proxy", "HTTP_PROXY", "HTTPS_PROXY", "all_proxy", "ALL_PROXY"]
+                .into_iter()
+                .map(|key| (key, DEAD_PROXY))
+                .collect(),
+            false => Vec::new(),
+        }
+    }
+
+    #[cfg(unix)]
+    fn apply_limits(&self, command: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        // Own process group, so a timeout can kill helpers the converter starts along with it
+        command.process_group(0);
+
+        let limits: Vec<(libc::c_int, u64)> = [
+            (libc::RLIMIT_AS as libc::c_int, self.max_memory_mb.map(|mb| mb * 1024 * 1024)),
+            (libc::RLIMIT_CPU as libc::c_int, self.max_cpu_seconds),
+            (libc::RLIMIT_FSIZE as libc::c_int, self.max_output_mb.map(|mb| mb * 1024 * 1024)),
+        ]
+        .into_iter()
+        .filter_map(|(resource, limit)| limit.map(|limit| (resource, limit)))
+        .collect();
+        if limits.is_empty() {
+            return;
+        }
+        // SAFETY: runs in the forked child before exec and only calls setrlimit, which is async-signal-safe
+        unsafe {
+            command.pre_exec(move || {
+                for &(resource, limit) in &limits {
+                    let rlimit = libc::rlimit { rlim_cur: limit as libc::rlim_t, rlim_max: limit as libc::rlim_t };
+                    if libc::setrlimit(resource as _, &rlimit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn apply_limits(&self, _command: &mut Command) {}
+}
+
+/// Run a converter to completion under an optional sandbox, collecting its output like `Command::output`
+pub fn run(command: &mut Command, sandbox: Option<&Sandbox>) -> io::Result<Output> {
+    let sandbox = match sandbox {
+        Some(sandbox) => sandbox,
+        None => return command.output(),
+    };
+    command.envs(sandbox.network_env());
+    sandbox.apply_limits(command);
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    // Drain both pipes while waiting, or a chatty converter blocks on a full pipe and looks hung
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    match wait(&mut child, sandbox.timeout_seconds)? {
+        Some(status) => {
+            let join = |handle: thread::JoinHandle<Vec<u8>>| handle.join().unwrap_or_default();
+            Ok(Output { status, stdout: join(stdout), stderr: join(stderr) })
+        }
+        // Helpers the converter spawned may still hold the pipes open, so the readers are left to finish on their own
+        None => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("killed after the sandbox timeout of {}s", sandbox.timeout_seconds.unwrap_or_default()),
+        )),
+    }
+}
+
+/// Kill the converter and, on Unix, its whole process group
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    // SAFETY: plain syscall; the group id is the child's pid because it was started with `process_group(0)`
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// Wait for the child, killing it once `timeout_seconds` pass; `None` means it was killed
+fn wait(child: &mut Child, timeout_seconds: Option<f64>) -> io::Result<Option<std::process::ExitStatus>> {
+    // A timeout too large for a `Duration` never expires, the same as none
+    let timeout = match crate::wait_limit(timeout_seconds) {
+        Some(timeout) => timeout,
+        None => return child.wait().map(Some),
+    };
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if started.elapsed() >= timeout {
+            kill(child);
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,100 @@
+use std::borrow::Cow;
+
+use ammonia::Builder;
+
+/// What raw HTML in a report survives rendering
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SanitizeMode {
+    /// Allowlist of formatting markup plus what rendering itself produces: SVG charts and diagrams, highlighted
+    /// code, heading anchors, footnotes, citations. Scripts, event handlers, iframes, forms and any inline style
+    /// beyond code colors go
+    Safe,
+    /// Only basic text formatting, links and tables; generated diagrams, colors and anchors are stripped too
+    Strict,
+    /// Raw HTML passes through untouched, for trusted content
+    Off,
+}
+
+impl SanitizeMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "safe" => Some(SanitizeMode::Safe),
+            "strict" => Some(SanitizeMode::Strict),
+            "off" | "none" => Some(SanitizeMode::Off),
+            _ => None,
+        }
+    }
+}
+
+/// SVG elements the chart and diagram renderers emit
+const SVG_TAGS: &[&str] = &[
+    "svg", "g", "rect", "circle", "ellipse", "line", "polyline", "polygon", "path", "text", "tspan", "title", "desc",
+];
+
+/// Presentation attributes of the SVG the renderers emit; none of them can run script
+const SVG_ATTRIBUTES: &[&str] = &[
+    "xmlns", "width", "height", "viewBox", "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "rx", "ry", "d",
+    "points", "fill", "fill-opacity", "stroke", "stroke-width", "stroke-dasharray", "opacity", "transform",
+    "font-family", "font-size", "font-weight", "text-anchor", "dominant-baseline", "xml:space",
+];
+
+/// Attributes comrak and the pre-processors put on any element: heading anchors, footnote and citation links,
+/// classes the stylesheet and the Mermaid and KaTeX loaders look for
+const GENERIC_ATTRIBUTES: &[&str] = &[
+    "class", "id", "title", "lang", "role", "aria-hidden", "aria-label", "inert", "data-footnotes",
+    "data-footnote-ref", "data-footnote-backref", "data-footnote-backref-idx",
+];
+
+/// CSS properties the syntax highlighter sets inline on `<pre>` and `<span>`; none of them can position, size or
+/// fetch anything
+const STYLE_PROPERTIES: &[&str] = &["color", "background-color", "font-weight", "font-style", "text-decoration"];
+
+/// A `style` value cut down to highlighter-style declarations with plain values (`#rrggbb` colors and keywords),
+/// or `None` when nothing is left
+fn highlight_style(style: &str) -> Option<String> {
+    let plain = |value: &str| {
+        let hex = |hex: &str| (3..=8).contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit());
+        let keyword = !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic() || c == '-');
+        value.strip_prefix('#').is_some_and(hex) || keyword
+    };
+    let kept: Vec<String> = style
+        .split(';')
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            let (property, value) = (property.trim().to_ascii_lowercase(), value.trim());
+            (STYLE_PROPERTIES.contains(&property.as_str()) && plain(value)).then(|| format!("{}:{};", property, value))
+        })
+        .collect();
+    (!kept.is_empty()).then(|| kept.concat())
+}
+
+fn safe_builder() -> Builder<'static> {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(["section", "input", "mark"])
+        .add_tags(SVG_TAGS)
+        .add_generic_attributes(GENERIC_ATTRIBUTES)
+        // Highlighted code is colored with inline styles; raw HTML in a report can carry `style` on the same tags,
+        // so only the colors and font styles the highlighter writes survive
+        .add_tag_attributes("pre", ["style"])
+        .add_tag_attributes("span", ["style"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("pre" | "span", "style") => highlight_style(value).map(Cow::Owned),
+            _ => Some(Cow::Borrowed(value)),
+        });
+    for tag in SVG_TAGS {
+        builder.add_tag_attributes(tag, SVG_ATTRIBUTES);
+    }
+    builder
+}
+
+/// Rendered HTML with raw HTML from the report cleaned as the mode allows. Run before anything appends scripts
+/// or embeds `data:` images, which the allowlists would strip
+pub fn sanitize_html(html: &str, mode: SanitizeMode) -> String {
+    match mode {
+        SanitizeMode::Safe => safe_builder().clean(html).to_string(),
+        SanitizeMode::Strict => ammonia::clean(html),
+        SanitizeMode::Off => html.to_string(),
+    }
+}
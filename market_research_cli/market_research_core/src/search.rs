@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::charts::escape_xml;
+use crate::frontmatter::{front_matter_mapping, mapping_str};
+use crate::lock_report;
+
+/// Longest snippet kept around a match when the sentence itself is longer
+const MAX_SNIPPET_CHARS: usize = 240;
+
+/// One matching line with its highlighted context
+pub struct Snippet {
+    /// 1-based line number in the report file
+    pub line: usize,
+    /// HTML-escaped sentence with matched terms wrapped in `<mark>`
+    pub html: String,
+}
+
+/// A report that contains every query term
+pub struct SearchHit {
+    pub filename: String,
+    pub title: Option<String>,
+    /// Total occurrences of all terms
+    pub score: usize,
+    pub snippets: Vec<Snippet>,
+}
+
+/// Split a query into terms; `"double quotes"` keep a phrase together
+pub fn parse_query(query: &str) -> Vec<String> {
+    let token = Regex::new(r#""([^"]+)"|(\S+)"#).unwrap();
+    token
+        .captures_iter(query)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str().trim().to_string()))
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+fn term_pattern(term: &str, case_sensitive: bool) -> Regex {
+    let flags = if case_sensitive { "" } else { "(?i)" };
+    Regex::new(&format!("{}{}", flags, regex::escape(term))).unwrap()
+}
+
+/// The sentence containing `start..end` within `line`, trimmed to `MAX_SNIPPET_CHARS` around the match
+fn sentence_around(line: &str, start: usize, end: usize) -> (usize, usize) {
+    let boundary = |c: char| matches!(c, '.' | '!' | '?');
+    let sentence_start = line[..start]
+        .char_indices()
+        .rev()
+        .find(|(i, c)| boundary(*c) && line[i + c.len_utf8()..].starts_with(char::is_whitespace))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let sentence_end = line[end..]
+        .char_indices()
+        .find(|(i, c)| boundary(*c) && line[end + i + c.len_utf8()..].chars().next().map(char::is_whitespace).unwrap_or(true))
+        .map(|(i, c)| end + i + c.len_utf8())
+        .unwrap_or(line.len());
+
+    // Keep long sentences readable by centring a window on the match
+    let (mut from, mut to) = (sentence_start, sentence_end);
+    if line[from..to].chars().count() > MAX_SNIPPET_CHARS {
+        let half = MAX_SNIPPET_CHARS / 2;
+        from = line[..start].char_indices().rev().nth(half).map(|(i, _)| i).unwrap_or(from).max(from);
+        to = line[end..].char_indices().nth(half).map(|(i, _)| end + i).unwrap_or(to).min(to);
+    }
+    (from, to)
+}
+
+/// HTML-escape `text` and wrap every occurrence of any term in `<mark>`
+pub fn highlight(text: &str, patterns: &[Regex]) -> String {
+    let mut ranges: Vec<(usize, usize)> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.find_iter(text).map(|m| (m.start(), m.end())))
+        .collect();
+    ranges.sort();
+
+    let mut html = String::new();
+    let mut cursor = 0;
+    for (start, end) in ranges {
+        // Overlapping terms merge into the mark already open
+        if start < cursor {
+            if end > cursor {
+                html.truncate(html.len() - "</mark>".len());
+                html.push_str(&escape_xml(&text[cursor..end]));
+                html.push_str("</mark>");
+                cursor = end;
+            }
+            continue;
+        }
+        html.push_str(&escape_xml(&text[cursor..start]));
+        html.push_str("<mark>");
+        html.push_str(&escape_xml(&text[start..end]));
+        html.push_str("</mark>");
+        cursor = end;
+    }
+    html.push_str(&escape_xml(&text[cursor..]));
+    html
+}
+
+/// Search one report's content; None unless every term occurs
+pub fn search_content(filename: &str, content: &str, patterns: &[Regex], max_snippets: usize) -> Option<SearchHit> {
+    if !patterns.iter().all(|pattern| pattern.is_match(content)) {
+        return None;
+    }
+
+    let mut score = 0;
+    let mut snippets = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let first = patterns.iter().filter_map(|pattern| pattern.find(line)).min_by_key(|m| m.start());
+        let first = match first {
+            Some(first) => first,
+            None => continue,
+        };
+        score += patterns.iter().map(|pattern| pattern.find_iter(line).count()).sum::<usize>();
+        if snippets.len() < max_snippets {
+            let (from, to) = sentence_around(line, first.start(), first.end());
+            let mut html = highlight(line[from..to].trim(), patterns);
+            if from > 0 {
+                html.insert_str(0, "… ");
+            }
+            if to < line.len() && !line[..to].trim_end().ends_with(['.', '!', '?']) {
+                html.push_str(" …");
+            }
+            snippets.push(Snippet { line: index + 1, html });
+        }
+    }
+
+    let title = front_matter_mapping(content).ok().and_then(|(mapping, _)| mapping_str(&mapping, "title"));
+    Some(SearchHit { filename: filename.to_string(), title, score, snippets })
+}
+
+/// Full-text search across reports in parallel, best matches first
+pub fn search_reports(
+    reports_dir: &str,
+    files: &[String],
+    query: &str,
+    case_sensitive: bool,
+    max_snippets: usize,
+) -> Result<Vec<SearchHit>> {
+    let terms = parse_query(query);
+    if terms.is_empty() {
+        return Err(anyhow!("Search query is empty"));
+    }
+    let patterns: Vec<Regex> = terms.iter().map(|term| term_pattern(term, case_sensitive)).collect();
+
+    let mut hits: Vec<SearchHit> = files
+        .par_iter()
+        .filter_map(|filename| {
+            let path = Path::new(reports_dir).join(filename);
+            let _lock = lock_report(&path, false).ok()?;
+            let content = fs::read_to_string(&path).ok()?;
+            search_content(filename, &content, &patterns, max_snippets)
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.filename.cmp(&b.filename)));
+    Ok(hits)
+}
@@ -0,0 +1,134 @@
+use pyo3::prelude::*;
+
+use crate::panics::guard;
+use crate::toc::{outline, OutlineNode, ANCHOR_PREFIX};
+
+/// A heading and the lines it owns, up to the next heading of any level
+#[derive(Clone, Debug)]
+pub struct Section {
+    pub level: usize,
+    pub heading: String,
+    /// Index of the heading line (or 0 for the preamble before the first heading)
+    pub start_line: usize,
+    /// Index one past the last line of the section body
+    pub end_line: usize,
+}
+
+/// Parse an ATX heading line such as `## Market Size` into its level and text
+pub fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    Some((level, text.to_string()))
+}
+
+/// Split markdown into sections at every heading outside fenced code blocks.
+/// Content before the first heading becomes a level-0 section with an empty heading.
+pub fn split_sections(markdown: &str) -> Vec<Section> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut sections: Vec<Section> = Vec::new();
+    let mut in_fence = false;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some((level, heading)) = parse_heading(line) {
+            if let Some(last) = sections.last_mut() {
+                last.end_line = idx;
+            } else if lines[..idx].iter().any(|l| !l.trim().is_empty()) {
+                sections.push(Section { level: 0, heading: String::new(), start_line: 0, end_line: idx });
+            }
+            sections.push(Section { level, heading, start_line: idx, end_line: lines.len() });
+        }
+    }
+
+    if sections.is_empty() && lines.iter().any(|l| !l.trim().is_empty()) {
+        sections.push(Section { level: 0, heading: String::new(), start_line: 0, end_line: lines.len() });
+    }
+    sections
+}
+
+/// The body lines of a section, excluding its heading line
+pub fn section_body<'a>(lines: &[&'a str], section: &Section) -> Vec<&'a str> {
+    let start = if section.level == 0 { section.start_line } else { section.start_line + 1 };
+    lines[start.min(section.end_line)..section.end_line].to_vec()
+}
+
+/// The first heading, depth first, whose text matches `heading` ignoring case, or whose slug or anchor is `heading`
+fn find_heading<'a>(nodes: &'a [OutlineNode], heading: &str) -> Option<&'a OutlineNode> {
+    let wanted = heading.trim().trim_start_matches('#').trim();
+    nodes.iter().find_map(|node| {
+        let matches = node.text.to_lowercase() == wanted.to_lowercase()
+            || node.slug == wanted
+            || wanted.strip_prefix(ANCHOR_PREFIX) == Some(node.slug.as_str());
+        match matches {
+            true => Some(node),
+            false => find_heading(&node.children, heading),
+        }
+    })
+}
+
+/// Byte range of a section's body: from after its heading line to the next heading that is not below it
+fn body_range(markdown: &str, heading: &str) -> PyResult<(usize, usize)> {
+    let nodes = outline(markdown);
+    let node = find_heading(&nodes, heading).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Section not found: {}", heading))
+    })?;
+    let body_start = match markdown[node.start..node.end].find('\n') {
+        Some(newline) => node.start + newline + 1,
+        None => node.end,
+    };
+    Ok((body_start, node.end))
+}
+
+/// The body of the section under `heading` (matched by text ignoring case, or by slug or anchor), including its
+/// subsections but not the heading line itself, without surrounding blank lines
+#[pyfunction]
+pub fn extract_section(markdown: &str, heading: &str) -> PyResult<String> {
+    guard("extract_section", || {
+        let (start, end) = body_range(markdown, heading)?;
+        Ok(markdown[start..end].trim_matches(['\n', '\r']).trim_end().to_string())
+    })
+}
+
+/// The report with the body of the section under `heading` replaced by `new_body`, keeping the heading line and
+/// everything outside the section. Subsections are part of the body and are replaced too
+#[pyfunction]
+pub fn replace_section(markdown: &str, heading: &str, new_body: &str) -> PyResult<String> {
+    guard("replace_section", || {
+        let (start, end) = body_range(markdown, heading)?;
+        let mut out = markdown[..start].to_string();
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        let body = new_body.trim_matches(['\n', '\r']).trim_end();
+        if !body.is_empty() {
+            out.push('\n');
+            out.push_str(body);
+            out.push('\n');
+        }
+        if end < markdown.len() {
+            out.push('\n');
+            out.push_str(&markdown[end..]);
+        } else if !markdown.ends_with('\n') {
+            out.pop();
+        }
+        Ok(out)
+    })
+}
@@ -0,0 +1,260 @@
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::artifacts::{self, ExportSettings};
+use crate::capabilities::{self, enabled_features};
+use crate::index;
+use crate::joblog;
+use crate::panics::{guard, panic_message};
+use crate::policy::Policy;
+use crate::render::RenderOptions;
+use crate::search;
+use crate::{export_report, render_report_html, sha256_hex, write_atomic};
+
+/// Filename the sample report is saved under
+const SAMPLE_FILENAME: &str = "self-test-sample.md";
+
+/// Bundled report touching each part of the pipeline: front matter, headings, a table, a diagram, a footnote
+const SAMPLE_REPORT: &str = r#"---
+title: Self-Test Sample Report
+date: 2024-01-15
+tags: [self-test, sample]
+---
+
+# Self-Test Sample Report
+
+## Summary
+
+The global widget market grew steadily, and market share shifted towards premium vendors.[^1]
+
+## Market Size
+
+| Year | Revenue ($M) | Growth |
+| --- | ---: | ---: |
+| 2021 | 1200 | 4.0% |
+| 2022 | 1280 | 6.7% |
+| 2023 | 1390 | 8.6% |
+
+## Position
+
+```swot
+title: Widget Vendors
+strengths:
+- Established distribution
+weaknesses:
+- Thin margins
+opportunities:
+- Premium segment
+threats:
+- New entrants
+```
+
+[^1]: Sample data bundled with market_research_core for installation checks.
+"#;
+
+/// Policy the sample report is linted against; every rule should pass
+const SAMPLE_POLICY: &str = r#"rules:
+  - id: front-matter
+    check: require_front_matter
+    keys: [title, date]
+  - id: summary
+    check: require_section
+    heading: Summary
+  - id: length
+    check: word_count
+    min: 20
+  - id: market-share
+    check: require_text
+    text: market share
+"#;
+
+/// What a step found, if it did not fail
+enum Outcome {
+    Passed(String),
+    Skipped(String),
+}
+
+/// One stage of the self-test
+struct Step {
+    name: String,
+    status: &'static str,
+    detail: String,
+    seconds: f64,
+}
+
+/// Run one step, recording a failure or panic instead of stopping the self-test
+fn run_step<F>(steps: &mut Vec<Step>, name: &str, step: F)
+where
+    F: FnOnce() -> Result<Outcome>,
+{
+    let started = Instant::now();
+    let (status, detail) = match panic::catch_unwind(AssertUnwindSafe(step)) {
+        Ok(Ok(Outcome::Passed(detail))) => ("passed", detail),
+        Ok(Ok(Outcome::Skipped(detail))) => ("skipped", detail),
+        Ok(Err(e)) => ("failed", format!("{:#}", e)),
+        Err(payload) => ("failed", format!("panicked: {}", panic_message(payload.as_ref()))),
+    };
+    steps.push(Step { name: name.to_string(), status, detail, seconds: started.elapsed().as_secs_f64() });
+}
+
+/// Check that rendered HTML has what the sample report should produce
+fn check_html(html: &str) -> Result<()> {
+    let expected = [
+        ("<h1", "heading"),
+        ("<table", "table"),
+        ("<svg", "SWOT diagram"),
+        ("class=\"footnotes\"", "footnotes"),
+    ];
+    let missing: Vec<&str> = expected.iter().filter(|(marker, _)| !html.contains(marker)).map(|(_, what)| *what).collect();
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow!("rendered HTML is missing: {}", missing.join(", "))),
+    }
+}
+
+/// Run every stage against the bundled sample in a fresh directory under `output_dir`
+fn run_self_test(output_dir: &Path) -> Vec<Step> {
+    let reports_dir = output_dir.join("reports");
+    let exports_dir = output_dir.join("exports");
+    let reports = reports_dir.to_string_lossy().to_string();
+    let options = RenderOptions::default().for_job(&joblog::new_job_id(), &output_dir.join("logs").to_string_lossy());
+    let job_id = options.job_id.clone().unwrap_or_default();
+    let mut steps = Vec::new();
+
+    run_step(&mut steps, "save", || {
+        fs::create_dir_all(&reports_dir)?;
+        write_atomic(&reports_dir.join(SAMPLE_FILENAME), SAMPLE_REPORT.as_bytes())?;
+        index::record_file(&reports, SAMPLE_FILENAME)?;
+        Ok(Outcome::Passed(format!("saved {}", SAMPLE_FILENAME)))
+    });
+
+    run_step(&mut steps, "index", || {
+        let report = index::verify(&reports)?;
+        if !report.ok.iter().any(|filename| filename == SAMPLE_FILENAME) {
+            return Err(anyhow!("sample report is not in the index as unchanged"));
+        }
+        let hits = search::search_reports(&reports, &[SAMPLE_FILENAME.to_string()], "widget market", false, 3)?;
+        match hits.first() {
+            Some(hit) if hit.filename == SAMPLE_FILENAME => {
+                Ok(Outcome::Passed(format!("indexed and found by search with {} snippet(s)", hit.snippets.len())))
+            }
+            _ => Err(anyhow!("search did not find the sample report")),
+        }
+    });
+
+    run_step(&mut steps, "render", || {
+        let html = render_report_html(SAMPLE_REPORT, &options).map_err(|e| anyhow!(e.to_string()))?;
+        check_html(&html)?;
+        Ok(Outcome::Passed(format!("{} bytes of HTML", html.len())))
+    });
+
+    run_step(&mut steps, "lint", || {
+        let policy_file = output_dir.join("policy.yaml");
+        fs::write(&policy_file, SAMPLE_POLICY)?;
+        let results = Policy::load(&policy_file)?.evaluate(SAMPLE_REPORT);
+        let failed: Vec<String> =
+            results.iter().filter(|result| !result.passed).map(|result| format!("[{}] {}", result.id, result.message)).collect();
+        match failed.is_empty() {
+            true => Ok(Outcome::Passed(format!("{} policy rules passed", results.len()))),
+            false => Err(anyhow!("policy rules failed: {}", failed.join("; "))),
+        }
+    });
+
+    let mut exported: Vec<PathBuf> = Vec::new();
+    for format in ["md", "html", "pdf"] {
+        run_step(&mut steps, &format!("export_{}", format), || {
+            if format == "pdf" {
+                if let Ok(Some(missing)) = capabilities::check("pdf_export") {
+                    return Ok(Outcome::Skipped(missing.message()));
+                }
+            }
+            fs::create_dir_all(&exports_dir)?;
+            let target = exports_dir.join(format!("self-test-sample.{}", format));
+            export_report(SAMPLE_REPORT, &target, format, &options)?;
+            if format == "html" {
+                check_html(&fs::read_to_string(&target)?)?;
+            }
+            artifacts::record_artifact(
+                &reports,
+                SAMPLE_FILENAME,
+                format,
+                &target,
+                SAMPLE_REPORT.as_bytes(),
+                &ExportSettings::default(),
+                &job_id,
+            )?;
+            let size = fs::metadata(&target)?.len();
+            exported.push(target);
+            Ok(Outcome::Passed(format!("{} bytes", size)))
+        });
+    }
+
+    run_step(&mut steps, "verify_hashes", || {
+        let report = index::verify(&reports)?;
+        if !report.modified.is_empty() || !report.missing.is_empty() {
+            return Err(anyhow!("report checksums do not match the index"));
+        }
+        let registry = artifacts::load_artifacts(&reports)?;
+        let recorded = registry.get(SAMPLE_FILENAME).map(Vec::as_slice).unwrap_or_default();
+        for path in &exported {
+            let canonical = fs::canonicalize(path)?.to_string_lossy().to_string();
+            let artifact = recorded
+                .iter()
+                .find(|artifact| artifact.path == canonical)
+                .with_context(|| format!("{} is not in the artifact registry", path.display()))?;
+            if sha256_hex(&fs::read(path)?) != artifact.sha256 {
+                return Err(anyhow!("{} does not match its recorded SHA-256", path.display()));
+            }
+        }
+        let store = artifacts::check_store(&reports, false)?;
+        if !store.corrupted.is_empty() || !store.missing.is_empty() {
+            return Err(anyhow!(
+                "artifact store has {} corrupted and {} missing object(s)",
+                store.corrupted.len(),
+                store.missing.len()
+            ));
+        }
+        Ok(Outcome::Passed(format!("{} report and {} export hash(es) verified", report.ok.len(), exported.len())))
+    });
+
+    steps
+}
+
+/// Validate the installation end to end: save the bundled sample report under a new directory in `tmp_dir`,
+/// index and search it, render it, lint it against a sample policy, export it to every available format and
+/// verify the recorded hashes. Returns `{passed, output_dir, version, features, steps: [{name, status, detail,
+/// seconds}]}` with `status` passed, failed or skipped (PDF without wkhtmltopdf); the directory is kept for inspection
+#[pyfunction]
+pub fn self_test(tmp_dir: &str, py: Python) -> PyResult<PyObject> {
+    guard("self_test", || {
+        let output_dir = Path::new(tmp_dir)
+            .join(format!("self_test_{}_{}", Local::now().format("%Y%m%d_%H%M%S"), std::process::id()));
+        fs::create_dir_all(&output_dir)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create self-test directory: {}", e)))?;
+        let steps = py.allow_threads(|| run_self_test(&output_dir));
+
+        let list = PyList::empty(py);
+        for step in &steps {
+            let entry = PyDict::new(py);
+            entry.set_item("name", &step.name)?;
+            entry.set_item("status", step.status)?;
+            entry.set_item("detail", &step.detail)?;
+            entry.set_item("seconds", step.seconds)?;
+            list.append(entry)?;
+        }
+        let dict = PyDict::new(py);
+        dict.set_item("passed", steps.iter().all(|step| step.status != "failed"))?;
+        dict.set_item("output_dir", output_dir.to_string_lossy().to_string())?;
+        dict.set_item("version", env!("CARGO_PKG_VERSION"))?;
+        dict.set_item("features", enabled_features())?;
+        dict.set_item("steps", list)?;
+        Ok(dict.into())
+    })
+}
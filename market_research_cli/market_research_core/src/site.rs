@@ -0,0 +1,353 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::budget::{self, Spill};
+use crate::charts::escape_xml;
+use crate::frontmatter::{front_matter_mapping, FrontMatterSummary};
+use crate::panics::lock;
+use crate::paths::portable_filename;
+use crate::render::RenderOptions;
+use crate::space::{ensure_space, export_estimate, total_size};
+use crate::{lock_report, render_report_html};
+
+/// Characters of plain text per report kept in the search index
+const SEARCH_TEXT_LIMIT: usize = 20_000;
+
+const SEARCH_JS: &str = r##"(function () {
+  var input = document.getElementById("search");
+  var rows = document.querySelectorAll("#reports tbody tr");
+  var count = document.getElementById("count");
+  var index = window.SEARCH_INDEX || [];
+  function update() {
+    var terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    var shown = 0;
+    index.forEach(function (entry, i) {
+      var haystack = (entry.title + " " + entry.tags.join(" ") + " " + entry.text).toLowerCase();
+      var match = terms.every(function (term) { return haystack.indexOf(term) !== -1; });
+      rows[i].style.display = match ? "" : "none";
+      if (match) shown++;
+    });
+    count.textContent = shown + " of " + index.length + " reports";
+  }
+  input.addEventListener("input", update);
+  update();
+})();
+"##;
+
+/// Built-in site themes; anything else is treated as a path to a CSS file
+fn theme_css(theme: &str) -> Result<String> {
+    let (background, text, muted, border, surface, link) = match theme {
+        "light" | "default" => ("#ffffff", "#222222", "#666666", "#dddddd", "#f5f5f5", "#0b5cad"),
+        "dark" => ("#16181d", "#e4e4e4", "#9a9a9a", "#33363d", "#22252b", "#6cb4ff"),
+        "sepia" => ("#f6f0e3", "#3b3024", "#7a6a55", "#d9cbb0", "#ece2cd", "#8a4b0f"),
+        path => {
+            return fs::read_to_string(path)
+                .map_err(|e| anyhow!("Unknown theme '{}' (expected light, dark, sepia or a CSS file): {}", path, e));
+        }
+    };
+    Ok(format!(
+        r#":root {{ --bg: {background}; --text: {text}; --muted: {muted}; --border: {border}; --surface: {surface}; --link: {link}; }}
+body {{ background: var(--bg); color: var(--text); font-family: Arial, sans-serif; line-height: 1.5; max-width: 60rem; margin: 0 auto; padding: 2rem; }}
+a {{ color: var(--link); }}
+h1, h2, h3, h4, h5, h6 {{ margin-top: 1.5em; margin-bottom: 0.5em; }}
+table {{ width: 100%; border-collapse: collapse; margin: 1em 0; }}
+th, td {{ border: 1px solid var(--border); padding: 8px; text-align: left; }}
+th {{ background: var(--surface); }}
+code {{ font-family: monospace; background: var(--surface); padding: 2px 4px; border-radius: 3px; }}
+pre {{ background: var(--surface); padding: 1em; border-radius: 5px; overflow-x: auto; }}
+blockquote {{ background: var(--surface); border-left: 4px solid var(--border); margin: 1em 0; padding: 0.5em 1em; }}
+img, svg {{ max-width: 100%; }}
+nav, .report-metadata, #count {{ color: var(--muted); }}
+.report-metadata {{ margin-bottom: 2em; font-style: italic; }}
+#search {{ width: 100%; padding: 0.6em; font-size: 1em; background: var(--surface); color: var(--text); border: 1px solid var(--border); border-radius: 4px; }}
+.tag {{ display: inline-block; background: var(--surface); border: 1px solid var(--border); border-radius: 3px; padding: 0 4px; margin-right: 4px; font-size: 0.85em; }}
+"#
+    ))
+}
+
+/// A rendered report page and its search entry
+#[derive(Serialize)]
+struct SiteEntry {
+    title: String,
+    date: String,
+    tags: Vec<String>,
+    href: String,
+    text: String,
+}
+
+/// A rendered report with the local files its page links to
+struct RenderedPage {
+    entry: SiteEntry,
+    references: Vec<PathBuf>,
+}
+
+/// What `export_site` produced
+pub struct SiteSummary {
+    pub index: PathBuf,
+    pub pages: usize,
+    pub assets: usize,
+    pub failed: Vec<(String, String)>,
+}
+
+fn page(title: &str, css_href: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"UTF-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{}\">\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape_xml(title),
+        css_href,
+        body
+    )
+}
+
+fn tags_html(tags: &[String]) -> String {
+    tags.iter().map(|tag| format!("<span class=\"tag\">{}</span>", escape_xml(tag))).collect::<Vec<_>>().join("")
+}
+
+/// Visible text of a rendered page, for the search index
+fn plain_text(html: &str) -> String {
+    let tags = Regex::new(r"(?s)<svg.*?</svg>|<[^>]+>").unwrap();
+    let text = tags.replace_all(html, " ");
+    let text = text.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"");
+    text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(SEARCH_TEXT_LIMIT).collect()
+}
+
+/// `target` joined onto `base` with `.` and `..` resolved, or `None` if it climbs above where `base` is relative to
+fn resolve_relative(base: &Path, target: &str) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in base.join(target).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if resolved.pop() => {}
+            _ => return None,
+        }
+    }
+    Some(resolved)
+}
+
+/// Relative local files referenced by `src`/`href` attributes of the page for `filename`, resolved against the
+/// report's own directory and returned relative to the reports directory. Paths that leave it are ignored.
+fn local_references(html: &str, filename: &str) -> Vec<PathBuf> {
+    let report_dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+    let attribute = Regex::new(r##"(?:src|href)\s*=\s*"([^"#?]+)[^"]*""##).unwrap();
+    attribute
+        .captures_iter(html)
+        .map(|caps| caps[1].to_string())
+        .filter(|target| !target.contains(':') && !target.starts_with('/') && !target.starts_with("//"))
+        .filter_map(|target| resolve_relative(report_dir, &target))
+        .filter(|path| !matches!(crate::convert::extension_of(path).as_str(), "md" | "markdown" | "html"))
+        .collect()
+}
+
+/// A relative path with every name made portable, keeping `.` and `..` so relative links still resolve
+fn portable_path(path: &str) -> String {
+    path.split('/')
+        .map(|part| match part {
+            "" | "." | ".." => part.to_string(),
+            name => portable_filename(name),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Point links between reports at their rendered pages, which mirror the reports' own directories under `reports/`
+fn rewrite_report_links(html: &str) -> String {
+    let link = Regex::new(r#"href="([^":?#]+)\.(?:md|markdown)(#[^"]*)?""#).unwrap();
+    link.replace_all(html, |caps: &regex::Captures| {
+        format!("href=\"{}.html{}\"", portable_path(&caps[1]), caps.get(2).map(|m| m.as_str()).unwrap_or_default())
+    })
+    .to_string()
+}
+
+/// Path of a report's page under `reports/`, without `.html`. Pages keep the report's directory, so two `q3.md`
+/// in different folders get pages of their own, and names are made valid wherever the site is copied to. Markdown
+/// pages drop the extension, which is what rewritten links point at; other formats keep it so `notes.txt` can't
+/// replace `notes.md`
+fn page_name(filename: &str) -> String {
+    let markdown = Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .filter(|extension| extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown"));
+    match markdown {
+        Some(extension) => portable_path(&filename[..filename.len() - extension.len() - 1]),
+        None => portable_path(filename),
+    }
+}
+
+fn render_entry(
+    reports_dir: &str,
+    filename: &str,
+    pages_dir: &Path,
+    render_options: &RenderOptions,
+) -> Result<RenderedPage> {
+    if !Path::new(filename).components().all(|component| matches!(component, Component::Normal(_))) {
+        return Err(anyhow!("Invalid report filename '{}'", filename));
+    }
+    let source = Path::new(reports_dir).join(filename);
+    let content = {
+        let _lock = lock_report(&source, false)?;
+        fs::read_to_string(&source)?
+    };
+    let (mapping, body) = front_matter_mapping(&content)?;
+    let summary = FrontMatterSummary::from_mapping(&mapping);
+    let stem = Path::new(filename).file_stem().and_then(|s| s.to_str()).unwrap_or(filename);
+    let title = summary
+        .title
+        .clone()
+        .or_else(|| body.lines().find_map(|line| line.strip_prefix("# ")).map(|h| h.trim().to_string()))
+        .unwrap_or_else(|| stem.to_string());
+
+    let page_name = page_name(filename);
+    let root = "../".repeat(page_name.matches('/').count() + 1);
+
+    let html = rewrite_report_links(&render_report_html(body, render_options).map_err(|e| anyhow!(e.to_string()))?);
+    let mut header = format!("<nav><a href=\"{}index.html\">&larr; All reports</a></nav>\n", root);
+    let details: Vec<String> = summary.date.iter().map(|date| escape_xml(date)).collect();
+    if !details.is_empty() || !summary.tags.is_empty() {
+        header.push_str(&format!("<div class=\"report-metadata\">{} {}</div>\n", details.join(" "), tags_html(&summary.tags)));
+    }
+    let href = format!("reports/{}.html", page_name);
+    let page_path = pages_dir.join(format!("{}.html", page_name));
+    if let Some(parent) = page_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&page_path, page(&title, &format!("{}assets/style.css", root), &format!("{}{}", header, html)))?;
+
+    let entry = SiteEntry {
+        title,
+        date: summary.date.unwrap_or_default(),
+        tags: summary.tags,
+        href,
+        text: plain_text(&html),
+    };
+    Ok(RenderedPage { entry, references: local_references(&html, filename) })
+}
+
+/// Render `files` into a self-contained static site: `index.html` with client-side search,
+/// one page per report under `reports/`, the theme under `assets/`, and any local images the
+/// reports reference, copied alongside the pages.
+pub fn export_site(
+    reports_dir: &str,
+    files: &[String],
+    output_dir: &str,
+    theme: &str,
+    render_options: &RenderOptions,
+) -> Result<SiteSummary> {
+    let css = theme_css(theme)?;
+    let output = Path::new(output_dir);
+    let estimate = export_estimate(total_size(reports_dir, files), "html");
+    ensure_space(output, estimate, "the site export")?;
+    let pages_dir = output.join("reports");
+    let assets_dir = output.join("assets");
+    fs::create_dir_all(&pages_dir)?;
+    fs::create_dir_all(&assets_dir)?;
+
+    // Names that are equal apart from case or characters made portable would share a page; the first one keeps it
+    let mut claimed = HashMap::new();
+    let mut failed = Vec::new();
+    let mut unique = Vec::new();
+    for filename in files {
+        match claimed.entry(page_name(filename).to_lowercase()) {
+            Entry::Vacant(slot) => {
+                slot.insert(filename);
+                unique.push(filename);
+            }
+            Entry::Occupied(owner) => {
+                failed.push((filename.clone(), format!("Its page would replace the page of {}", owner.get())));
+            }
+        }
+    }
+
+    // 1. Render every report in parallel, within the memory budget. Search text is the bulk of what is kept per
+    // report, so it goes to a buffer that spills to disk on large sites and is read back when the index is written
+    let texts = Mutex::new(Spill::within_budget());
+    let outcomes: Vec<(String, Result<(RenderedPage, usize)>)> = unique
+        .par_iter()
+        .map(|filename| {
+            let size = fs::metadata(Path::new(reports_dir).join(filename)).map(|m| m.len()).unwrap_or(0);
+            let _reservation = budget::reserve(budget::render_cost(size));
+            let outcome = render_entry(reports_dir, filename, &pages_dir, render_options).and_then(|mut page| {
+                let text = std::mem::take(&mut page.entry.text);
+                let size = text.len();
+                let slot = lock(&texts).push(text, size)?;
+                Ok((page, slot))
+            });
+            (filename.to_string(), outcome)
+        })
+        .collect();
+    let texts = texts.into_inner().unwrap_or_else(PoisonError::into_inner);
+
+    let mut entries = Vec::new();
+    let mut references = BTreeSet::new();
+    for (filename, outcome) in outcomes {
+        match outcome {
+            Ok((page, slot)) => {
+                entries.push((page.entry, slot));
+                references.extend(page.references);
+            }
+            Err(e) => failed.push((filename, e.to_string())),
+        }
+    }
+    entries.sort_by(|(a, _), (b, _)| b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)));
+
+    // 2. Copy referenced local assets next to the pages so relative links keep working
+    let mut assets = 0;
+    for reference in references {
+        let source = Path::new(reports_dir).join(&reference);
+        if !source.is_file() {
+            continue;
+        }
+        let target = pages_dir.join(&reference);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&source, &target)?;
+        assets += 1;
+    }
+
+    // 3. Write the theme, search script and index page
+    fs::write(assets_dir.join("style.css"), css)?;
+    fs::write(assets_dir.join("search.js"), SEARCH_JS)?;
+    // Loaded as a script rather than fetched, so search also works when opened from file://
+    let mut search_index = BufWriter::new(fs::File::create(assets_dir.join("search-index.js"))?);
+    search_index.write_all(b"window.SEARCH_INDEX = [")?;
+    for (position, (entry, slot)) in entries.iter_mut().enumerate() {
+        if position > 0 {
+            search_index.write_all(b",")?;
+        }
+        entry.text = texts.get(*slot)?;
+        serde_json::to_writer(&mut search_index, entry)?;
+        entry.text = String::new();
+    }
+    search_index.write_all(b"];\n")?;
+    search_index.flush()?;
+
+    let rows: String = entries
+        .iter()
+        .map(|(entry, _)| {
+            format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+                escape_xml(&entry.href),
+                escape_xml(&entry.title),
+                escape_xml(&entry.date),
+                tags_html(&entry.tags)
+            )
+        })
+        .collect();
+    let body = format!(
+        "<h1>Reports</h1>\n<input id=\"search\" type=\"search\" placeholder=\"Search reports\" autofocus>\n<p id=\"count\"></p>\n<table id=\"reports\">\n<thead><tr><th>Title</th><th>Date</th><th>Tags</th></tr></thead>\n<tbody>\n{}</tbody>\n</table>\n<script src=\"assets/search-index.js\"></script>\n<script src=\"assets/search.js\"></script>",
+        rows
+    );
+    let index = output.join("index.html");
+    fs::write(&index, page("Reports", "assets/style.css", &body))?;
+
+    Ok(SiteSummary { index, pages: entries.len(), assets, failed })
+}
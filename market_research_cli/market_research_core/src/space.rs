@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+/// Free space an operation must leave behind, so the system and other writers keep working
+const HEADROOM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Estimates are rough; require this much more than estimated
+const SAFETY_FACTOR: f64 = 1.25;
+
+/// Expected size of one exported report from the size of its markdown source
+pub fn export_estimate(source_bytes: u64, format: &str) -> u64 {
+    match format {
+        // Styling, inline charts and diagrams
+        "html" => source_bytes * 3 + 16 * 1024,
+        // Embedded fonts and rasterized graphics
+        "pdf" => source_bytes * 20 + 256 * 1024,
+        _ => source_bytes,
+    }
+}
+
+/// Total size of the given files in a directory, skipping any that cannot be read
+pub fn total_size(dir: &str, filenames: &[String]) -> u64 {
+    filenames
+        .iter()
+        .filter_map(|filename| fs::metadata(Path::new(dir).join(filename)).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// `1.5 GB`-style size for messages
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+/// Fail before `operation` starts writing if the volume holding `target` (which need not exist yet) lacks room
+/// for about `needed` bytes. When free space cannot be determined the operation is allowed to proceed.
+pub fn ensure_space(target: &Path, needed: u64, operation: &str) -> Result<()> {
+    let existing = match target.ancestors().find(|dir| !dir.as_os_str().is_empty() && dir.exists()) {
+        Some(existing) => existing,
+        None => Path::new("."),
+    };
+    let available = match fs2::available_space(existing) {
+        Ok(available) => available,
+        Err(_) => return Ok(()),
+    };
+
+    let required = (needed as f64 * SAFETY_FACTOR) as u64 + HEADROOM_BYTES;
+    if available < required {
+        return Err(anyhow!(
+            "Not enough disk space for {}: about {} needed on the volume holding {} but only {} is free. \
+             Free some space or choose a target on another volume",
+            operation,
+            human_bytes(required),
+            existing.display(),
+            human_bytes(available)
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use pyo3::prelude::*;
+use rust_xlsxwriter::{DocProperties, ExcelDateTime, Format, Workbook, Worksheet};
+
+use crate::facts::{facts_from_py, Facts};
+use crate::metrics::{cagr, growth};
+use crate::panics::guard;
+use crate::render::pinned_timestamp;
+use crate::tables::{find_tables, parse_number, MarkdownTable};
+
+const MAX_SHEET_NAME: usize = 31;
+
+/// A table pulled out of a report together with the heading it sits under
+struct NamedTable {
+    name: String,
+    table: MarkdownTable,
+}
+
+/// Excel sheet names are limited to 31 characters and may not contain `[]:*?/\`
+fn sheet_name(raw: &str, used: &mut HashSet<String>) -> String {
+    let cleaned: String = raw
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('\'');
+    let base: String = if cleaned.is_empty() { "Table" } else { cleaned }.chars().take(MAX_SHEET_NAME).collect();
+
+    let mut candidate = base.clone();
+    let mut counter = 2;
+    while used.contains(&candidate.to_lowercase()) {
+        let suffix = format!(" ({})", counter);
+        let keep = MAX_SHEET_NAME - suffix.len();
+        candidate = format!("{}{}", base.chars().take(keep).collect::<String>(), suffix);
+        counter += 1;
+    }
+    used.insert(candidate.to_lowercase());
+    candidate
+}
+
+/// Name each table after the nearest heading above it
+fn named_tables(markdown: &str) -> Vec<NamedTable> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    find_tables(markdown)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, table)| {
+            let name = lines[..table.start_line]
+                .iter()
+                .rev()
+                .find_map(|line| {
+                    let heading = line.trim_start().trim_start_matches('#');
+                    (line.trim_start().starts_with('#') && heading.starts_with(' ')).then(|| heading.trim().replace("**", ""))
+                })
+                .unwrap_or_else(|| format!("Table {}", idx + 1));
+            NamedTable { name, table }
+        })
+        .collect()
+}
+
+fn write_header(sheet: &mut Worksheet, headers: &[&str], bold: &Format) -> Result<()> {
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, bold)?;
+    }
+    Ok(())
+}
+
+/// Write a cell as a number when it parses as one, otherwise as text
+fn write_cell(sheet: &mut Worksheet, row: u32, col: u16, cell: &str, percent: &Format) -> Result<()> {
+    match parse_number(cell) {
+        Some(number) if number.percent => {
+            sheet.write_number_with_format(row, col, number.value / 100.0, percent)?;
+        }
+        Some(number) => {
+            sheet.write_number(row, col, number.value)?;
+        }
+        None => {
+            sheet.write_string(row, col, cell.replace("**", "").trim())?;
+        }
+    }
+    Ok(())
+}
+
+/// First-to-last growth of one numeric table column
+struct SeriesMetrics {
+    series: String,
+    first: f64,
+    last: f64,
+    cagr: Option<f64>,
+    total_growth: Option<f64>,
+}
+
+/// Growth metrics for every fully numeric column of a table, first row to last
+fn column_metrics(table: &MarkdownTable) -> Vec<SeriesMetrics> {
+    let mut metrics = Vec::new();
+    for col in 1..table.header.len() {
+        let values: Option<Vec<f64>> = table.rows.iter().map(|row| parse_number(&row[col]).map(|n| n.value)).collect();
+        let values = match values {
+            Some(values) if values.len() > 1 => values,
+            _ => continue,
+        };
+        let (first, last) = (values[0], values[values.len() - 1]);
+        let periods = (values.len() - 1) as f64;
+        metrics.push(SeriesMetrics {
+            series: table.header[col].clone(),
+            first,
+            last,
+            cagr: cagr(first, last, periods),
+            total_growth: growth(first, last),
+        });
+    }
+    metrics
+}
+
+/// Write every table, the supplied facts, and per-column metrics to a multi-sheet workbook
+pub fn write_workbook(markdown: &str, facts: &Facts, path: &Path, deterministic: bool) -> Result<usize> {
+    let mut workbook = Workbook::new();
+    if deterministic {
+        let created = ExcelDateTime::from_timestamp(pinned_timestamp(markdown).and_utc().timestamp())?;
+        workbook.set_properties(&DocProperties::new().set_creation_datetime(&created));
+    }
+    let bold = Format::new().set_bold();
+    let percent = Format::new().set_num_format("0.0%");
+    let mut used = HashSet::new();
+    let tables = named_tables(markdown);
+
+    // 1. One sheet per table
+    let mut sheet_names = Vec::with_capacity(tables.len());
+    for named in &tables {
+        let name = sheet_name(&named.name, &mut used);
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(&name)?;
+        let headers: Vec<&str> = named.table.header.iter().map(|h| h.as_str()).collect();
+        write_header(sheet, &headers, &bold)?;
+        for (row_idx, row) in named.table.rows.iter().enumerate() {
+            for (col, cell) in row.iter().enumerate() {
+                write_cell(sheet, row_idx as u32 + 1, col as u16, cell, &percent)?;
+            }
+        }
+        sheet.autofit();
+        sheet_names.push(name);
+    }
+
+    // 2. Facts, when provided
+    if !facts.is_empty() {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(sheet_name("Facts", &mut used))?;
+        write_header(sheet, &["Key", "Value", "Source", "URL"], &bold)?;
+        for (row, (key, fact)) in facts.iter().enumerate() {
+            let row = row as u32 + 1;
+            sheet.write_string(row, 0, key)?;
+            write_cell(sheet, row, 1, &fact.value, &percent)?;
+            if let Some(source) = &fact.source {
+                sheet.write_string(row, 2, source)?;
+            }
+            if let Some(url) = &fact.url {
+                sheet.write_string(row, 3, url)?;
+            }
+        }
+        sheet.autofit();
+    }
+
+    // 3. Growth metrics for each numeric series
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(sheet_name("Metrics", &mut used))?;
+    write_header(sheet, &["Sheet", "Series", "First", "Last", "CAGR", "Total growth"], &bold)?;
+    let mut row = 1;
+    for (named, name) in tables.iter().zip(&sheet_names) {
+        for metrics in column_metrics(&named.table) {
+            sheet.write_string(row, 0, name)?;
+            sheet.write_string(row, 1, &metrics.series)?;
+            sheet.write_number(row, 2, metrics.first)?;
+            sheet.write_number(row, 3, metrics.last)?;
+            if let Some(value) = metrics.cagr {
+                sheet.write_number_with_format(row, 4, value, &percent)?;
+            }
+            if let Some(value) = metrics.total_growth {
+                sheet.write_number_with_format(row, 5, value, &percent)?;
+            }
+            row += 1;
+        }
+    }
+    sheet.autofit();
+
+    workbook.save(path)?;
+    Ok(tables.len())
+}
+
+/// Export all tables in a report (plus optional facts and derived metrics) to an XLSX workbook
+#[pyfunction]
+#[pyo3(signature = (markdown, path, facts=None, deterministic=false))]
+pub fn export_data_xlsx(markdown: &str, path: &str, facts: Option<&PyAny>, deterministic: bool, py: Python) -> PyResult<usize> {
+    guard("export_data_xlsx", || {
+        let facts = match facts {
+            Some(facts) => facts_from_py(facts)?,
+            None => Facts::new(),
+        };
+
+        py.allow_threads(|| write_workbook(markdown, &facts, Path::new(path), deterministic))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to export workbook: {}", e)))
+    })
+}
@@ -0,0 +1,202 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the accept loop checks whether it should stop
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+
+/// Comment line sent on an idle stream, so proxies and browsers keep the connection open
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request line plus headers a client may send; past it the request is refused
+const MAX_REQUEST_HEAD: u64 = 8 * 1024;
+
+/// Connections served at once, each on its own thread; more are turned away with 503 until one closes
+const MAX_CLIENTS: usize = 16;
+
+/// Current state to stream: a key that changes whenever the state does, and the JSON to send
+pub type Snapshot = Arc<dyn Fn() -> (String, String) + Send + Sync>;
+
+/// Serve `GET /events` as a server-sent event stream of snapshots (one `data:` message per change, checked every
+/// `interval`) and `GET /progress` as the current snapshot, on a background thread until `stop` is set. Responses
+/// carry `Access-Control-Allow-Origin` only when `allow_origin` is given. Requests whose `Host` is not this server
+/// get 403, and request heads over `MAX_REQUEST_HEAD` get 431
+pub fn spawn(
+    listener: TcpListener,
+    stop: Arc<AtomicBool>,
+    interval: Duration,
+    allow_origin: Option<String>,
+    snapshot: Snapshot,
+) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let cors = Arc::new(match allow_origin {
+        Some(origin) => format!("Access-Control-Allow-Origin: {}\r\nVary: Origin\r\n", origin),
+        None => String::new(),
+    });
+    let clients = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        while !stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((mut stream, _)) => {
+                    if clients.fetch_add(1, Ordering::SeqCst) >= MAX_CLIENTS {
+                        clients.fetch_sub(1, Ordering::SeqCst);
+                        let _ = stream
+                            .set_nonblocking(false)
+                            .and_then(|_| respond(&mut stream, "503 Service Unavailable", "text/plain", "", "Too many clients\n"));
+                        continue;
+                    }
+                    let (stop, snapshot, cors, clients) =
+                        (Arc::clone(&stop), Arc::clone(&snapshot), Arc::clone(&cors), Arc::clone(&clients));
+                    thread::spawn(move || {
+                        let _ = handle(stream, &stop, interval, &cors, &snapshot);
+                        clients.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(_) => thread::sleep(ACCEPT_POLL),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle(stream: TcpStream, stop: &AtomicBool, interval: Duration, cors: &str, snapshot: &Snapshot) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut stream = stream;
+    let (request_line, host) = match read_head(&stream)? {
+        Some(head) => head,
+        None => return respond(&mut stream, "431 Request Header Fields Too Large", "text/plain", cors, "Request too large\n"),
+    };
+    // A DNS-rebinding page reaches this port under its own host name; only answer requests addressed to us
+    if !host_allowed(host.as_deref(), stream.local_addr()?) {
+        return respond(&mut stream, "403 Forbidden", "text/plain", cors, "Host not allowed\n");
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path = path.split('?').next().unwrap_or_default();
+    match (method, path) {
+        ("GET", "/events") => stream_events(&mut stream, stop, interval, cors, snapshot),
+        ("GET", "/progress") => {
+            let (_, json) = snapshot();
+            respond(&mut stream, "200 OK", "application/json", cors, &json)
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", cors, "Not found; use /events or /progress\n"),
+    }
+}
+
+/// Read the request line and the `Host` header, reading the remaining headers so the client is not cut off
+/// mid-request; `None` when they run past `MAX_REQUEST_HEAD`
+fn read_head(stream: &TcpStream) -> io::Result<Option<(String, Option<String>)>> {
+    let mut reader = BufReader::new(stream.try_clone()?).take(MAX_REQUEST_HEAD);
+    let mut request_line = String::new();
+    let mut host = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        if !line.ends_with('\n') {
+            if reader.limit() == 0 {
+                return Ok(None);
+            }
+            break;
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        if request_line.is_empty() {
+            request_line = line.clone();
+        } else if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            }
+        }
+    }
+    Ok(Some((request_line, host)))
+}
+
+/// Whether `host` names this server: `localhost`, a loopback address or the address the request came in on, with
+/// the bound port if any
+fn host_allowed(host: Option<&str>, local: SocketAddr) -> bool {
+    let host = match host {
+        Some(host) => host.to_ascii_lowercase(),
+        None => return false,
+    };
+    let (name, port) = match host.strip_prefix('[') {
+        Some(bracketed) => match bracketed.split_once(']') {
+            Some((name, rest)) if rest.is_empty() || rest.starts_with(':') => (name, rest.strip_prefix(':')),
+            _ => return false,
+        },
+        None => match host.split_once(':') {
+            Some((name, port)) => (name, Some(port)),
+            None => (host.as_str(), None),
+        },
+    };
+    if port.is_some_and(|port| port != local.port().to_string()) {
+        return false;
+    }
+    name == "localhost" || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback() || ip == local.ip())
+}
+
+/// Write a complete response; `cors` is empty or the `Access-Control-Allow-Origin` header lines
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, cors: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        cors,
+        body
+    )?;
+    stream.flush()
+}
+
+/// Send a message whenever the snapshot changes until the client disconnects or the server stops
+fn stream_events(
+    stream: &mut TcpStream,
+    stop: &AtomicBool,
+    interval: Duration,
+    cors: &str,
+    snapshot: &Snapshot,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n{}Connection: keep-alive\r\n\r\n",
+        cors
+    )?;
+    stream.flush()?;
+    // Waiting between checks is a read, so a client that hangs up frees its slot without waiting for a write to fail
+    stream.set_read_timeout(Some(interval.max(Duration::from_millis(1))))?;
+
+    let mut last_key = None;
+    let mut last_write = Instant::now();
+    while !stop.load(Ordering::SeqCst) {
+        let (key, json) = snapshot();
+        if last_key.as_ref() != Some(&key) {
+            write!(stream, "data: {}\n\n", json)?;
+            stream.flush()?;
+            last_key = Some(key);
+            last_write = Instant::now();
+        } else if last_write.elapsed() >= KEEP_ALIVE {
+            write!(stream, ": keep-alive\n\n")?;
+            stream.flush()?;
+            last_write = Instant::now();
+        }
+        match stream.read(&mut [0; 64]) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::prelude::*;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::list_files;
+
+/// Per-file facts gathered during the directory scan
+#[derive(Clone, Debug)]
+pub struct FileStats {
+    pub filename: String,
+    pub size: u64,
+    pub words: usize,
+    pub date: NaiveDateTime,
+}
+
+/// Aggregate statistics for the whole reports directory
+#[derive(Debug, Default)]
+pub struct LibraryStats {
+    pub total_reports: usize,
+    pub total_bytes: u64,
+    pub total_words: usize,
+    pub reports_per_month: BTreeMap<String, usize>,
+    pub largest: Option<FileStats>,
+    pub oldest: Option<FileStats>,
+    pub newest: Option<FileStats>,
+}
+
+/// Parse the date formats found in report front matter
+pub fn parse_date(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+        return Some(parsed.naive_local());
+    }
+    if let Ok(parsed) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+        return Some(parsed);
+    }
+    ["%Y-%m-%d", "%B %d, %Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+/// Best-effort report date: front matter `date`, then the `_YYYYMMDD_HHMMSS` filename stamp, then mtime
+pub fn report_date(filename: &str, content: &str, modified: Option<std::time::SystemTime>) -> NaiveDateTime {
+    if let Ok((metadata, _)) = crate::parse_report_metadata(content) {
+        if let Some(parsed) = metadata.get("date").and_then(|date| parse_date(date)) {
+            return parsed;
+        }
+    }
+
+    let stamp = Regex::new(r"(\d{8})_(\d{6})").unwrap();
+    if let Some(caps) = stamp.captures(filename) {
+        let joined = format!("{}{}", &caps[1], &caps[2]);
+        if let Ok(parsed) = NaiveDateTime::parse_from_str(&joined, "%Y%m%d%H%M%S") {
+            return parsed;
+        }
+    }
+
+    modified
+        .map(|time| DateTime::<Local>::from(time).naive_local())
+        .unwrap_or_default()
+}
+
+/// Size, word count and date for one report, or `None` if it cannot be read
+pub fn scan_file(reports_dir: &str, filename: &str) -> Option<FileStats> {
+    let path = Path::new(reports_dir).join(filename);
+    let metadata = fs::metadata(&path).ok()?;
+    let content = fs::read_to_string(&path).ok()?;
+    let body = crate::parse_report_metadata(&content)
+        .map(|(_, body)| body)
+        .unwrap_or_else(|_| content.clone());
+
+    Some(FileStats {
+        filename: filename.to_string(),
+        size: metadata.len(),
+        words: body.split_whitespace().count(),
+        date: report_date(filename, &content, metadata.modified().ok()),
+    })
+}
+
+/// Scan every report with one of `extensions` in parallel and aggregate library statistics
+pub fn compute(reports_dir: &str, extensions: &[String]) -> Result<LibraryStats> {
+    let files: Vec<FileStats> = list_files(reports_dir, extensions)?
+        .par_iter()
+        .filter_map(|(filename, _)| scan_file(reports_dir, filename))
+        .collect();
+
+    let mut stats = LibraryStats {
+        total_reports: files.len(),
+        total_bytes: files.iter().map(|f| f.size).sum(),
+        total_words: files.iter().map(|f| f.words).sum(),
+        ..Default::default()
+    };
+    for file in &files {
+        *stats.reports_per_month.entry(file.date.format("%Y-%m").to_string()).or_insert(0) += 1;
+    }
+    stats.largest = files.iter().max_by_key(|f| f.size).cloned();
+    stats.oldest = files.iter().min_by_key(|f| f.date).cloned();
+    stats.newest = files.iter().max_by_key(|f| f.date).cloned();
+
+    Ok(stats)
+}
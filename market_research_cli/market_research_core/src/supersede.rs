@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::facts::parse_fact_number;
+use crate::qa::{prose_sentences, terms};
+use crate::stats::report_date;
+use crate::tables::ParsedNumber;
+
+/// Words that describe figures in general rather than what is measured
+const GENERIC_WORDS: &[&str] = &[
+    "estimated", "expected", "reached", "around", "approximately", "roughly", "nearly", "over", "about", "by", "in", "of",
+];
+
+/// A figure stated in a report sentence
+struct Figure {
+    line: usize,
+    sentence: String,
+    value: String,
+    number: ParsedNumber,
+    /// Terms naming what the figure measures
+    subject: HashSet<String>,
+}
+
+/// A figure in an older report that a newer report states differently
+pub struct Superseded {
+    pub line: usize,
+    pub claim: String,
+    pub value: String,
+    pub newer_filename: String,
+    pub newer_date: NaiveDate,
+    pub newer_line: usize,
+    pub newer_claim: String,
+    pub newer_value: String,
+    /// Relative change from the old figure to the new one
+    pub change: f64,
+    /// Overlap of what the two sentences are about, from 0 to 1
+    pub similarity: f64,
+}
+
+/// Figures in a report, skipping bare years
+fn figures(content: &str) -> Vec<Figure> {
+    let number = Regex::new(
+        r"(?i)[$€£¥]?\d[\d,]*(?:\.\d+)?(?:\s*%|\s*(?:thousand|million|mn|billion|bn|trillion)\b|[KMBT]\b)?",
+    )
+    .unwrap();
+    let year = Regex::new(r"^(?:19|20)\d{2}$").unwrap();
+
+    let mut found = Vec::new();
+    for sentence in prose_sentences(content) {
+        let values: Vec<&str> = number
+            .find_iter(&sentence.text)
+            .map(|m| m.as_str().trim().trim_end_matches(','))
+            .filter(|value| !year.is_match(value))
+            .collect();
+        // Sentences with several figures compare poorly as a whole; keep the headline (first) figure
+        let value = match values.first() {
+            Some(value) => value.to_string(),
+            None => continue,
+        };
+        let parsed = match parse_fact_number(&value) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        let subject: HashSet<String> = terms(&number.replace_all(&sentence.text, " "))
+            .into_iter()
+            .filter(|term| !term.chars().all(|c| c.is_ascii_digit()) && !GENERIC_WORDS.contains(&term.as_str()))
+            .collect();
+        if subject.len() < 2 {
+            continue;
+        }
+        found.push(Figure { line: sentence.line, sentence: sentence.text, value, number: parsed, subject });
+    }
+    found
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(b).count() as f64 / union as f64
+    }
+}
+
+fn dated_content(reports_dir: &str, filename: &str) -> Option<(NaiveDate, String)> {
+    let path = Path::new(reports_dir).join(filename);
+    let content = fs::read_to_string(&path).ok()?;
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    Some((report_date(filename, &content, modified).date(), content))
+}
+
+/// Figures in `filename` that a newer report restates about the same subject with a value
+/// more than `tolerance` (relative) away. Each old figure is matched to its closest newer statement.
+pub fn find_superseded(reports_dir: &str, filename: &str, files: &[String], tolerance: f64, min_similarity: f64) -> Result<Vec<Superseded>> {
+    let (date, content) = dated_content(reports_dir, filename).ok_or_else(|| anyhow!("Report file not found: {}", filename))?;
+    let old_figures = figures(&content);
+    if old_figures.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let newer: Vec<(String, NaiveDate, Vec<Figure>)> = files
+        .par_iter()
+        .filter(|other| other.as_str() != filename)
+        .filter_map(|other| {
+            let (other_date, other_content) = dated_content(reports_dir, other)?;
+            (other_date > date).then(|| (other.clone(), other_date, figures(&other_content)))
+        })
+        .collect();
+
+    let mut superseded = Vec::new();
+    for old in &old_figures {
+        let mut best: Option<(f64, &String, NaiveDate, &Figure)> = None;
+        for (other, other_date, other_figures) in &newer {
+            for new in other_figures {
+                if new.number.prefix != old.number.prefix || new.number.percent != old.number.percent {
+                    continue;
+                }
+                let similarity = jaccard(&old.subject, &new.subject);
+                if similarity < min_similarity {
+                    continue;
+                }
+                // Prefer the closest subject, then the most recent report
+                let better = match &best {
+                    None => true,
+                    Some((score, _, best_date, _)) => similarity > *score || (similarity == *score && other_date > best_date),
+                };
+                if better {
+                    best = Some((similarity, other, *other_date, new));
+                }
+            }
+        }
+
+        let (similarity, other, other_date, new) = match best {
+            Some(best) => best,
+            None => continue,
+        };
+        let change = if old.number.value == 0.0 {
+            if new.number.value == 0.0 { 0.0 } else { f64::INFINITY }
+        } else {
+            (new.number.value - old.number.value) / old.number.value.abs()
+        };
+        if change.abs() <= tolerance {
+            continue;
+        }
+        superseded.push(Superseded {
+            line: old.line,
+            claim: old.sentence.clone(),
+            value: old.value.clone(),
+            newer_filename: other.clone(),
+            newer_date: other_date,
+            newer_line: new.line,
+            newer_claim: new.sentence.clone(),
+            newer_value: new.value.clone(),
+            change,
+            similarity,
+        });
+    }
+    Ok(superseded)
+}
@@ -0,0 +1,544 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::panics::guard;
+
+/// Column alignment as declared by a markdown table delimiter row
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// A GFM table located in a markdown document
+#[derive(Clone, Debug)]
+pub struct MarkdownTable {
+    /// Index of the header line in the source
+    pub start_line: usize,
+    /// Index one past the last body line in the source
+    pub end_line: usize,
+    pub header: Vec<String>,
+    pub alignments: Vec<Alignment>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A numeric cell value with the decorations it was written with
+#[derive(Clone, Debug)]
+pub struct ParsedNumber {
+    pub value: f64,
+    pub prefix: String,
+    pub percent: bool,
+}
+
+/// Split a table row into trimmed cells, honouring escaped pipes
+pub fn split_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = if inner.ends_with('|') && !inner.ends_with("\\|") {
+        &inner[..inner.len() - 1]
+    } else {
+        inner
+    };
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push_str("\\|");
+                chars.next();
+            }
+            '|' => {
+                cells.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+/// Parse a delimiter row like `| --- | :---: | ---: |`
+pub fn parse_delimiter_row(line: &str) -> Option<Vec<Alignment>> {
+    if !line.contains('-') {
+        return None;
+    }
+
+    let mut alignments = Vec::new();
+    for cell in split_row(line) {
+        let cell = cell.trim();
+        let body = cell.trim_matches(':');
+        if body.is_empty() || !body.chars().all(|c| c == '-') {
+            return None;
+        }
+        let alignment = match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Alignment::Center,
+            (true, false) => Alignment::Left,
+            (false, true) => Alignment::Right,
+            (false, false) => Alignment::None,
+        };
+        alignments.push(alignment);
+    }
+    Some(alignments)
+}
+
+/// Find every table in a markdown document, skipping fenced code blocks
+pub fn find_tables(markdown: &str) -> Vec<MarkdownTable> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut tables = Vec::new();
+    let mut in_fence = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            i += 1;
+            continue;
+        }
+
+        if !in_fence && line.contains('|') && i + 1 < lines.len() {
+            if let Some(alignments) = parse_delimiter_row(lines[i + 1]) {
+                let header = split_row(line);
+                if header.len() == alignments.len() {
+                    let mut rows = Vec::new();
+                    let mut end = i + 2;
+                    while end < lines.len() && lines[end].contains('|') && !lines[end].trim().is_empty() {
+                        let mut row = split_row(lines[end]);
+                        row.resize(header.len(), String::new());
+                        rows.push(row);
+                        end += 1;
+                    }
+                    tables.push(MarkdownTable {
+                        start_line: i,
+                        end_line: end,
+                        header,
+                        alignments,
+                        rows,
+                    });
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    tables
+}
+
+/// Render a table back to markdown with padded columns
+pub fn render_table(table: &MarkdownTable) -> String {
+    let columns = table.header.len();
+    let mut widths = vec![3usize; columns];
+    for row in std::iter::once(&table.header).chain(table.rows.iter()) {
+        for (idx, cell) in row.iter().enumerate().take(columns) {
+            widths[idx] = widths[idx].max(cell.chars().count());
+        }
+    }
+
+    let pad = |cell: &str, idx: usize| -> String {
+        let width = widths[idx];
+        match table.alignments[idx] {
+            Alignment::Right => format!("{:>width$}", cell, width = width),
+            Alignment::Center => format!("{:^width$}", cell, width = width),
+            _ => format!("{:<width$}", cell, width = width),
+        }
+    };
+
+    let mut out = Vec::with_capacity(table.rows.len() + 2);
+    let header: Vec<String> = table.header.iter().enumerate().map(|(i, c)| pad(c, i)).collect();
+    out.push(format!("| {} |", header.join(" | ")));
+
+    let delimiter: Vec<String> = table
+        .alignments
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            let dashes = "-".repeat(widths[i].saturating_sub(2).max(1));
+            match a {
+                Alignment::Left => format!(":{}-", dashes),
+                Alignment::Center => format!(":{}:", dashes),
+                Alignment::Right => format!("-{}:", dashes),
+                Alignment::None => format!("-{}-", dashes),
+            }
+        })
+        .collect();
+    out.push(format!("| {} |", delimiter.join(" | ")));
+
+    for row in &table.rows {
+        let cells: Vec<String> = row.iter().enumerate().take(columns).map(|(i, c)| pad(c, i)).collect();
+        out.push(format!("| {} |", cells.join(" | ")));
+    }
+
+    out.join("\n")
+}
+
+/// Replace the source lines of each table with new content
+pub fn replace_tables<F>(markdown: &str, mut render: F) -> String
+where
+    F: FnMut(&MarkdownTable) -> String,
+{
+    let tables = find_tables(markdown);
+    if tables.is_empty() {
+        return markdown.to_string();
+    }
+
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut cursor = 0;
+    for table in &tables {
+        out.extend(lines[cursor..table.start_line].iter().map(|l| l.to_string()));
+        out.push(render(table));
+        cursor = table.end_line;
+    }
+    out.extend(lines[cursor..].iter().map(|l| l.to_string()));
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Parse a cell such as "4,200,000", "$4.2M", "12.5%" or "(1,200)" into a number
+pub fn parse_number(cell: &str) -> Option<ParsedNumber> {
+    let mut text = cell.trim().replace("**", "");
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut negative = false;
+    if text.starts_with('(') && text.ends_with(')') {
+        negative = true;
+        text = text[1..text.len() - 1].trim().to_string();
+    }
+    if let Some(rest) = text.strip_prefix('-') {
+        negative = !negative;
+        text = rest.trim_start().to_string();
+    }
+
+    let prefix: String = text.chars().take_while(|c| matches!(c, '$' | '€' | '£' | '¥')).collect();
+    let mut body = text[prefix.len()..].trim().to_string();
+
+    let percent = body.ends_with('%');
+    if percent {
+        body.pop();
+    }
+
+    let multiplier = match body.chars().last() {
+        Some('K') | Some('k') => 1e3,
+        Some('M') | Some('m') => 1e6,
+        Some('B') | Some('b') => 1e9,
+        Some('T') | Some('t') => 1e12,
+        _ => 1.0,
+    };
+    if multiplier != 1.0 {
+        if percent {
+            return None;
+        }
+        body.pop();
+    }
+
+    let digits = body.trim().replace(',', "");
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let mut value: f64 = digits.parse().ok()?;
+    value *= multiplier;
+    if negative {
+        value = -value;
+    }
+
+    Some(ParsedNumber { value, prefix, percent })
+}
+
+/// Insert thousands separators into the integer part of a formatted number
+pub fn group_thousands(formatted: &str) -> String {
+    let (sign, rest) = match formatted.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", formatted),
+    };
+    let (int_part, frac_part) = match rest.find('.') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, ""),
+    };
+
+    let mut grouped = String::with_capacity(int_part.len() + int_part.len() / 3);
+    for (idx, c) in int_part.chars().enumerate() {
+        if idx > 0 && (int_part.len() - idx) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    format!("{}{}{}", sign, grouped, frac_part)
+}
+
+/// Rules controlling how numeric table columns are formatted
+#[derive(Clone, Debug)]
+pub struct NumberFormatRules {
+    pub thousands_separator: bool,
+    pub decimals: Option<usize>,
+    /// One of "none", "K", "M", "B" or "auto"
+    pub unit: String,
+    pub align_right: bool,
+}
+
+impl Default for NumberFormatRules {
+    fn default() -> Self {
+        NumberFormatRules {
+            thousands_separator: true,
+            decimals: None,
+            unit: "auto".to_string(),
+            align_right: true,
+        }
+    }
+}
+
+impl NumberFormatRules {
+    /// Build rules from an optional Python dict, rejecting unknown units
+    pub fn from_dict(rules: Option<&PyDict>) -> PyResult<Self> {
+        let mut parsed = NumberFormatRules::default();
+        let rules = match rules {
+            Some(rules) => rules,
+            None => return Ok(parsed),
+        };
+
+        if let Some(value) = rules.get_item("thousands_separator") {
+            parsed.thousands_separator = value.extract()?;
+        }
+        if let Some(value) = rules.get_item("decimals") {
+            parsed.decimals = value.extract()?;
+        }
+        if let Some(value) = rules.get_item("align_right") {
+            parsed.align_right = value.extract()?;
+        }
+        if let Some(value) = rules.get_item("unit") {
+            let unit: String = value.extract()?;
+            parsed.unit = match unit.to_lowercase().as_str() {
+                "none" | "" => "none".to_string(),
+                "k" => "K".to_string(),
+                "m" => "M".to_string(),
+                "b" => "B".to_string(),
+                "auto" => "auto".to_string(),
+                _ => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Unknown unit '{}'. Expected none, K, M, B or auto", unit)
+                    ));
+                }
+            };
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn unit_divisor(unit: &str) -> f64 {
+    match unit {
+        "K" => 1e3,
+        "M" => 1e6,
+        "B" => 1e9,
+        _ => 1.0,
+    }
+}
+
+/// Pick a single unit for a column so every row uses the same scale
+fn column_unit(values: &[ParsedNumber], rules: &NumberFormatRules) -> String {
+    if rules.unit != "auto" {
+        return rules.unit.clone();
+    }
+
+    let max = values
+        .iter()
+        .filter(|v| !v.percent)
+        .map(|v| v.value.abs())
+        .fold(0.0_f64, f64::max);
+    if max >= 1e9 {
+        "B".to_string()
+    } else if max >= 1e6 {
+        "M".to_string()
+    } else {
+        "none".to_string()
+    }
+}
+
+/// Pick the decimals for a whole column so its rows line up: the configured count, else one for scaled or
+/// percentage columns, two when any plain value has a fraction, and none otherwise
+fn column_decimals(values: &[ParsedNumber], unit: &str, rules: &NumberFormatRules) -> usize {
+    rules.decimals.unwrap_or_else(|| {
+        let plain: Vec<&ParsedNumber> = values.iter().filter(|v| !v.percent).collect();
+        if unit != "none" || plain.is_empty() {
+            1
+        } else if plain.iter().any(|v| v.value.fract() != 0.0) {
+            2
+        } else {
+            0
+        }
+    })
+}
+
+/// Whether a column holds years or periods, which read wrong once grouped or scaled (`2,024`, `2.0K`): a Year or
+/// Period header, or cells that are all bare four-digit integers
+fn is_year_column(header: &str, cells: &[&str]) -> bool {
+    let header = header.trim().to_lowercase();
+    if matches!(header.as_str(), "year" | "years" | "period" | "fy" | "fiscal year") {
+        return true;
+    }
+    cells.iter().all(|cell| {
+        let cell = cell.trim();
+        cell.len() == 4 && cell.bytes().all(|b| b.is_ascii_digit()) && !cell.starts_with('0')
+    })
+}
+
+fn format_number(number: &ParsedNumber, unit: &str, decimals: usize, rules: &NumberFormatRules) -> String {
+    let (scaled, suffix) = if number.percent {
+        (number.value, "%")
+    } else {
+        (number.value / unit_divisor(unit), if unit == "none" { "" } else { unit })
+    };
+
+    let mut formatted = format!("{:.*}", decimals, scaled.abs());
+    if rules.thousands_separator {
+        formatted = group_thousands(&formatted);
+    }
+    let sign = if scaled < 0.0 { "-" } else { "" };
+
+    format!("{}{}{}{}", sign, number.prefix, formatted, suffix)
+}
+
+/// Apply number formatting rules to the numeric columns of one table, leaving year columns as they are
+pub fn format_table(table: &MarkdownTable, rules: &NumberFormatRules) -> MarkdownTable {
+    let mut formatted = table.clone();
+
+    for col in 0..table.header.len() {
+        let cells: Vec<&str> = table
+            .rows
+            .iter()
+            .map(|row| row[col].as_str())
+            .filter(|cell| !cell.trim().is_empty() && cell.trim() != "-")
+            .collect();
+        if cells.is_empty() || is_year_column(&table.header[col], &cells) {
+            continue;
+        }
+
+        let parsed: Vec<ParsedNumber> = cells.iter().filter_map(|c| parse_number(c)).collect();
+        if parsed.len() != cells.len() {
+            continue;
+        }
+
+        let unit = column_unit(&parsed, rules);
+        let decimals = column_decimals(&parsed, &unit, rules);
+        for row in formatted.rows.iter_mut() {
+            if let Some(number) = parse_number(&row[col]) {
+                row[col] = format_number(&number, &unit, decimals, rules);
+            }
+        }
+        if rules.align_right {
+            formatted.alignments[col] = Alignment::Right;
+        }
+    }
+
+    formatted
+}
+
+/// Normalize numeric columns across all tables in a report
+#[pyfunction]
+#[pyo3(signature = (markdown, rules=None))]
+pub fn format_table_numbers(markdown: &str, rules: Option<&PyDict>) -> PyResult<String> {
+    guard("format_table_numbers", || {
+        let rules = NumberFormatRules::from_dict(rules)?;
+        Ok(replace_tables(markdown, |table| render_table(&format_table(table, &rules))))
+    })
+}
+
+/// Row keys for a table: blank header cells become `column_N` and repeated names get a `_2`, `_3` suffix
+fn column_keys(header: &[String]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::with_capacity(header.len());
+    for (idx, cell) in header.iter().enumerate() {
+        let name = match cell.replace("\\|", "|") {
+            name if name.is_empty() => format!("column_{}", idx + 1),
+            name => name,
+        };
+        let mut key = name.clone();
+        let mut suffix = 2;
+        while keys.contains(&key) {
+            key = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        keys.push(key);
+    }
+    keys
+}
+
+/// A cell as a Python value: None when blank, an int or float when it reads as a number, otherwise its text
+fn cell_to_py(py: Python, cell: &str, parse_numbers: bool) -> PyObject {
+    if cell.is_empty() {
+        return py.None();
+    }
+    match parse_number(cell).filter(|_| parse_numbers) {
+        Some(number) if number.value.fract() == 0.0 && number.value.abs() < 9e15 => (number.value as i64).into_py(py),
+        Some(number) => number.value.into_py(py),
+        None => cell.replace("\\|", "|").into_py(py),
+    }
+}
+
+/// Every table in a report as a list of rows keyed by header, e.g. for `pandas.DataFrame`. Numbers are parsed
+/// unless `parse_numbers` is false: currency symbols, `%`, thousands separators and K/M/B/T suffixes are dropped
+/// ("$4.2M" is 4200000, "12.5%" is 12.5) and accounting parentheses negate. Blank cells are None
+#[pyfunction]
+#[pyo3(signature = (markdown, parse_numbers=true))]
+pub fn extract_tables(markdown: &str, parse_numbers: bool, py: Python) -> PyResult<PyObject> {
+    guard("extract_tables", || {
+        let tables = PyList::empty(py);
+        for table in find_tables(markdown) {
+            let keys = column_keys(&table.header);
+            let rows = PyList::empty(py);
+            for row in &table.rows {
+                let entry = PyDict::new(py);
+                for (key, cell) in keys.iter().zip(row) {
+                    entry.set_item(key, cell_to_py(py, cell, parse_numbers))?;
+                }
+                rows.append(entry)?;
+            }
+            tables.append(rows)?;
+        }
+        Ok(tables.into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_tables, format_table, NumberFormatRules};
+
+    /// Formatted rows of the first table in `markdown`
+    fn formatted_rows(markdown: &str, rules: &NumberFormatRules) -> Vec<Vec<String>> {
+        format_table(&find_tables(markdown)[0], rules).rows
+    }
+
+    #[test]
+    fn leaves_year_columns_alone() {
+        let markdown = "| Year | Revenue |\n|---|---|\n| 2023 | 4200000 |\n| 2024 | 5100000 |\n";
+        let rows = formatted_rows(markdown, &NumberFormatRules::default());
+        assert_eq!(rows, vec![vec!["2023", "4.2M"], vec!["2024", "5.1M"]]);
+
+        // Bare four-digit integers are years whatever the header says, even with a unit forced
+        let markdown = "| | Units |\n|---|---|\n| 2023 | 1500 |\n| 2024 | 2500.5 |\n";
+        let rules = NumberFormatRules { unit: "K".to_string(), ..NumberFormatRules::default() };
+        let rows = formatted_rows(markdown, &rules);
+        assert_eq!(rows, vec![vec!["2023", "1.5K"], vec!["2024", "2.5K"]]);
+    }
+
+    #[test]
+    fn uses_one_decimal_count_per_column() {
+        let markdown = "| Segment | Share | Price |\n|---|---|---|\n| A | 4.5 | 12 |\n| B | 4200000 | 7 |\n";
+        let rules = NumberFormatRules { unit: "none".to_string(), ..NumberFormatRules::default() };
+        let rows = formatted_rows(markdown, &rules);
+        assert_eq!(rows, vec![vec!["A", "4.50", "12"], vec!["B", "4,200,000.00", "7"]]);
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+
+use pyo3::prelude::*;
+use regex::Regex;
+
+use crate::panics::guard;
+use crate::sections::split_sections;
+
+/// Sections whose sentences are never takeaways
+const SKIPPED_SECTIONS: &[&str] = &[
+    "sources", "references", "bibliography", "citations", "appendix", "methodology", "key takeaways", "executive summary",
+];
+
+/// Words that say nothing about what a sentence is about
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "was", "were", "with", "that", "this", "from", "have", "has", "had", "its", "their",
+    "they", "which", "while", "will", "would", "can", "could", "into", "over", "than", "then", "also", "been", "being",
+    "more", "most", "such", "these", "those", "our", "not", "but", "all", "any", "per", "about", "between",
+];
+
+/// Sentences shorter or longer than this (in words) make poor bullets
+const MIN_WORDS: usize = 6;
+const MAX_WORDS: usize = 60;
+
+/// Candidates sharing more than this fraction of words with a chosen takeaway are redundant
+const MAX_OVERLAP: f64 = 0.6;
+
+/// A candidate sentence and its score
+pub struct Takeaway {
+    pub sentence: String,
+    pub score: f64,
+    /// Position in the report, used to keep bullets in reading order
+    position: usize,
+}
+
+fn terms(sentence: &str) -> Vec<String> {
+    let word = Regex::new(r"[A-Za-z][A-Za-z0-9'\-]+").unwrap();
+    word.find_iter(sentence)
+        .map(|m| m.as_str().to_lowercase())
+        .filter(|w| w.len() > 2 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Prose sentences of the report, skipping headings, code, tables and reference sections
+fn candidate_sentences(markdown: &str) -> Vec<String> {
+    let body = crate::parse_report_metadata(markdown)
+        .map(|(_, body)| body)
+        .unwrap_or_else(|_| markdown.to_string());
+    let lines: Vec<&str> = body.lines().collect();
+    let list_marker = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+").unwrap();
+    let sentence_end = Regex::new(r"[.!?](?:\[[^\]]*\])*\s+").unwrap();
+
+    let mut sentences = Vec::new();
+    for section in split_sections(&body) {
+        let heading = section.heading.to_lowercase();
+        if SKIPPED_SECTIONS.iter().any(|skipped| heading.contains(skipped)) {
+            continue;
+        }
+        let body_start = if section.level == 0 { section.start_line } else { section.start_line + 1 };
+
+        // Join wrapped lines into paragraphs; list items and blank lines end a paragraph
+        let mut paragraphs: Vec<String> = Vec::new();
+        let mut current = String::new();
+        let mut in_fence = false;
+        for line in &lines[body_start..section.end_line] {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                continue;
+            }
+            let is_item = list_marker.is_match(line);
+            if in_fence || trimmed.is_empty() || trimmed.starts_with('|') || trimmed.starts_with('!') || is_item {
+                if !current.is_empty() {
+                    paragraphs.push(std::mem::take(&mut current));
+                }
+                if is_item && !in_fence {
+                    current = list_marker.replace(line, "").trim().to_string();
+                }
+                continue;
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(trimmed.trim_start_matches('>').trim());
+        }
+        if !current.is_empty() {
+            paragraphs.push(current);
+        }
+
+        for paragraph in paragraphs {
+            let mut start = 0;
+            for end in sentence_end.find_iter(&paragraph) {
+                sentences.push(paragraph[start..end.end()].trim().to_string());
+                start = end.end();
+            }
+            sentences.push(paragraph[start..].trim().to_string());
+        }
+    }
+    sentences.retain(|s| (MIN_WORDS..=MAX_WORDS).contains(&s.split_whitespace().count()));
+    sentences
+}
+
+fn cosine(a: &HashMap<&str, f64>, b: &HashMap<&str, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(term, x)| b.get(term).map(|y| x * y)).sum();
+    let norm = |v: &HashMap<&str, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// Pick the `n` most central sentences, favoring ones that carry figures and citations.
+/// Centrality is similarity to the report as a whole; near-duplicates of chosen sentences are skipped.
+/// Results are returned in reading order.
+pub fn extract_takeaways(markdown: &str, n: usize) -> Vec<Takeaway> {
+    let sentences = candidate_sentences(markdown);
+    let sentence_terms: Vec<Vec<String>> = sentences.iter().map(|s| terms(s)).collect();
+
+    // Weight terms by how many sentences use them, so one-off words do not make a sentence look central
+    let mut document: HashMap<&str, f64> = HashMap::new();
+    for words in &sentence_terms {
+        for word in words.iter().collect::<HashSet<_>>() {
+            *document.entry(word.as_str()).or_insert(0.0) += 1.0;
+        }
+    }
+
+    let figure = Regex::new(r"\d").unwrap();
+    let citation = Regex::new(r"\[\^?\d+\]|\[[^\]]+\]\([^)]+\)|\(([^()]*\b(?:19|20)\d{2}|[Ss]ource:[^()]*)\)").unwrap();
+    let mut candidates: Vec<Takeaway> = sentences
+        .into_iter()
+        .zip(&sentence_terms)
+        .enumerate()
+        .map(|(position, (sentence, words))| {
+            let mut vector: HashMap<&str, f64> = HashMap::new();
+            for word in words {
+                *vector.entry(word.as_str()).or_insert(0.0) += 1.0;
+            }
+            let has_figure = figure.is_match(&sentence);
+            let has_citation = citation.is_match(&sentence);
+            let score = cosine(&vector, &document)
+                * if has_figure { 1.5 } else { 1.0 }
+                * if has_citation { 1.3 } else { 1.0 };
+            Takeaway { sentence, score, position }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.position.cmp(&b.position)));
+
+    let mut chosen: Vec<Takeaway> = Vec::new();
+    let mut chosen_terms: Vec<HashSet<String>> = Vec::new();
+    for candidate in candidates {
+        if chosen.len() >= n {
+            break;
+        }
+        let words: HashSet<String> = terms(&candidate.sentence).into_iter().collect();
+        let redundant = chosen_terms.iter().any(|other| {
+            let smaller = words.len().min(other.len()).max(1) as f64;
+            words.intersection(other).count() as f64 / smaller > MAX_OVERLAP
+        });
+        if !redundant {
+            chosen_terms.push(words);
+            chosen.push(candidate);
+        }
+    }
+    chosen.sort_by_key(|takeaway| takeaway.position);
+    chosen
+}
+
+/// The `n` most central, figure-bearing sentences of a report as a markdown bullet list,
+/// a starting point for the executive summary.
+#[pyfunction]
+#[pyo3(signature = (markdown, n=5))]
+pub fn key_takeaways(markdown: &str, n: usize, py: Python) -> PyResult<String> {
+    guard("key_takeaways", || {
+        let takeaways = py.allow_threads(|| extract_takeaways(markdown, n));
+        Ok(takeaways.iter().map(|takeaway| format!("- {}\n", takeaway.sentence)).collect())
+    })
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::{Captures, Regex};
+
+use crate::panics::guard;
+use crate::{ids, store_report, write_atomic};
+
+/// Template directory inside the reports directory
+pub const TEMPLATES_DIR: &str = ".templates";
+
+/// `{{ name }}` placeholders; `{{fact:key}}` and `{{ cagr(...) }}` are left for the renderer
+fn placeholder_pattern() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.-]*)\s*\}\}").unwrap()
+}
+
+/// Placeholder names in order of first appearance
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    placeholder_pattern()
+        .captures_iter(template)
+        .map(|caps| caps[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Values every template can use without passing them: `date`, `datetime` and `year`
+fn builtin_vars() -> HashMap<String, String> {
+    let now = Local::now();
+    HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("datetime".to_string(), now.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ("year".to_string(), now.format("%Y").to_string()),
+    ])
+}
+
+/// Substitute placeholders, failing with the full list of names that have no value
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let builtins = builtin_vars();
+    let mut missing = Vec::new();
+    let rendered = placeholder_pattern().replace_all(template, |caps: &Captures| {
+        let name = &caps[1];
+        match vars.get(name).or_else(|| builtins.get(name)) {
+            Some(value) => value.clone(),
+            None => {
+                if !missing.contains(&name.to_string()) {
+                    missing.push(name.to_string());
+                }
+                caps[0].to_string()
+            }
+        }
+    });
+
+    if !missing.is_empty() {
+        return Err(anyhow!("Missing template variables: {}", missing.join(", ")));
+    }
+    Ok(rendered.to_string())
+}
+
+fn template_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!("Invalid template name '{}'. Use letters, digits, '-' and '_'", name));
+    }
+    Ok(dir.join(format!("{}.md", name)))
+}
+
+fn to_py_err(action: &str, e: anyhow::Error) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to {}: {}", action, e))
+}
+
+/// Named markdown skeletons with `{{placeholders}}`, stored under `.templates/` in the reports directory
+#[pyclass]
+pub struct TemplateManager {
+    reports_dir: String,
+    templates_dir: PathBuf,
+}
+
+#[pymethods]
+impl TemplateManager {
+    #[new]
+    #[pyo3(signature = (reports_dir, templates_dir=None))]
+    fn new(reports_dir: &str, templates_dir: Option<&str>) -> PyResult<Self> {
+        guard("TemplateManager.new", || {
+            let templates_dir = match templates_dir {
+                Some(dir) => PathBuf::from(dir),
+                None => Path::new(reports_dir).join(TEMPLATES_DIR),
+            };
+            Ok(TemplateManager { reports_dir: reports_dir.to_string(), templates_dir })
+        })
+    }
+
+    /// Store a template under `name`; refuses to replace an existing one unless `overwrite`
+    #[pyo3(signature = (name, content, overwrite=false))]
+    fn add_template(&self, name: &str, content: &str, overwrite: bool) -> PyResult<()> {
+        guard("TemplateManager.add_template", || {
+            let path = template_path(&self.templates_dir, name).map_err(|e| to_py_err("add template", e))?;
+            if path.exists() && !overwrite {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileExistsError, _>(
+                    format!("Template '{}' already exists", name)
+                ));
+            }
+            fs::create_dir_all(&self.templates_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create directory: {}", e)))?;
+            write_atomic(&path, content.as_bytes())
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to save template: {}", e)))
+        })
+    }
+
+    /// Return a template's raw content
+    fn get_template(&self, name: &str) -> PyResult<String> {
+        guard("TemplateManager.get_template", || {
+            let path = template_path(&self.templates_dir, name).map_err(|e| to_py_err("read template", e))?;
+            if !path.is_file() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Template '{}' not found", name)
+                ));
+            }
+            fs::read_to_string(&path)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read template: {}", e)))
+        })
+    }
+
+    /// Delete a template, returning whether it existed
+    fn remove_template(&self, name: &str) -> PyResult<bool> {
+        guard("TemplateManager.remove_template", || {
+            let path = template_path(&self.templates_dir, name).map_err(|e| to_py_err("remove template", e))?;
+            if !path.is_file() {
+                return Ok(false);
+            }
+            fs::remove_file(&path)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to remove template: {}", e)))?;
+            Ok(true)
+        })
+    }
+
+    /// List templates as `[{name, placeholders}]`, sorted by name
+    fn list_templates(&self, py: Python) -> PyResult<PyObject> {
+        guard("TemplateManager.list_templates", || {
+            let result = PyList::empty(py);
+            if !self.templates_dir.is_dir() {
+                return Ok(result.into());
+            }
+
+            let mut names: Vec<String> = fs::read_dir(&self.templates_dir)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to list templates: {}", e)))?
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    let is_template = path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false);
+                    is_template.then(|| path.file_stem()?.to_str().map(str::to_string)).flatten()
+                })
+                .collect();
+            names.sort();
+
+            for name in names {
+                let dict = PyDict::new(py);
+                dict.set_item("placeholders", placeholders(&self.get_template(&name)?))?;
+                dict.set_item("name", name)?;
+                result.append(dict)?;
+            }
+            Ok(result.into())
+        })
+    }
+
+    /// Render a template with `vars` without saving; `date`, `datetime` and `year` are filled in automatically
+    #[pyo3(signature = (name, vars=None))]
+    fn render(&self, name: &str, vars: Option<&PyDict>) -> PyResult<String> {
+        guard("TemplateManager.render", || {
+            let template = self.get_template(name)?;
+            render_template(&template, &vars_from_py(vars)?).map_err(|e| to_py_err("render template", e))
+        })
+    }
+
+    /// Render a template and save it as a new report, returning its path.
+    /// Without `filename` the report gets an id and is named after its title, like `save_report(auto_id=True)`.
+    #[pyo3(signature = (name, vars=None, filename=None))]
+    fn create_from_template(&self, name: &str, vars: Option<&PyDict>, filename: Option<&str>, py: Python) -> PyResult<String> {
+        guard("TemplateManager.create_from_template", || {
+            let content = self.render(name, vars)?;
+            let (filename, content) = match filename {
+                Some(filename) => (filename.to_string(), content),
+                None => ids::assign_identity(&self.reports_dir, &format!("{}.md", name), &content)
+                    .map_err(|e| to_py_err("assign report id", e))?,
+            };
+
+            // Same checks, locking, index and history as `ReportManager.save_report`, but never over an existing report
+            let path = store_report(&self.reports_dir, &filename, &content, false, py)?;
+            Ok(path.to_string_lossy().to_string())
+        })
+    }
+}
+
+/// Template variables from a Python dict; non-string values use their display text
+fn vars_from_py(vars: Option<&PyDict>) -> PyResult<HashMap<String, String>> {
+    let mut result = HashMap::new();
+    if let Some(vars) = vars {
+        for (key, value) in vars.iter() {
+            result.insert(key.str()?.to_string(), value.str()?.to_string());
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,220 @@
+/// Built-in looks for HTML and PDF exports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme {
+    /// Sans-serif with shaded table headers and code, the long-standing default
+    Corporate,
+    /// Light rules instead of boxes, more white space
+    Minimal,
+    /// Light text on a dark page
+    Dark,
+    /// Serif, black on white, no shading, links followed by their URL and no page breaks inside tables or figures
+    Print,
+}
+
+impl Theme {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "corporate" | "default" => Some(Theme::Corporate),
+            "minimal" => Some(Theme::Minimal),
+            "dark" => Some(Theme::Dark),
+            "print" => Some(Theme::Print),
+            _ => None,
+        }
+    }
+}
+
+/// Colors and type of a theme. Values are written out rather than set as CSS variables, which wkhtmltopdf's
+/// WebKit does not support
+struct Palette {
+    font: &'static str,
+    background: &'static str,
+    text: &'static str,
+    heading: &'static str,
+    muted: &'static str,
+    border: &'static str,
+    header: &'static str,
+    surface: &'static str,
+    quote: &'static str,
+    link: &'static str,
+}
+
+fn palette(theme: Theme) -> Palette {
+    match theme {
+        Theme::Corporate => Palette {
+            font: "Arial, sans-serif",
+            background: "#ffffff",
+            text: "#000000",
+            heading: "#333",
+            muted: "#666",
+            border: "#ddd",
+            header: "#f2f2f2",
+            surface: "#f5f5f5",
+            quote: "#f9f9f9",
+            link: "#0b5cad",
+        },
+        Theme::Minimal => Palette {
+            font: "\"Helvetica Neue\", Helvetica, Arial, sans-serif",
+            background: "#ffffff",
+            text: "#222",
+            heading: "#111",
+            muted: "#888",
+            border: "#e6e6e6",
+            header: "#ffffff",
+            surface: "#fafafa",
+            quote: "#ffffff",
+            link: "#222",
+        },
+        Theme::Dark => Palette {
+            font: "Arial, sans-serif",
+            background: "#16181d",
+            text: "#e4e4e4",
+            heading: "#f2f2f2",
+            muted: "#9a9a9a",
+            border: "#33363d",
+            header: "#22252b",
+            surface: "#22252b",
+            quote: "#1d2025",
+            link: "#6cb4ff",
+        },
+        Theme::Print => Palette {
+            font: "Georgia, \"Times New Roman\", serif",
+            background: "#ffffff",
+            text: "#000000",
+            heading: "#000000",
+            muted: "#444",
+            border: "#000000",
+            header: "#ffffff",
+            surface: "#ffffff",
+            quote: "#ffffff",
+            link: "#000000",
+        },
+    }
+}
+
+/// Rules a theme adds after the shared ones
+fn extra_rules(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Corporate | Theme::Dark => "",
+        Theme::Minimal => {
+            r#"
+        th, td { border: none; border-bottom: 1px solid #e6e6e6; }
+        th { font-weight: 600; }
+        blockquote { border-left-width: 2px; font-style: italic; }
+        h1, h2 { font-weight: 300; }
+"#
+        }
+        Theme::Print => {
+            r#"
+        body { margin: 1.5cm; font-size: 11pt; }
+        a { text-decoration: none; }
+        a[href^="http"]:after { content: " (" attr(href) ")"; font-size: 9pt; }
+        table, pre, blockquote, svg, img, .diagram { page-break-inside: avoid; }
+        h1, h2, h3, h4, h5, h6 { page-break-after: avoid; }
+        pre, code { border: 1px solid #000000; }
+"#
+        }
+    }
+}
+
+/// Stylesheet of a theme for exported documents
+pub fn theme_css(theme: Theme) -> String {
+    let p = palette(theme);
+    format!(
+        r#"
+        body {{
+            font-family: {font};
+            font-size: 12pt;
+            line-height: 1.5;
+            margin: 2cm;
+            background-color: {background};
+            color: {text};
+        }}
+        a {{
+            color: {link};
+        }}
+        h1, h2, h3, h4, h5, h6 {{
+            color: {heading};
+            margin-top: 1.5em;
+            margin-bottom: 0.5em;
+        }}
+        h1 {{ font-size: 24pt; }}
+        h2 {{ font-size: 20pt; }}
+        h3 {{ font-size: 16pt; }}
+        table {{
+            width: 100%;
+            border-collapse: collapse;
+            margin: 1em 0;
+        }}
+        th, td {{
+            border: 1px solid {border};
+            padding: 8px;
+            text-align: left;
+        }}
+        th {{
+            background-color: {header};
+        }}
+        .report-metadata {{
+            margin-bottom: 2em;
+            color: {muted};
+            font-style: italic;
+        }}
+        ul, ol {{
+            margin: 0.5em 0;
+            padding-left: 2em;
+        }}
+        code {{
+            font-family: monospace;
+            background-color: {surface};
+            padding: 2px 4px;
+            border-radius: 3px;
+        }}
+        pre {{
+            background-color: {surface};
+            padding: 1em;
+            border-radius: 5px;
+            overflow-x: auto;
+        }}
+        blockquote {{
+            background-color: {quote};
+            border-left: 4px solid {border};
+            margin: 1em 0;
+            padding: 0.5em 1em;
+        }}
+        .footnotes {{
+            margin-top: 2em;
+            border-top: 1px solid {border};
+            font-size: 10pt;
+        }}
+        .footnotes-title {{
+            font-size: 14pt;
+        }}
+        .citation a {{
+            text-decoration: none;
+        }}
+        .references {{
+            margin-top: 2em;
+            font-size: 10pt;
+        }}
+        .references-title {{
+            font-size: 14pt;
+        }}
+        .wikilinks {{
+            margin-top: 2em;
+            font-size: 10pt;
+        }}
+        .wikilinks-title {{
+            font-size: 14pt;
+        }}{extra}"#,
+        font = p.font,
+        background = p.background,
+        text = p.text,
+        heading = p.heading,
+        muted = p.muted,
+        border = p.border,
+        header = p.header,
+        surface = p.surface,
+        quote = p.quote,
+        link = p.link,
+        extra = extra_rules(theme),
+    )
+}
@@ -0,0 +1,269 @@
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyList};
+use regex::Regex;
+
+use crate::frontmatter::split_front_matter;
+use crate::panics::guard;
+use crate::sections::split_sections;
+
+/// Line replaced by the table of contents when `format_report` injects one
+pub const MARKER: &str = "[[TOC]]";
+
+/// Prefix comrak puts before heading ids in rendered HTML, so TOC links resolve
+pub const ANCHOR_PREFIX: &str = "section-";
+
+/// Deepest heading level listed when the `toc` option is just `True`
+pub const DEFAULT_DEPTH: usize = 3;
+
+/// A heading the table of contents links to
+pub struct TocEntry {
+    pub level: usize,
+    pub text: String,
+    /// Id of the rendered heading, including `ANCHOR_PREFIX`
+    pub anchor: String,
+}
+
+/// The `toc` option as a maximum heading depth: `True` for `DEFAULT_DEPTH`, a depth from 1 to 6, or 0 for
+/// `False`/`None`
+pub fn depth_from_py(value: &PyAny) -> PyResult<usize> {
+    if value.is_none() {
+        return Ok(0);
+    }
+    if let Ok(enabled) = value.downcast::<PyBool>() {
+        return Ok(if enabled.is_true() { DEFAULT_DEPTH } else { 0 });
+    }
+    let depth: usize = value.extract()?;
+    match depth <= 6 {
+        true => Ok(depth),
+        false => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid toc depth {}. Expected a heading level from 1 to 6", depth)
+        )),
+    }
+}
+
+/// Heading text as comrak sees it when building the id: inline markup, link targets and HTML tags dropped
+fn plain_text(heading: &str) -> String {
+    let link = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+    let tag = Regex::new(r"<[^>]+>").unwrap();
+    let underscores = Regex::new(r"(^|[^\w])_+|_+([^\w]|$)").unwrap();
+    let text = link.replace_all(heading, "$1");
+    let text = tag.replace_all(&text, "");
+    let text = text.replace(['*', '`'], "").replace("~~", "");
+    underscores.replace_all(&text, "$1$2").trim().to_string()
+}
+
+/// Comrak's heading id for `text`: lowercased, punctuation dropped, spaces as hyphens, and a `-1`, `-2`, ...
+/// suffix for repeats already in `seen`
+fn anchorize(text: &str, seen: &mut HashSet<String>) -> String {
+    let rejected = Regex::new(r"[^\p{L}\p{M}\p{N}\p{Pc} -]").unwrap();
+    let base = rejected.replace_all(&text.to_lowercase(), "").replace(' ', "-");
+    let mut anchor = base.clone();
+    let mut repeat = 0;
+    while seen.contains(&anchor) {
+        repeat += 1;
+        anchor = format!("{}-{}", base, repeat);
+    }
+    seen.insert(anchor.clone());
+    anchor
+}
+
+/// The id `format_report` renders a heading with, for links into another report (first occurrence of the text)
+pub fn heading_anchor(heading: &str) -> String {
+    format!("{}{}", ANCHOR_PREFIX, anchorize(&plain_text(heading), &mut HashSet::new()))
+}
+
+/// Every heading outside fenced code blocks, in document order, with the id it renders with
+pub fn toc_entries(markdown: &str) -> Vec<TocEntry> {
+    let mut seen = HashSet::new();
+    split_sections(markdown)
+        .into_iter()
+        .filter(|section| section.level > 0)
+        .map(|section| {
+            let text = plain_text(&section.heading);
+            let anchor = format!("{}{}", ANCHOR_PREFIX, anchorize(&text, &mut seen));
+            TocEntry { level: section.level, text, anchor }
+        })
+        .collect()
+}
+
+/// A heading in the outline, with the headings below it
+pub struct OutlineNode {
+    pub level: usize,
+    pub text: String,
+    /// Heading id without `ANCHOR_PREFIX`
+    pub slug: String,
+    /// 1-based line number of the heading
+    pub line: usize,
+    /// Byte offset of the heading line
+    pub start: usize,
+    /// Byte offset where the section ends: the next heading at the same or a higher level, or the end of the report
+    pub end: usize,
+    pub children: Vec<OutlineNode>,
+}
+
+/// The heading tree of a report, skipping front matter and fenced code. A heading deeper than the one before it
+/// nests under it even if levels are skipped (`#` then `###`)
+pub fn outline(markdown: &str) -> Vec<OutlineNode> {
+    let body_start = split_front_matter(markdown).map_or(0, |(_, _, body)| markdown.len() - body.len());
+    let skipped_lines = markdown[..body_start].matches('\n').count();
+    let mut line_starts: Vec<usize> = markdown[body_start..]
+        .split_inclusive('\n')
+        .scan(body_start, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some(start)
+        })
+        .collect();
+    line_starts.push(markdown.len());
+
+    let mut seen = HashSet::new();
+    let headings: Vec<OutlineNode> = split_sections(&markdown[body_start..])
+        .into_iter()
+        .filter(|section| section.level > 0)
+        .map(|section| {
+            let text = plain_text(&section.heading);
+            OutlineNode {
+                level: section.level,
+                slug: anchorize(&text, &mut seen),
+                text,
+                line: skipped_lines + section.start_line + 1,
+                start: line_starts[section.start_line],
+                end: markdown.len(),
+                children: Vec::new(),
+            }
+        })
+        .collect();
+
+    // Close each section at the next heading that is not below it, then nest
+    let mut nodes = headings;
+    for i in 0..nodes.len() {
+        if let Some(next) = nodes[i + 1..].iter().find(|next| next.level <= nodes[i].level) {
+            nodes[i].end = next.start;
+        }
+    }
+    let mut roots: Vec<OutlineNode> = Vec::new();
+    let mut open: Vec<OutlineNode> = Vec::new();
+    for node in nodes {
+        while open.last().is_some_and(|parent| parent.level >= node.level) {
+            close_node(&mut open, &mut roots);
+        }
+        open.push(node);
+    }
+    while !open.is_empty() {
+        close_node(&mut open, &mut roots);
+    }
+    roots
+}
+
+/// Pop the innermost open heading into its parent, or into the roots if it has none
+fn close_node(open: &mut Vec<OutlineNode>, roots: &mut Vec<OutlineNode>) {
+    if let Some(node) = open.pop() {
+        match open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    }
+}
+
+fn outline_to_py(py: Python, nodes: &[OutlineNode]) -> PyResult<PyObject> {
+    let list = PyList::empty(py);
+    for node in nodes {
+        let dict = PyDict::new(py);
+        dict.set_item("level", node.level)?;
+        dict.set_item("text", &node.text)?;
+        dict.set_item("slug", &node.slug)?;
+        dict.set_item("anchor", format!("{}{}", ANCHOR_PREFIX, node.slug))?;
+        dict.set_item("line", node.line)?;
+        dict.set_item("start", node.start)?;
+        dict.set_item("end", node.end)?;
+        dict.set_item("children", outline_to_py(py, &node.children)?)?;
+        list.append(dict)?;
+    }
+    Ok(list.into())
+}
+
+/// Nested markdown list linking to the entries up to `max_depth`, indented relative to the shallowest one
+fn toc_list(entries: &[TocEntry], max_depth: usize) -> String {
+    let listed: Vec<&TocEntry> = entries.iter().filter(|entry| entry.level <= max_depth).collect();
+    let top = listed.iter().map(|entry| entry.level).min().unwrap_or(1);
+    listed
+        .iter()
+        .map(|entry| {
+            let indent = "  ".repeat(entry.level - top);
+            let text = entry.text.replace('[', "\\[").replace(']', "\\]");
+            format!("{}- [{}](#{})\n", indent, text, entry.anchor)
+        })
+        .collect()
+}
+
+/// Linked table of contents as an HTML list, for document templates; empty for reports without headings
+pub fn toc_html(markdown: &str, max_depth: usize) -> String {
+    let list = toc_list(&toc_entries(markdown), max_depth);
+    match list.is_empty() {
+        true => String::new(),
+        false => comrak::markdown_to_html(&list, &comrak::ComrakOptions::default()),
+    }
+}
+
+/// Replace a `[[TOC]]` line with a linked table of contents, or insert one after the first heading (listing the
+/// headings that follow it) when there is no marker. Reports without headings are returned unchanged
+pub fn insert_toc(markdown: &str, max_depth: usize) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let entries = toc_entries(markdown);
+    let first_heading = split_sections(markdown).iter().find(|section| section.level > 0).map(|section| section.start_line);
+
+    let mut in_fence = false;
+    let marker = lines.iter().position(|line| {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        !in_fence && trimmed == MARKER
+    });
+    let (at, rest, list) = match (marker, first_heading) {
+        (Some(at), _) => (at, at + 1, toc_list(&entries, max_depth)),
+        (None, Some(heading)) => (heading + 1, heading + 1, toc_list(&entries[1..], max_depth)),
+        (None, None) => return markdown.to_string(),
+    };
+
+    let mut out: Vec<String> = lines[..at].iter().map(|line| line.to_string()).collect();
+    if !list.is_empty() {
+        // Blank lines around the list keep it from merging into a neighbouring paragraph or the HTML block
+        if out.last().is_some_and(|line| !line.trim().is_empty()) {
+            out.push(String::new());
+        }
+        out.push(format!("<nav class=\"toc\">\n\n{}\n</nav>", list));
+        if lines.get(rest).is_some_and(|line| !line.trim().is_empty()) {
+            out.push(String::new());
+        }
+    }
+    out.extend(lines[rest..].iter().map(|line| line.to_string()));
+
+    let mut result = out.join("\n");
+    if markdown.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Linked table of contents for a report as a nested markdown list, covering headings up to `max_depth`. The
+/// links match the heading ids `format_report` renders
+#[pyfunction]
+#[pyo3(signature = (markdown, max_depth=DEFAULT_DEPTH))]
+pub fn generate_toc(markdown: &str, max_depth: usize) -> PyResult<String> {
+    guard("generate_toc", || {
+        Ok(toc_list(&toc_entries(markdown), max_depth))
+    })
+}
+
+/// The heading tree of a report as `[{level, text, slug, anchor, line, start, end, children}]`, for outline
+/// navigation. `start`/`end` are byte offsets of the section including its subsections, `anchor` is the id
+/// `format_report` renders the heading with, and front matter and fenced code are skipped
+#[pyfunction]
+pub fn extract_outline(markdown: &str, py: Python) -> PyResult<PyObject> {
+    guard("extract_outline", || {
+        outline_to_py(py, &outline(markdown))
+    })
+}
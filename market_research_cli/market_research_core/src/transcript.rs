@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::Regex;
+
+use crate::panics::guard;
+
+/// One subtitle cue
+#[derive(Clone, Debug)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// Consecutive cues from one speaker, merged into a citable passage
+#[derive(Clone, Debug)]
+pub struct TranscriptChunk {
+    pub start: f64,
+    pub end: f64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+impl TranscriptChunk {
+    /// Timestamp label used in citations, e.g. "14:32" or "1:02:03"
+    pub fn label(&self) -> String {
+        format_timestamp(self.start)
+    }
+}
+
+/// Format seconds as `M:SS`, or `H:MM:SS` past the first hour
+pub fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// Link to a moment in a recording: YouTube's `t=` query parameter, or a media fragment `#t=` elsewhere
+pub fn timestamp_url(url: &str, seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    if url.contains("youtube.com/") || url.contains("youtu.be/") {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        format!("{}{}t={}s", url, separator, seconds)
+    } else {
+        format!("{}#t={}", url, seconds)
+    }
+}
+
+/// Parse `HH:MM:SS.mmm`, `MM:SS.mmm` (WebVTT) or `HH:MM:SS,mmm` (SRT)
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+    let text = text.trim().replace(',', ".");
+    let parts: Vec<&str> = text.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (h.parse::<f64>().ok()?, m.parse::<f64>().ok()?, s.parse::<f64>().ok()?),
+        [m, s] => (0.0, m.parse::<f64>().ok()?, s.parse::<f64>().ok()?),
+        _ => return None,
+    };
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Strip markup from cue text, returning the speaker from a WebVTT `<v Name>` tag or a `NAME:` prefix
+fn clean_cue_text(lines: &[&str]) -> (Option<String>, String) {
+    let voice = Regex::new(r"<v(?:\.[^ >]+)?\s+([^>]+)>").unwrap();
+    let tags = Regex::new(r"<[^>]+>|\{\\[^}]*\}").unwrap();
+    let prefix = Regex::new(r"^(?:-\s*)?([A-Z][\w .'-]{0,40}?):\s+(.+)$").unwrap();
+
+    let joined = lines.join(" ");
+    let mut speaker = voice.captures(&joined).map(|caps| caps[1].trim().to_string());
+    let mut text = tags.replace_all(&joined, "").split_whitespace().collect::<Vec<_>>().join(" ");
+    if speaker.is_none() {
+        if let Some(caps) = prefix.captures(&text) {
+            speaker = Some(caps[1].trim().to_string());
+            text = caps[2].to_string();
+        }
+    }
+    (speaker, html_entities(&text))
+}
+
+fn html_entities(text: &str) -> String {
+    text.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&nbsp;", " ")
+}
+
+/// Parse WebVTT or SRT text into cues; the format is detected from the `WEBVTT` header
+pub fn parse_cues(content: &str) -> Result<Vec<Cue>> {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let is_vtt = content.trim_start().starts_with("WEBVTT");
+
+    let mut cues = Vec::new();
+    for block in content.split("\n\n") {
+        let lines: Vec<&str> = block.lines().filter(|line| !line.trim().is_empty()).collect();
+        let timing_index = match lines.iter().position(|line| line.contains("-->")) {
+            Some(index) => index,
+            None => continue,
+        };
+        // Skip WebVTT NOTE, STYLE and REGION blocks
+        if is_vtt && lines.first().map(|l| l.starts_with("NOTE") || l.starts_with("STYLE") || l.starts_with("REGION")).unwrap_or(false) {
+            continue;
+        }
+
+        let timing = lines[timing_index];
+        let (start, rest) = timing.split_once("-->").unwrap_or_default();
+        // WebVTT cue settings (e.g. `align:start`) follow the end time
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        let (start, end) = match (parse_timestamp(start), parse_timestamp(end)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(anyhow!("Invalid cue timing: {}", timing)),
+        };
+
+        let (speaker, text) = clean_cue_text(&lines[timing_index + 1..]);
+        if !text.is_empty() {
+            cues.push(Cue { start, end, speaker, text });
+        }
+    }
+
+    if cues.is_empty() {
+        return Err(anyhow!("No subtitle cues found; expected WebVTT or SRT"));
+    }
+    Ok(cues)
+}
+
+/// Merge cues into passages of about `chunk_seconds`, breaking early when the speaker changes.
+/// Auto-captions repeat the previous line at the start of each cue, so exact repeats are dropped.
+pub fn chunk_cues(cues: &[Cue], chunk_seconds: f64) -> Vec<TranscriptChunk> {
+    let mut chunks: Vec<TranscriptChunk> = Vec::new();
+    let mut last_text = String::new();
+
+    for cue in cues {
+        if cue.text == last_text {
+            continue;
+        }
+        last_text = cue.text.clone();
+
+        match chunks.last_mut() {
+            Some(chunk)
+                if (cue.speaker.is_none() || cue.speaker == chunk.speaker) && cue.start - chunk.start < chunk_seconds =>
+            {
+                chunk.end = chunk.end.max(cue.end);
+                chunk.text.push(' ');
+                chunk.text.push_str(&cue.text);
+            }
+            _ => chunks.push(TranscriptChunk {
+                start: cue.start,
+                end: cue.end,
+                speaker: cue.speaker.clone().or_else(|| chunks.last().and_then(|c| c.speaker.clone())),
+                text: cue.text.clone(),
+            }),
+        }
+    }
+    chunks
+}
+
+/// Markdown body with one timestamped paragraph per chunk, so quotes can be cited "at 14:32"
+pub fn transcript_markdown(title: &str, chunks: &[TranscriptChunk]) -> String {
+    let mut markdown = format!("# {}\n\n## Transcript\n", title);
+    for chunk in chunks {
+        markdown.push('\n');
+        markdown.push_str(&format!("**[{}]** ", chunk.label()));
+        if let Some(speaker) = &chunk.speaker {
+            markdown.push_str(&format!("*{}:* ", speaker));
+        }
+        markdown.push_str(&chunk.text);
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// Python dicts for chunks; with a source URL, each chunk gets a deep link to its start
+pub fn chunks_to_py(py: Python, chunks: &[TranscriptChunk], url: Option<&str>) -> PyResult<PyObject> {
+    let result = PyList::empty(py);
+    for chunk in chunks {
+        let dict = PyDict::new(py);
+        dict.set_item("start", chunk.start)?;
+        dict.set_item("end", chunk.end)?;
+        dict.set_item("timestamp", chunk.label())?;
+        dict.set_item("speaker", &chunk.speaker)?;
+        dict.set_item("text", &chunk.text)?;
+        dict.set_item("url", url.map(|url| timestamp_url(url, chunk.start)))?;
+        result.append(dict)?;
+    }
+    Ok(result.into())
+}
+
+/// Parse WebVTT/SRT text into timestamped chunks without saving anything
+#[pyfunction]
+#[pyo3(signature = (content, chunk_seconds=60.0, url=None))]
+pub fn parse_transcript(content: &str, chunk_seconds: f64, url: Option<&str>, py: Python) -> PyResult<PyObject> {
+    guard("parse_transcript", || {
+        let cues = parse_cues(content)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to parse transcript: {}", e)))?;
+        chunks_to_py(py, &chunk_cues(&cues, chunk_seconds), url)
+    })
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::backup::read_backup_reports;
+use crate::import::collect_sources;
+use crate::panics::guard;
+use crate::stats::report_date;
+
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Period length for trend buckets
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Granularity {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Granularity {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "month" | "monthly" => Some(Granularity::Month),
+            "quarter" | "quarterly" => Some(Granularity::Quarter),
+            "year" | "yearly" | "annual" => Some(Granularity::Year),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Granularity::Month => "month",
+            Granularity::Quarter => "quarter",
+            Granularity::Year => "year",
+        }
+    }
+
+    /// Sortable period index: months since year 0, quarters since year 0, or the year
+    fn index(&self, date: NaiveDate) -> i64 {
+        let (year, month0) = (date.year() as i64, date.month0() as i64);
+        match self {
+            Granularity::Month => year * 12 + month0,
+            Granularity::Quarter => year * 4 + month0 / 3,
+            Granularity::Year => year,
+        }
+    }
+
+    /// Label such as `2024-03`, `2024-Q1` or `2024`
+    fn label(&self, index: i64) -> String {
+        match self {
+            Granularity::Month => format!("{}-{:02}", index.div_euclid(12), index.rem_euclid(12) + 1),
+            Granularity::Quarter => format!("{}-Q{}", index.div_euclid(4), index.rem_euclid(4) + 1),
+            Granularity::Year => index.to_string(),
+        }
+    }
+}
+
+/// Term usage within one period
+#[derive(Clone, Debug, Default)]
+pub struct PeriodCount {
+    pub period: String,
+    pub mentions: usize,
+    /// Dated sources in the period
+    pub sources: usize,
+    /// Sources in the period that mention the term
+    pub matching_sources: usize,
+    pub words: usize,
+}
+
+impl PeriodCount {
+    /// Mentions per 1,000 words, so busy months with many sources do not look like rising interest
+    pub fn per_1k_words(&self) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            self.mentions as f64 * 1000.0 / self.words as f64
+        }
+    }
+}
+
+/// Date a source and strip its front matter so metadata never counts as a mention
+fn dated_body(name: &str, content: &str, modified: Option<std::time::SystemTime>) -> (NaiveDateTime, String) {
+    let date = report_date(name, content, modified);
+    let body = crate::parse_report_metadata(content)
+        .map(|(_, body)| body)
+        .unwrap_or_else(|_| content.to_string());
+    (date, body)
+}
+
+/// Dated text sources from a directory (a research session or reports folder) or a backup archive
+pub fn load_sources(path: &Path) -> Result<Vec<(NaiveDateTime, String)>> {
+    if path.is_dir() {
+        let files = collect_sources(path, true, &["md", "markdown", "txt"]);
+        return Ok(files
+            .par_iter()
+            .filter_map(|file| {
+                let content = fs::read_to_string(file).ok()?;
+                let name = file.file_name()?.to_string_lossy().to_string();
+                let modified = fs::metadata(file).and_then(|m| m.modified()).ok();
+                Some(dated_body(&name, &content, modified))
+            })
+            .collect());
+    }
+    if path.is_file() {
+        return Ok(read_backup_reports(path)?
+            .into_iter()
+            .map(|(name, bytes)| {
+                let content = String::from_utf8_lossy(&bytes).to_string();
+                dated_body(&name, &content, None)
+            })
+            .collect());
+    }
+    Err(anyhow!("No such session directory or archive: {}", path.display()))
+}
+
+/// Count case-insensitive, whole-word mentions of `term` per period; periods without sources are kept as zeros
+pub fn term_frequency(term: &str, sources: &[(NaiveDateTime, String)], granularity: Granularity) -> Result<Vec<PeriodCount>> {
+    let term = term.trim();
+    if term.is_empty() {
+        return Err(anyhow!("Term cannot be empty"));
+    }
+    let pattern = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(term)))?;
+
+    let mut buckets: BTreeMap<i64, PeriodCount> = BTreeMap::new();
+    for (date, content) in sources {
+        // Undated sources fall back to the epoch; they say nothing about timing
+        if *date == NaiveDateTime::default() {
+            continue;
+        }
+        let bucket = buckets.entry(granularity.index(date.date())).or_default();
+        let mentions = pattern.find_iter(content).count();
+        bucket.mentions += mentions;
+        bucket.sources += 1;
+        bucket.matching_sources += usize::from(mentions > 0);
+        bucket.words += content.split_whitespace().count();
+    }
+
+    let (first, last) = match (buckets.keys().next(), buckets.keys().last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Ok(Vec::new()),
+    };
+    Ok((first..=last)
+        .map(|index| {
+            let mut count = buckets.remove(&index).unwrap_or_default();
+            count.period = granularity.label(index);
+            count
+        })
+        .collect())
+}
+
+/// Unicode block sparkline scaled to the largest value
+pub fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().cloned().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            if max <= 0.0 {
+                SPARK_BARS[0]
+            } else {
+                SPARK_BARS[((value / max) * (SPARK_BARS.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Markdown summary: the sparkline with its range, then a period table
+pub fn trend_markdown(term: &str, counts: &[PeriodCount], granularity: Granularity, normalize: bool) -> String {
+    if counts.is_empty() {
+        return format!("No dated sources mention **{}**.\n", term);
+    }
+    let values: Vec<f64> = counts.iter().map(|c| if normalize { c.per_1k_words() } else { c.mentions as f64 }).collect();
+    let mut markdown = format!(
+        "**{}** {} by {}, {} – {}: `{}`\n\n| Period | Mentions | Sources mentioning | Per 1k words |\n|---|---:|---:|---:|\n",
+        term,
+        if normalize { "mentions per 1k words" } else { "mentions" },
+        granularity.as_str(),
+        counts[0].period,
+        counts[counts.len() - 1].period,
+        sparkline(&values)
+    );
+    for count in counts {
+        markdown.push_str(&format!(
+            "| {} | {} | {} / {} | {:.2} |\n",
+            count.period,
+            count.mentions,
+            count.matching_sources,
+            count.sources,
+            count.per_1k_words()
+        ));
+    }
+    markdown
+}
+
+/// How often `term` appears in dated sources per month, quarter or year, with a sparkline.
+/// `session_or_archive` is a directory of markdown/text sources or a backup `.tar.gz`.
+#[pyfunction]
+#[pyo3(signature = (term, session_or_archive, granularity="month", normalize=false))]
+pub fn term_frequency_over_time(term: &str, session_or_archive: &str, granularity: &str, normalize: bool, py: Python) -> PyResult<PyObject> {
+    guard("term_frequency_over_time", || {
+        let granularity = Granularity::parse(granularity).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Unknown granularity '{}'. Expected month, quarter or year", granularity)
+            )
+        })?;
+        let counts = py.allow_threads(|| -> Result<Vec<PeriodCount>> {
+            let sources = load_sources(Path::new(session_or_archive))?;
+            term_frequency(term, &sources, granularity)
+        })
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to compute term frequency: {}", e)))?;
+
+        let periods = PyList::empty(py);
+        for count in &counts {
+            let dict = PyDict::new(py);
+            dict.set_item("period", &count.period)?;
+            dict.set_item("mentions", count.mentions)?;
+            dict.set_item("sources", count.sources)?;
+            dict.set_item("matching_sources", count.matching_sources)?;
+            dict.set_item("per_1k_words", count.per_1k_words())?;
+            periods.append(dict)?;
+        }
+        let values: Vec<f64> = counts.iter().map(|c| if normalize { c.per_1k_words() } else { c.mentions as f64 }).collect();
+
+        let dict = PyDict::new(py);
+        dict.set_item("term", term)?;
+        dict.set_item("granularity", granularity.as_str())?;
+        dict.set_item("periods", periods)?;
+        dict.set_item("sparkline", sparkline(&values))?;
+        dict.set_item("markdown", trend_markdown(term, &counts, granularity, normalize))?;
+        Ok(dict.into())
+    })
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::convert::html_unescape;
+use crate::panics::guard;
+use crate::transcript::{format_timestamp, parse_timestamp, timestamp_url};
+
+/// A chapter marker from the video description
+#[derive(Clone, Debug)]
+pub struct Chapter {
+    pub start: f64,
+    pub title: String,
+}
+
+/// Citation metadata for a video page
+#[derive(Clone, Debug, Default)]
+pub struct VideoMetadata {
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub channel_url: Option<String>,
+    pub publish_date: Option<String>,
+    pub description: Option<String>,
+    pub duration: Option<f64>,
+    pub thumbnail: Option<String>,
+    pub url: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl VideoMetadata {
+    /// Fill fields that are still empty from a lower-priority source
+    fn merge(&mut self, other: VideoMetadata) {
+        self.title = self.title.take().or(other.title);
+        self.channel = self.channel.take().or(other.channel);
+        self.channel_url = self.channel_url.take().or(other.channel_url);
+        self.publish_date = self.publish_date.take().or(other.publish_date);
+        self.description = self.description.take().or(other.description);
+        self.duration = self.duration.take().or(other.duration);
+        self.thumbnail = self.thumbnail.take().or(other.thumbnail);
+        self.url = self.url.take().or(other.url);
+    }
+
+    /// Reference-list entry: `Channel. "Title." Video, 2024-05-01. URL`
+    pub fn citation(&self) -> String {
+        let mut citation = String::new();
+        if let Some(channel) = &self.channel {
+            citation.push_str(&format!("{}. ", channel));
+        }
+        citation.push_str(&format!("\"{}.\" Video", self.title.as_deref().unwrap_or("Untitled video")));
+        if let Some(date) = &self.publish_date {
+            citation.push_str(&format!(", {}", date));
+        }
+        citation.push('.');
+        if let Some(url) = &self.url {
+            citation.push_str(&format!(" {}", url));
+        }
+        citation
+    }
+}
+
+fn non_empty(text: Option<&str>) -> Option<String> {
+    text.map(|t| html_unescape(t.trim())).filter(|t| !t.is_empty())
+}
+
+/// `<meta>` tags keyed by their `property`, `name` or `itemprop` attribute
+fn meta_tags(html: &str) -> HashMap<String, String> {
+    let tag = Regex::new(r"(?is)<(?:meta|link)\s[^>]*>").unwrap();
+    let attribute = Regex::new(r#"(?s)([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+
+    let mut tags = HashMap::new();
+    for element in tag.find_iter(html) {
+        let attributes: HashMap<String, String> = attribute
+            .captures_iter(element.as_str())
+            .map(|caps| {
+                let value = caps.get(2).or_else(|| caps.get(3)).map(|m| m.as_str()).unwrap_or_default();
+                (caps[1].to_lowercase(), value.to_string())
+            })
+            .collect();
+        let key = ["property", "name", "itemprop"].iter().find_map(|k| attributes.get(*k));
+        let value = attributes.get("content").or_else(|| attributes.get("href"));
+        if let (Some(key), Some(value)) = (key, value) {
+            tags.entry(key.to_lowercase()).or_insert_with(|| value.clone());
+        }
+    }
+    tags
+}
+
+fn from_meta(html: &str) -> VideoMetadata {
+    let tags = meta_tags(html);
+    let get = |keys: &[&str]| keys.iter().find_map(|key| non_empty(tags.get(*key).map(|v| v.as_str())));
+    let title_tag = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+
+    VideoMetadata {
+        title: get(&["og:title", "twitter:title", "title"])
+            .or_else(|| non_empty(title_tag.captures(html).map(|caps| caps.get(1).unwrap().as_str()))),
+        channel: get(&["og:video:director", "author", "article:author"]),
+        channel_url: None,
+        publish_date: get(&["uploaddate", "datepublished", "article:published_time", "og:video:release_date"]),
+        description: get(&["og:description", "description", "twitter:description"]),
+        duration: get(&["og:video:duration", "video:duration"]).and_then(|d| d.parse().ok())
+            .or_else(|| get(&["duration"]).and_then(|d| parse_iso_duration(&d))),
+        thumbnail: get(&["og:image", "twitter:image", "thumbnailurl"]),
+        url: get(&["og:url", "canonical"]),
+        chapters: Vec::new(),
+    }
+}
+
+/// Parse an ISO 8601 duration such as `PT1H2M3S`
+fn parse_iso_duration(text: &str) -> Option<f64> {
+    let caps = Regex::new(r"^P(?:(\d+)D)?T?(?:(\d+)H)?(?:(\d+)M)?(?:(\d+(?:\.\d+)?)S)?$").unwrap().captures(text.trim())?;
+    let part = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<f64>().ok()).unwrap_or(0.0);
+    Some(part(1) * 86400.0 + part(2) * 3600.0 + part(3) * 60.0 + part(4))
+}
+
+fn json_str(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(text) => non_empty(Some(text)),
+        Value::Object(_) => json_str(value.get(key)?, "name").or_else(|| json_str(value.get(key)?, "simpleText")),
+        Value::Array(items) => items.iter().find_map(|item| match item {
+            Value::String(text) => non_empty(Some(text)),
+            other => json_str(other, "name").or_else(|| json_str(other, "url")),
+        }),
+        _ => None,
+    }
+}
+
+/// Find a schema.org `VideoObject` in the page's JSON-LD blocks
+fn from_json_ld(html: &str) -> VideoMetadata {
+    let script = Regex::new(r#"(?is)<script[^>]*type\s*=\s*["']application/ld\+json["'][^>]*>(.*?)</script>"#).unwrap();
+
+    fn find_video(value: &Value) -> Option<&Value> {
+        match value {
+            Value::Array(items) => items.iter().find_map(find_video),
+            Value::Object(object) => {
+                let is_video = match object.get("@type") {
+                    Some(Value::String(kind)) => kind == "VideoObject",
+                    Some(Value::Array(kinds)) => kinds.iter().any(|k| k == "VideoObject"),
+                    _ => false,
+                };
+                if is_video {
+                    Some(value)
+                } else {
+                    object.get("@graph").and_then(find_video)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    for caps in script.captures_iter(html) {
+        let parsed: Value = match serde_json::from_str(caps[1].trim()) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        if let Some(video) = find_video(&parsed) {
+            let channel_url = video.get("author").and_then(|author| match author {
+                Value::Array(items) => items.first().and_then(|a| json_str(a, "url")),
+                other => json_str(other, "url"),
+            });
+            return VideoMetadata {
+                title: json_str(video, "name"),
+                channel: json_str(video, "author").or_else(|| json_str(video, "publisher")),
+                channel_url,
+                publish_date: json_str(video, "uploadDate").or_else(|| json_str(video, "datePublished")),
+                description: json_str(video, "description"),
+                duration: json_str(video, "duration").and_then(|d| parse_iso_duration(&d)),
+                thumbnail: json_str(video, "thumbnailUrl"),
+                url: json_str(video, "url").or_else(|| json_str(video, "embedUrl")),
+                chapters: Vec::new(),
+            };
+        }
+    }
+    VideoMetadata::default()
+}
+
+/// Read YouTube's inline `ytInitialPlayerResponse` object, which carries the full description
+fn from_youtube_player(html: &str) -> VideoMetadata {
+    let start = match Regex::new(r"ytInitialPlayerResponse\s*=\s*\{").unwrap().find(html) {
+        Some(found) => found.end() - 1,
+        None => return VideoMetadata::default(),
+    };
+    // The object is followed by more script, so parse exactly one JSON value from the start
+    let player = match serde_json::Deserializer::from_str(&html[start..]).into_iter::<Value>().next() {
+        Some(Ok(player)) => player,
+        _ => return VideoMetadata::default(),
+    };
+
+    let details = player.get("videoDetails").cloned().unwrap_or(Value::Null);
+    let microformat = player.pointer("/microformat/playerMicroformatRenderer").cloned().unwrap_or(Value::Null);
+    VideoMetadata {
+        title: json_str(&details, "title"),
+        channel: json_str(&details, "author").or_else(|| json_str(&microformat, "ownerChannelName")),
+        channel_url: json_str(&microformat, "ownerProfileUrl")
+            .or_else(|| json_str(&details, "channelId").map(|id| format!("https://www.youtube.com/channel/{}", id))),
+        publish_date: json_str(&microformat, "publishDate").or_else(|| json_str(&microformat, "uploadDate")),
+        description: json_str(&details, "shortDescription").or_else(|| json_str(&microformat, "description")),
+        duration: json_str(&details, "lengthSeconds").and_then(|s| s.parse().ok()),
+        thumbnail: details.pointer("/thumbnail/thumbnails").and_then(|t| t.as_array()).and_then(|t| t.last())
+            .and_then(|t| json_str(t, "url")),
+        url: json_str(&details, "videoId").map(|id| format!("https://www.youtube.com/watch?v={}", id)),
+        chapters: Vec::new(),
+    }
+}
+
+/// Chapter list from description lines like `0:00 Intro` or `(12:45) - Q&A`.
+/// Like YouTube, a list only counts when it starts at 0:00 and has at least two entries.
+pub fn parse_chapters(description: &str) -> Vec<Chapter> {
+    let line = Regex::new(r"^\s*(?:[-*•]\s*)?\(?((?:\d{1,2}:)?\d{1,2}:\d{2})\)?\s*[-–—:|]?\s*(.+?)\s*$").unwrap();
+
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for caps in description.lines().filter_map(|l| line.captures(l)) {
+        let start = match parse_timestamp(&caps[1]) {
+            Some(start) => start,
+            None => continue,
+        };
+        // Timestamps must increase; a smaller one starts an unrelated list (e.g. a tracklist)
+        if chapters.last().map(|last| start <= last.start).unwrap_or(false) {
+            break;
+        }
+        chapters.push(Chapter { start, title: caps[2].to_string() });
+    }
+
+    if chapters.len() < 2 || chapters[0].start != 0.0 {
+        return Vec::new();
+    }
+    chapters
+}
+
+/// Extract title, channel, publish date, description and chapters from a fetched video page.
+/// JSON-LD wins over YouTube's player data, which wins over `<meta>` tags.
+pub fn extract_metadata(html: &str, url: Option<&str>) -> VideoMetadata {
+    let player = from_youtube_player(html);
+    // Page descriptions are usually truncated; the player's copy is complete and holds the chapter list
+    let full_description = player.description.clone();
+
+    let mut metadata = from_json_ld(html);
+    metadata.merge(player);
+    metadata.merge(from_meta(html));
+    metadata.description = full_description.or(metadata.description);
+    if let Some(url) = url {
+        metadata.url = Some(url.to_string());
+    }
+    metadata.chapters = metadata.description.as_deref().map(parse_chapters).unwrap_or_default();
+    metadata
+}
+
+/// Parse a fetched video page (YouTube or any page with HTML5 video metadata) into a citation dict
+#[pyfunction]
+#[pyo3(signature = (html, url=None))]
+pub fn extract_video_metadata(html: &str, url: Option<&str>, py: Python) -> PyResult<PyObject> {
+    guard("extract_video_metadata", || {
+        let metadata = py.allow_threads(|| extract_metadata(html, url));
+
+        let chapters = PyList::empty(py);
+        for chapter in &metadata.chapters {
+            let dict = PyDict::new(py);
+            dict.set_item("start", chapter.start)?;
+            dict.set_item("timestamp", format_timestamp(chapter.start))?;
+            dict.set_item("title", &chapter.title)?;
+            dict.set_item("url", metadata.url.as_deref().map(|url| timestamp_url(url, chapter.start)))?;
+            chapters.append(dict)?;
+        }
+
+        let dict = PyDict::new(py);
+        dict.set_item("title", &metadata.title)?;
+        dict.set_item("channel", &metadata.channel)?;
+        dict.set_item("channel_url", &metadata.channel_url)?;
+        dict.set_item("publish_date", &metadata.publish_date)?;
+        dict.set_item("description", &metadata.description)?;
+        dict.set_item("duration", metadata.duration)?;
+        dict.set_item("duration_label", metadata.duration.map(format_timestamp))?;
+        dict.set_item("thumbnail", &metadata.thumbnail)?;
+        dict.set_item("url", &metadata.url)?;
+        dict.set_item("chapters", chapters)?;
+        dict.set_item("citation", metadata.citation())?;
+        Ok(dict.into())
+    })
+}
@@ -0,0 +1,198 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::panics::{guard, lock};
+use crate::wait_limit;
+
+/// Maximum number of undelivered events kept in queue mode
+const MAX_QUEUED_EVENTS: usize = 10_000;
+
+/// A filesystem change to a report
+#[derive(Clone, Debug)]
+pub struct WatchEvent {
+    pub kind: &'static str,
+    pub filename: String,
+    pub timestamp: f64,
+}
+
+impl WatchEvent {
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("kind", self.kind)?;
+        dict.set_item("filename", &self.filename)?;
+        dict.set_item("timestamp", self.timestamp)?;
+        Ok(dict)
+    }
+}
+
+type EventQueue = Arc<(Mutex<VecDeque<WatchEvent>>, Condvar)>;
+
+/// Ignore hidden files, trash, and temporary files written during atomic saves
+fn is_report_path(roots: &[PathBuf], path: &Path) -> Option<String> {
+    let relative = roots.iter().find_map(|root| path.strip_prefix(root).ok())?;
+    let hidden = relative.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    let name = relative.to_string_lossy().to_string();
+    if hidden || name.is_empty() || name.ends_with(".tmp") || name.ends_with('~') {
+        return None;
+    }
+    Some(name)
+}
+
+/// Translate a notify event into report events
+pub fn translate(roots: &[PathBuf], event: &Event) -> Vec<WatchEvent> {
+    let kind = match event.kind {
+        EventKind::Create(_) => "created",
+        EventKind::Modify(notify::event::ModifyKind::Name(notify::event::RenameMode::From)) => "deleted",
+        EventKind::Modify(notify::event::ModifyKind::Name(notify::event::RenameMode::To)) => "created",
+        EventKind::Modify(notify::event::ModifyKind::Metadata(_)) => return Vec::new(),
+        EventKind::Modify(_) => "modified",
+        EventKind::Remove(_) => "deleted",
+        _ => return Vec::new(),
+    };
+
+    let timestamp = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+    event
+        .paths
+        .iter()
+        .filter_map(|path| is_report_path(roots, path))
+        .map(|filename| WatchEvent { kind, filename, timestamp })
+        .collect()
+}
+
+/// Watches the reports directory and delivers created/modified/deleted events
+#[pyclass]
+pub struct ReportWatcher {
+    reports_dir: PathBuf,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    events: EventQueue,
+}
+
+#[pymethods]
+impl ReportWatcher {
+    #[new]
+    fn new(reports_dir: &str) -> PyResult<Self> {
+        guard("ReportWatcher.new", || {
+            Ok(ReportWatcher {
+                reports_dir: PathBuf::from(reports_dir),
+                watcher: Mutex::new(None),
+                events: Arc::new((Mutex::new(VecDeque::new()), Condvar::new())),
+            })
+        })
+    }
+
+    /// Start watching; events go to `callback(event_dict)` if given, otherwise to an internal queue
+    #[pyo3(signature = (callback=None, recursive=false))]
+    fn start(&self, callback: Option<PyObject>, recursive: bool) -> PyResult<()> {
+        guard("ReportWatcher.start", || {
+            let mut running = lock(&self.watcher);
+            if running.is_some() {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Watcher is already running"));
+            }
+            if !self.reports_dir.is_dir() {
+                return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                    format!("Reports directory does not exist: {}", self.reports_dir.display())
+                ));
+            }
+
+            // notify reports canonical paths on some platforms, so accept both forms of the root
+            let mut roots = vec![self.reports_dir.clone()];
+            if let Ok(canonical) = self.reports_dir.canonicalize() {
+                roots.push(canonical);
+            }
+            let events = Arc::clone(&self.events);
+            let handler = move |result: notify::Result<Event>| {
+                let event = match result {
+                    Ok(event) => event,
+                    Err(_) => return,
+                };
+                let translated = translate(&roots, &event);
+                if translated.is_empty() {
+                    return;
+                }
+
+                match &callback {
+                    Some(callback) => Python::with_gil(|py| {
+                        for event in &translated {
+                            let result = event.to_dict(py).and_then(|dict| callback.call1(py, (dict,)));
+                            if let Err(err) = result {
+                                err.print(py);
+                            }
+                        }
+                    }),
+                    None => {
+                        let (queue, available) = &*events;
+                        let mut queue = lock(queue);
+                        for event in translated {
+                            if queue.len() >= MAX_QUEUED_EVENTS {
+                                queue.pop_front();
+                            }
+                            queue.push_back(event);
+                        }
+                        available.notify_all();
+                    }
+                }
+            };
+
+            let mut watcher = notify::recommended_watcher(handler).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to create watcher: {}", e))
+            })?;
+            let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+            watcher.watch(&self.reports_dir, mode).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to watch directory: {}", e))
+            })?;
+
+            *running = Some(watcher);
+            Ok(())
+        })
+    }
+
+    /// Stop watching the directory
+    fn stop(&self, py: Python) -> PyResult<()> {
+        guard("ReportWatcher.stop", || {
+            // Dropping the watcher joins its event thread, which may be waiting for the GIL
+            let watcher = lock(&self.watcher).take();
+            py.allow_threads(move || drop(watcher));
+            Ok(())
+        })
+    }
+
+    /// Whether the watcher is currently running
+    fn is_running(&self) -> PyResult<bool> {
+        guard("ReportWatcher.is_running", || {
+            Ok(lock(&self.watcher).is_some())
+        })
+    }
+
+    /// Drain queued events, waiting up to `timeout` seconds for at least one (indefinitely when it is infinite)
+    #[pyo3(signature = (timeout=None))]
+    fn poll_events(&self, py: Python, timeout: Option<f64>) -> PyResult<PyObject> {
+        guard("ReportWatcher.poll_events", || {
+            let drained: Vec<WatchEvent> = py.allow_threads(|| {
+                let (queue, available) = &*self.events;
+                let mut queue = lock(queue);
+                if queue.is_empty() {
+                    queue = match (timeout, wait_limit(timeout)) {
+                        // No timeout returns what is queued right away
+                        (None, _) => queue,
+                        (Some(_), Some(wait)) => {
+                            available.wait_timeout_while(queue, wait, |q| q.is_empty()).unwrap_or_else(PoisonError::into_inner).0
+                        }
+                        (Some(_), None) => available.wait_while(queue, |q| q.is_empty()).unwrap_or_else(PoisonError::into_inner),
+                    };
+                }
+                queue.drain(..).collect()
+            });
+
+            let result = PyList::empty(py);
+            for event in &drained {
+                result.append(event.to_dict(py)?)?;
+            }
+            Ok(result.into())
+        })
+    }
+}
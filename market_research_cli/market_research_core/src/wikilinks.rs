@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use regex::{Captures, Regex};
+
+use crate::charts::escape_xml;
+use crate::frontmatter::{mapping_str, read_front_matter};
+use crate::links::{LinkResolver, LinkTarget};
+use crate::list_reports;
+use crate::paths::portable_filename;
+use crate::render::map_outside_fences;
+use crate::toc;
+
+/// Class marking resolved wikilinks in rendered HTML, so PDF export can turn them into endnotes
+const WIKILINK_CLASS: &str = "wikilink";
+
+/// The reports `[[...]]` links resolve against, with their titles
+#[derive(Clone, Debug)]
+pub struct Library {
+    resolver: Arc<LinkResolver>,
+    titles: Arc<HashMap<String, String>>,
+}
+
+impl Library {
+    /// Index the reports in `reports_dir` by filename, filename slug and front matter title
+    pub fn load(reports_dir: &str) -> Result<Self> {
+        let filenames = list_reports(reports_dir).with_context(|| format!("Failed to list reports in {}", reports_dir))?;
+        let titles: HashMap<String, String> = filenames
+            .par_iter()
+            .filter_map(|filename| {
+                let metadata = read_front_matter(&Path::new(reports_dir).join(filename)).ok()?;
+                Some((filename.clone(), mapping_str(&metadata, "title")?))
+            })
+            .collect();
+        Ok(Library::new(&filenames, titles))
+    }
+
+    /// A library of the given report filenames, some of them with titles (filename to title)
+    pub fn new(filenames: &[String], titles: HashMap<String, String>) -> Self {
+        let mut resolver = LinkResolver::new(filenames);
+        for (filename, title) in &titles {
+            resolver.add_title(title, filename);
+        }
+        Library { resolver: Arc::new(resolver), titles: Arc::new(titles) }
+    }
+
+    /// How a linked report is named in hover text and endnotes: its title and filename
+    fn describe(&self, filename: &str) -> String {
+        match self.titles.get(filename) {
+            Some(title) => format!("{} ({})", title, filename),
+            None => filename.to_string(),
+        }
+    }
+}
+
+pub fn wikilink_pattern() -> Regex {
+    Regex::new(r"\[\[([^\[\]|#]+)(?:#([^\[\]|]*))?(?:\|([^\[\]]*))?\]\]").unwrap()
+}
+
+/// Replace `[[Report]]`, `[[Report|label]]` and `[[Report#Section]]` with links to the report's HTML export,
+/// which sits beside this one as `<name>.html` (as `export_many` and `export_site` write it). Links to reports
+/// that are not in the library are left as written
+pub fn link_wikilinks(markdown: &str, library: &Library) -> String {
+    let pattern = wikilink_pattern();
+    map_outside_fences(markdown, |line| {
+        // Odd segments between backticks are code spans
+        line.split('`')
+            .enumerate()
+            .map(|(index, segment)| match index % 2 {
+                0 => pattern.replace_all(segment, |caps: &Captures| link(caps, library)).into_owned(),
+                _ => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("`")
+    })
+}
+
+fn link(caps: &Captures, library: &Library) -> String {
+    let name = caps[1].trim();
+    if caps.get(2).is_none() && caps.get(3).is_none() && format!("[[{}]]", name) == toc::MARKER {
+        return caps[0].to_string();
+    }
+    let filename = match library.resolver.resolve(&LinkTarget::Wiki(name.to_string())) {
+        Some(filename) => filename,
+        None => return caps[0].to_string(),
+    };
+    let stem = Path::new(&filename).file_stem().and_then(|stem| stem.to_str()).unwrap_or(&filename);
+    let anchor = match caps.get(2).map(|section| section.as_str().trim()).filter(|section| !section.is_empty()) {
+        Some(section) => format!("#{}", toc::heading_anchor(section)),
+        None => String::new(),
+    };
+    let label = caps.get(3).map(|label| label.as_str().trim()).filter(|label| !label.is_empty()).unwrap_or(name);
+    format!(
+        "<a class=\"{}\" href=\"{}.html{}\" title=\"{}\">{}</a>",
+        WIKILINK_CLASS,
+        escape_xml(&portable_filename(stem)),
+        escape_xml(&anchor),
+        escape_xml(&library.describe(&filename)),
+        escape_xml(label)
+    )
+}
+
+/// Turn the wikilinks in rendered HTML into numbered references to a "Linked reports" list at the end, for PDF
+/// export, where links to sibling files lead nowhere
+pub fn wikilinks_as_endnotes(html: &str) -> String {
+    let anchor = Regex::new(&format!(r#"<a class="{}"[^>]*?\btitle="([^"]*)"[^>]*>(.*?)</a>"#, WIKILINK_CLASS)).unwrap();
+    let mut linked: Vec<String> = Vec::new();
+    let replaced = anchor
+        .replace_all(html, |caps: &Captures| {
+            let number = match linked.iter().position(|title| *title == caps[1]) {
+                Some(position) => position + 1,
+                None => {
+                    linked.push(caps[1].to_string());
+                    linked.len()
+                }
+            };
+            format!("{}<sup class=\"wikilink-ref\"><a href=\"#wikilink-{1}\">[{1}]</a></sup>", &caps[2], number)
+        })
+        .into_owned();
+
+    if linked.is_empty() {
+        return replaced;
+    }
+    let mut out = replaced;
+    out.push_str("<section class=\"wikilinks\">\n<h2 class=\"wikilinks-title\">Linked reports</h2>\n<ol>\n");
+    for (index, title) in linked.iter().enumerate() {
+        out.push_str(&format!("<li id=\"wikilink-{}\">{}</li>\n", index + 1, title));
+    }
+    out.push_str("</ol>\n</section>\n");
+    out
+}